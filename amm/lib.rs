@@ -0,0 +1,383 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use ink_lang as ink;
+
+pub use self::pair::{Pair, PairRef};
+
+/// A constant-product pool between the `erc20` token and the native currency.
+///
+/// Token amounts are measured as the change of the pool's token balance, so
+/// taxed and burning transfers into the pool are priced correctly.
+#[ink::contract]
+mod pair {
+    use erc20::{math::mul_div, Erc20Ref};
+    use ink_lang::codegen::EmitEvent;
+    use ink_storage::{traits::SpreadAllocate, Mapping};
+
+    /// Fee kept in the pool on every swap, in thousandths of the input.
+    const FEE_PER_MILLE: Balance = 3;
+
+    #[ink(storage)]
+    #[derive(SpreadAllocate)]
+    pub struct Pair {
+        /// The token traded against the native currency.
+        token: AccountId,
+        /// Native currency held by the pool.
+        reserve_native: Balance,
+        /// Tokens held by the pool.
+        reserve_token: Balance,
+        /// Sum of all liquidity shares.
+        total_liquidity: Balance,
+        /// Liquidity shares per provider.
+        liquidity: Mapping<AccountId, Balance>,
+    }
+
+    /// The pair error types.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Returned if liquidity is added or removed in zero amounts.
+        InsufficientLiquidity,
+        /// Returned if a swap would pay out less than the caller's minimum.
+        InsufficientOutput,
+        /// Returned if a call to the token contract fails.
+        TokenCallFailed,
+        /// Returned if the pool cannot pay out native currency.
+        NativeTransferFailed,
+    }
+
+    /// The pair result type.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    #[ink(event)]
+    pub struct LiquidityAdded {
+        #[ink(topic)]
+        provider: AccountId,
+        native: Balance,
+        tokens: Balance,
+        liquidity: Balance,
+    }
+
+    #[ink(event)]
+    pub struct LiquidityRemoved {
+        #[ink(topic)]
+        provider: AccountId,
+        native: Balance,
+        tokens: Balance,
+        liquidity: Balance,
+    }
+
+    #[ink(event)]
+    pub struct Swap {
+        #[ink(topic)]
+        trader: AccountId,
+        native_in: Balance,
+        tokens_in: Balance,
+        native_out: Balance,
+        tokens_out: Balance,
+    }
+
+    impl Pair {
+        /// Creates an empty pool for the token deployed at `token`.
+        #[ink(constructor)]
+        pub fn new(token: AccountId) -> Self {
+            ink_lang::utils::initialize_contract(|pair: &mut Self| {
+                pair.token = token;
+            })
+        }
+
+        /// Returns the token traded in the pool.
+        #[ink(message)]
+        pub fn token(&self) -> AccountId {
+            self.token
+        }
+
+        /// Returns the native and token reserves of the pool.
+        #[ink(message)]
+        pub fn reserves(&self) -> (Balance, Balance) {
+            (self.reserve_native, self.reserve_token)
+        }
+
+        /// Returns the liquidity shares of `provider`.
+        #[ink(message)]
+        pub fn liquidity_of(&self, provider: AccountId) -> Balance {
+            self.liquidity.get(provider).unwrap_or_default()
+        }
+
+        /// Returns the sum of all liquidity shares.
+        #[ink(message)]
+        pub fn total_liquidity(&self) -> Balance {
+            self.total_liquidity
+        }
+
+        /// Adds the transferred native currency and `tokens` of the caller to
+        /// the pool and returns the liquidity shares minted for them.
+        ///
+        /// The caller must have approved the pool to spend `tokens`.
+        #[ink(message, payable)]
+        pub fn add_liquidity(&mut self, tokens: Balance) -> Result<Balance> {
+            let provider = self.env().caller();
+            let native = self.env().transferred_value();
+            self.token_ref()
+                .transfer_from(provider, self.env().account_id(), tokens)
+                .map_err(|_| Error::TokenCallFailed)?;
+            let balance = self.token_balance();
+            let tokens = balance.saturating_sub(self.reserve_token);
+            let liquidity = liquidity_for(
+                native,
+                tokens,
+                self.reserve_native,
+                self.reserve_token,
+                self.total_liquidity,
+            );
+            if liquidity == 0 {
+                return Err(Error::InsufficientLiquidity)
+            }
+            self.liquidity
+                .insert(provider, &(self.liquidity_of(provider) + liquidity));
+            self.total_liquidity += liquidity;
+            self.reserve_native += native;
+            self.reserve_token = balance;
+            EmitEvent::<Pair>::emit_event(
+                self.env(),
+                LiquidityAdded {
+                    provider,
+                    native,
+                    tokens,
+                    liquidity,
+                },
+            );
+            Ok(liquidity)
+        }
+
+        /// Burns `liquidity` shares of the caller and pays out their part of
+        /// both reserves as `(native, tokens)`.
+        #[ink(message)]
+        pub fn remove_liquidity(&mut self, liquidity: Balance) -> Result<(Balance, Balance)> {
+            let provider = self.env().caller();
+            let shares = self.liquidity_of(provider);
+            if liquidity == 0 || liquidity > shares {
+                return Err(Error::InsufficientLiquidity)
+            }
+            let native = mul_div(self.reserve_native, liquidity, self.total_liquidity)
+                .expect("shares never exceed the total");
+            let tokens = mul_div(self.reserve_token, liquidity, self.total_liquidity)
+                .expect("shares never exceed the total");
+            self.liquidity.insert(provider, &(shares - liquidity));
+            self.total_liquidity -= liquidity;
+            self.reserve_native -= native;
+            self.reserve_token -= tokens;
+            self.send_tokens(provider, tokens)?;
+            self.env()
+                .transfer(provider, native)
+                .map_err(|_| Error::NativeTransferFailed)?;
+            EmitEvent::<Pair>::emit_event(
+                self.env(),
+                LiquidityRemoved {
+                    provider,
+                    native,
+                    tokens,
+                    liquidity,
+                },
+            );
+            Ok((native, tokens))
+        }
+
+        /// Sells the transferred native currency for at least `min_tokens_out`
+        /// tokens and returns the amount bought.
+        #[ink(message, payable)]
+        pub fn swap_native_for_tokens(&mut self, min_tokens_out: Balance) -> Result<Balance> {
+            let trader = self.env().caller();
+            let native_in = self.env().transferred_value();
+            let tokens_out = amount_out(native_in, self.reserve_native, self.reserve_token);
+            if tokens_out == 0 || tokens_out < min_tokens_out {
+                return Err(Error::InsufficientOutput)
+            }
+            self.reserve_native += native_in;
+            self.reserve_token -= tokens_out;
+            self.send_tokens(trader, tokens_out)?;
+            EmitEvent::<Pair>::emit_event(
+                self.env(),
+                Swap {
+                    trader,
+                    native_in,
+                    tokens_in: 0,
+                    native_out: 0,
+                    tokens_out,
+                },
+            );
+            Ok(tokens_out)
+        }
+
+        /// Sells `tokens_in` of the caller's tokens for at least
+        /// `min_native_out` native currency and returns the amount bought.
+        ///
+        /// The caller must have approved the pool to spend `tokens_in`.
+        #[ink(message)]
+        pub fn swap_tokens_for_native(
+            &mut self,
+            tokens_in: Balance,
+            min_native_out: Balance,
+        ) -> Result<Balance> {
+            let trader = self.env().caller();
+            self.swap_exact_tokens_for_native(tokens_in, min_native_out, trader)
+        }
+
+        /// Like `swap_tokens_for_native`, but pays `to`. This is the router
+        /// interface the token sells its collected tax through.
+        #[ink(message)]
+        pub fn swap_exact_tokens_for_native(
+            &mut self,
+            tokens_in: Balance,
+            min_native_out: Balance,
+            to: AccountId,
+        ) -> Result<Balance> {
+            let trader = self.env().caller();
+            self.token_ref()
+                .transfer_from(trader, self.env().account_id(), tokens_in)
+                .map_err(|_| Error::TokenCallFailed)?;
+            let balance = self.token_balance();
+            let tokens_in = balance.saturating_sub(self.reserve_token);
+            let native_out = amount_out(tokens_in, self.reserve_token, self.reserve_native);
+            if native_out == 0 || native_out < min_native_out {
+                return Err(Error::InsufficientOutput)
+            }
+            self.reserve_token = balance;
+            self.reserve_native -= native_out;
+            self.env()
+                .transfer(to, native_out)
+                .map_err(|_| Error::NativeTransferFailed)?;
+            EmitEvent::<Pair>::emit_event(
+                self.env(),
+                Swap {
+                    trader,
+                    native_in: 0,
+                    tokens_in,
+                    native_out,
+                    tokens_out: 0,
+                },
+            );
+            Ok(native_out)
+        }
+
+        /// Sets the token reserve to the pool's token balance, e.g. after the
+        /// token rebased or burnt part of it, and returns it. Anyone may call
+        /// this.
+        #[ink(message)]
+        pub fn sync(&mut self) -> Balance {
+            self.reserve_token = self.token_balance();
+            self.reserve_token
+        }
+
+        fn token_ref(&self) -> Erc20Ref {
+            ink_env::call::FromAccountId::from_account_id(self.token)
+        }
+
+        fn token_balance(&self) -> Balance {
+            self.token_ref().balance_of(self.env().account_id())
+        }
+
+        fn send_tokens(&self, to: AccountId, tokens: Balance) -> Result<()> {
+            self.token_ref()
+                .transfer(to, tokens)
+                .map_err(|_| Error::TokenCallFailed)
+        }
+    }
+
+    /// Returns the output of a swap of `amount_in` against the given
+    /// reserves, after the pool fee.
+    fn amount_out(amount_in: Balance, reserve_in: Balance, reserve_out: Balance) -> Balance {
+        if reserve_in == 0 || reserve_out == 0 {
+            return 0
+        }
+        let amount_in = mul_div(amount_in, 1_000 - FEE_PER_MILLE, 1_000)
+            .expect("the fee only shrinks the input");
+        mul_div(amount_in, reserve_out, reserve_in.saturating_add(amount_in))
+            .expect("the output never exceeds the reserve")
+    }
+
+    /// Returns the liquidity shares minted for a deposit. The first deposit
+    /// sets the price; later ones are credited for the smaller of their two
+    /// legs, so unbalanced extra goes to the existing providers.
+    fn liquidity_for(
+        native: Balance,
+        tokens: Balance,
+        reserve_native: Balance,
+        reserve_token: Balance,
+        total_liquidity: Balance,
+    ) -> Balance {
+        if total_liquidity == 0 {
+            return sqrt_product(native, tokens)
+        }
+        let by_native = mul_div(native, total_liquidity, reserve_native).unwrap_or_default();
+        let by_tokens = mul_div(tokens, total_liquidity, reserve_token).unwrap_or_default();
+        by_native.min(by_tokens)
+    }
+
+    /// Integer square root of `a * b`, rounded down, without overflowing on
+    /// the product.
+    fn sqrt_product(a: Balance, b: Balance) -> Balance {
+        if a == 0 || b == 0 {
+            return 0
+        }
+        // Newton's method from above; `x` never drops below the rounded-down
+        // root, so `a * b / x` fits.
+        let mut x = a.max(b);
+        loop {
+            let quotient = mul_div(a, b, x).expect("the root never exceeds the larger factor");
+            let y = x.min(quotient) + x.abs_diff(quotient) / 2;
+            if y >= x {
+                return x
+            }
+            x = y;
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        use ink_lang as ink;
+
+        #[test]
+        fn amount_out_keeps_the_product_and_fee() {
+            assert_eq!(amount_out(100, 0, 1_000), 0);
+            // Without the fee this would pay out 90.
+            assert_eq!(amount_out(100, 1_000, 1_000), 90);
+            let out = amount_out(1_000, 10_000, 50_000);
+            assert!((10_000 + 1_000) * (50_000 - out) >= 10_000 * 50_000);
+            // Reserves whose products overflow still price correctly.
+            assert_eq!(amount_out(u128::MAX / 2, u128::MAX / 2, 1_000), 499);
+        }
+
+        #[test]
+        fn liquidity_follows_the_smaller_leg() {
+            assert_eq!(liquidity_for(100, 400, 0, 0, 0), 200);
+            assert_eq!(liquidity_for(50, 200, 100, 400, 200), 100);
+            assert_eq!(liquidity_for(50, 100, 100, 400, 200), 50);
+            assert_eq!(liquidity_for(u128::MAX, u128::MAX, 0, 0, 0), u128::MAX);
+            assert_eq!(liquidity_for(u128::MAX, 1, u128::MAX, 1, 1_000), 1_000);
+        }
+
+        #[test]
+        fn sqrt_product_rounds_down() {
+            assert_eq!(sqrt_product(0, 7), 0);
+            assert_eq!(sqrt_product(1, 1), 1);
+            assert_eq!(sqrt_product(3, 5), 3);
+            assert_eq!(sqrt_product(4, 4), 4);
+            assert_eq!(sqrt_product(2, 8), 4);
+            assert_eq!(sqrt_product(u128::MAX, 1), u64::MAX as u128);
+            assert_eq!(sqrt_product(u128::MAX, u128::MAX), u128::MAX);
+        }
+
+        #[ink::test]
+        fn new_pool_is_empty() {
+            let token = AccountId::from([0x7; 32]);
+            let mut pair = Pair::new(token);
+            assert_eq!(pair.token(), token);
+            assert_eq!(pair.reserves(), (0, 0));
+            assert_eq!(pair.total_liquidity(), 0);
+            assert_eq!(pair.remove_liquidity(1), Err(Error::InsufficientLiquidity));
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! Storage cost report for every contract message.
+//!
+//! The off-chain environment has no weight metering, so this reports what
+//! drives weight and storage deposit on-chain instead: contract storage reads
+//! and writes per call and the number of storage cells the call adds. Each
+//! message runs the way the ink! dispatcher executes it, loading the storage
+//! root before the call and writing it back afterwards for mutating messages.
+//!
+//! Run with `cargo bench --features benchmarks`.
+
+use erc20::Erc20;
+use ink_env::{test, AccountId, DefaultEnvironment};
+use ink_primitives::Key;
+use ink_storage::traits::{pull_spread_root, push_spread_root};
+
+/// Storage root key used by the ink! dispatcher.
+const ROOT_KEY: Key = Key::new([0x00; 32]);
+
+/// Numbers of token holders the messages are measured against.
+const STATE_SIZES: &[u32] = &[1, 100, 10_000];
+
+#[derive(Debug)]
+struct Cost {
+    reads: usize,
+    writes: usize,
+    new_cells: isize,
+}
+
+fn account(index: u32) -> AccountId {
+    let mut bytes = [0xff; 32];
+    bytes[..4].copy_from_slice(&index.to_be_bytes());
+    AccountId::from(bytes)
+}
+
+fn alice() -> AccountId {
+    test::default_accounts::<DefaultEnvironment>().alice
+}
+
+fn used_cells() -> isize {
+    let callee = test::callee::<DefaultEnvironment>();
+    test::count_used_storage_cells::<DefaultEnvironment>(&callee).unwrap_or(0) as isize
+}
+
+/// Executes `message` against the stored contract and reports its storage cost.
+fn measure<R>(mutates: bool, message: impl FnOnce(&mut Erc20) -> R) -> Cost {
+    let callee = test::callee::<DefaultEnvironment>();
+    let (reads_before, writes_before) = test::get_contract_storage_rw::<DefaultEnvironment>(&callee);
+    let cells_before = used_cells();
+
+    let mut contract = pull_spread_root::<Erc20>(&ROOT_KEY);
+    let _ = message(&mut contract);
+    if mutates {
+        push_spread_root(&contract, &ROOT_KEY);
+    }
+
+    let (reads, writes) = test::get_contract_storage_rw::<DefaultEnvironment>(&callee);
+    Cost {
+        reads: reads - reads_before,
+        writes: writes - writes_before,
+        new_cells: used_cells() - cells_before,
+    }
+}
+
+/// Deploys the contract and spreads the supply over `holders` accounts.
+fn deploy(holders: u32) {
+    test::set_caller::<DefaultEnvironment>(alice());
+    let mut contract = Erc20::new(holders as u128 * 1_000);
+    for index in 1..holders {
+        contract.transfer(account(index), 1_000).unwrap();
+    }
+    push_spread_root(&contract, &ROOT_KEY);
+}
+
+fn report(holders: u32) {
+    test::run_test::<DefaultEnvironment, _>(|accounts| {
+        deploy(holders);
+        let fresh = account(u32::MAX);
+
+        let rows: Vec<(&str, Cost)> = vec![
+            ("total_supply", measure(false, |c| c.total_supply())),
+            ("balance_of", measure(false, |c| c.balance_of(accounts.alice))),
+            ("transfer (new holder)", measure(true, |c| c.transfer(fresh, 1))),
+            ("transfer (existing holder)", measure(true, |c| c.transfer(fresh, 1))),
+            ("transfer (to self)", measure(true, |c| c.transfer(accounts.alice, 1))),
+            ("approve", measure(true, |c| c.approve(accounts.bob, 100))),
+            ("allowance", measure(false, |c| c.allowance(accounts.alice, accounts.bob))),
+            ("transfer_from", {
+                test::set_caller::<DefaultEnvironment>(accounts.bob);
+                measure(true, |c| c.transfer_from(accounts.alice, accounts.bob, 1))
+            }),
+            ("transfer_from (zero value)", {
+                measure(true, |c| c.transfer_from(accounts.alice, accounts.bob, 0))
+            }),
+        ];
+
+        println!("\n{} holder(s)", holders);
+        println!("{:<28} {:>6} {:>7} {:>10}", "message", "reads", "writes", "new cells");
+        for (message, cost) in rows {
+            println!(
+                "{:<28} {:>6} {:>7} {:>10}",
+                message, cost.reads, cost.writes, cost.new_cells
+            );
+        }
+        Ok(())
+    })
+    .unwrap();
+}
+
+fn main() {
+    for &holders in STATE_SIZES {
+        report(holders);
+    }
+}
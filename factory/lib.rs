@@ -0,0 +1,169 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use ink_lang as ink;
+
+pub use self::factory::{Factory, FactoryRef};
+
+/// Deploys `erc20` tokens from uploaded code and keeps a registry of them.
+#[ink::contract]
+mod factory {
+    use erc20::Erc20Ref;
+    use ink_lang::{codegen::EmitEvent, ToAccountId};
+    use ink_prelude::{string::String, vec::Vec};
+    use ink_storage::{traits::SpreadAllocate, Mapping};
+
+    #[ink(storage)]
+    #[derive(SpreadAllocate)]
+    pub struct Factory {
+        /// Code hash of the uploaded `erc20` contract.
+        token_code_hash: Hash,
+        /// All tokens in the order they were created.
+        tokens: Mapping<u32, AccountId>,
+        tokens_count: u32,
+        /// Tokens of each creator in the order they were created.
+        creator_tokens: Mapping<(AccountId, u32), AccountId>,
+        creator_counts: Mapping<AccountId, u32>,
+    }
+
+    /// The factory error types.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Returned if instantiating the token fails.
+        InstantiationFailed,
+    }
+
+    /// The factory result type.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    #[ink(event)]
+    pub struct TokenCreated {
+        #[ink(topic)]
+        creator: AccountId,
+        #[ink(topic)]
+        token: AccountId,
+        name: String,
+        symbol: String,
+        initial_supply: Balance,
+    }
+
+    impl Factory {
+        /// Creates a factory deploying the `erc20` code uploaded under
+        /// `token_code_hash`.
+        #[ink(constructor)]
+        pub fn new(token_code_hash: Hash) -> Self {
+            ink_lang::utils::initialize_contract(|factory: &mut Self| {
+                factory.token_code_hash = token_code_hash;
+            })
+        }
+
+        /// Returns the code hash tokens are deployed from.
+        #[ink(message)]
+        pub fn token_code_hash(&self) -> Hash {
+            self.token_code_hash
+        }
+
+        /// Deploys a token crediting `initial_supply` to the caller, who also
+        /// becomes its owner and minter, and returns its address.
+        #[ink(message)]
+        pub fn create_token(
+            &mut self,
+            name: String,
+            symbol: String,
+            decimals: u8,
+            initial_supply: Balance,
+        ) -> Result<AccountId> {
+            let creator = self.env().caller();
+            // Unique per creator and token, so creators cannot collide.
+            let salt = scale::Encode::encode(&(creator, self.creator_count(creator)));
+            let token = Erc20Ref::new_with_metadata(
+                Some(name.clone()),
+                Some(symbol.clone()),
+                decimals,
+                initial_supply,
+                creator,
+                creator,
+            )
+            .code_hash(self.token_code_hash)
+            .endowment(0)
+            .salt_bytes(salt)
+            .instantiate()
+            .map_err(|_| Error::InstantiationFailed)?;
+            let token = token.to_account_id();
+            self.record(creator, token);
+            EmitEvent::<Factory>::emit_event(
+                self.env(),
+                TokenCreated {
+                    creator,
+                    token,
+                    name,
+                    symbol,
+                    initial_supply,
+                },
+            );
+            Ok(token)
+        }
+
+        /// Returns the number of tokens created.
+        #[ink(message)]
+        pub fn tokens_count(&self) -> u32 {
+            self.tokens_count
+        }
+
+        /// Returns up to `limit` tokens starting at `offset`, oldest first.
+        #[ink(message)]
+        pub fn all_tokens(&self, offset: u32, limit: u32) -> Vec<AccountId> {
+            let end = offset.saturating_add(limit).min(self.tokens_count);
+            (offset..end).filter_map(|index| self.tokens.get(index)).collect()
+        }
+
+        /// Returns the number of tokens `creator` created.
+        #[ink(message)]
+        pub fn creator_count(&self, creator: AccountId) -> u32 {
+            self.creator_counts.get(creator).unwrap_or_default()
+        }
+
+        /// Returns the tokens `creator` created, oldest first.
+        #[ink(message)]
+        pub fn tokens_of(&self, creator: AccountId) -> Vec<AccountId> {
+            (0..self.creator_count(creator))
+                .filter_map(|index| self.creator_tokens.get((creator, index)))
+                .collect()
+        }
+
+        fn record(&mut self, creator: AccountId, token: AccountId) {
+            self.tokens.insert(self.tokens_count, &token);
+            self.tokens_count += 1;
+            let count = self.creator_count(creator);
+            self.creator_tokens.insert((creator, count), &token);
+            self.creator_counts.insert(creator, &(count + 1));
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        use ink_lang as ink;
+
+        #[ink::test]
+        fn registry_lists_tokens_by_creator() {
+            let mut factory = Factory::new(Hash::from([0x7; 32]));
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let tokens: Vec<_> = (0..3).map(|index| AccountId::from([0x10 + index; 32])).collect();
+            factory.record(alice, tokens[0]);
+            factory.record(bob, tokens[1]);
+            factory.record(alice, tokens[2]);
+
+            assert_eq!(factory.token_code_hash(), Hash::from([0x7; 32]));
+            assert_eq!(factory.tokens_count(), 3);
+            assert_eq!(factory.all_tokens(0, 10), tokens);
+            assert_eq!(factory.all_tokens(1, 1), vec![tokens[1]]);
+            assert_eq!(factory.all_tokens(5, 1), vec![]);
+            assert_eq!(factory.tokens_of(alice), vec![tokens[0], tokens[2]]);
+            assert_eq!(factory.creator_count(bob), 1);
+            assert_eq!(factory.tokens_of(AccountId::from([0x3; 32])), vec![]);
+        }
+    }
+}
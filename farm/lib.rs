@@ -0,0 +1,936 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use ink_lang as ink;
+
+pub use self::farm::{Farm, FarmRef};
+
+/// Liquidity mining programs paying out the `erc20` token to stakers of PSP22
+/// tokens, such as the liquidity shares of a pool.
+///
+/// Every program streams its rewards per second to the stakers of its staked
+/// token, pro rata to their stakes. The owner funds a program in full when
+/// creating it, so the reward token must not take transfer fees from the
+/// farm. Rewards streamed while nothing is staked stay in the farm.
+///
+/// Programs may stake the reward token itself, whose stakers can then have
+/// their rewards compounded into their stakes.
+#[ink::contract]
+mod farm {
+    use erc20::{math::mul_div, Erc20Ref};
+    use ink_env::{
+        call::{build_call, Call, ExecutionInput, Selector},
+        DefaultEnvironment,
+    };
+    use ink_lang::codegen::EmitEvent;
+    use ink_prelude::vec::Vec;
+    use ink_storage::{
+        traits::{PackedLayout, SpreadAllocate, SpreadLayout},
+        Mapping,
+    };
+
+    /// Identifies a reward program.
+    pub type ProgramId = u32;
+
+    /// Fixed-point scale of the rewards-per-stake accumulator.
+    const PRECISION: u128 = 1 << 64;
+
+    /// Milliseconds per second, the unit reward rates are given in.
+    const MILLIS_PER_SECOND: u128 = 1_000;
+
+    /// Upper bound for the unbonding chunks an account has in a program at
+    /// once, since they are kept in a single cell.
+    const MAX_UNBONDING_CHUNKS: usize = 16;
+
+    /// Upper bound for the cut of compounded rewards paid to the caller, in
+    /// basis points.
+    const MAX_COMPOUND_INCENTIVE: u16 = 500;
+
+    /// A reward program and its accounting.
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Program {
+        /// The PSP22 token staked in the program.
+        pub staked_token: AccountId,
+        /// Rewards streamed per second.
+        pub reward_rate: Balance,
+        /// Time the program stops streaming rewards.
+        pub end: Timestamp,
+        /// Tokens staked in the program.
+        pub total_staked: Balance,
+        /// Rewards streamed per staked token so far, scaled by `PRECISION`.
+        pub rewards_per_stake: u128,
+        /// Time the rewards were last brought up to date.
+        pub updated_at: Timestamp,
+    }
+
+    impl Program {
+        /// Returns the program with its rewards brought up to `now`.
+        fn at(&self, now: Timestamp) -> Option<Self> {
+            let now = now.min(self.end);
+            if now <= self.updated_at {
+                return Some(self.clone())
+            }
+            let mut program = self.clone();
+            program.updated_at = now;
+            if self.total_staked > 0 {
+                let elapsed = u128::from(now - self.updated_at);
+                let streamed = mul_div(self.reward_rate, elapsed, MILLIS_PER_SECOND)?;
+                program.rewards_per_stake = self
+                    .rewards_per_stake
+                    .checked_add(mul_div(streamed, PRECISION, self.total_staked)?)?;
+            }
+            Some(program)
+        }
+    }
+
+    /// A stake in a program.
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Stake {
+        pub amount: Balance,
+        /// The program's `rewards_per_stake` when the rewards were settled.
+        pub rewards_per_stake: u128,
+        /// Rewards settled but not claimed yet.
+        pub unclaimed: Balance,
+    }
+
+    impl Stake {
+        /// Returns the stake with the rewards streamed to it settled.
+        fn settled(&self, program: &Program) -> Option<Self> {
+            let growth = program.rewards_per_stake - self.rewards_per_stake;
+            let earned = mul_div(self.amount, growth, PRECISION)?;
+            Some(Self {
+                rewards_per_stake: program.rewards_per_stake,
+                unclaimed: self.unclaimed.checked_add(earned)?,
+                ..self.clone()
+            })
+        }
+    }
+
+    /// Tokens leaving a stake, released once they mature.
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Unbonding {
+        pub amount: Balance,
+        pub matures_at: Timestamp,
+    }
+
+    #[ink(storage)]
+    #[derive(SpreadAllocate)]
+    pub struct Farm {
+        /// The `erc20` token paid out as rewards.
+        reward_token: AccountId,
+        /// The account allowed to create programs.
+        owner: AccountId,
+        programs: Mapping<ProgramId, Program>,
+        programs_count: ProgramId,
+        stakes: Mapping<(ProgramId, AccountId), Stake>,
+        /// The account allowed to slash stakes.
+        slasher: Option<AccountId>,
+        /// The account slashed stakes go to, rather than being burnt.
+        insurance_fund: Option<AccountId>,
+        /// Time unstaked tokens take to mature.
+        unbonding_period: Timestamp,
+        unbondings: Mapping<(ProgramId, AccountId), Vec<Unbonding>>,
+        /// Cut of compounded rewards paid to the caller, in basis points.
+        compound_incentive: u16,
+    }
+
+    /// The farm error types.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Returned if the caller is not the owner.
+        NotOwner,
+        /// Returned if no program has the given id.
+        ProgramNotFound,
+        /// Returned if a program has ended.
+        ProgramEnded,
+        /// Returned if a program, deposit or withdrawal comes to nothing.
+        ZeroAmount,
+        /// Returned if a withdrawal exceeds the caller's stake.
+        InsufficientStake,
+        /// Returned if an amount does not fit into a balance.
+        Overflow,
+        /// Returned if a call to the staked token fails.
+        StakedTokenCallFailed,
+        /// Returned if a call to the reward token fails.
+        RewardTokenCallFailed,
+        /// Returned if the caller is not the slasher.
+        NotSlasher,
+        /// Returned if an account already has `MAX_UNBONDING_CHUNKS` chunks
+        /// unbonding in a program.
+        TooManyUnbondings,
+        /// Returned if rewards are compounded in a program that does not
+        /// stake the reward token.
+        NotCompoundable,
+        /// Returned if the compound incentive exceeds `MAX_COMPOUND_INCENTIVE`.
+        IncentiveTooHigh,
+    }
+
+    /// The farm result type.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    #[ink(event)]
+    pub struct ProgramCreated {
+        #[ink(topic)]
+        program: ProgramId,
+        #[ink(topic)]
+        staked_token: AccountId,
+        reward_rate: Balance,
+        end: Timestamp,
+    }
+
+    #[ink(event)]
+    pub struct Deposited {
+        #[ink(topic)]
+        program: ProgramId,
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct UnstakeRequested {
+        #[ink(topic)]
+        program: ProgramId,
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+        matures_at: Timestamp,
+    }
+
+    #[ink(event)]
+    pub struct Withdrawn {
+        #[ink(topic)]
+        program: ProgramId,
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct Slashed {
+        #[ink(topic)]
+        program: ProgramId,
+        #[ink(topic)]
+        account: AccountId,
+        amount: Balance,
+        /// The stake left to the account.
+        remaining: Balance,
+        /// Where the tokens went, or `None` if they were burnt.
+        insurance_fund: Option<AccountId>,
+        reason: Vec<u8>,
+    }
+
+    #[ink(event)]
+    pub struct Compounded {
+        #[ink(topic)]
+        program: ProgramId,
+        #[ink(topic)]
+        account: AccountId,
+        /// Rewards added to the stake.
+        restaked: Balance,
+        /// Rewards paid to `caller` for compounding.
+        incentive: Balance,
+        caller: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct RewardsClaimed {
+        #[ink(topic)]
+        program: ProgramId,
+        #[ink(topic)]
+        account: AccountId,
+        rewards: Balance,
+    }
+
+    impl Farm {
+        /// Creates a farm paying out `reward_token`, owned by the caller.
+        #[ink(constructor)]
+        pub fn new(reward_token: AccountId) -> Self {
+            ink_lang::utils::initialize_contract(|farm: &mut Self| {
+                farm.reward_token = reward_token;
+                farm.owner = Self::env().caller();
+            })
+        }
+
+        /// Returns the token paid out as rewards.
+        #[ink(message)]
+        pub fn reward_token(&self) -> AccountId {
+            self.reward_token
+        }
+
+        /// Returns the account allowed to create programs.
+        #[ink(message)]
+        pub fn owner(&self) -> AccountId {
+            self.owner
+        }
+
+        /// Returns the account allowed to slash stakes.
+        #[ink(message)]
+        pub fn slasher(&self) -> Option<AccountId> {
+            self.slasher
+        }
+
+        /// Sets the account allowed to slash stakes, or disables slashing.
+        ///
+        /// Only the owner may call this.
+        #[ink(message)]
+        pub fn set_slasher(&mut self, slasher: Option<AccountId>) -> Result<()> {
+            self.ensure_owner()?;
+            self.slasher = slasher;
+            Ok(())
+        }
+
+        /// Returns the account slashed stakes go to, if they are not burnt.
+        #[ink(message)]
+        pub fn insurance_fund(&self) -> Option<AccountId> {
+            self.insurance_fund
+        }
+
+        /// Sends slashed stakes to `insurance_fund`, or burns them if `None`.
+        ///
+        /// Only the owner may call this.
+        #[ink(message)]
+        pub fn set_insurance_fund(&mut self, insurance_fund: Option<AccountId>) -> Result<()> {
+            self.ensure_owner()?;
+            self.insurance_fund = insurance_fund;
+            Ok(())
+        }
+
+        /// Returns the time unstaked tokens take to mature.
+        #[ink(message)]
+        pub fn unbonding_period(&self) -> Timestamp {
+            self.unbonding_period
+        }
+
+        /// Sets the time tokens unstaked from now on take to mature.
+        ///
+        /// Only the owner may call this.
+        #[ink(message)]
+        pub fn set_unbonding_period(&mut self, unbonding_period: Timestamp) -> Result<()> {
+            self.ensure_owner()?;
+            self.unbonding_period = unbonding_period;
+            Ok(())
+        }
+
+        /// Returns the chunks `account` has unbonding in a program, oldest
+        /// first.
+        #[ink(message)]
+        pub fn unbondings_of(&self, program: ProgramId, account: AccountId) -> Vec<Unbonding> {
+            self.unbondings.get((program, account)).unwrap_or_default()
+        }
+
+        /// Returns the unbonded tokens `account` can withdraw from a program.
+        #[ink(message)]
+        pub fn withdrawable_of(&self, program: ProgramId, account: AccountId) -> Balance {
+            let now = self.env().block_timestamp();
+            self.unbondings_of(program, account)
+                .iter()
+                .filter(|chunk| chunk.matures_at <= now)
+                .map(|chunk| chunk.amount)
+                .sum()
+        }
+
+        /// Returns the cut of compounded rewards paid to the caller, in basis
+        /// points.
+        #[ink(message)]
+        pub fn compound_incentive(&self) -> u16 {
+            self.compound_incentive
+        }
+
+        /// Sets the cut of compounded rewards paid to the caller, at most
+        /// `MAX_COMPOUND_INCENTIVE` basis points.
+        ///
+        /// Only the owner may call this.
+        #[ink(message)]
+        pub fn set_compound_incentive(&mut self, compound_incentive: u16) -> Result<()> {
+            self.ensure_owner()?;
+            if compound_incentive > MAX_COMPOUND_INCENTIVE {
+                return Err(Error::IncentiveTooHigh)
+            }
+            self.compound_incentive = compound_incentive;
+            Ok(())
+        }
+
+        /// Returns the number of programs created.
+        #[ink(message)]
+        pub fn programs_count(&self) -> ProgramId {
+            self.programs_count
+        }
+
+        /// Returns a program with its rewards brought up to now.
+        #[ink(message)]
+        pub fn program(&self, program: ProgramId) -> Option<Program> {
+            self.programs
+                .get(program)
+                .and_then(|program| program.at(self.env().block_timestamp()))
+        }
+
+        /// Returns the tokens `account` has staked in a program.
+        #[ink(message)]
+        pub fn staked_of(&self, program: ProgramId, account: AccountId) -> Balance {
+            self.stakes
+                .get((program, account))
+                .unwrap_or_default()
+                .amount
+        }
+
+        /// Returns the rewards `account` can claim from a program.
+        #[ink(message)]
+        pub fn pending_rewards(&self, program: ProgramId, account: AccountId) -> Balance {
+            self.settle(program, account)
+                .map(|(_, stake)| stake.unclaimed)
+                .unwrap_or_default()
+        }
+
+        /// Starts a program streaming `reward_rate` tokens per second to the
+        /// stakers of `staked_token` for `duration` milliseconds, and returns
+        /// its id. All of the program's rewards are pulled from the caller,
+        /// who must have approved the farm to spend them.
+        ///
+        /// Only the owner may call this.
+        #[ink(message)]
+        pub fn create_program(
+            &mut self,
+            staked_token: AccountId,
+            reward_rate: Balance,
+            duration: Timestamp,
+        ) -> Result<ProgramId> {
+            self.ensure_owner()?;
+            let rewards = mul_div(reward_rate, duration.into(), MILLIS_PER_SECOND)
+                .ok_or(Error::Overflow)?;
+            if rewards == 0 {
+                return Err(Error::ZeroAmount)
+            }
+            let now = self.env().block_timestamp();
+            let end = now.checked_add(duration).ok_or(Error::Overflow)?;
+            self.reward_token_ref()
+                .transfer_from(self.owner, self.env().account_id(), rewards)
+                .map_err(|_| Error::RewardTokenCallFailed)?;
+            let id = self.programs_count;
+            let program = Program {
+                staked_token,
+                reward_rate,
+                end,
+                updated_at: now,
+                ..Default::default()
+            };
+            self.programs.insert(id, &program);
+            self.programs_count += 1;
+            EmitEvent::<Farm>::emit_event(
+                self.env(),
+                ProgramCreated {
+                    program: id,
+                    staked_token,
+                    reward_rate,
+                    end,
+                },
+            );
+            Ok(id)
+        }
+
+        /// Stakes `amount` of the program's staked token, which the caller
+        /// must have approved the farm to pull.
+        #[ink(message)]
+        pub fn deposit(&mut self, program: ProgramId, amount: Balance) -> Result<()> {
+            if amount == 0 {
+                return Err(Error::ZeroAmount)
+            }
+            let account = self.env().caller();
+            let (mut state, mut stake) = self.settle(program, account)?;
+            if state.updated_at >= state.end {
+                return Err(Error::ProgramEnded)
+            }
+            self.pull_staked(state.staked_token, account, amount)?;
+            state.total_staked = state.total_staked.checked_add(amount).ok_or(Error::Overflow)?;
+            stake.amount += amount;
+            self.programs.insert(program, &state);
+            self.stakes.insert((program, account), &stake);
+            EmitEvent::<Farm>::emit_event(
+                self.env(),
+                Deposited {
+                    program,
+                    account,
+                    amount,
+                },
+            );
+            Ok(())
+        }
+
+        /// Starts unbonding `amount` of the caller's stake. The tokens earn
+        /// no more rewards and can be withdrawn with `withdraw_unbonded` once
+        /// the unbonding period has passed. The rewards earned so far stay
+        /// claimable.
+        #[ink(message)]
+        pub fn request_unstake(&mut self, program: ProgramId, amount: Balance) -> Result<()> {
+            if amount == 0 {
+                return Err(Error::ZeroAmount)
+            }
+            let account = self.env().caller();
+            let (mut state, mut stake) = self.settle(program, account)?;
+            if stake.amount < amount {
+                return Err(Error::InsufficientStake)
+            }
+            let mut unbondings = self.unbondings_of(program, account);
+            if unbondings.len() >= MAX_UNBONDING_CHUNKS {
+                return Err(Error::TooManyUnbondings)
+            }
+            let matures_at = self
+                .env()
+                .block_timestamp()
+                .checked_add(self.unbonding_period)
+                .ok_or(Error::Overflow)?;
+            unbondings.push(Unbonding { amount, matures_at });
+            state.total_staked -= amount;
+            stake.amount -= amount;
+            self.programs.insert(program, &state);
+            self.stakes.insert((program, account), &stake);
+            self.unbondings.insert((program, account), &unbondings);
+            EmitEvent::<Farm>::emit_event(
+                self.env(),
+                UnstakeRequested {
+                    program,
+                    account,
+                    amount,
+                    matures_at,
+                },
+            );
+            Ok(())
+        }
+
+        /// Sends the caller's matured unbonding chunks back to them and
+        /// returns the amount.
+        #[ink(message)]
+        pub fn withdraw_unbonded(&mut self, program: ProgramId) -> Result<Balance> {
+            let account = self.env().caller();
+            let state = self.programs.get(program).ok_or(Error::ProgramNotFound)?;
+            let now = self.env().block_timestamp();
+            let (matured, unbonding): (Vec<_>, Vec<_>) = self
+                .unbondings_of(program, account)
+                .into_iter()
+                .partition(|chunk| chunk.matures_at <= now);
+            let amount: Balance = matured.iter().map(|chunk| chunk.amount).sum();
+            if amount == 0 {
+                return Err(Error::ZeroAmount)
+            }
+            if unbonding.is_empty() {
+                self.unbondings.remove((program, account));
+            } else {
+                self.unbondings.insert((program, account), &unbonding);
+            }
+            self.send_staked(state.staked_token, account, amount)?;
+            EmitEvent::<Farm>::emit_event(
+                self.env(),
+                Withdrawn {
+                    program,
+                    account,
+                    amount,
+                },
+            );
+            Ok(amount)
+        }
+
+        /// Pays out the caller's rewards from a program and returns them.
+        #[ink(message)]
+        pub fn claim(&mut self, program: ProgramId) -> Result<Balance> {
+            let account = self.env().caller();
+            let (state, mut stake) = self.settle(program, account)?;
+            let rewards = core::mem::take(&mut stake.unclaimed);
+            self.programs.insert(program, &state);
+            self.stakes.insert((program, account), &stake);
+            if rewards > 0 {
+                self.reward_token_ref()
+                    .transfer(account, rewards)
+                    .map_err(|_| Error::RewardTokenCallFailed)?;
+            }
+            EmitEvent::<Farm>::emit_event(
+                self.env(),
+                RewardsClaimed {
+                    program,
+                    account,
+                    rewards,
+                },
+            );
+            Ok(rewards)
+        }
+
+        /// Takes `amount` of `account`'s stake in a program, sending it to the
+        /// insurance fund or burning it if there is none. Tokens other than the
+        /// reward token are burnt through `PSP22Burnable::burn`. The rewards
+        /// earned so far stay claimable.
+        ///
+        /// Only the slasher may call this.
+        #[ink(message)]
+        pub fn slash(
+            &mut self,
+            program: ProgramId,
+            account: AccountId,
+            amount: Balance,
+            reason: Vec<u8>,
+        ) -> Result<()> {
+            if self.slasher != Some(self.env().caller()) {
+                return Err(Error::NotSlasher)
+            }
+            if amount == 0 {
+                return Err(Error::ZeroAmount)
+            }
+            let (mut state, mut stake) = self.settle(program, account)?;
+            if stake.amount < amount {
+                return Err(Error::InsufficientStake)
+            }
+            state.total_staked -= amount;
+            stake.amount -= amount;
+            self.programs.insert(program, &state);
+            self.stakes.insert((program, account), &stake);
+            match self.insurance_fund {
+                Some(fund) => self.send_staked(state.staked_token, fund, amount)?,
+                None => self.burn_staked(state.staked_token, amount)?,
+            }
+            EmitEvent::<Farm>::emit_event(
+                self.env(),
+                Slashed {
+                    program,
+                    account,
+                    amount,
+                    remaining: stake.amount,
+                    insurance_fund: self.insurance_fund,
+                    reason,
+                },
+            );
+            Ok(())
+        }
+
+        /// Restakes the rewards `account` has earned in a program staking the
+        /// reward token, less the compound incentive paid to the caller, and
+        /// returns the amount restaked. Anyone may call this.
+        #[ink(message)]
+        pub fn compound(&mut self, program: ProgramId, account: AccountId) -> Result<Balance> {
+            let (mut state, mut stake) = self.settle(program, account)?;
+            if state.staked_token != self.reward_token {
+                return Err(Error::NotCompoundable)
+            }
+            if state.updated_at >= state.end {
+                return Err(Error::ProgramEnded)
+            }
+            let rewards = core::mem::take(&mut stake.unclaimed);
+            if rewards == 0 {
+                return Err(Error::ZeroAmount)
+            }
+            let incentive = mul_div(rewards, self.compound_incentive.into(), 10_000)
+                .ok_or(Error::Overflow)?;
+            let restaked = rewards - incentive;
+            state.total_staked = state.total_staked.checked_add(restaked).ok_or(Error::Overflow)?;
+            stake.amount += restaked;
+            self.programs.insert(program, &state);
+            self.stakes.insert((program, account), &stake);
+            let caller = self.env().caller();
+            if incentive > 0 {
+                self.reward_token_ref()
+                    .transfer(caller, incentive)
+                    .map_err(|_| Error::RewardTokenCallFailed)?;
+            }
+            EmitEvent::<Farm>::emit_event(
+                self.env(),
+                Compounded {
+                    program,
+                    account,
+                    restaked,
+                    incentive,
+                    caller,
+                },
+            );
+            Ok(restaked)
+        }
+
+        /// Returns a program brought up to now and `account`'s stake in it
+        /// with its rewards settled.
+        fn settle(&self, id: ProgramId, account: AccountId) -> Result<(Program, Stake)> {
+            let program = self.program(id).ok_or(Error::ProgramNotFound)?;
+            let stake = self
+                .stakes
+                .get((id, account))
+                .unwrap_or_default()
+                .settled(&program)
+                .ok_or(Error::Overflow)?;
+            Ok((program, stake))
+        }
+
+        fn ensure_owner(&self) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner)
+            }
+            Ok(())
+        }
+
+        fn reward_token_ref(&self) -> Erc20Ref {
+            ink_env::call::FromAccountId::from_account_id(self.reward_token)
+        }
+
+        fn pull_staked(&self, token: AccountId, from: AccountId, amount: Balance) -> Result<()> {
+            if token == self.reward_token {
+                return self
+                    .reward_token_ref()
+                    .transfer_from(from, self.env().account_id(), amount)
+                    .map_err(|_| Error::StakedTokenCallFailed)
+            }
+            build_call::<DefaultEnvironment>()
+                .call_type(Call::new().callee(token))
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink_lang::selector_bytes!(
+                        "PSP22::transfer_from"
+                    )))
+                    .push_arg(from)
+                    .push_arg(self.env().account_id())
+                    .push_arg(amount)
+                    .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<core::result::Result<(), u8>>()
+                .fire()
+                .ok()
+                .and_then(|result| result.ok())
+                .ok_or(Error::StakedTokenCallFailed)
+        }
+
+        fn send_staked(&self, token: AccountId, to: AccountId, amount: Balance) -> Result<()> {
+            if token == self.reward_token {
+                return self
+                    .reward_token_ref()
+                    .transfer(to, amount)
+                    .map_err(|_| Error::StakedTokenCallFailed)
+            }
+            build_call::<DefaultEnvironment>()
+                .call_type(Call::new().callee(token))
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink_lang::selector_bytes!(
+                        "PSP22::transfer"
+                    )))
+                    .push_arg(to)
+                    .push_arg(amount)
+                    .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<core::result::Result<(), u8>>()
+                .fire()
+                .ok()
+                .and_then(|result| result.ok())
+                .ok_or(Error::StakedTokenCallFailed)
+        }
+
+        fn burn_staked(&self, token: AccountId, amount: Balance) -> Result<()> {
+            if token == self.reward_token {
+                return self
+                    .reward_token_ref()
+                    .burn(amount)
+                    .map_err(|_| Error::StakedTokenCallFailed)
+            }
+            build_call::<DefaultEnvironment>()
+                .call_type(Call::new().callee(token))
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink_lang::selector_bytes!(
+                        "PSP22Burnable::burn"
+                    )))
+                    .push_arg(self.env().account_id())
+                    .push_arg(amount),
+                )
+                .returns::<core::result::Result<(), u8>>()
+                .fire()
+                .ok()
+                .and_then(|result| result.ok())
+                .ok_or(Error::StakedTokenCallFailed)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        use ink_lang as ink;
+
+        fn program(total_staked: Balance) -> Program {
+            Program {
+                reward_rate: 10,
+                end: 10_000,
+                total_staked,
+                ..Default::default()
+            }
+        }
+
+        #[test]
+        fn rewards_stream_pro_rata_until_the_end() {
+            let program = program(400);
+            let alice = Stake {
+                amount: 100,
+                ..Default::default()
+            };
+            let bob = Stake {
+                amount: 300,
+                ..Default::default()
+            };
+            // Five seconds stream 50 tokens, split one to three.
+            let halfway = program.at(5_000).unwrap();
+            assert_eq!(alice.settled(&halfway).unwrap().unclaimed, 12);
+            assert_eq!(bob.settled(&halfway).unwrap().unclaimed, 37);
+            // Nothing streams past the end of the program.
+            let ended = program.at(20_000).unwrap();
+            assert_eq!(ended.updated_at, 10_000);
+            assert_eq!(alice.settled(&ended).unwrap().unclaimed, 25);
+            assert_eq!(bob.settled(&ended).unwrap().unclaimed, 75);
+        }
+
+        #[test]
+        fn settled_rewards_are_not_paid_twice() {
+            let stake = Stake {
+                amount: 100,
+                ..Default::default()
+            };
+            let halfway = program(100).at(5_000).unwrap();
+            let stake = stake.settled(&halfway).unwrap();
+            assert_eq!(stake.settled(&halfway).unwrap().unclaimed, 50);
+            let ended = halfway.at(10_000).unwrap();
+            assert_eq!(stake.settled(&ended).unwrap().unclaimed, 100);
+        }
+
+        #[test]
+        fn nothing_streams_without_stakers() {
+            let idle = program(0).at(5_000).unwrap();
+            assert_eq!(idle.rewards_per_stake, 0);
+            assert_eq!(idle.updated_at, 5_000);
+        }
+
+        #[ink::test]
+        fn only_the_owner_creates_programs() {
+            let reward_token = AccountId::from([0x7; 32]);
+            let mut farm = Farm::new(reward_token);
+            assert_eq!(farm.reward_token(), reward_token);
+            assert_eq!(farm.owner(), AccountId::from([0x1; 32]));
+            assert_eq!(farm.programs_count(), 0);
+            assert_eq!(farm.program(0), None);
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(AccountId::from([0x2; 32]));
+            assert_eq!(
+                farm.create_program(AccountId::from([0x8; 32]), 10, 1_000),
+                Err(Error::NotOwner)
+            );
+            assert_eq!(farm.deposit(0, 1), Err(Error::ProgramNotFound));
+            assert_eq!(farm.request_unstake(0, 0), Err(Error::ZeroAmount));
+            assert_eq!(farm.set_unbonding_period(1_000), Err(Error::NotOwner));
+        }
+    
+        #[ink::test]
+        fn only_the_slasher_slashes() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut farm = Farm::new(AccountId::from([0x7; 32]));
+            farm.programs.insert(0, &program(100));
+            let stake = Stake {
+                amount: 100,
+                ..Default::default()
+            };
+            farm.stakes.insert((0, accounts.bob), &stake);
+            assert_eq!(
+                farm.slash(0, accounts.bob, 10, b"double sign".to_vec()),
+                Err(Error::NotSlasher)
+            );
+            assert_eq!(farm.set_insurance_fund(Some(accounts.django)), Ok(()));
+            assert_eq!(farm.insurance_fund(), Some(accounts.django));
+            assert_eq!(farm.set_slasher(Some(accounts.charlie)), Ok(()));
+            assert_eq!(farm.slasher(), Some(accounts.charlie));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(farm.set_slasher(None), Err(Error::NotOwner));
+            assert_eq!(farm.slash(1, accounts.bob, 10, Vec::new()), Err(Error::ProgramNotFound));
+            assert_eq!(farm.slash(0, accounts.bob, 0, Vec::new()), Err(Error::ZeroAmount));
+            assert_eq!(
+                farm.slash(0, accounts.bob, 101, Vec::new()),
+                Err(Error::InsufficientStake)
+            );
+            assert_eq!(farm.staked_of(0, accounts.bob), 100);
+        }
+    
+        #[ink::test]
+        fn unstaked_tokens_unbond_in_chunks() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let mut farm = Farm::new(AccountId::from([0x7; 32]));
+            assert_eq!(farm.set_unbonding_period(1_000), Ok(()));
+            farm.programs.insert(0, &program(100));
+            let stake = Stake {
+                amount: 100,
+                ..Default::default()
+            };
+            farm.stakes.insert((0, accounts.alice), &stake);
+            assert_eq!(farm.request_unstake(0, 101), Err(Error::InsufficientStake));
+            assert_eq!(farm.request_unstake(0, 30), Ok(()));
+            ink_env::test::advance_block::<ink_env::DefaultEnvironment>();
+            assert_eq!(farm.request_unstake(0, 20), Ok(()));
+            let first = farm.unbondings_of(0, accounts.alice)[0].matures_at;
+            assert_eq!(
+                farm.unbondings_of(0, accounts.alice),
+                [
+                    Unbonding {
+                        amount: 30,
+                        matures_at: first
+                    },
+                    Unbonding {
+                        amount: 20,
+                        matures_at: first + 6
+                    },
+                ]
+            );
+            // Unbonding tokens leave the stake and earn no rewards.
+            assert_eq!(farm.staked_of(0, accounts.alice), 50);
+            assert_eq!(farm.programs.get(0).unwrap().total_staked, 50);
+            assert_eq!(farm.withdrawable_of(0, accounts.alice), 0);
+            assert_eq!(farm.withdraw_unbonded(0), Err(Error::ZeroAmount));
+            for _ in 2..MAX_UNBONDING_CHUNKS {
+                assert_eq!(farm.request_unstake(0, 1), Ok(()));
+            }
+            assert_eq!(farm.request_unstake(0, 1), Err(Error::TooManyUnbondings));
+        }
+    
+        #[ink::test]
+        fn compounding_restakes_reward_token_rewards() {
+            let accounts = ink_env::test::default_accounts::<ink_env::DefaultEnvironment>();
+            let reward_token = AccountId::from([0x7; 32]);
+            let mut farm = Farm::new(reward_token);
+            assert_eq!(farm.set_compound_incentive(501), Err(Error::IncentiveTooHigh));
+            assert_eq!(farm.set_compound_incentive(500), Ok(()));
+            assert_eq!(farm.compound_incentive(), 500);
+            assert_eq!(farm.set_compound_incentive(0), Ok(()));
+            farm.programs.insert(0, &program(100));
+            let compoundable = Program {
+                staked_token: reward_token,
+                ..program(100)
+            };
+            farm.programs.insert(1, &compoundable);
+            let stake = Stake {
+                amount: 100,
+                unclaimed: 40,
+                ..Default::default()
+            };
+            farm.stakes.insert((0, accounts.bob), &stake);
+            farm.stakes.insert((1, accounts.bob), &stake);
+            assert_eq!(farm.compound(0, accounts.bob), Err(Error::NotCompoundable));
+            assert_eq!(farm.compound(1, accounts.alice), Err(Error::ZeroAmount));
+            // Anyone compounds on behalf of the staker.
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(farm.compound(1, accounts.bob), Ok(40));
+            assert_eq!(farm.staked_of(1, accounts.bob), 140);
+            assert_eq!(farm.pending_rewards(1, accounts.bob), 0);
+            assert_eq!(farm.programs.get(1).unwrap().total_staked, 140);
+            assert_eq!(farm.compound(1, accounts.bob), Err(Error::ZeroAmount));
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! Decodes the fuzzer input into a stream of contract operations and runs
+//! them against the off-chain environment.
+//!
+//! Run with `cargo fuzz run operations` from the repository root.
+
+#![no_main]
+
+use erc20::Erc20;
+use ink_env::{test, AccountId, DefaultEnvironment};
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+
+type Balance = <DefaultEnvironment as ink_env::Environment>::Balance;
+
+/// Accounts taking part in the decoded operations.
+const ACCOUNTS: u8 = 4;
+
+fn account(index: u8) -> AccountId {
+    AccountId::from([index % ACCOUNTS; 32])
+}
+
+#[derive(Debug, Arbitrary)]
+enum Op {
+    Transfer { caller: u8, to: u8, value: Balance },
+    Approve { caller: u8, spender: u8, value: Balance },
+    TransferFrom { caller: u8, from: u8, to: u8, value: Balance },
+    Mint { caller: u8, to: u8, value: Balance },
+}
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    initial_supply: Balance,
+    ops: Vec<Op>,
+}
+
+fuzz_target!(|input: Input| {
+    test::run_test::<DefaultEnvironment, _>(|_| {
+        test::set_caller::<DefaultEnvironment>(account(1));
+        let mut erc20 = Erc20::new(input.initial_supply);
+        for op in &input.ops {
+            let _ = match *op {
+                Op::Transfer { caller, to, value } => {
+                    test::set_caller::<DefaultEnvironment>(account(caller));
+                    erc20.transfer(account(to), value)
+                }
+                Op::Approve { caller, spender, value } => {
+                    test::set_caller::<DefaultEnvironment>(account(caller));
+                    erc20.approve(account(spender), value)
+                }
+                Op::TransferFrom { caller, from, to, value } => {
+                    test::set_caller::<DefaultEnvironment>(account(caller));
+                    erc20.transfer_from(account(from), account(to), value)
+                }
+                Op::Mint { caller, to, value } => {
+                    test::set_caller::<DefaultEnvironment>(account(caller));
+                    erc20.mint(account(to), value)
+                }
+            };
+            let held = (0..ACCOUNTS)
+                .map(|index| erc20.balance_of(account(index)))
+                .try_fold(0 as Balance, Balance::checked_add);
+            assert_eq!(held, Some(erc20.total_supply()), "{:?} broke supply accounting", op);
+        }
+        Ok(())
+    })
+    .unwrap();
+});
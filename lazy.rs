@@ -0,0 +1,77 @@
+//! Storage values that are only loaded when they are accessed.
+
+use ink_primitives::KeyPtr;
+use ink_storage::{
+    traits::{PackedLayout, SpreadAllocate, SpreadLayout},
+    Mapping,
+};
+
+/// A single value kept in its own storage cell.
+///
+/// Plain fields of the contract storage are read on every call and written
+/// back on every mutating call. A `Lazy` field is read and written only by
+/// the messages that actually use it.
+#[derive(Debug)]
+pub struct Lazy<T> {
+    cell: Mapping<(), T>,
+}
+
+impl<T: PackedLayout + scale::EncodeLike> Lazy<T> {
+    /// Returns the stored value, if any.
+    #[inline]
+    pub fn get(&self) -> Option<T> {
+        self.cell.get(())
+    }
+
+    /// Writes `value` into the storage cell.
+    #[inline]
+    pub fn set(&mut self, value: &T) {
+        self.cell.insert((), value);
+    }
+}
+
+impl<T: PackedLayout + scale::EncodeLike + Default> Lazy<T> {
+    /// Returns the stored value, or the default if it was never set.
+    #[inline]
+    pub fn get_or_default(&self) -> T {
+        self.get().unwrap_or_default()
+    }
+}
+
+impl<T> SpreadLayout for Lazy<T> {
+    const FOOTPRINT: u64 = <Mapping<(), T> as SpreadLayout>::FOOTPRINT;
+    const REQUIRES_DEEP_CLEAN_UP: bool = false;
+
+    #[inline]
+    fn pull_spread(ptr: &mut KeyPtr) -> Self {
+        Self {
+            cell: SpreadLayout::pull_spread(ptr),
+        }
+    }
+
+    #[inline]
+    fn push_spread(&self, ptr: &mut KeyPtr) {
+        SpreadLayout::push_spread(&self.cell, ptr)
+    }
+
+    #[inline]
+    fn clear_spread(&self, ptr: &mut KeyPtr) {
+        SpreadLayout::clear_spread(&self.cell, ptr)
+    }
+}
+
+impl<T> SpreadAllocate for Lazy<T> {
+    #[inline]
+    fn allocate_spread(ptr: &mut KeyPtr) -> Self {
+        Self {
+            cell: SpreadAllocate::allocate_spread(ptr),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: scale_info::TypeInfo + 'static> ink_storage::traits::StorageLayout for Lazy<T> {
+    fn layout(key_ptr: &mut KeyPtr) -> ink_metadata::layout::Layout {
+        <Mapping<(), T> as ink_storage::traits::StorageLayout>::layout(key_ptr)
+    }
+}
@@ -1,205 +1,14909 @@
 #![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "openbrush", feature(min_specialization))]
+// ink! 3 ignores `#[cfg]` on messages, events and storage fields, so
+// partial builds evaluate the feature gates before the contract macro runs.
+#![cfg_attr(not(feature = "full"), feature(cfg_eval))]
 
 use ink_lang as ink;
 
+#[cfg(not(feature = "openbrush"))]
+mod lazy;
+#[cfg(not(feature = "openbrush"))]
+pub mod math;
+#[cfg(all(feature = "oracle", not(feature = "openbrush")))]
+mod oracle;
+#[cfg(feature = "openbrush")]
+mod psp22;
+#[cfg(all(feature = "randomness", not(feature = "openbrush")))]
+mod randomness;
+#[cfg(not(feature = "openbrush"))]
+mod storage_key;
+#[cfg(all(feature = "std", not(feature = "openbrush")))]
+pub mod testing;
+#[cfg(all(feature = "xcm", not(feature = "openbrush")))]
+mod xcm;
+
+#[cfg(not(feature = "openbrush"))]
+pub use self::erc20::{Erc20, Erc20Ref};
+#[cfg(feature = "openbrush")]
+pub use self::psp22::erc20::{Erc20, Erc20Ref};
+
+// ink! 3 needs the storage, events and messages in the one inline module
+// below, so the extensions are feature gates within it rather than modules
+// of their own.
+#[cfg(not(feature = "openbrush"))]
+#[cfg_attr(not(feature = "full"), cfg_eval)]
+// Disabled hooks leave empty statements and unused parameters behind.
+#[cfg_attr(not(feature = "full"), allow(redundant_semicolons, unused_variables))]
 #[ink::contract]
 mod erc20 {
-    use ink_storage::{traits::SpreadAllocate, Mapping};
+    use crate::{lazy::Lazy, math::mul_div, storage_key::ManualKey};
+    #[cfg(any(
+        feature = "compliance",
+        feature = "dividends",
+        feature = "fees",
+        feature = "governance",
+        feature = "migration",
+    ))]
+    use ink_env::call::{self, build_call, ExecutionInput, Selector};
+    #[cfg(any(
+        feature = "airdrops",
+        feature = "evm",
+        feature = "raffles",
+        feature = "signed-transfers",
+        feature = "splitter",
+        feature = "vouchers",
+    ))]
+    use ink_env::hash::Blake2x256;
+    #[cfg(feature = "htlc")]
+    use ink_env::hash::Sha2x256;
+    #[cfg(any(feature = "fees", feature = "governance"))]
+    use ink_env::CallFlags;
+    use ink_prelude::{string::String, vec::Vec};
+    use ink_storage::{
+        traits::{PackedLayout, SpreadAllocate, SpreadLayout},
+        Mapping,
+    };
+
+    /// Denominator for rates expressed in basis points.
+    #[cfg(any(
+        feature = "emissions",
+        feature = "fees",
+        feature = "governance",
+        feature = "pausable",
+        feature = "sale",
+    ))]
+    const BASIS_POINTS: u16 = 10_000;
+
+    /// Upper bound for the burn rate: 10% of every transfer.
+    #[cfg(feature = "fees")]
+    pub const MAX_BURN_RATE: u16 = 1_000;
+
+    /// Upper bound for the tax rate: 10% of every transfer.
+    #[cfg(feature = "fees")]
+    pub const MAX_TAX_RATE: u16 = 1_000;
+
+    /// Upper bound for the referral rate: 10% of every purchase.
+    #[cfg(feature = "sale")]
+    pub const MAX_REFERRAL_RATE: u16 = 1_000;
+
+    /// Upper bound for the number of winners of a raffle, since the draw
+    /// pays out each of them.
+    #[cfg(feature = "raffles")]
+    pub const MAX_RAFFLE_WINNERS: u32 = 10;
+
+    /// How long after a raffle ends its winners can be drawn, in
+    /// milliseconds. Tickets of raffles not drawn by then are refunded.
+    #[cfg(feature = "raffles")]
+    pub const RAFFLE_DRAW_PERIOD: Timestamp = 24 * 60 * 60 * 1_000;
+
+    /// Upper bound for the number of partitions a holder's balance is split
+    /// across, since wiping an account clears each of them.
+    #[cfg(feature = "partitions")]
+    pub const MAX_PARTITIONS: u32 = 8;
+
+    /// Upper bound for the number of accounts excluded from the circulating
+    /// supply, since `circulating_supply` reads the balance of each of them.
+    #[cfg(feature = "circulating")]
+    pub const MAX_EXCLUDED_ACCOUNTS: u32 = 16;
+
+    /// Upper bound for the number of transfers kept in the transfer log, since
+    /// every slot holds on to storage.
+    #[cfg(feature = "transfer-log")]
+    pub const MAX_TRANSFER_LOG: u32 = 1_000;
+
+    /// Upper bound for the number of recipients `airdrop` processes per call,
+    /// so a single call stays within the block's gas limit.
+    #[cfg(feature = "airdrops")]
+    pub const MAX_AIRDROP_BATCH: u32 = 100;
+
+    /// Longest vote-escrow lock, four years in milliseconds. A lock this long
+    /// gives one unit of voting power per token.
+    #[cfg(feature = "vote-escrow")]
+    pub const MAX_VE_LOCK_TIME: Timestamp = 4 * 365 * 24 * 60 * 60 * 1_000;
+
+    /// Upper bound for the number of guardians of an account's recovery,
+    /// since they are kept in a single cell.
+    #[cfg(feature = "recovery")]
+    pub const MAX_RECOVERY_GUARDIANS: u32 = 8;
+
+    /// Upper bound for the number of mint approvers, since they are kept in
+    /// a single cell.
+    #[cfg(feature = "mintable")]
+    pub const MAX_MINT_APPROVERS: u32 = 16;
+
+    /// Fixed-point scale of `exchange_rate`: one token per share.
+    #[cfg(feature = "elastic")]
+    pub const EXCHANGE_RATE_SCALE: u128 = 1_000_000_000_000_000_000;
+
+    /// Fixed-point scale of the dividends-per-token accumulator.
+    #[cfg(feature = "dividends")]
+    const DIVIDEND_MAGNITUDE: u128 = 1 << 64;
+
+    /// Upper bound for the exponent of the bonding curve.
+    #[cfg(feature = "sale")]
+    pub const MAX_CURVE_EXPONENT: u32 = 4;
+
+    /// Upper bound for the number of PSP22 reward tokens, since every balance
+    /// change settles the rewards of each of them.
+    #[cfg(feature = "dividends")]
+    pub const MAX_REWARD_TOKENS: u32 = 8;
+
+    /// Version of the storage layout this code reads and writes, see `migrate`.
+    pub const STORAGE_VERSION: u32 = 1;
+
+    /// Version of the event formats, bumped whenever an event gains, loses or
+    /// reorders fields. Version 2 added `event_seq` to every event.
+    pub const EVENT_SCHEMA_VERSION: u32 = 2;
+
+    /// How long a positive answer of the KYC registry is trusted, in milliseconds.
+    #[cfg(feature = "compliance")]
+    pub const KYC_CACHE_TTL: Timestamp = 10 * 60 * 1000;
+
+    /// Prefix of the `HashedAddressMapping` pallet-evm uses to map H160
+    /// addresses to accounts.
+    #[cfg(feature = "evm")]
+    const EVM_ADDRESS_PREFIX: &[u8] = b"evm:";
+
+    /// Messages of this contract a governance proposal may call on it, see
+    /// `propose`. The contract's own balance holds escrowed and locked tokens
+    /// of its users, so messages that move tokens out of it are left out,
+    /// apart from `withdraw_treasury`, which only pays out the collected tax.
+    /// Roles such as the controller may still be handed out: `controller_transfer`
+    /// and `wipe` refuse the contract's balance.
+    #[cfg(feature = "governance")]
+    const GOVERNABLE_SELECTORS: [[u8; 4]; 57] = [
+        ink_lang::selector_bytes!("set_token_metadata"),
+        ink_lang::selector_bytes!("transfer_ownership"),
+        ink_lang::selector_bytes!("set_dead_man_switch"),
+        ink_lang::selector_bytes!("heartbeat"),
+        ink_lang::selector_bytes!("add_minter"),
+        ink_lang::selector_bytes!("remove_minter"),
+        ink_lang::selector_bytes!("set_max_mint_per_block"),
+        ink_lang::selector_bytes!("set_mint_approval"),
+        ink_lang::selector_bytes!("set_excluded_from_circulation"),
+        ink_lang::selector_bytes!("set_transfer_log_capacity"),
+        ink_lang::selector_bytes!("set_stats_enabled"),
+        ink_lang::selector_bytes!("set_zero_transfer_policy"),
+        ink_lang::selector_bytes!("set_denylisted"),
+        ink_lang::selector_bytes!("set_circuit_breaker"),
+        ink_lang::selector_bytes!("set_guardian"),
+        ink_lang::selector_bytes!("reset_breaker"),
+        ink_lang::selector_bytes!("freeze"),
+        ink_lang::selector_bytes!("unfreeze"),
+        ink_lang::selector_bytes!("set_controller"),
+        ink_lang::selector_bytes!("set_distributor"),
+        ink_lang::selector_bytes!("set_document"),
+        ink_lang::selector_bytes!("remove_document"),
+        ink_lang::selector_bytes!("set_accreditation_required"),
+        ink_lang::selector_bytes!("set_accredited"),
+        ink_lang::selector_bytes!("set_kyc_registry"),
+        ink_lang::selector_bytes!("set_launch_protection"),
+        ink_lang::selector_bytes!("clear_sniper"),
+        ink_lang::selector_bytes!("set_swap_config"),
+        ink_lang::selector_bytes!("set_tax_rate"),
+        ink_lang::selector_bytes!("set_trade_tax_rates"),
+        ink_lang::selector_bytes!("set_amm_pair"),
+        ink_lang::selector_bytes!("set_fee_exempt"),
+        ink_lang::selector_bytes!("set_treasury"),
+        ink_lang::selector_bytes!("withdraw_treasury"),
+        ink_lang::selector_bytes!("set_protocol_fee_share"),
+        ink_lang::selector_bytes!("set_fee_collector"),
+        ink_lang::selector_bytes!("set_burn_rate"),
+        ink_lang::selector_bytes!("set_max_tx_amount"),
+        ink_lang::selector_bytes!("set_tx_limit_exempt"),
+        ink_lang::selector_bytes!("set_max_wallet"),
+        ink_lang::selector_bytes!("set_wallet_limit_exempt"),
+        ink_lang::selector_bytes!("set_dust_threshold"),
+        ink_lang::selector_bytes!("set_rebaser"),
+        ink_lang::selector_bytes!("set_bridge"),
+        ink_lang::selector_bytes!("set_xcm_config"),
+        ink_lang::selector_bytes!("set_inflation"),
+        ink_lang::selector_bytes!("set_governance_config"),
+        ink_lang::selector_bytes!("set_oracle_config"),
+        ink_lang::selector_bytes!("set_fee_controller"),
+        ink_lang::selector_bytes!("set_code"),
+        ink_lang::selector_bytes!("terminate"),
+        ink_lang::selector_bytes!("migrate"),
+        ink_lang::selector_bytes!("set_mint_sale"),
+        ink_lang::selector_bytes!("set_redeem_rate"),
+        ink_lang::selector_bytes!("set_referral_rate"),
+        ink_lang::selector_bytes!("set_bonding_curve"),
+        ink_lang::selector_bytes!("set_randomness_config"),
+    ];
+
+    /// Mixed with the contract account to derive the payment splitter account.
+    #[cfg(feature = "splitter")]
+    const SPLITTER_SALT: &[u8] = b"payment-splitter";
+
+    /// Create storage for a simple ERC-20 contract.
+    ///
+    /// Every field is pinned to its storage key with `ManualKey`. New fields
+    /// take the next unused key, and keys of removed fields are never reused.
+    /// `storage_layout.json` records the current layout, so any change to it
+    /// shows up in review.
+    #[ink(storage)]
+    #[derive(SpreadAllocate)]
+    pub struct Erc20 {
+        /// Total token supply.
+        ///
+        /// Kept out of the eagerly loaded storage root since transfers never touch it.
+        /// Contracts deployed while it was a plain field keep it at key 0
+        /// itself, where this version does not look.
+        total_supply: ManualKey<Lazy<Balance>, 0>,
+        /// Mapping from owner to number of owned gons, see `GonsRate`.
+        balances: ManualKey<Mapping<AccountId, Balance>, 1>,
+        /// Balances that can be transferred by non-owners: (owner, spender) -> allowance
+        allowances: ManualKey<Mapping<(AccountId, AccountId), Allowance>, 2>,
+        /// Token name, if one was given at deployment.
+        name: ManualKey<Lazy<String>, 3>,
+        /// Token symbol, if one was given at deployment.
+        symbol: ManualKey<Lazy<String>, 4>,
+        /// Number of decimals used to display balances.
+        decimals: ManualKey<Lazy<u8>, 5>,
+        /// Account allowed to administer the token.
+        owner: ManualKey<Lazy<AccountId>, 6>,
+        /// Account allowed to mint new tokens.
+        minter: ManualKey<Lazy<AccountId>, 7>,
+        /// Further minters and the amount each may still mint.
+        #[cfg(feature = "mintable")]
+        minter_caps: ManualKey<Mapping<AccountId, Balance>, 8>,
+        /// Share of every transfer that is burnt, in basis points.
+        #[cfg(feature = "fees")]
+        burn_rate: ManualKey<Lazy<u16>, 9>,
+        /// Largest amount a single transfer may move, if limited.
+        #[cfg(feature = "limits")]
+        max_tx_amount: ManualKey<Lazy<Option<Balance>>, 10>,
+        /// Accounts whose transfers are not subject to `max_tx_amount`.
+        #[cfg(feature = "limits")]
+        tx_limit_exempt: ManualKey<Mapping<AccountId, bool>, 11>,
+        /// Largest balance an account may receive tokens up to, if limited.
+        #[cfg(feature = "limits")]
+        max_wallet: ManualKey<Lazy<Option<Balance>>, 12>,
+        /// Accounts whose balance is not subject to `max_wallet`.
+        #[cfg(feature = "limits")]
+        wallet_limit_exempt: ManualKey<Mapping<AccountId, bool>, 13>,
+        /// Share of every transfer collected as tax, in basis points.
+        #[cfg(feature = "fees")]
+        tax_rate: ManualKey<Lazy<u16>, 14>,
+        /// Account that accumulated taxes are forwarded to.
+        #[cfg(feature = "fees")]
+        treasury: ManualKey<Lazy<Option<AccountId>>, 15>,
+        /// Accumulated tax at which it is forwarded to the treasury automatically.
+        #[cfg(feature = "fees")]
+        treasury_threshold: ManualKey<Lazy<Option<Balance>>, 16>,
+        /// Gons of the contract's own balance that are collected tax.
+        #[cfg(feature = "fees")]
+        collected_tax: ManualKey<Lazy<Balance>, 17>,
+        /// DEX pairs whose outgoing transfers are buys and incoming ones sells.
+        #[cfg(feature = "fees")]
+        amm_pairs: ManualKey<Mapping<AccountId, bool>, 18>,
+        /// Tax rate for transfers out of a DEX pair, in basis points.
+        #[cfg(feature = "fees")]
+        buy_tax_rate: ManualKey<Lazy<u16>, 19>,
+        /// Tax rate for transfers into a DEX pair, in basis points.
+        #[cfg(feature = "fees")]
+        sell_tax_rate: ManualKey<Lazy<u16>, 20>,
+        /// Native dividends paid out per gon so far, scaled by `DIVIDEND_MAGNITUDE`.
+        #[cfg(feature = "dividends")]
+        dividends_per_token: ManualKey<Lazy<u128>, 21>,
+        /// Part of past deposits too small to distribute yet.
+        #[cfg(feature = "dividends")]
+        undistributed_dividends: ManualKey<Lazy<Balance>, 22>,
+        /// Dividend bookkeeping of every account that held tokens during a distribution.
+        #[cfg(feature = "dividends")]
+        dividend_accounts: ManualKey<Mapping<AccountId, DividendAccount>, 23>,
+        /// Sum of all balances in gons. Unset until the first rebase, while gons
+        /// and tokens are the same.
+        total_gons: ManualKey<Lazy<Balance>, 24>,
+        /// Account besides the owner allowed to rebase, e.g. a supply oracle.
+        #[cfg(feature = "elastic")]
+        rebaser: ManualKey<Lazy<Option<AccountId>>, 25>,
+        /// Parameters of the built-in governor.
+        #[cfg(feature = "governance")]
+        governance_config: ManualKey<Lazy<GovernanceConfig>, 26>,
+        /// Identifier the next proposal will get.
+        #[cfg(feature = "governance")]
+        next_proposal_id: ManualKey<Lazy<ProposalId>, 27>,
+        /// Governance proposals by identifier.
+        #[cfg(feature = "governance")]
+        proposals: ManualKey<Mapping<ProposalId, Proposal>, 28>,
+        /// Accounts that have voted on a proposal: (proposal, voter) -> voted
+        #[cfg(feature = "governance")]
+        votes: ManualKey<Mapping<(ProposalId, AccountId), bool>, 29>,
+        /// Balances pledged to running votes, which cannot be transferred out.
+        #[cfg(feature = "governance")]
+        vote_locks: ManualKey<Mapping<AccountId, VoteLock>, 30>,
+        /// The token sale, once one has been started.
+        #[cfg(feature = "sale")]
+        sale: ManualKey<Lazy<Sale>, 31>,
+        /// Native currency contributed to the sale per buyer.
+        #[cfg(feature = "sale")]
+        sale_contributions: ManualKey<Mapping<AccountId, Balance>, 32>,
+        /// Gons per account held back by token locks.
+        #[cfg(feature = "locks")]
+        locked: ManualKey<Mapping<AccountId, Balance>, 33>,
+        /// Token locks: (owner, lock) -> lock
+        #[cfg(feature = "locks")]
+        token_locks: ManualKey<Mapping<(AccountId, LockId), TokenLock>, 34>,
+        /// Number of locks each account has created.
+        #[cfg(feature = "locks")]
+        lock_counts: ManualKey<Mapping<AccountId, LockId>, 35>,
+        /// Identifier the next escrow will get.
+        #[cfg(feature = "escrow")]
+        next_escrow_id: ManualKey<Lazy<EscrowId>, 36>,
+        /// Escrows by identifier.
+        #[cfg(feature = "escrow")]
+        escrows: ManualKey<Mapping<EscrowId, Escrow>, 37>,
+        /// Payment splitter shares per payee.
+        #[cfg(feature = "splitter")]
+        payee_shares: ManualKey<Mapping<AccountId, u32>, 38>,
+        /// Sum of all payee shares.
+        #[cfg(feature = "splitter")]
+        total_shares: ManualKey<Lazy<u32>, 39>,
+        /// Gons paid out of the splitter per payee.
+        #[cfg(feature = "splitter")]
+        released_gons: ManualKey<Mapping<AccountId, Balance>, 40>,
+        /// Gons paid out of the splitter in total.
+        #[cfg(feature = "splitter")]
+        total_released_gons: ManualKey<Lazy<Balance>, 41>,
+        /// Identifier the next stream will get.
+        #[cfg(feature = "streams")]
+        next_stream_id: ManualKey<Lazy<StreamId>, 42>,
+        /// Running payment streams by identifier.
+        #[cfg(feature = "streams")]
+        streams: ManualKey<Mapping<StreamId, Stream>, 43>,
+        /// Accounts with a non-zero balance, by position in the holder list.
+        #[cfg(feature = "holders")]
+        holders: ManualKey<Mapping<u32, AccountId>, 44>,
+        /// Position of each holder in the holder list.
+        #[cfg(feature = "holders")]
+        holder_indices: ManualKey<Mapping<AccountId, u32>, 45>,
+        /// Length of the holder list.
+        #[cfg(feature = "holders")]
+        holders_count: ManualKey<Lazy<u32>, 46>,
+        /// Spenders with a non-zero allowance: (owner, position) -> spender
+        spenders: ManualKey<Mapping<(AccountId, u32), AccountId>, 47>,
+        /// Position of each spender in its owner's spender list.
+        spender_indices: ManualKey<Mapping<(AccountId, AccountId), u32>, 48>,
+        /// Length of each owner's spender list.
+        spender_counts: ManualKey<Mapping<AccountId, u32>, 49>,
+        /// Layout version of the data in storage; unset before version 1.
+        storage_version: ManualKey<Lazy<u32>, 50>,
+        /// Queued proposal that will replace the contract code.
+        #[cfg(feature = "governance")]
+        pending_upgrade: ManualKey<Lazy<Option<ProposalId>>, 51>,
+        /// Chain extension function serving the price feed, if configured.
+        #[cfg(feature = "fees")]
+        oracle: ManualKey<Lazy<Option<OracleConfig>>, 52>,
+        /// Bounds and peg of the oracle-driven tax rate, if enabled.
+        #[cfg(feature = "fees")]
+        fee_controller: ManualKey<Lazy<Option<FeeController>>, 53>,
+        /// Block of the last oracle-driven tax rate update.
+        #[cfg(feature = "fees")]
+        fee_updated_at: ManualKey<Lazy<Option<BlockNumber>>, 54>,
+        /// DEX router collected tax is sold through, if enabled.
+        #[cfg(feature = "fees")]
+        swap_config: ManualKey<Lazy<Option<SwapConfig>>, 55>,
+        /// Set while collected tax is being sold, so the router's pull is
+        /// neither taxed nor triggers another swap.
+        #[cfg(feature = "fees")]
+        in_swap: ManualKey<Lazy<bool>, 56>,
+        /// Relayer allowed to move tokens across the bridge, if any.
+        #[cfg(feature = "bridge")]
+        bridge: ManualKey<Lazy<Option<AccountId>>, 57>,
+        /// Source chain transactions whose tokens have been bridged in.
+        #[cfg(feature = "bridge")]
+        bridged_in: ManualKey<Mapping<Hash, bool>, 58>,
+        /// Chain extension function cross-chain transfers go through, if configured.
+        #[cfg(feature = "bridge")]
+        xcm: ManualKey<Lazy<Option<XcmConfig>>, 59>,
+        /// Number of signed transfers submitted per account, see
+        /// `transfer_with_signature`.
+        #[cfg(feature = "signed-transfers")]
+        nonces: ManualKey<Mapping<AccountId, u64>, 60>,
+        /// Schedule supply is released on, if set at deployment.
+        #[cfg(feature = "emissions")]
+        emission: ManualKey<Lazy<Option<EmissionSchedule>>, 61>,
+        /// Start of the first period not released yet.
+        #[cfg(feature = "emissions")]
+        emitted_until: ManualKey<Lazy<Timestamp>, 62>,
+        /// Inflation minted into the reward pool, if enabled.
+        #[cfg(feature = "emissions")]
+        inflation: ManualKey<Lazy<Option<InflationConfig>>, 63>,
+        /// Block inflation has been minted up to.
+        #[cfg(feature = "emissions")]
+        inflation_accrued_at: ManualKey<Lazy<BlockNumber>, 64>,
+        /// Allocation buckets created at deployment, by name.
+        #[cfg(feature = "emissions")]
+        buckets: ManualKey<Mapping<String, Bucket>, 65>,
+        /// PSP22 tokens rewards have been funded in, by position.
+        #[cfg(feature = "dividends")]
+        reward_tokens: ManualKey<Mapping<u32, AccountId>, 66>,
+        /// Number of reward tokens.
+        #[cfg(feature = "dividends")]
+        reward_tokens_count: ManualKey<Lazy<u32>, 67>,
+        /// Rewards paid out per gon so far, per reward token, scaled by
+        /// `DIVIDEND_MAGNITUDE`.
+        #[cfg(feature = "dividends")]
+        rewards_per_token: ManualKey<Mapping<AccountId, u128>, 68>,
+        /// Part of past funding too small to distribute yet, per reward token.
+        #[cfg(feature = "dividends")]
+        undistributed_rewards: ManualKey<Mapping<AccountId, Balance>, 69>,
+        /// Reward bookkeeping: (account, reward token) -> account
+        #[cfg(feature = "dividends")]
+        reward_accounts: ManualKey<Mapping<(AccountId, AccountId), DividendAccount>, 70>,
+        /// Accounts that may neither send nor receive tokens.
+        #[cfg(feature = "compliance")]
+        denylisted: ManualKey<Mapping<AccountId, bool>, 71>,
+        /// Whether recipients of transfers must be accredited.
+        #[cfg(feature = "compliance")]
+        accreditation_required: ManualKey<Lazy<bool>, 72>,
+        /// Accounts allowed to receive transfers while accreditation is required.
+        #[cfg(feature = "compliance")]
+        accredited: ManualKey<Mapping<AccountId, bool>, 73>,
+        /// Contract asked whether transfer parties passed KYC, if any.
+        #[cfg(feature = "compliance")]
+        kyc_registry: ManualKey<Lazy<Option<AccountId>>, 74>,
+        /// When the registry last confirmed an account: (registry, account) -> time
+        #[cfg(feature = "compliance")]
+        kyc_verified_at: ManualKey<Mapping<(AccountId, AccountId), Timestamp>, 75>,
+        /// Whether balances are bound to their holders, see `new_soulbound`.
+        #[cfg(feature = "soulbound")]
+        soulbound: ManualKey<Lazy<bool>, 76>,
+        /// Launch protection settings, if enabled.
+        #[cfg(feature = "limits")]
+        launch_protection: ManualKey<Lazy<Option<LaunchProtection>>, 77>,
+        /// Block the launch protection was enabled in.
+        #[cfg(feature = "limits")]
+        launched_at: ManualKey<Lazy<BlockNumber>, 78>,
+        /// Block each account last bought from a DEX pair in.
+        #[cfg(feature = "limits")]
+        last_buy_block: ManualKey<Mapping<AccountId, BlockNumber>, 79>,
+        /// Accounts that bought within the first `sniper_blocks`.
+        #[cfg(feature = "limits")]
+        snipers: ManualKey<Mapping<AccountId, bool>, 80>,
+        /// How transfers of zero tokens are handled.
+        zero_transfer_policy: ManualKey<Lazy<ZeroTransferPolicy>, 81>,
+        /// Terms tokens are minted for native currency on, if enabled.
+        #[cfg(feature = "sale")]
+        mint_sale: ManualKey<Lazy<Option<MintSale>>, 82>,
+        /// Tokens each account has bought through `purchase`.
+        #[cfg(feature = "sale")]
+        mint_sale_purchases: ManualKey<Mapping<AccountId, Balance>, 83>,
+        /// Tokens bought through `purchase` in total.
+        #[cfg(feature = "sale")]
+        mint_sale_sold: ManualKey<Lazy<Balance>, 84>,
+        /// Native currency paid for minted tokens or deposited with
+        /// `fund_reserve`, less withdrawals and redemptions.
+        #[cfg(feature = "sale")]
+        mint_proceeds: ManualKey<Lazy<Balance>, 85>,
+        /// Tokens redeemed per unit of native currency, if redemptions are enabled.
+        #[cfg(feature = "sale")]
+        redeem_rate: ManualKey<Lazy<Option<Balance>>, 86>,
+        /// Curve tokens are bought and sold along, if enabled.
+        #[cfg(feature = "sale")]
+        bonding_curve: ManualKey<Lazy<Option<BondingCurve>>, 87>,
+        /// Tokens bought along the bonding curve and not sold back yet.
+        #[cfg(feature = "sale")]
+        curve_supply: ManualKey<Lazy<Balance>, 88>,
+        /// Share of purchased tokens minted to the referrer, in basis points.
+        #[cfg(feature = "sale")]
+        referral_rate: ManualKey<Lazy<u16>, 89>,
+        /// Tokens each referrer has been credited with.
+        #[cfg(feature = "sale")]
+        referral_rewards: ManualKey<Mapping<AccountId, Balance>, 90>,
+        /// Purchases each referrer has referred.
+        #[cfg(feature = "sale")]
+        referral_counts: ManualKey<Mapping<AccountId, u32>, 91>,
+        /// Chain extension function raffle draws read randomness from, if configured.
+        #[cfg(feature = "raffles")]
+        randomness: ManualKey<Lazy<Option<RandomnessConfig>>, 92>,
+        /// Identifier the next raffle will get.
+        #[cfg(feature = "raffles")]
+        next_raffle_id: ManualKey<Lazy<RaffleId>, 93>,
+        /// Raffles by identifier.
+        #[cfg(feature = "raffles")]
+        raffles: ManualKey<Mapping<RaffleId, Raffle>, 94>,
+        /// Ticket purchases: (raffle, position) -> (buyer, first ticket)
+        #[cfg(feature = "raffles")]
+        raffle_entries: ManualKey<Mapping<(RaffleId, u32), (AccountId, u32)>, 95>,
+        /// Tickets per raffle and account.
+        #[cfg(feature = "raffles")]
+        raffle_tickets: ManualKey<Mapping<(RaffleId, AccountId), u32>, 96>,
+        /// Drawn winners: (raffle, position) -> winner
+        #[cfg(feature = "raffles")]
+        raffle_winners: ManualKey<Mapping<(RaffleId, u32), AccountId>, 97>,
+        /// Block each holder's balance last became non-zero in.
+        #[cfg(feature = "holders")]
+        held_since: ManualKey<Mapping<AccountId, BlockNumber>, 98>,
+        /// Periodic caps on allowances: (owner, spender) -> limit
+        #[cfg(feature = "spending-limits")]
+        spending_limits: ManualKey<Mapping<(AccountId, AccountId), SpendingLimit>, 99>,
+        /// Accounts temporarily barred from sending and receiving tokens.
+        #[cfg(feature = "compliance")]
+        frozen: ManualKey<Mapping<AccountId, bool>, 100>,
+        /// Regulator allowed to move tokens without an allowance.
+        #[cfg(feature = "compliance")]
+        controller: ManualKey<Lazy<Option<AccountId>>, 101>,
+        /// Documents attached to the token, by name.
+        #[cfg(feature = "documents")]
+        documents: ManualKey<Mapping<String, Document>, 102>,
+        /// Names of all documents, enumerable by index.
+        #[cfg(feature = "documents")]
+        document_names: ManualKey<Mapping<u32, String>, 103>,
+        /// Position of each document in the name list.
+        #[cfg(feature = "documents")]
+        document_indices: ManualKey<Mapping<String, u32>, 104>,
+        /// Length of the name list.
+        #[cfg(feature = "documents")]
+        documents_count: ManualKey<Lazy<u32>, 105>,
+        /// Gons of each holder assigned to a partition.
+        #[cfg(feature = "partitions")]
+        partition_balances: ManualKey<Mapping<(AccountId, String), Balance>, 106>,
+        /// Gons of each holder assigned to any partition.
+        #[cfg(feature = "partitions")]
+        partitioned: ManualKey<Mapping<AccountId, Balance>, 107>,
+        /// Partitions each holder has a balance in.
+        #[cfg(feature = "partitions")]
+        holder_partitions: ManualKey<Mapping<AccountId, Vec<String>>, 108>,
+        /// Operators a holder allowed to transfer from one of their partitions.
+        #[cfg(feature = "partitions")]
+        partition_operators: ManualKey<Mapping<(AccountId, String, AccountId), bool>, 109>,
+        /// Audit of the total supply in progress, reset by every balance change.
+        #[cfg(feature = "holders")]
+        supply_audit: ManualKey<Lazy<Option<SupplyAudit>>, 110>,
+        /// Share of every fee kept as protocol fee, in basis points.
+        #[cfg(feature = "fees")]
+        protocol_fee_share: ManualKey<Lazy<u16>, 111>,
+        /// Account allowed to collect protocol fees.
+        #[cfg(feature = "fees")]
+        fee_collector: ManualKey<Lazy<Option<AccountId>>, 112>,
+        /// Protocol fees held by the contract per source, in gons.
+        #[cfg(feature = "fees")]
+        protocol_fees: ManualKey<Mapping<FeeSource, Balance>, 113>,
+        /// Limit on the transferred volume, if any.
+        #[cfg(feature = "pausable")]
+        circuit_breaker: ManualKey<Lazy<Option<CircuitBreaker>>, 114>,
+        /// Volume transferred within the current window.
+        #[cfg(feature = "pausable")]
+        breaker_window: ManualKey<Lazy<BreakerWindow>, 115>,
+        /// Whether the circuit breaker stopped all transfers.
+        #[cfg(feature = "pausable")]
+        breaker_tripped: ManualKey<Lazy<bool>, 116>,
+        /// Most tokens that may be minted within one block, if limited.
+        #[cfg(feature = "mintable")]
+        max_mint_per_block: ManualKey<Lazy<Option<Balance>>, 117>,
+        /// Block of the last limited mint and the tokens minted in it.
+        #[cfg(feature = "mintable")]
+        minted_in_block: ManualKey<Lazy<(BlockNumber, Balance)>, 118>,
+        /// Tokens taken out of supply by all burns so far.
+        total_burned: ManualKey<Lazy<Balance>, 119>,
+        /// Accounts whose tokens do not count as circulating, in no order.
+        #[cfg(feature = "circulating")]
+        excluded_accounts: ManualKey<Mapping<u32, AccountId>, 120>,
+        /// Position of each excluded account in the excluded list.
+        #[cfg(feature = "circulating")]
+        excluded_indices: ManualKey<Mapping<AccountId, u32>, 121>,
+        /// Length of the excluded list.
+        #[cfg(feature = "circulating")]
+        excluded_count: ManualKey<Lazy<u32>, 122>,
+        /// Number of transfers the transfer log keeps; zero disables it.
+        #[cfg(feature = "transfer-log")]
+        transfer_log_capacity: ManualKey<Lazy<u32>, 123>,
+        /// The last transfers, in a ring buffer of `transfer_log_capacity`
+        /// slots.
+        #[cfg(feature = "transfer-log")]
+        transfer_log: ManualKey<Mapping<u32, TransferRecord>, 124>,
+        /// Transfers logged since the capacity was last set.
+        #[cfg(feature = "transfer-log")]
+        transfer_log_count: ManualKey<Lazy<u64>, 125>,
+        /// Sequence number of the next event.
+        event_seq: ManualKey<Lazy<u64>, 126>,
+        /// Branding set by the owner.
+        #[cfg(feature = "metadata")]
+        token_metadata: ManualKey<Lazy<TokenMetadata>, 127>,
+        /// Sessions per owner and spender.
+        #[cfg(feature = "sessions")]
+        sessions: ManualKey<Mapping<(AccountId, AccountId), Session>, 128>,
+        /// Account allowed to airdrop tokens.
+        #[cfg(feature = "airdrops")]
+        distributor: ManualKey<Lazy<Option<AccountId>>, 129>,
+        /// Recipients already paid per airdrop campaign.
+        #[cfg(feature = "airdrops")]
+        airdropped: ManualKey<Mapping<(u32, AccountId), bool>, 130>,
+        /// Vote-escrow lock of every account, expired ones included.
+        #[cfg(feature = "vote-escrow")]
+        ve_locks: ManualKey<Mapping<AccountId, VeLock>, 131>,
+        /// History of every account's vote-escrow lock, as the lock from each
+        /// time on, oldest first.
+        #[cfg(feature = "vote-escrow")]
+        ve_checkpoints: ManualKey<Mapping<(AccountId, u32), (Timestamp, VeLock)>, 132>,
+        /// Number of vote-escrow checkpoints each account has.
+        #[cfg(feature = "vote-escrow")]
+        ve_checkpoint_counts: ManualKey<Mapping<AccountId, u32>, 133>,
+        /// Accounts whose transfers are neither taxed, burnt from, nor held to
+        /// `max_tx_amount` and `max_wallet`.
+        #[cfg(feature = "fees")]
+        fee_exempt: ManualKey<Mapping<AccountId, bool>, 134>,
+        /// Account allowed to trip the circuit breaker, and nothing else.
+        #[cfg(feature = "pausable")]
+        guardian: ManualKey<Lazy<Option<AccountId>>, 135>,
+        /// Recovery of ownership should the owner stop calling `heartbeat`.
+        #[cfg(feature = "recovery")]
+        dead_man_switch: ManualKey<Lazy<Option<DeadManSwitch>>, 136>,
+        /// Time of the owner's last sign of life, see `heartbeat`.
+        #[cfg(feature = "recovery")]
+        last_heartbeat: ManualKey<Lazy<Timestamp>, 137>,
+        /// Guardians each account registered to recover its balance.
+        #[cfg(feature = "recovery")]
+        recovery_configs: ManualKey<Mapping<AccountId, RecoveryConfig>, 138>,
+        /// Recovery of each account's balance the guardians are approving.
+        #[cfg(feature = "recovery")]
+        recoveries: ManualKey<Mapping<AccountId, Recovery>, 139>,
+        /// Balance below which holdings count as dust, see `sweep_dust`.
+        #[cfg(feature = "holders")]
+        dust_threshold: ManualKey<Lazy<Option<Balance>>, 140>,
+        /// Index of the next holder `sweep_dust` checks.
+        #[cfg(feature = "holders")]
+        dust_cursor: ManualKey<Lazy<u32>, 141>,
+        /// Whether transfers update the per-account statistics.
+        #[cfg(feature = "stats")]
+        stats_enabled: ManualKey<Lazy<bool>, 142>,
+        /// Transfer statistics of each account, kept while enabled.
+        #[cfg(feature = "stats")]
+        stats: ManualKey<Mapping<AccountId, AccountStats>, 143>,
+        /// Identifier the next scheduled transfer will get.
+        #[cfg(feature = "scheduled")]
+        next_scheduled_id: ManualKey<Lazy<ScheduledId>, 144>,
+        /// Scheduled transfers not executed or cancelled yet.
+        #[cfg(feature = "scheduled")]
+        scheduled: ManualKey<Mapping<ScheduledId, ScheduledTransfer>, 145>,
+        /// Identifier the next subscription will get.
+        #[cfg(feature = "subscriptions")]
+        next_subscription_id: ManualKey<Lazy<SubscriptionId>, 146>,
+        /// Subscriptions not cancelled yet.
+        #[cfg(feature = "subscriptions")]
+        subscriptions: ManualKey<Mapping<SubscriptionId, Subscription>, 147>,
+        /// Identifier the next hashlocked transfer will get.
+        #[cfg(feature = "htlc")]
+        next_htlc_id: ManualKey<Lazy<HtlcId>, 148>,
+        /// Hashlocked transfers not claimed or refunded yet.
+        #[cfg(feature = "htlc")]
+        htlcs: ManualKey<Mapping<HtlcId, Htlc>, 149>,
+        /// Identifier the next voucher will get.
+        #[cfg(feature = "vouchers")]
+        next_voucher_id: ManualKey<Lazy<VoucherId>, 150>,
+        /// Vouchers not redeemed or reclaimed yet.
+        #[cfg(feature = "vouchers")]
+        vouchers: ManualKey<Mapping<VoucherId, Voucher>, 151>,
+        /// Approvers that must confirm mints above the direct limit.
+        #[cfg(feature = "mintable")]
+        mint_approval: ManualKey<Lazy<Option<MintApprovalConfig>>, 152>,
+        /// Identifier the next mint request will get.
+        #[cfg(feature = "mintable")]
+        next_mint_request_id: ManualKey<Lazy<MintRequestId>, 153>,
+        /// Mint requests not executed or cancelled yet.
+        #[cfg(feature = "mintable")]
+        mint_requests: ManualKey<Mapping<MintRequestId, MintRequest>, 154>,
+        /// Merkle airdrop open for claims or waiting to be swept.
+        #[cfg(feature = "airdrops")]
+        merkle_airdrop: ManualKey<Lazy<Option<MerkleAirdrop>>, 155>,
+    }
+
+    /// An allowance as kept in storage.
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        Copy,
+        PartialEq,
+        Eq,
+        scale::Encode,
+        scale::Decode,
+        PackedLayout,
+        SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    struct Allowance {
+        value: Balance,
+        expires_at: Option<Timestamp>,
+    }
+
+    /// A cap on what a spender may move per period, see `approve_with_limit`.
+    #[cfg(feature = "spending-limits")]
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct SpendingLimit {
+        /// Most tokens the spender may move within one period.
+        pub cap: Balance,
+        /// Length of a period, in milliseconds.
+        pub period: Timestamp,
+        /// Start of the current period.
+        pub period_start: Timestamp,
+        /// Tokens moved within the current period.
+        pub spent: Balance,
+    }
+
+    #[cfg(feature = "spending-limits")]
+    impl SpendingLimit {
+        /// Returns the limit as of `now`, with a fresh period once the
+        /// current one is over.
+        fn at(&self, now: Timestamp) -> Self {
+            let elapsed = now.saturating_sub(self.period_start);
+            if elapsed < self.period {
+                return self.clone()
+            }
+            Self {
+                period_start: self.period_start + elapsed / self.period * self.period,
+                spent: 0,
+                ..self.clone()
+            }
+        }
+    }
+
+    /// A constrained authorization to spend an owner's tokens, see
+    /// `create_session`.
+    #[cfg(feature = "sessions")]
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Session {
+        /// Most tokens the spender may move over the whole session.
+        pub total_cap: Balance,
+        /// Most tokens the spender may move in one transfer.
+        pub per_tx_cap: Balance,
+        /// Time from which the session can no longer be used.
+        pub expires_at: Timestamp,
+        /// Tokens moved within the session so far.
+        pub spent: Balance,
+    }
+
+    /// Guardians an account registered to recover its balance, see
+    /// `recover_balance`.
+    #[cfg(feature = "recovery")]
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct RecoveryConfig {
+        pub guardians: Vec<AccountId>,
+        /// Number of guardians that must approve a recovery.
+        pub threshold: u32,
+        /// Time the account has to veto a recovery once it is approved.
+        pub delay: Timestamp,
+    }
+
+    /// A recovery of an account's balance to `new_account`.
+    #[cfg(feature = "recovery")]
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Recovery {
+        pub new_account: AccountId,
+        /// Guardians that approved the recovery.
+        pub approvals: Vec<AccountId>,
+        /// Time from which the recovery can be carried out, once the
+        /// threshold is reached.
+        pub executable_at: Option<Timestamp>,
+    }
+
+    /// Approvers that must confirm larger mints, see `request_mint`.
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct MintApprovalConfig {
+        pub approvers: Vec<AccountId>,
+        /// Number of approvers that must confirm a mint request.
+        pub threshold: u32,
+        /// Most tokens a minter may mint at once without approval.
+        pub direct_limit: Balance,
+        /// Time after which an unexecuted mint request expires.
+        pub request_ttl: Timestamp,
+    }
+
+    /// Identifier of a mint request.
+    #[cfg(feature = "mintable")]
+    pub type MintRequestId = u64;
+
+    /// A mint above the direct limit, waiting for the approvers.
+    #[cfg(feature = "mintable")]
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct MintRequest {
+        pub minter: AccountId,
+        pub to: AccountId,
+        pub value: Balance,
+        /// Approvers that confirmed the request.
+        pub approvals: Vec<AccountId>,
+        /// Time from which the request can no longer be executed.
+        pub expires_at: Timestamp,
+    }
+
+    /// A document attached to the token, such as a prospectus.
+    #[cfg(feature = "documents")]
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Document {
+        pub uri: String,
+        /// Hash of the document's content.
+        pub hash: Hash,
+        pub updated_at: Timestamp,
+    }
+
+    /// Branding wallets show next to the token. Empty fields are unset.
+    #[cfg(feature = "metadata")]
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct TokenMetadata {
+        pub logo_uri: String,
+        pub description: String,
+        pub website: String,
+        pub tags: Vec<String>,
+    }
+
+    /// A transfer kept in the transfer log.
+    #[cfg(feature = "transfer-log")]
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct TransferRecord {
+        pub from: AccountId,
+        pub to: AccountId,
+        /// Tokens received, after burn and tax.
+        pub value: Balance,
+        pub block: BlockNumber,
+    }
+
+    /// Transfer activity of an account, see `stats_of`.
+    #[cfg(feature = "stats")]
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct AccountStats {
+        /// Tokens sent, burn and tax included.
+        pub total_sent: Balance,
+        /// Tokens received, after burn and tax.
+        pub total_received: Balance,
+        /// Transfers the account sent or received.
+        pub tx_count: u64,
+        /// Block of the first transfer counted for the account.
+        pub first_seen_block: BlockNumber,
+    }
+
+    /// Progress of `verify_supply` through the holder index.
+    #[cfg(feature = "holders")]
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    struct SupplyAudit {
+        /// Index of the next holder to check.
+        next: u32,
+        /// Gons of the holders checked so far.
+        gons: Balance,
+    }
+
+    /// Outcome of a `verify_supply` call that found no discrepancy.
+    #[cfg(feature = "holders")]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum AuditStatus {
+        /// More holders are left to check.
+        InProgress { checked: u32, holders: u32 },
+        /// All balances add up to the total supply.
+        Verified,
+    }
+
+    /// Discrepancy found by `verify_supply`.
+    #[cfg(feature = "holders")]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum AuditError {
+        /// The balances of all holders add up to `recorded` rather than the
+        /// total supply.
+        Mismatch {
+            recorded: Balance,
+            total_supply: Balance,
+        },
+    }
+
+    /// Limit on the volume transferred within a window, see `set_circuit_breaker`.
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct CircuitBreaker {
+        /// Volume that trips the breaker, in basis points of the total supply.
+        pub threshold: u16,
+        /// Length of a window, in milliseconds.
+        pub window: Timestamp,
+    }
+
+    /// Recovery of ownership by `recovery` once the owner has not called
+    /// `heartbeat` for `window`, see `claim_ownership`.
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct DeadManSwitch {
+        pub recovery: AccountId,
+        /// Longest time between heartbeats, in milliseconds.
+        pub window: Timestamp,
+    }
+
+    /// Volume transferred within the current window of the circuit breaker.
+    #[cfg(feature = "pausable")]
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    struct BreakerWindow {
+        start: Timestamp,
+        volume: Balance,
+    }
+
+    /// Conversion between tokens and gons, the unit balances are stored in.
+    ///
+    /// Rebasing changes the total supply but no stored balance, so every
+    /// holder's share of the supply stays the same while their token balance
+    /// scales along. Until the first rebase one gon is one token.
+    #[derive(Clone, Copy)]
+    struct GonsRate {
+        /// `(total_gons, total_supply)` once the supply has been rebased.
+        rebased: Option<(Balance, Balance)>,
+    }
+
+    impl GonsRate {
+        /// Converts tokens into gons, rounding down. Saturates for amounts larger
+        /// than the total supply, which no balance can cover anyway.
+        fn to_gons(self, value: Balance) -> Balance {
+            match self.rebased {
+                Some((total_gons, total_supply)) => {
+                    mul_div(value, total_gons, total_supply).unwrap_or(Balance::MAX)
+                }
+                None => value,
+            }
+        }
+
+        /// Converts gons into tokens, rounding down. No gons are worth
+        /// nothing once every gon has been burned.
+        fn to_tokens(self, gons: Balance) -> Balance {
+            match self.rebased {
+                Some((total_gons, total_supply)) => {
+                    mul_div(gons, total_supply, total_gons).unwrap_or_default()
+                }
+                None => gons,
+            }
+        }
+    }
+
+    /// Dividend bookkeeping for a single account, in gons.
+    #[cfg(feature = "dividends")]
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct DividendAccount {
+        /// Value of `dividends_per_token` when the account was last settled.
+        dividends_per_token: u128,
+        /// Dividends earned up to the last settlement and not yet claimed.
+        unclaimed: Balance,
+    }
+
+    /// Identifier of a governance proposal.
+    #[cfg(feature = "governance")]
+    pub type ProposalId = u32;
+
+    /// Parameters of the built-in governor.
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct GovernanceConfig {
+        /// Number of blocks a proposal is open for voting. Zero disables governance.
+        pub voting_period: BlockNumber,
+        /// Share of the total supply that must vote in favour, in basis points.
+        pub quorum: u16,
+        /// Time in milliseconds between queueing and executing a proposal.
+        pub timelock: Timestamp,
+        /// Balance an account needs to create proposals.
+        pub proposal_threshold: Balance,
+    }
+
+    /// A call on `target` put to a token-weighted vote.
+    #[cfg(feature = "governance")]
+    #[derive(
+        Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Proposal {
+        pub proposer: AccountId,
+        /// Contract the proposal calls when executed.
+        pub target: AccountId,
+        /// Selector followed by the SCALE encoded arguments of the call.
+        pub call_data: Vec<u8>,
+        /// Hash of the off-chain description.
+        pub description_hash: Hash,
+        /// Last block in which votes are accepted.
+        pub vote_end: BlockNumber,
+        /// Gons voting in favour needed for the proposal to pass.
+        pub quorum_gons: Balance,
+        pub for_gons: Balance,
+        pub against_gons: Balance,
+        /// Earliest execution time, once queued.
+        pub eta: Option<Timestamp>,
+        pub executed: bool,
+    }
+
+    /// Lifecycle state of a governance proposal.
+    #[cfg(feature = "governance")]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum ProposalState {
+        /// Open for voting.
+        Active,
+        /// Voting ended without a majority in favour or without quorum.
+        Defeated,
+        /// Voting passed; waiting to be queued.
+        Succeeded,
+        /// Waiting for the timelock to pass.
+        Queued,
+        Executed,
+    }
+
+    /// Gons of an account that back votes until `until`.
+    #[cfg(feature = "governance")]
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct VoteLock {
+        gons: Balance,
+        until: BlockNumber,
+    }
+
+    /// Gons an account has locked into the vote escrow until `end`.
+    #[cfg(feature = "vote-escrow")]
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct VeLock {
+        gons: Balance,
+        end: Timestamp,
+    }
+
+    #[cfg(feature = "vote-escrow")]
+    impl VeLock {
+        /// Returns the voting power of the lock at `at`, in gons. It decays
+        /// linearly to zero at the end of the lock.
+        fn power_at(&self, at: Timestamp) -> Balance {
+            let remaining = self.end.saturating_sub(at);
+            mul_div(self.gons, remaining.into(), MAX_VE_LOCK_TIME.into())
+                .expect("remaining time never exceeds the longest lock")
+        }
+    }
+
+    /// Terms of a token sale.
+    #[cfg(feature = "sale")]
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct SaleConfig {
+        /// Tokens sold per unit of native currency.
+        pub rate: Balance,
+        /// Time the sale opens, in milliseconds.
+        pub start: Timestamp,
+        /// Time the sale closes, in milliseconds.
+        pub end: Timestamp,
+        /// Smallest native amount accepted per purchase.
+        pub min_purchase: Balance,
+        /// Largest native amount a single buyer may contribute in total.
+        pub max_purchase: Balance,
+        /// Native amount that must be raised, or every buyer is refunded.
+        pub soft_cap: Balance,
+        /// Native amount at which the sale closes.
+        pub hard_cap: Balance,
+        /// Account that receives the raised funds.
+        pub beneficiary: AccountId,
+    }
+
+    /// A token sale and its progress.
+    #[cfg(feature = "sale")]
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Sale {
+        pub config: SaleConfig,
+        /// Account that provided the tokens and gets back what remains unsold.
+        pub seller: AccountId,
+        /// Native currency raised so far.
+        pub raised: Balance,
+        /// Outcome once finalized: whether the soft cap was reached.
+        pub succeeded: Option<bool>,
+        /// Tokens held for the buyers, in gons: enough for the hard cap until
+        /// the sale is finalized, then those sold.
+        gons: Balance,
+    }
+
+    /// Terms of minting tokens for native currency, see `purchase`.
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct MintSale {
+        /// Tokens minted per unit of native currency.
+        pub rate: Balance,
+        /// Most tokens a single account may buy in total, if limited.
+        pub max_per_account: Option<Balance>,
+        /// Most tokens that may be bought in total, if limited.
+        pub max_total: Option<Balance>,
+    }
+
+    /// Identifier of a token lock, counted per account.
+    #[cfg(feature = "locks")]
+    pub type LockId = u32;
+
+    /// Part of an account's balance that cannot be spent before `unlock_at`.
+    #[cfg(feature = "locks")]
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct TokenLock {
+        /// Locked amount, in gons.
+        gons: Balance,
+        /// Time from which the lock can be released, in milliseconds.
+        pub unlock_at: Timestamp,
+    }
+
+    /// Identifier of an escrow.
+    #[cfg(feature = "escrow")]
+    pub type EscrowId = u64;
+
+    /// Tokens held by the contract until an arbiter hands them to the
+    /// beneficiary or back to the depositor.
+    #[cfg(feature = "escrow")]
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Escrow {
+        pub depositor: AccountId,
+        pub beneficiary: AccountId,
+        pub arbiter: AccountId,
+        /// Escrowed amount, in gons.
+        gons: Balance,
+        /// Time from which the depositor may take the tokens back themselves.
+        pub expires_at: Timestamp,
+        /// Outcome once resolved: whether the tokens went to the beneficiary.
+        pub released: Option<bool>,
+    }
+
+    /// Identifier of a scheduled transfer.
+    #[cfg(feature = "scheduled")]
+    pub type ScheduledId = u64;
+
+    /// Tokens held by the contract until anyone pays them out to `to` after
+    /// `execute_after`, see `schedule_transfer`.
+    #[cfg(feature = "scheduled")]
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct ScheduledTransfer {
+        pub sender: AccountId,
+        pub to: AccountId,
+        /// Amount for `to`, in gons.
+        gons: Balance,
+        /// Amount for whoever executes the transfer, in gons.
+        tip_gons: Balance,
+        /// Time from which the transfer can be executed.
+        pub execute_after: Timestamp,
+    }
+
+    /// Identifier of a subscription.
+    #[cfg(feature = "subscriptions")]
+    pub type SubscriptionId = u64;
+
+    /// A payment `payer` authorized `merchant` to collect once per
+    /// `interval`, see `collect`.
+    #[cfg(feature = "subscriptions")]
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Subscription {
+        pub payer: AccountId,
+        pub merchant: AccountId,
+        /// Tokens collected each interval.
+        pub amount: Balance,
+        pub interval: Timestamp,
+        /// Time from which the next payment can be collected.
+        pub next_collection: Timestamp,
+    }
+
+    /// Identifier of a hashlocked transfer.
+    #[cfg(feature = "htlc")]
+    pub type HtlcId = u64;
+
+    /// Tokens held by the contract until whoever knows the preimage of
+    /// `hashlock` claims them for `recipient` before `timelock`, see
+    /// `create_htlc`.
+    #[cfg(feature = "htlc")]
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Htlc {
+        pub sender: AccountId,
+        pub recipient: AccountId,
+        gons: Balance,
+        /// SHA-256 hash of the preimage that releases the tokens.
+        pub hashlock: [u8; 32],
+        /// Time from which the tokens can no longer be claimed, only
+        /// refunded to the sender.
+        pub timelock: Timestamp,
+    }
+
+    /// Identifier of a voucher.
+    #[cfg(feature = "vouchers")]
+    pub type VoucherId = u64;
+
+    /// Tokens held by the contract until someone holding the key of
+    /// `signer` redeems them before `expires_at`, see `create_voucher`.
+    #[cfg(feature = "vouchers")]
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Voucher {
+        pub sender: AccountId,
+        /// Account of the ECDSA key whose signature redeems the voucher,
+        /// derived as with `transfer_with_signature`.
+        pub signer: AccountId,
+        gons: Balance,
+        /// Time from which the voucher can no longer be redeemed, only
+        /// reclaimed by the sender.
+        pub expires_at: Timestamp,
+    }
+
+    /// Tokens held by the contract for the accounts of a Merkle tree, see
+    /// `start_merkle_airdrop`.
+    #[cfg(feature = "airdrops")]
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct MerkleAirdrop {
+        /// Campaign claims are recorded under, as for `airdrop`.
+        pub campaign: u32,
+        /// Root of the tree of `(account, amount)` leaves.
+        pub root: [u8; 32],
+        pub starts_at: Timestamp,
+        /// Time from which nothing can be claimed and the rest can be swept.
+        pub deadline: Timestamp,
+        /// Gons not claimed yet.
+        gons: Balance,
+    }
+
+    /// Identifier of a raffle.
+    #[cfg(feature = "raffles")]
+    pub type RaffleId = u32;
+
+    /// A raffle whose ticket stakes are pooled and shared by the winners.
+    #[cfg(feature = "raffles")]
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Raffle {
+        /// Tokens staked per ticket.
+        pub ticket_price: Balance,
+        /// Time from which no more tickets are sold and winners can be drawn.
+        pub ends_at: Timestamp,
+        /// Number of winners drawn, if that many tickets were sold.
+        pub winners: u32,
+        /// Hash of the secret revealed at the draw, if the draw uses
+        /// commit-reveal instead of the randomness chain extension.
+        pub commitment: Option<Hash>,
+        /// Number of tickets sold.
+        pub tickets: u32,
+        /// Number of ticket purchases.
+        entries: u32,
+        /// Pooled stakes, in gons.
+        pot_gons: Balance,
+        /// Whether the winners have been drawn.
+        pub drawn: bool,
+        /// Number of tickets refunded since the raffle went undrawn.
+        pub refunded: u32,
+    }
+
+    /// Identifier of a payment stream.
+    #[cfg(feature = "streams")]
+    pub type StreamId = u64;
+
+    /// Tokens held by the contract that vest to `recipient` linearly between
+    /// `start` and `stop`.
+    #[cfg(feature = "streams")]
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Stream {
+        pub sender: AccountId,
+        pub recipient: AccountId,
+        /// Streamed amount, in gons.
+        deposit_gons: Balance,
+        /// Gons the recipient has withdrawn so far.
+        withdrawn_gons: Balance,
+        pub start: Timestamp,
+        pub stop: Timestamp,
+    }
+
+    /// One of the caller's own token operations, batched with `multicall`.
+    #[cfg(feature = "multicall")]
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Call {
+        Transfer { to: AccountId, value: Balance },
+        TransferFrom { from: AccountId, to: AccountId, value: Balance },
+        Approve { spender: AccountId, value: Balance },
+        ApproveWithDeadline { spender: AccountId, value: Balance, expires_at: Timestamp },
+        ClaimDividends,
+        Lock { value: Balance, unlock_at: Timestamp },
+        Unlock { lock_id: LockId },
+    }
+
+    /// Chain extension function and feed the oracle price is read from.
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct OracleConfig {
+        pub extension_id: u16,
+        pub function_id: u16,
+        /// Runtime identifier of the price feed.
+        pub feed: u32,
+    }
+
+    /// How the tokens of an allocation bucket vest after its cliff.
+    #[cfg(feature = "emissions")]
+    #[derive(
+        Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub enum VestingCurve {
+        /// Evenly over the vesting duration.
+        Linear,
+        /// In the given number of equal installments over the vesting duration.
+        Steps(u32),
+    }
+
+    /// An allocation bucket as passed to `new_with_buckets`.
+    #[cfg(feature = "emissions")]
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct BucketConfig {
+        pub name: String,
+        pub beneficiary: AccountId,
+        pub amount: Balance,
+        /// Time after deployment before which nothing vests.
+        pub cliff: Timestamp,
+        /// Time after deployment at which everything has vested.
+        pub duration: Timestamp,
+        pub curve: VestingCurve,
+    }
+
+    /// Tokens of an allocation bucket, held by the contract until they vest.
+    #[cfg(feature = "emissions")]
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct Bucket {
+        pub beneficiary: AccountId,
+        /// Allocated amount, in gons.
+        gons: Balance,
+        /// Part of the allocation paid out so far, in gons.
+        released_gons: Balance,
+        /// Time vesting is measured from.
+        pub start: Timestamp,
+        pub cliff: Timestamp,
+        pub duration: Timestamp,
+        pub curve: VestingCurve,
+    }
+
+    #[cfg(feature = "emissions")]
+    impl Bucket {
+        /// Returns the gons of the bucket vested at `now`, released or not.
+        fn vested_gons(&self, now: Timestamp) -> Balance {
+            let elapsed = now.saturating_sub(self.start);
+            if elapsed < self.cliff {
+                return 0
+            }
+            if elapsed >= self.duration {
+                return self.gons
+            }
+            let (elapsed, duration) = match self.curve {
+                VestingCurve::Linear => (elapsed, self.duration),
+                VestingCurve::Steps(steps) => {
+                    let step = self.duration / Timestamp::from(steps);
+                    (elapsed / step.max(1), Timestamp::from(steps))
+                }
+            };
+            mul_div(self.gons, elapsed.into(), duration.into())
+                .expect("elapsed time never exceeds the vesting duration")
+                .min(self.gons)
+        }
+    }
+
+    /// Machine readable reasons a transfer is rejected, as reported by
+    /// `detect_transfer_restriction` in the manner of ERC-1404.
+    #[cfg(feature = "compliance")]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum TransferRestriction {
+        None = 0,
+        Denylisted = 1,
+        Locked = 2,
+        Unaccredited = 3,
+        InsufficientBalance = 4,
+        MaxTransactionExceeded = 5,
+        MaxWalletExceeded = 6,
+        Unverified = 7,
+        NonTransferable = 8,
+        LaunchProtected = 9,
+        ZeroAmount = 10,
+        Frozen = 11,
+        CircuitBreakerTripped = 12,
+    }
+
+    #[cfg(feature = "compliance")]
+    impl TransferRestriction {
+        /// Returns the restriction reported for a transfer failing with `error`.
+        fn from_error(error: &Error) -> Self {
+            match error {
+                Error::Denylisted => Self::Denylisted,
+                Error::TokensLocked | Error::TokensLockedByVote | Error::TokensPartitioned => {
+                    Self::Locked
+                }
+                Error::NotAccredited => Self::Unaccredited,
+                Error::MaxTransactionExceeded => Self::MaxTransactionExceeded,
+                Error::MaxWalletExceeded => Self::MaxWalletExceeded,
+                Error::NotVerified => Self::Unverified,
+                Error::NonTransferable => Self::NonTransferable,
+                Error::SellTooSoon | Error::FlaggedSniper => Self::LaunchProtected,
+                Error::ZeroAmount => Self::ZeroAmount,
+                Error::AccountFrozen => Self::Frozen,
+                Error::CircuitBreakerTripped => Self::CircuitBreakerTripped,
+                _ => Self::InsufficientBalance,
+            }
+        }
+
+        fn from_code(code: u8) -> Option<Self> {
+            [
+                Self::None,
+                Self::Denylisted,
+                Self::Locked,
+                Self::Unaccredited,
+                Self::InsufficientBalance,
+                Self::MaxTransactionExceeded,
+                Self::MaxWalletExceeded,
+                Self::Unverified,
+                Self::NonTransferable,
+                Self::LaunchProtected,
+                Self::ZeroAmount,
+                Self::Frozen,
+                Self::CircuitBreakerTripped,
+            ]
+            .into_iter()
+            .find(|restriction| *restriction as u8 == code)
+        }
+
+        fn message(self) -> &'static str {
+            match self {
+                Self::None => "No restriction",
+                Self::Denylisted => "Sender or recipient is denylisted",
+                Self::Locked => "Tokens are locked",
+                Self::Unaccredited => "Recipient is not accredited",
+                Self::InsufficientBalance => "Insufficient balance",
+                Self::MaxTransactionExceeded => "Transfer exceeds the transaction limit",
+                Self::MaxWalletExceeded => "Recipient would exceed the wallet limit",
+                Self::Unverified => "Sender or recipient has not passed KYC",
+                Self::NonTransferable => "Tokens are not transferable",
+                Self::LaunchProtected => "Transfer is blocked by launch protection",
+                Self::ZeroAmount => "Transfers of zero tokens are not allowed",
+                Self::Frozen => "Sender or recipient is frozen",
+                Self::CircuitBreakerTripped => "Transfers are halted by the circuit breaker",
+            }
+        }
+    }
+
+    /// Protection against bots buying from a DEX pair right at launch.
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct LaunchProtection {
+        /// Blocks after a buy before the buyer may sell to a pair; one
+        /// rejects same-block round trips.
+        pub sell_delay: BlockNumber,
+        /// Blocks from enabling the protection in which every buyer is
+        /// flagged as a sniper and cannot move their tokens.
+        pub sniper_blocks: BlockNumber,
+        /// Time at which the protection lifts by itself.
+        pub ends_at: Timestamp,
+    }
+
+    /// How transfers of zero tokens are handled.
+    #[derive(
+        Debug, Default, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout,
+        SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub enum ZeroTransferPolicy {
+        /// Allowed and reported with a `Transfer` event, as ERC-20 requires.
+        #[default]
+        Emit,
+        /// Allowed without an event.
+        Silent,
+        /// Rejected with `Error::ZeroAmount`.
+        Reject,
+    }
+
+    /// A path protocol fees are taken on.
+    ///
+    /// The transfer tax is the only fee this token charges: it offers no
+    /// flash loans, and `accrue` passes all vault yield on to the holders.
+    /// A fee-charging path added later gets its own variant here.
+    #[cfg(feature = "fees")]
+    #[derive(
+        Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub enum FeeSource {
+        /// The tax taken on transfers.
+        TransferTax,
+    }
+
+    #[cfg(feature = "fees")]
+    impl FeeSource {
+        const ALL: [Self; 1] = [Self::TransferTax];
+    }
+
+    /// Privileged settings whose changes are reported by `ConfigChanged`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Setting {
+        Treasury,
+        TreasuryThreshold,
+        TaxRate,
+        BuyTaxRate,
+        SellTaxRate,
+        AmmPair,
+        BurnRate,
+        MaxTxAmount,
+        TxLimitExempt,
+        MaxWallet,
+        WalletLimitExempt,
+        Rebaser,
+        MinterCap,
+        Bridge,
+        XcmConfig,
+        Inflation,
+        GovernanceConfig,
+        OracleConfig,
+        FeeController,
+        SwapConfig,
+        Denylisted,
+        AccreditationRequired,
+        Accredited,
+        KycRegistry,
+        LaunchProtection,
+        Sniper,
+        ZeroTransferPolicy,
+        MintSale,
+        RedeemRate,
+        BondingCurve,
+        ReferralRate,
+        RandomnessConfig,
+        Frozen,
+        Controller,
+        ProtocolFeeShare,
+        FeeCollector,
+        CircuitBreaker,
+        MaxMintPerBlock,
+        ExcludedFromCirculation,
+        TransferLogCapacity,
+        Distributor,
+        FeeExempt,
+        Guardian,
+        DeadManSwitch,
+        RecoveryConfig,
+        DustThreshold,
+        StatsEnabled,
+        MintApproval,
+    }
+
+    /// Value of a setting before or after a `ConfigChanged`.
+    #[derive(Debug, Clone, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum ConfigValue {
+        Unset,
+        Count(u32),
+        Rate(u16),
+        Amount(Balance),
+        Account(AccountId),
+        Flag(bool),
+        Governance(GovernanceConfig),
+        Oracle(OracleConfig),
+        FeeController(FeeController),
+        Swap(SwapConfig),
+        Xcm(XcmConfig),
+        Inflation(InflationConfig),
+        LaunchProtection(LaunchProtection),
+        ZeroTransferPolicy(ZeroTransferPolicy),
+        MintSale(MintSale),
+        BondingCurve(BondingCurve),
+        Randomness(RandomnessConfig),
+        CircuitBreaker(CircuitBreaker),
+        DeadManSwitch(DeadManSwitch),
+        MintApproval(MintApprovalConfig),
+    }
+
+    macro_rules! impl_config_value {
+        ($($ty:ty => $variant:ident),* $(,)?) => {
+            $(
+                impl From<$ty> for ConfigValue {
+                    fn from(value: $ty) -> Self {
+                        Self::$variant(value)
+                    }
+                }
+            )*
+        };
+    }
+
+    // The environment's `Balance` and `AccountId` are projections, which
+    // coherence cannot tell apart from `ConfigValue`, so name them directly.
+    impl_config_value! {
+        u32 => Count,
+        u16 => Rate,
+        u128 => Amount,
+        ink_env::AccountId => Account,
+        bool => Flag,
+        GovernanceConfig => Governance,
+        OracleConfig => Oracle,
+        FeeController => FeeController,
+        SwapConfig => Swap,
+        XcmConfig => Xcm,
+        InflationConfig => Inflation,
+        LaunchProtection => LaunchProtection,
+        ZeroTransferPolicy => ZeroTransferPolicy,
+        MintSale => MintSale,
+        BondingCurve => BondingCurve,
+        RandomnessConfig => Randomness,
+        CircuitBreaker => CircuitBreaker,
+        DeadManSwitch => DeadManSwitch,
+        MintApprovalConfig => MintApproval,
+    }
+
+    impl<T: Into<ConfigValue>> From<Option<T>> for ConfigValue {
+        fn from(value: Option<T>) -> Self {
+            value.map_or(Self::Unset, Into::into)
+        }
+    }
+
+    /// Supply released to `recipient` at a fixed rate, see `drip`.
+    #[cfg(feature = "emissions")]
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct EmissionSchedule {
+        pub recipient: AccountId,
+        /// Tokens released per elapsed period.
+        pub amount_per_period: Balance,
+        /// Length of a period in milliseconds.
+        pub period: Timestamp,
+        /// Time after which nothing more is released.
+        pub end: Timestamp,
+    }
+
+    /// Supply inflation minted into a reward pool, see `mint_inflation`.
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct InflationConfig {
+        /// Account the inflation is minted to, e.g. a staking contract.
+        pub reward_pool: AccountId,
+        /// Yearly supply growth, in basis points.
+        pub annual_rate: u16,
+        /// Number of blocks the chain produces in a year.
+        pub blocks_per_year: BlockNumber,
+    }
+
+    #[cfg(feature = "emissions")]
+    impl InflationConfig {
+        /// Returns the tokens accrued on `supply` over `blocks`. The rate is
+        /// not compounded within an accrual, so frequent minting compounds
+        /// slightly faster than rare minting.
+        fn accrued(&self, supply: Balance, blocks: BlockNumber) -> Balance {
+            let yearly = mul_div(supply, self.annual_rate.into(), BASIS_POINTS.into())
+                .expect("basis point share of the supply fits");
+            mul_div(yearly, blocks.into(), self.blocks_per_year.into()).unwrap_or(Balance::MAX)
+        }
+    }
+
+    /// Price curve of `buy_on_curve` and `sell_on_curve`.
+    ///
+    /// Buying the token at curve supply `x` costs
+    /// `(base_price + slope * (x / scale) ^ exponent) / scale` native currency,
+    /// so `base_price` and `slope` are prices of `scale` tokens, e.g. of one
+    /// whole token. An exponent of one gives a linear curve.
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct BondingCurve {
+        /// Price of `scale` tokens at zero curve supply.
+        pub base_price: Balance,
+        /// Price `scale` tokens gain once the curve supply reaches `scale`.
+        pub slope: Balance,
+        /// Number of tokens prices are quoted for.
+        pub scale: Balance,
+        /// Power the curve supply enters the price with, up to `MAX_CURVE_EXPONENT`.
+        pub exponent: u32,
+    }
+
+    #[cfg(feature = "sale")]
+    impl BondingCurve {
+        /// Returns the native currency backing a curve supply of `supply`:
+        /// the area under the curve from zero to `supply`, rounded down.
+        ///
+        /// The rounding is monotonic in `supply`, so the reserve always covers
+        /// selling everything back.
+        fn reserve_at(&self, supply: Balance) -> Option<Balance> {
+            let mut power = supply;
+            for _ in 0..self.exponent {
+                power = mul_div(power, supply, self.scale)?;
+            }
+            let divisor = self.scale.checked_mul(Balance::from(self.exponent + 1))?;
+            mul_div(self.base_price, supply, self.scale)?
+                .checked_add(mul_div(power, self.slope, divisor)?)
+        }
+    }
+
+    /// Chain extension function that provides randomness for raffle draws.
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct RandomnessConfig {
+        pub extension_id: u16,
+        pub function_id: u16,
+    }
+
+    /// Chain extension function that sends tokens to sibling parachains.
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct XcmConfig {
+        pub extension_id: u16,
+        pub function_id: u16,
+    }
+
+    /// Derives the tax rate from the oracle price: the further the price is
+    /// from `target_price`, the higher the rate.
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct FeeController {
+        /// Price the token is meant to trade at.
+        pub target_price: Balance,
+        pub min_rate: u16,
+        pub max_rate: u16,
+    }
+
+    #[cfg(feature = "fees")]
+    impl FeeController {
+        /// Returns the deviation of `price` from the target in basis points,
+        /// bounded by the configured rates.
+        fn rate_for(&self, price: Balance) -> u16 {
+            let deviation = price.abs_diff(self.target_price);
+            let deviation_bps = mul_div(deviation, BASIS_POINTS.into(), self.target_price)
+                .unwrap_or(Balance::MAX);
+            let rate = u16::try_from(deviation_bps).unwrap_or(u16::MAX);
+            rate.clamp(self.min_rate, self.max_rate)
+        }
+    }
+
+    /// Router that sells collected tax once enough has accumulated.
+    ///
+    /// The router must provide `swap_exact_tokens_for_native(amount_in,
+    /// min_amount_out, to) -> Balance`, pulling the tokens with `transfer_from`.
+    #[derive(
+        Debug, Default, Clone, PartialEq, Eq, scale::Encode, scale::Decode, PackedLayout, SpreadLayout,
+    )]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink_storage::traits::StorageLayout)
+    )]
+    pub struct SwapConfig {
+        pub router: AccountId,
+        /// Collected tax at which a transfer triggers the swap.
+        pub threshold: Balance,
+    }
+
+    /// Call arguments that are already SCALE encoded.
+    #[cfg(feature = "governance")]
+    struct EncodedArgs<'a>(&'a [u8]);
+
+    #[cfg(feature = "governance")]
+    impl scale::Encode for EncodedArgs<'_> {
+        fn size_hint(&self) -> usize {
+            self.0.len()
+        }
+
+        fn encode_to<T: scale::Output + ?Sized>(&self, dest: &mut T) {
+            dest.write(self.0)
+        }
+    }
+
+    /// Splecify ERC-20 error type
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Return if the balance cannot fulfill a request
+        InsufficientBalance,
+        InsufficientAllowance,
+        /// Returned if the caller is not the token owner.
+        NotOwner,
+        /// Returned if the caller is not a minter.
+        NotMinter,
+        /// Returned if minting would overflow the total supply.
+        SupplyOverflow,
+        /// Returned if a burn rate above `MAX_BURN_RATE` is requested.
+        BurnRateTooHigh,
+        /// Returned if a transfer moves more than `max_tx_amount`.
+        MaxTransactionExceeded,
+        /// Returned if the recipient would end up holding more than `max_wallet`.
+        MaxWalletExceeded,
+        /// Returned if a tax rate above `MAX_TAX_RATE` is requested.
+        TaxRateTooHigh,
+        /// Returned if dividends are deposited while there are no tokens.
+        NoSupply,
+        /// Returned if there are no dividends to claim.
+        NoDividends,
+        /// Returned if sending native currency out of the contract failed.
+        NativeTransferFailed,
+        /// Returned if the caller is neither the owner nor the rebaser.
+        NotRebaser,
+        /// Returned if a rebase would take the supply to zero or overflow it.
+        RebaseOutOfRange,
+        /// Returned if proposals are made while the voting period is zero.
+        GovernanceDisabled,
+        /// Returned if the proposer holds less than the proposal threshold.
+        BelowProposalThreshold,
+        /// Returned if no proposal exists with the given identifier.
+        ProposalNotFound,
+        /// Returned if the proposal is not in the state the operation requires.
+        InvalidProposalState,
+        /// Returned if the account already voted on the proposal.
+        AlreadyVoted,
+        /// Returned if the voter holds no tokens.
+        NoVotingPower,
+        /// Returned if a queued proposal is executed before its timelock passed.
+        TimelockNotExpired,
+        /// Returned if the call made by an executed proposal failed.
+        ProposalCallFailed,
+        /// Returned if a transfer would move tokens pledged to a running vote.
+        TokensLockedByVote,
+        /// Returned if a sale is started while another one exists.
+        SaleAlreadyStarted,
+        /// Returned if there is no sale.
+        NoSale,
+        /// Returned if tokens are bought outside the sale window or after the hard cap.
+        SaleNotOpen,
+        /// Returned if a purchase is below the minimum or above the buyer's maximum.
+        PurchaseOutOfBounds,
+        /// Returned if a purchase would exceed the hard cap.
+        HardCapExceeded,
+        /// Returned if the sale is finalized before it closed, or twice.
+        SaleNotClosed,
+        /// Returned if sale tokens, refunds, payments or vested bucket tokens
+        /// are claimed while nothing is due.
+        NothingToClaim,
+        /// Returned if a transfer would spend locked tokens.
+        TokensLocked,
+        /// Returned if no lock exists with the given identifier.
+        LockNotFound,
+        /// Returned if a lock is released before its unlock time.
+        LockNotExpired,
+        /// Returned if no escrow exists with the given identifier.
+        EscrowNotFound,
+        /// Returned if an escrow has already been released or refunded.
+        EscrowResolved,
+        /// Returned if the caller may not resolve the escrow.
+        NotArbiter,
+        /// Returned if a payee is added twice or with zero shares.
+        InvalidPayee,
+        /// Returned if `migrate` finds storage already at the current version.
+        AlreadyMigrated,
+        /// Returned if the runtime rejects a new code hash.
+        CodeUpdateFailed,
+        /// Returned if the owner terminates the contract while tokens are
+        /// still in circulation.
+        SupplyNotZero,
+        /// Returned if the code is replaced other than by an executed
+        /// governance proposal.
+        UpgradeNotApproved,
+        /// Returned if no oracle is configured, the contract was built without
+        /// the `oracle` feature, or the runtime has no price for the feed.
+        OracleUnavailable,
+        /// Returned if the fee controller is missing or misconfigured.
+        InvalidFeeController,
+        /// Returned if the tax rate was already updated from the oracle in
+        /// this block.
+        FeeUpdateTooSoon,
+        /// Returned if `transfer_from` spends an allowance past its deadline.
+        AllowanceExpired,
+        /// Returned if a stream is empty or stops before it starts.
+        InvalidStream,
+        /// Returned if no stream exists with the given identifier.
+        StreamNotFound,
+        /// Returned if the caller is not the sender or recipient the stream
+        /// operation requires.
+        NotStreamParty,
+        /// Returned if the caller is not the bridge.
+        NotBridge,
+        /// Returned if tokens of a source transaction are bridged in twice.
+        AlreadyBridged,
+        /// Returned if XCM is not configured, the contract was built without
+        /// the `xcm` feature, or the runtime could not send the message.
+        XcmUnavailable,
+        /// Returned if a signed transfer does not carry the signer's next nonce.
+        InvalidNonce,
+        /// Returned if a signed transfer is submitted after its deadline.
+        SignatureExpired,
+        /// Returned if a transfer signature was not made by `from`.
+        InvalidSignature,
+        /// Returned if a minter mints more than its remaining cap.
+        MinterCapExceeded,
+        /// Returned if the token was deployed without an emission schedule.
+        NoEmission,
+        /// Returned if inflation is minted while disabled, or configured
+        /// without blocks per year.
+        InvalidInflation,
+        /// Returned if no allocation bucket exists with the given name.
+        BucketNotFound,
+        /// Returned if rewards are funded in more than `MAX_REWARD_TOKENS`
+        /// tokens.
+        TooManyRewardTokens,
+        /// Returned if a call to a reward token failed.
+        RewardTransferFailed,
+        /// Returned if the sender or recipient of a transfer is denylisted.
+        Denylisted,
+        /// Returned if the recipient of a transfer is not accredited while
+        /// accreditation is required.
+        NotAccredited,
+        /// Returned if the KYC registry does not confirm the sender or
+        /// recipient of a transfer.
+        NotVerified,
+        /// Returned if tokens of a soulbound deployment are moved.
+        NonTransferable,
+        /// Returned if an account sells to a DEX pair too soon after buying.
+        SellTooSoon,
+        /// Returned if an account flagged as a sniper moves tokens during
+        /// launch protection.
+        FlaggedSniper,
+        /// Returned if zero tokens are transferred while the zero transfer
+        /// policy rejects such transfers.
+        ZeroAmount,
+        /// Returned if tokens are redeemed while redemptions are disabled.
+        RedemptionDisabled,
+        /// Returned if the native reserve cannot cover a redemption.
+        InsufficientReserve,
+        /// Returned if tokens are bought or sold along the bonding curve while
+        /// there is none.
+        NoBondingCurve,
+        /// Returned if a bonding curve has a zero scale or too large an
+        /// exponent, or is changed while tokens bought along it are outstanding.
+        InvalidBondingCurve,
+        /// Returned if the referral rate is set above `MAX_REFERRAL_RATE`.
+        ReferralRateTooHigh,
+        /// Returned if a buyer names themselves as referrer.
+        SelfReferral,
+        /// Returned if there is no raffle with the given identifier.
+        RaffleNotFound,
+        /// Returned if tickets are bought after a raffle ended, or winners are
+        /// drawn twice or after `RAFFLE_DRAW_PERIOD`.
+        RaffleClosed,
+        /// Returned if winners are drawn before a raffle ended, or tickets
+        /// are refunded before `RAFFLE_DRAW_PERIOD` is over.
+        RaffleNotEnded,
+        /// Returned if a raffle is started with a zero ticket price, no
+        /// winners or more than `MAX_RAFFLE_WINNERS`.
+        InvalidRaffle,
+        /// Returned if the secret revealed at a draw does not match the
+        /// raffle's commitment.
+        InvalidReveal,
+        /// Returned if a raffle without commitment is drawn while the
+        /// randomness chain extension is unavailable.
+        RandomnessUnavailable,
+        /// Returned if a spender moves more than its spending limit allows
+        /// within the current period, or a limit with a zero period is set.
+        SpendingLimitExceeded,
+        /// Returned if a frozen account sends or receives tokens.
+        AccountFrozen,
+        /// Returned if the caller is not the controller.
+        NotController,
+        /// Returned if an account that is not frozen is wiped.
+        AccountNotFrozen,
+        /// Returned if no document has the given name.
+        DocumentNotFound,
+        /// Returned if a transfer would spend tokens assigned to a partition.
+        TokensPartitioned,
+        /// Returned if a partition holds fewer tokens than requested.
+        InsufficientPartitionBalance,
+        /// Returned if a holder's balance would be split across more than
+        /// `MAX_PARTITIONS` partitions.
+        TooManyPartitions,
+        /// Returned if the caller is not an operator of the partition.
+        NotPartitionOperator,
+        /// Returned if `accrue` would lower the exchange rate.
+        ExchangeRateDecrease,
+        /// Returned if the caller is not the fee collector.
+        NotFeeCollector,
+        /// Returned if the protocol fee share exceeds all of the fee.
+        ProtocolFeeShareTooHigh,
+        /// Returned if tokens are transferred while the circuit breaker is
+        /// tripped.
+        CircuitBreakerTripped,
+        /// Returned if a circuit breaker has a zero window or a threshold
+        /// outside of (0, 10000] basis points.
+        InvalidCircuitBreaker,
+        /// Returned if a mint exceeds what may be minted within the block.
+        MintRateExceeded,
+        /// Returned if more than `MAX_EXCLUDED_ACCOUNTS` accounts would be
+        /// excluded from the circulating supply.
+        TooManyExcludedAccounts,
+        /// Returned if the transfer log would keep more than
+        /// `MAX_TRANSFER_LOG` transfers.
+        TransferLogTooLarge,
+        /// Returned if the spender has no session on the owner's tokens.
+        SessionNotFound,
+        /// Returned if a session is used from its expiry on.
+        SessionExpired,
+        /// Returned if a transfer exceeds the per-transfer or total cap of a
+        /// session.
+        SessionCapExceeded,
+        /// Returned if the caller is not the distributor.
+        NotDistributor,
+        /// Returned if the caller already has an unexpired vote-escrow lock.
+        VeLockExists,
+        /// Returned if the caller has no unexpired vote-escrow lock.
+        VeLockNotFound,
+        /// Returned if a vote-escrow lock would end in the past, more than
+        /// `MAX_VE_LOCK_TIME` ahead, or earlier than it already does.
+        InvalidUnlockTime,
+        /// Returned if the caller is neither the guardian nor the owner.
+        NotGuardian,
+        /// Returned if a dead-man switch has a zero window.
+        InvalidDeadManSwitch,
+        /// Returned if the caller is not the recovery account of the
+        /// dead-man switch.
+        NotRecoveryAccount,
+        /// Returned if ownership is claimed while the owner's heartbeat is
+        /// still within the window.
+        OwnerStillActive,
+        /// Returned if a recovery has no guardians, more than
+        /// `MAX_RECOVERY_GUARDIANS`, repeated ones, the account itself, or a
+        /// threshold that is zero or larger than the number of guardians.
+        InvalidRecoveryConfig,
+        /// Returned if the caller is not a guardian of the account.
+        NotRecoveryGuardian,
+        /// Returned if a guardian approves a recovery to another account than
+        /// the one in progress.
+        RecoveryInProgress,
+        /// Returned if an account has no recovery in progress.
+        RecoveryNotFound,
+        /// Returned if a recovery lacks approvals or its delay has not passed.
+        RecoveryNotReady,
+        /// Returned if dust is swept without a dust threshold.
+        DustThresholdNotSet,
+        /// Returned if no pending scheduled transfer has the given identifier.
+        ScheduledTransferNotFound,
+        /// Returned if a scheduled transfer is executed before its time.
+        ScheduledTransferNotDue,
+        /// Returned if the caller is not the sender of a scheduled transfer.
+        NotScheduledSender,
+        /// Returned if no subscription has the given identifier.
+        SubscriptionNotFound,
+        /// Returned if a subscription is collected before its next payment
+        /// is due.
+        SubscriptionNotDue,
+        /// Returned if the caller is not the payer of a subscription.
+        NotSubscriptionPayer,
+        /// Returned if a subscription is created with a zero interval.
+        InvalidSubscriptionInterval,
+        /// Returned if no pending hashlocked transfer has the given
+        /// identifier.
+        HtlcNotFound,
+        /// Returned if a hashlocked transfer is created or claimed once its
+        /// timelock has passed.
+        HtlcExpired,
+        /// Returned if a hashlocked transfer is refunded before its timelock.
+        HtlcNotExpired,
+        /// Returned if the preimage does not hash to the hashlock.
+        InvalidPreimage,
+        /// Returned if no pending voucher has the given identifier.
+        VoucherNotFound,
+        /// Returned if a voucher is created or redeemed once it has expired.
+        VoucherExpired,
+        /// Returned if a voucher is reclaimed before it has expired.
+        VoucherNotExpired,
+        /// Returned if a voucher redemption is not signed by its signer.
+        InvalidVoucherProof,
+        /// Returned if the caller is not the sender of a voucher.
+        NotVoucherSender,
+        /// Returned if a mint above the direct limit is not requested with
+        /// `request_mint`.
+        MintApprovalRequired,
+        /// Returned if mint approvers are set with a threshold of zero or
+        /// above their number, more than `MAX_MINT_APPROVERS`, repeated
+        /// ones, or a zero request lifetime.
+        InvalidMintApproval,
+        /// Returned if a mint is requested without mint approvers.
+        MintApprovalNotSet,
+        /// Returned if no pending mint request has the given identifier.
+        MintRequestNotFound,
+        /// Returned if a mint request is approved or executed once it has
+        /// expired.
+        MintRequestExpired,
+        /// Returned if the caller is not a mint approver.
+        NotMintApprover,
+        /// Returned if a mint request is executed before enough approvers
+        /// confirmed it.
+        MintNotApproved,
+        /// Returned if a proposal calls a message of this contract that
+        /// governance may not call, see `GOVERNABLE_SELECTORS`.
+        SelectorNotGovernable,
+        /// Returned if a lock holds more than the locked total of its owner,
+        /// which would mean the lock bookkeeping is broken.
+        LockedTotalMismatch,
+        /// Returned if a spending limit is given a period of zero.
+        InvalidSpendingPeriod,
+        /// Returned if a Merkle airdrop is started before the last one has
+        /// been swept.
+        AirdropActive,
+        /// Returned if there is no Merkle airdrop.
+        NoMerkleAirdrop,
+        /// Returned if a Merkle airdrop is started or claimed once its
+        /// deadline has passed.
+        AirdropEnded,
+        /// Returned if a Merkle airdrop is swept before its deadline.
+        AirdropNotEnded,
+        /// Returned if a Merkle proof does not lead to the airdrop root.
+        InvalidMerkleProof,
+        /// Returned if the caller has already been paid in the campaign.
+        AlreadyAirdropped,
+        /// Returned if tokens are taken from an account that holds them on
+        /// behalf of others, such as the contract itself or the splitter.
+        ProtectedAccount,
+        /// Returned if a payee is added after the splitter has paid out.
+        PayoutsStarted,
+        /// Returned if a proposal calls a contract whose tokens this contract
+        /// holds for others, such as a reward token.
+        TargetNotGovernable,
+        /// Returned if storage is migrated from a version this code cannot
+        /// convert.
+        UnsupportedStorageVersion,
+    }
+
+    /// Stable numeric codes for frontends and indexers. Codes are never reused
+    /// or reordered; new variants get the next free code.
+    impl From<Error> for u32 {
+        fn from(error: Error) -> Self {
+            match error {
+                Error::InsufficientBalance => 1,
+                Error::InsufficientAllowance => 2,
+                Error::NotOwner => 3,
+                Error::NotMinter => 4,
+                Error::SupplyOverflow => 5,
+                Error::BurnRateTooHigh => 6,
+                Error::MaxTransactionExceeded => 7,
+                Error::MaxWalletExceeded => 8,
+                Error::TaxRateTooHigh => 9,
+                Error::NoSupply => 10,
+                Error::NoDividends => 11,
+                Error::NativeTransferFailed => 12,
+                Error::NotRebaser => 13,
+                Error::RebaseOutOfRange => 14,
+                Error::GovernanceDisabled => 15,
+                Error::BelowProposalThreshold => 16,
+                Error::ProposalNotFound => 17,
+                Error::InvalidProposalState => 18,
+                Error::AlreadyVoted => 19,
+                Error::NoVotingPower => 20,
+                Error::TimelockNotExpired => 21,
+                Error::ProposalCallFailed => 22,
+                Error::TokensLockedByVote => 23,
+                Error::SaleAlreadyStarted => 24,
+                Error::NoSale => 25,
+                Error::SaleNotOpen => 26,
+                Error::PurchaseOutOfBounds => 27,
+                Error::HardCapExceeded => 28,
+                Error::SaleNotClosed => 29,
+                Error::NothingToClaim => 30,
+                Error::TokensLocked => 31,
+                Error::LockNotFound => 32,
+                Error::LockNotExpired => 33,
+                Error::EscrowNotFound => 34,
+                Error::EscrowResolved => 35,
+                Error::NotArbiter => 36,
+                Error::InvalidPayee => 37,
+                Error::AlreadyMigrated => 38,
+                Error::CodeUpdateFailed => 39,
+                Error::SupplyNotZero => 40,
+                Error::UpgradeNotApproved => 41,
+                Error::OracleUnavailable => 42,
+                Error::InvalidFeeController => 43,
+                Error::FeeUpdateTooSoon => 44,
+                Error::AllowanceExpired => 45,
+                Error::InvalidStream => 46,
+                Error::StreamNotFound => 47,
+                Error::NotStreamParty => 48,
+                Error::NotBridge => 49,
+                Error::AlreadyBridged => 50,
+                Error::XcmUnavailable => 51,
+                Error::InvalidNonce => 52,
+                Error::SignatureExpired => 53,
+                Error::InvalidSignature => 54,
+                Error::MinterCapExceeded => 55,
+                Error::NoEmission => 56,
+                Error::InvalidInflation => 57,
+                Error::BucketNotFound => 58,
+                Error::TooManyRewardTokens => 59,
+                Error::RewardTransferFailed => 60,
+                Error::Denylisted => 61,
+                Error::NotAccredited => 62,
+                Error::NotVerified => 63,
+                Error::NonTransferable => 64,
+                Error::SellTooSoon => 65,
+                Error::FlaggedSniper => 66,
+                Error::ZeroAmount => 67,
+                Error::RedemptionDisabled => 68,
+                Error::InsufficientReserve => 69,
+                Error::NoBondingCurve => 70,
+                Error::InvalidBondingCurve => 71,
+                Error::ReferralRateTooHigh => 72,
+                Error::SelfReferral => 73,
+                Error::RaffleNotFound => 74,
+                Error::RaffleClosed => 75,
+                Error::RaffleNotEnded => 76,
+                Error::InvalidRaffle => 77,
+                Error::InvalidReveal => 78,
+                Error::RandomnessUnavailable => 79,
+                Error::SpendingLimitExceeded => 80,
+                Error::AccountFrozen => 81,
+                Error::NotController => 82,
+                Error::AccountNotFrozen => 83,
+                Error::DocumentNotFound => 84,
+                Error::TokensPartitioned => 85,
+                Error::InsufficientPartitionBalance => 86,
+                Error::TooManyPartitions => 87,
+                Error::NotPartitionOperator => 88,
+                Error::ExchangeRateDecrease => 89,
+                Error::NotFeeCollector => 90,
+                Error::ProtocolFeeShareTooHigh => 91,
+                Error::CircuitBreakerTripped => 92,
+                Error::InvalidCircuitBreaker => 93,
+                Error::MintRateExceeded => 94,
+                Error::TooManyExcludedAccounts => 95,
+                Error::TransferLogTooLarge => 96,
+                Error::SessionNotFound => 97,
+                Error::SessionExpired => 98,
+                Error::SessionCapExceeded => 99,
+                Error::NotDistributor => 100,
+                Error::VeLockExists => 101,
+                Error::VeLockNotFound => 102,
+                Error::InvalidUnlockTime => 103,
+                Error::NotGuardian => 104,
+                Error::InvalidDeadManSwitch => 105,
+                Error::NotRecoveryAccount => 106,
+                Error::OwnerStillActive => 107,
+                Error::InvalidRecoveryConfig => 108,
+                Error::NotRecoveryGuardian => 109,
+                Error::RecoveryInProgress => 110,
+                Error::RecoveryNotFound => 111,
+                Error::RecoveryNotReady => 112,
+                Error::DustThresholdNotSet => 113,
+                Error::ScheduledTransferNotFound => 114,
+                Error::ScheduledTransferNotDue => 115,
+                Error::NotScheduledSender => 116,
+                Error::SubscriptionNotFound => 117,
+                Error::SubscriptionNotDue => 118,
+                Error::NotSubscriptionPayer => 119,
+                Error::InvalidSubscriptionInterval => 120,
+                Error::HtlcNotFound => 121,
+                Error::HtlcExpired => 122,
+                Error::HtlcNotExpired => 123,
+                Error::InvalidPreimage => 124,
+                Error::VoucherNotFound => 125,
+                Error::VoucherExpired => 126,
+                Error::VoucherNotExpired => 127,
+                Error::InvalidVoucherProof => 128,
+                Error::NotVoucherSender => 129,
+                Error::MintApprovalRequired => 130,
+                Error::InvalidMintApproval => 131,
+                Error::MintApprovalNotSet => 132,
+                Error::MintRequestNotFound => 133,
+                Error::MintRequestExpired => 134,
+                Error::NotMintApprover => 135,
+                Error::MintNotApproved => 136,
+                Error::SelectorNotGovernable => 137,
+                Error::LockedTotalMismatch => 138,
+                Error::InvalidSpendingPeriod => 139,
+                Error::AirdropActive => 140,
+                Error::NoMerkleAirdrop => 141,
+                Error::AirdropEnded => 142,
+                Error::AirdropNotEnded => 143,
+                Error::InvalidMerkleProof => 144,
+                Error::AlreadyAirdropped => 145,
+                Error::ProtectedAccount => 146,
+                Error::PayoutsStarted => 147,
+                Error::TargetNotGovernable => 148,
+                Error::UnsupportedStorageVersion => 149,
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl core::fmt::Display for Error {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            f.write_str(match self {
+                Error::InsufficientBalance => "insufficient balance",
+                Error::InsufficientAllowance => "insufficient allowance",
+                Error::NotOwner => "caller is not the owner",
+                Error::NotMinter => "caller is not a minter",
+                Error::SupplyOverflow => "total supply would overflow",
+                Error::BurnRateTooHigh => "burn rate above the maximum",
+                Error::MaxTransactionExceeded => "transfer exceeds the transaction limit",
+                Error::MaxWalletExceeded => "recipient would exceed the wallet limit",
+                Error::TaxRateTooHigh => "tax rate above the maximum",
+                Error::NoSupply => "there are no tokens",
+                Error::NoDividends => "no dividends to claim",
+                Error::NativeTransferFailed => "native currency transfer failed",
+                Error::NotRebaser => "caller may not rebase",
+                Error::RebaseOutOfRange => "rebase out of range",
+                Error::GovernanceDisabled => "governance is disabled",
+                Error::BelowProposalThreshold => "balance below the proposal threshold",
+                Error::ProposalNotFound => "proposal not found",
+                Error::InvalidProposalState => "proposal is not in the required state",
+                Error::AlreadyVoted => "account already voted",
+                Error::NoVotingPower => "voter holds no tokens",
+                Error::TimelockNotExpired => "proposal timelock has not expired",
+                Error::ProposalCallFailed => "proposal call failed",
+                Error::TokensLockedByVote => "tokens are pledged to a running vote",
+                Error::SaleAlreadyStarted => "a sale already exists",
+                Error::NoSale => "there is no sale",
+                Error::SaleNotOpen => "sale is not open",
+                Error::PurchaseOutOfBounds => "purchase outside the allowed bounds",
+                Error::HardCapExceeded => "purchase exceeds the hard cap",
+                Error::SaleNotClosed => "sale has not closed",
+                Error::NothingToClaim => "nothing to claim",
+                Error::TokensLocked => "tokens are locked",
+                Error::LockNotFound => "lock not found",
+                Error::LockNotExpired => "lock has not expired",
+                Error::EscrowNotFound => "escrow not found",
+                Error::EscrowResolved => "escrow already resolved",
+                Error::NotArbiter => "caller may not resolve the escrow",
+                Error::InvalidPayee => "invalid payee",
+                Error::AlreadyMigrated => "storage already migrated",
+                Error::CodeUpdateFailed => "code update failed",
+                Error::SupplyNotZero => "tokens are still in circulation",
+                Error::UpgradeNotApproved => "upgrade not approved by governance",
+                Error::OracleUnavailable => "oracle price unavailable",
+                Error::InvalidFeeController => "invalid fee controller",
+                Error::FeeUpdateTooSoon => "fee already updated in this block",
+                Error::AllowanceExpired => "allowance expired",
+                Error::InvalidStream => "invalid stream",
+                Error::StreamNotFound => "stream not found",
+                Error::NotStreamParty => "caller is not party to the stream",
+                Error::NotBridge => "caller is not the bridge",
+                Error::AlreadyBridged => "source transaction already bridged",
+                Error::XcmUnavailable => "cross-chain transfer unavailable",
+                Error::InvalidNonce => "invalid nonce",
+                Error::SignatureExpired => "signature expired",
+                Error::InvalidSignature => "invalid signature",
+                Error::MinterCapExceeded => "minter cap exceeded",
+                Error::NoEmission => "no emission schedule",
+                Error::InvalidInflation => "inflation disabled or misconfigured",
+                Error::BucketNotFound => "allocation bucket not found",
+                Error::TooManyRewardTokens => "too many reward tokens",
+                Error::RewardTransferFailed => "reward token transfer failed",
+                Error::Denylisted => "account is denylisted",
+                Error::NotAccredited => "recipient is not accredited",
+                Error::NotVerified => "account has not passed KYC",
+                Error::NonTransferable => "tokens are not transferable",
+                Error::SellTooSoon => "selling too soon after buying",
+                Error::FlaggedSniper => "account is flagged as a sniper",
+                Error::ZeroAmount => "zero tokens transferred",
+                Error::RedemptionDisabled => "redemptions are disabled",
+                Error::InsufficientReserve => "insufficient native reserve",
+                Error::NoBondingCurve => "no bonding curve",
+                Error::InvalidBondingCurve => "invalid bonding curve",
+                Error::ReferralRateTooHigh => "referral rate above the maximum",
+                Error::SelfReferral => "buyer cannot refer themselves",
+                Error::RaffleNotFound => "raffle not found",
+                Error::RaffleClosed => "raffle is closed",
+                Error::RaffleNotEnded => "raffle has not ended",
+                Error::InvalidRaffle => "invalid raffle",
+                Error::InvalidReveal => "revealed secret does not match the commitment",
+                Error::RandomnessUnavailable => "randomness unavailable",
+                Error::SpendingLimitExceeded => "spending limit exceeded",
+                Error::AccountFrozen => "account is frozen",
+                Error::NotController => "caller is not the controller",
+                Error::AccountNotFrozen => "account is not frozen",
+                Error::DocumentNotFound => "document not found",
+                Error::TokensPartitioned => "tokens are assigned to a partition",
+                Error::InsufficientPartitionBalance => "insufficient partition balance",
+                Error::TooManyPartitions => "too many partitions",
+                Error::NotPartitionOperator => "caller is not an operator of the partition",
+                Error::ExchangeRateDecrease => "exchange rate must not decrease",
+                Error::NotFeeCollector => "caller is not the fee collector",
+                Error::ProtocolFeeShareTooHigh => "protocol fee share too high",
+                Error::CircuitBreakerTripped => "circuit breaker tripped",
+                Error::InvalidCircuitBreaker => "invalid circuit breaker",
+                Error::MintRateExceeded => "mint rate exceeded",
+                Error::TooManyExcludedAccounts => "too many excluded accounts",
+                Error::TransferLogTooLarge => "transfer log too large",
+                Error::SessionNotFound => "no session for the spender",
+                Error::SessionExpired => "session expired",
+                Error::SessionCapExceeded => "session cap exceeded",
+                Error::NotDistributor => "caller is not the distributor",
+                Error::VeLockExists => "vote-escrow lock exists",
+                Error::VeLockNotFound => "no vote-escrow lock",
+                Error::InvalidUnlockTime => "invalid unlock time",
+                Error::NotGuardian => "caller is not the guardian",
+                Error::InvalidDeadManSwitch => "invalid dead-man switch",
+                Error::NotRecoveryAccount => "caller is not the recovery account",
+                Error::OwnerStillActive => "owner still active",
+                Error::InvalidRecoveryConfig => "invalid recovery config",
+                Error::NotRecoveryGuardian => "caller is not a recovery guardian",
+                Error::RecoveryInProgress => "another recovery in progress",
+                Error::RecoveryNotFound => "no recovery in progress",
+                Error::RecoveryNotReady => "recovery not ready",
+                Error::DustThresholdNotSet => "no dust threshold",
+                Error::ScheduledTransferNotFound => "scheduled transfer not found",
+                Error::ScheduledTransferNotDue => "scheduled transfer not due",
+                Error::NotScheduledSender => "caller is not the sender",
+                Error::SubscriptionNotFound => "subscription not found",
+                Error::SubscriptionNotDue => "subscription payment not due",
+                Error::NotSubscriptionPayer => "caller is not the payer",
+                Error::InvalidSubscriptionInterval => "invalid subscription interval",
+                Error::HtlcNotFound => "hashlocked transfer not found",
+                Error::HtlcExpired => "hashlocked transfer expired",
+                Error::HtlcNotExpired => "hashlocked transfer not expired",
+                Error::InvalidPreimage => "invalid preimage",
+                Error::VoucherNotFound => "voucher not found",
+                Error::VoucherExpired => "voucher expired",
+                Error::VoucherNotExpired => "voucher not expired",
+                Error::InvalidVoucherProof => "invalid voucher proof",
+                Error::NotVoucherSender => "caller is not the sender",
+                Error::MintApprovalRequired => "mint requires approval",
+                Error::InvalidMintApproval => "invalid mint approval",
+                Error::MintApprovalNotSet => "no mint approvers",
+                Error::MintRequestNotFound => "mint request not found",
+                Error::MintRequestExpired => "mint request expired",
+                Error::NotMintApprover => "caller is not a mint approver",
+                Error::MintNotApproved => "mint not approved",
+                Error::SelectorNotGovernable => "message not callable by governance",
+                Error::LockedTotalMismatch => "locked total mismatch",
+                Error::InvalidSpendingPeriod => "invalid spending period",
+                Error::AirdropActive => "airdrop still active",
+                Error::NoMerkleAirdrop => "no merkle airdrop",
+                Error::AirdropEnded => "airdrop ended",
+                Error::AirdropNotEnded => "airdrop not ended",
+                Error::InvalidMerkleProof => "invalid merkle proof",
+                Error::AlreadyAirdropped => "already airdropped",
+                Error::ProtectedAccount => "protected account",
+                Error::PayoutsStarted => "payouts started",
+                Error::TargetNotGovernable => "contract not callable by governance",
+                Error::UnsupportedStorageVersion => "unsupported storage version",
+            })
+        }
+    }
+
+    #[ink(event)]
+    pub struct Transfer {
+        #[ink(topic)]
+        from: Option<AccountId>,
+        #[ink(topic)]
+        to: Option<AccountId>,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    /// Emitted next to `Transfer { from: None, .. }` whenever tokens are minted.
+    #[ink(event)]
+    pub struct Minted {
+        #[ink(topic)]
+        to: AccountId,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    /// Emitted next to `Transfer { to: None, .. }` whenever tokens are burned.
+    #[ink(event)]
+    pub struct Burned {
+        #[ink(topic)]
+        from: AccountId,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    /// Emitted after every burn with the resulting supply figures.
+    #[ink(event)]
+    pub struct SupplyChanged {
+        total_supply: Balance,
+        total_burned: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[ink(event)]
+    pub struct Approve {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        spender: AccountId,
+        value: Balance,
+        /// The allowance spendable right before the approval.
+        previous: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "fees")]
+    #[ink(event)]
+    pub struct AmmPairUpdated {
+        #[ink(topic)]
+        pair: AccountId,
+        is_pair: bool,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "dividends")]
+    #[ink(event)]
+    pub struct DividendsDeposited {
+        #[ink(topic)]
+        from: AccountId,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "dividends")]
+    #[ink(event)]
+    pub struct DividendsClaimed {
+        #[ink(topic)]
+        account: AccountId,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "dividends")]
+    #[ink(event)]
+    pub struct RewardsFunded {
+        #[ink(topic)]
+        token: AccountId,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "dividends")]
+    #[ink(event)]
+    pub struct RewardsClaimed {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        token: AccountId,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[ink(event)]
+    pub struct OwnershipTransferred {
+        #[ink(topic)]
+        previous_owner: AccountId,
+        #[ink(topic)]
+        new_owner: AccountId,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "recovery")]
+    #[ink(event)]
+    pub struct Heartbeat {
+        #[ink(topic)]
+        owner: AccountId,
+        /// Time from which the recovery account may claim ownership.
+        lapses_at: Timestamp,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "recovery")]
+    #[ink(event)]
+    pub struct OwnershipClaimed {
+        #[ink(topic)]
+        previous_owner: AccountId,
+        #[ink(topic)]
+        recovery: AccountId,
+        last_heartbeat: Timestamp,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "governance")]
+    #[ink(event)]
+    pub struct ProposalCreated {
+        #[ink(topic)]
+        proposal_id: ProposalId,
+        #[ink(topic)]
+        proposer: AccountId,
+        target: AccountId,
+        description_hash: Hash,
+        vote_end: BlockNumber,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "governance")]
+    #[ink(event)]
+    pub struct VoteCast {
+        #[ink(topic)]
+        proposal_id: ProposalId,
+        #[ink(topic)]
+        voter: AccountId,
+        support: bool,
+        weight: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "governance")]
+    #[ink(event)]
+    pub struct ProposalQueued {
+        #[ink(topic)]
+        proposal_id: ProposalId,
+        eta: Timestamp,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "governance")]
+    #[ink(event)]
+    pub struct ProposalExecuted {
+        #[ink(topic)]
+        proposal_id: ProposalId,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "sale")]
+    #[ink(event)]
+    pub struct TokensPurchased {
+        #[ink(topic)]
+        buyer: AccountId,
+        value: Balance,
+        tokens: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "sale")]
+    #[ink(event)]
+    pub struct SaleFinalized {
+        raised: Balance,
+        succeeded: bool,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "sale")]
+    #[ink(event)]
+    pub struct SaleRefunded {
+        #[ink(topic)]
+        buyer: AccountId,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "compliance")]
+    #[ink(event)]
+    pub struct Frozen {
+        #[ink(topic)]
+        account: AccountId,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "compliance")]
+    #[ink(event)]
+    pub struct Unfrozen {
+        #[ink(topic)]
+        account: AccountId,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "compliance")]
+    #[ink(event)]
+    pub struct ControllerTransfer {
+        #[ink(topic)]
+        controller: AccountId,
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        value: Balance,
+        reason: Vec<u8>,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "compliance")]
+    #[ink(event)]
+    pub struct Wiped {
+        #[ink(topic)]
+        controller: AccountId,
+        #[ink(topic)]
+        account: AccountId,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "metadata")]
+    #[ink(event)]
+    pub struct MetadataUpdated {
+        metadata: TokenMetadata,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "documents")]
+    #[ink(event)]
+    pub struct DocumentUpdated {
+        name: String,
+        uri: String,
+        hash: Hash,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "documents")]
+    #[ink(event)]
+    pub struct DocumentRemoved {
+        name: String,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "partitions")]
+    #[ink(event)]
+    pub struct TransferByPartition {
+        partition: String,
+        #[ink(topic)]
+        operator: Option<AccountId>,
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "partitions")]
+    #[ink(event)]
+    pub struct PartitionChanged {
+        #[ink(topic)]
+        holder: AccountId,
+        from: Option<String>,
+        to: Option<String>,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "partitions")]
+    #[ink(event)]
+    pub struct PartitionOperatorUpdated {
+        #[ink(topic)]
+        holder: AccountId,
+        #[ink(topic)]
+        operator: AccountId,
+        partition: String,
+        authorized: bool,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "holders")]
+    #[ink(event)]
+    pub struct SupplyVerified {
+        holders: u32,
+        total_supply: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "holders")]
+    #[ink(event)]
+    pub struct DustSwept {
+        /// Number of balances swept.
+        holders: u32,
+        value: Balance,
+        /// Where the dust went, or `None` if it was burnt.
+        treasury: Option<AccountId>,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "airdrops")]
+    #[ink(event)]
+    pub struct Airdropped {
+        #[ink(topic)]
+        campaign: u32,
+        /// Recipients paid by this call, leaving out those paid before.
+        recipients: u32,
+        /// Tokens the recipients received, after burn and tax.
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "airdrops")]
+    #[ink(event)]
+    pub struct MerkleAirdropStarted {
+        #[ink(topic)]
+        campaign: u32,
+        root: [u8; 32],
+        value: Balance,
+        deadline: Timestamp,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "airdrops")]
+    #[ink(event)]
+    pub struct AirdropClaimed {
+        #[ink(topic)]
+        campaign: u32,
+        #[ink(topic)]
+        account: AccountId,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "airdrops")]
+    #[ink(event)]
+    pub struct UnclaimedSwept {
+        #[ink(topic)]
+        campaign: u32,
+        to: AccountId,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "sessions")]
+    #[ink(event)]
+    pub struct SessionCreated {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        spender: AccountId,
+        total_cap: Balance,
+        per_tx_cap: Balance,
+        expires_at: Timestamp,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "sessions")]
+    #[ink(event)]
+    pub struct SessionRevoked {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        spender: AccountId,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "recovery")]
+    #[ink(event)]
+    pub struct RecoveryApproved {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        new_account: AccountId,
+        #[ink(topic)]
+        guardian: AccountId,
+        approvals: u32,
+        /// Time from which the recovery can be carried out, once approved.
+        executable_at: Option<Timestamp>,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "recovery")]
+    #[ink(event)]
+    pub struct RecoveryVetoed {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        new_account: AccountId,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "recovery")]
+    #[ink(event)]
+    pub struct BalanceRecovered {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        new_account: AccountId,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "mintable")]
+    #[ink(event)]
+    pub struct MintRequested {
+        #[ink(topic)]
+        id: MintRequestId,
+        #[ink(topic)]
+        minter: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        value: Balance,
+        expires_at: Timestamp,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "mintable")]
+    #[ink(event)]
+    pub struct MintApproved {
+        #[ink(topic)]
+        id: MintRequestId,
+        #[ink(topic)]
+        approver: AccountId,
+        approvals: u32,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "mintable")]
+    #[ink(event)]
+    pub struct MintExecuted {
+        #[ink(topic)]
+        id: MintRequestId,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "mintable")]
+    #[ink(event)]
+    pub struct MintRequestCancelled {
+        #[ink(topic)]
+        id: MintRequestId,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "fees")]
+    #[ink(event)]
+    pub struct ProtocolFeesCollected {
+        #[ink(topic)]
+        collector: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "pausable")]
+    #[ink(event)]
+    pub struct CircuitBreakerTripped {
+        volume: Balance,
+        total_supply: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "pausable")]
+    #[ink(event)]
+    pub struct Paused {
+        #[ink(topic)]
+        caller: AccountId,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "pausable")]
+    #[ink(event)]
+    pub struct CircuitBreakerReset {
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "raffles")]
+    #[ink(event)]
+    pub struct RaffleStarted {
+        #[ink(topic)]
+        raffle_id: RaffleId,
+        ticket_price: Balance,
+        ends_at: Timestamp,
+        winners: u32,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "raffles")]
+    #[ink(event)]
+    pub struct TicketsBought {
+        #[ink(topic)]
+        raffle_id: RaffleId,
+        #[ink(topic)]
+        buyer: AccountId,
+        count: u32,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "raffles")]
+    #[ink(event)]
+    pub struct RaffleDrawn {
+        #[ink(topic)]
+        raffle_id: RaffleId,
+        winners: Vec<AccountId>,
+        prize: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "raffles")]
+    #[ink(event)]
+    pub struct RaffleRefunded {
+        #[ink(topic)]
+        raffle_id: RaffleId,
+        #[ink(topic)]
+        buyer: AccountId,
+        tickets: u32,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "sale")]
+    #[ink(event)]
+    pub struct ReferralPaid {
+        #[ink(topic)]
+        referrer: AccountId,
+        #[ink(topic)]
+        buyer: AccountId,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "sale")]
+    #[ink(event)]
+    pub struct TokensRedeemed {
+        #[ink(topic)]
+        account: AccountId,
+        tokens: Balance,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "sale")]
+    #[ink(event)]
+    pub struct ProceedsWithdrawn {
+        #[ink(topic)]
+        to: AccountId,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "locks")]
+    #[ink(event)]
+    pub struct TokensLocked {
+        #[ink(topic)]
+        account: AccountId,
+        lock_id: LockId,
+        value: Balance,
+        unlock_at: Timestamp,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "vote-escrow")]
+    #[ink(event)]
+    pub struct VeLockUpdated {
+        #[ink(topic)]
+        account: AccountId,
+        value: Balance,
+        unlock_time: Timestamp,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "locks")]
+    #[ink(event)]
+    pub struct TokensUnlocked {
+        #[ink(topic)]
+        account: AccountId,
+        lock_id: LockId,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "escrow")]
+    #[ink(event)]
+    pub struct EscrowCreated {
+        #[ink(topic)]
+        escrow_id: EscrowId,
+        #[ink(topic)]
+        depositor: AccountId,
+        #[ink(topic)]
+        beneficiary: AccountId,
+        arbiter: AccountId,
+        value: Balance,
+        expires_at: Timestamp,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "escrow")]
+    #[ink(event)]
+    pub struct EscrowResolved {
+        #[ink(topic)]
+        escrow_id: EscrowId,
+        #[ink(topic)]
+        to: AccountId,
+        value: Balance,
+        released: bool,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "scheduled")]
+    #[ink(event)]
+    pub struct TransferScheduled {
+        #[ink(topic)]
+        id: ScheduledId,
+        #[ink(topic)]
+        sender: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        value: Balance,
+        tip: Balance,
+        execute_after: Timestamp,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "scheduled")]
+    #[ink(event)]
+    pub struct ScheduledTransferExecuted {
+        #[ink(topic)]
+        id: ScheduledId,
+        #[ink(topic)]
+        executor: AccountId,
+        value: Balance,
+        tip: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "scheduled")]
+    #[ink(event)]
+    pub struct ScheduledTransferCancelled {
+        #[ink(topic)]
+        id: ScheduledId,
+        /// Tokens returned to the sender, tip included.
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "subscriptions")]
+    #[ink(event)]
+    pub struct SubscriptionCreated {
+        #[ink(topic)]
+        id: SubscriptionId,
+        #[ink(topic)]
+        payer: AccountId,
+        #[ink(topic)]
+        merchant: AccountId,
+        amount: Balance,
+        interval: Timestamp,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "subscriptions")]
+    #[ink(event)]
+    pub struct SubscriptionCollected {
+        #[ink(topic)]
+        id: SubscriptionId,
+        #[ink(topic)]
+        collector: AccountId,
+        amount: Balance,
+        /// Intervals that passed without a collection and were skipped.
+        missed: u64,
+        next_collection: Timestamp,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "subscriptions")]
+    #[ink(event)]
+    pub struct SubscriptionCancelled {
+        #[ink(topic)]
+        id: SubscriptionId,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "htlc")]
+    #[ink(event)]
+    pub struct HtlcCreated {
+        #[ink(topic)]
+        id: HtlcId,
+        #[ink(topic)]
+        sender: AccountId,
+        #[ink(topic)]
+        recipient: AccountId,
+        value: Balance,
+        hashlock: [u8; 32],
+        timelock: Timestamp,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "htlc")]
+    #[ink(event)]
+    pub struct HtlcClaimed {
+        #[ink(topic)]
+        id: HtlcId,
+        value: Balance,
+        /// Revealed so that the counterparty of a swap can claim on the
+        /// other side.
+        preimage: Vec<u8>,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "htlc")]
+    #[ink(event)]
+    pub struct HtlcRefunded {
+        #[ink(topic)]
+        id: HtlcId,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "vouchers")]
+    #[ink(event)]
+    pub struct VoucherCreated {
+        #[ink(topic)]
+        id: VoucherId,
+        #[ink(topic)]
+        sender: AccountId,
+        value: Balance,
+        expires_at: Timestamp,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "vouchers")]
+    #[ink(event)]
+    pub struct VoucherRedeemed {
+        #[ink(topic)]
+        id: VoucherId,
+        #[ink(topic)]
+        to: AccountId,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "vouchers")]
+    #[ink(event)]
+    pub struct VoucherReclaimed {
+        #[ink(topic)]
+        id: VoucherId,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "splitter")]
+    #[ink(event)]
+    pub struct PayeeAdded {
+        #[ink(topic)]
+        payee: AccountId,
+        shares: u32,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "splitter")]
+    #[ink(event)]
+    pub struct PaymentReleased {
+        #[ink(topic)]
+        payee: AccountId,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "streams")]
+    #[ink(event)]
+    pub struct StreamCreated {
+        #[ink(topic)]
+        stream_id: StreamId,
+        #[ink(topic)]
+        sender: AccountId,
+        #[ink(topic)]
+        recipient: AccountId,
+        deposit: Balance,
+        start: Timestamp,
+        stop: Timestamp,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "streams")]
+    #[ink(event)]
+    pub struct StreamWithdrawn {
+        #[ink(topic)]
+        stream_id: StreamId,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "streams")]
+    #[ink(event)]
+    pub struct StreamCanceled {
+        #[ink(topic)]
+        stream_id: StreamId,
+        recipient_value: Balance,
+        sender_value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[ink(event)]
+    pub struct CodeUpdated {
+        #[ink(topic)]
+        old: Hash,
+        #[ink(topic)]
+        new: Hash,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "fees")]
+    #[ink(event)]
+    pub struct TaxRateUpdated {
+        old_rate: u16,
+        new_rate: u16,
+        price: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "fees")]
+    #[ink(event)]
+    pub struct TaxSwapped {
+        #[ink(topic)]
+        router: AccountId,
+        tokens: Balance,
+        proceeds: Balance,
+        to: AccountId,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[ink(event)]
+    pub struct StorageMigrated {
+        from_version: u32,
+        to_version: u32,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "bridge")]
+    #[ink(event)]
+    pub struct BridgedOut {
+        #[ink(topic)]
+        from: AccountId,
+        value: Balance,
+        #[ink(topic)]
+        dest_chain: u32,
+        dest_address: Vec<u8>,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "bridge")]
+    #[ink(event)]
+    pub struct BridgedIn {
+        #[ink(topic)]
+        to: AccountId,
+        value: Balance,
+        #[ink(topic)]
+        source_tx: Hash,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "bridge")]
+    #[ink(event)]
+    pub struct CrossChainTransfer {
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        para_id: u32,
+        beneficiary: AccountId,
+        value: Balance,
+        #[ink(topic)]
+        message_hash: Hash,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "mintable")]
+    #[ink(event)]
+    pub struct MinterUpdated {
+        #[ink(topic)]
+        account: AccountId,
+        cap: Option<Balance>,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "emissions")]
+    #[ink(event)]
+    pub struct BucketReleased {
+        name: String,
+        #[ink(topic)]
+        beneficiary: AccountId,
+        value: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[ink(event)]
+    pub struct ConfigChanged {
+        #[ink(topic)]
+        setting: Setting,
+        /// Account the setting applies to, for per-account settings.
+        #[ink(topic)]
+        account: Option<AccountId>,
+        old: ConfigValue,
+        new: ConfigValue,
+        #[ink(topic)]
+        caller: AccountId,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    #[cfg(feature = "elastic")]
+    #[ink(event)]
+    pub struct Rebase {
+        total_supply: Balance,
+        block_number: BlockNumber,
+        timestamp: Timestamp,
+        event_seq: u64,
+    }
+
+    /// Specify the ERC-20 result type
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    impl Erc20 {
+        /// Create a new ERC-20 contract with an initial supply.
+        #[ink(constructor)]
+        pub fn new(initial_supply: Balance) -> Self {
+            // Initialize mapping for the contract.
+            ink_lang::utils::initialize_contract(|contract| {
+                Self::new_init(contract, initial_supply)
+            })
+        }
+
+        /// Initialize the ERC-20 contract with the specified initial supply.
+        fn new_init(&mut self, initial_supply: Balance) {
+            let caller = Self::env().caller();
+            self.init_roles(caller, caller);
+            self.init_supply(caller, initial_supply);
+        }
+
+        /// Create a new ERC-20 contract with token metadata, handing the initial
+        /// supply and all privileges to `owner` and `minter` rather than the deployer.
+        #[ink(constructor)]
+        pub fn new_with_metadata(
+            name: Option<String>,
+            symbol: Option<String>,
+            decimals: u8,
+            initial_supply: Balance,
+            owner: AccountId,
+            minter: AccountId,
+        ) -> Self {
+            ink_lang::utils::initialize_contract(|contract: &mut Self| {
+                if let Some(name) = name {
+                    contract.name.set(&name);
+                }
+                if let Some(symbol) = symbol {
+                    contract.symbol.set(&symbol);
+                }
+                contract.decimals.set(&decimals);
+                contract.init_roles(owner, minter);
+                contract.init_supply(owner, initial_supply);
+            })
+        }
+
+        /// Create a new ERC-20 contract with an initial supply that grows by
+        /// `schedule.amount_per_period` every period from now until
+        /// `schedule.end`, released through `drip`.
+        ///
+        /// Panics if the period is zero.
+        #[cfg(feature = "emissions")]
+        #[ink(constructor)]
+        pub fn new_with_emission(initial_supply: Balance, schedule: EmissionSchedule) -> Self {
+            assert!(schedule.period > 0, "emission period must not be zero");
+            ink_lang::utils::initialize_contract(|contract: &mut Self| {
+                Self::new_init(contract, initial_supply);
+                contract.emission.set(&Some(schedule));
+                contract.emitted_until.set(&Self::env().block_timestamp());
+            })
+        }
+
+        fn init_roles(&mut self, owner: AccountId, minter: AccountId) {
+            self.owner.set(&owner);
+            self.minter.set(&minter);
+            // Every constructor goes through here; fresh storage needs no migration.
+            self.storage_version.set(&STORAGE_VERSION);
+        }
+
+        // Constructors run before any rebase, so they store token amounts as gons.
+        fn init_supply(&mut self, holder: AccountId, initial_supply: Balance) {
+            self.set_balance(&holder, initial_supply);
+            self.total_supply.set(&initial_supply);
+            self.emit_minted(holder, initial_supply);
+        }
+
+        /// Create a new ERC-20 contract with `total_supply` split across the
+        /// given accounts at genesis.
+        ///
+        /// Panics unless the allocations add up to exactly `total_supply`.
+        #[ink(constructor)]
+        pub fn new_with_allocations(
+            total_supply: Balance,
+            allocations: Vec<(AccountId, Balance)>,
+        ) -> Self {
+            ink_lang::utils::initialize_contract(|contract: &mut Self| {
+                let allocated = allocations
+                    .iter()
+                    .try_fold(0, |sum: Balance, (_, value)| sum.checked_add(*value));
+                assert_eq!(
+                    allocated,
+                    Some(total_supply),
+                    "allocations must add up to the total supply"
+                );
+                let caller = Self::env().caller();
+                contract.init_roles(caller, caller);
+                for (holder, value) in allocations {
+                    let balance = contract.gons_of(&holder);
+                    contract.set_balance(&holder, balance + value);
+                    contract.emit_minted(holder, value);
+                }
+                contract.total_supply.set(&total_supply);
+            })
+        }
+
+        /// Create a new ERC-20 contract whose balances cannot be transferred,
+        /// only minted and burnt, e.g. for reputation or credential points.
+        #[cfg(feature = "soulbound")]
+        #[ink(constructor)]
+        pub fn new_soulbound(initial_supply: Balance) -> Self {
+            ink_lang::utils::initialize_contract(|contract: &mut Self| {
+                Self::new_init(contract, initial_supply);
+                contract.soulbound.set(&true);
+            })
+        }
+
+        /// Create a new ERC-20 contract for a token generation event: the
+        /// deployer gets `initial_supply`, and every bucket's amount is minted
+        /// into the contract to vest to its beneficiary, see `release_bucket`.
+        ///
+        /// Panics if bucket names repeat, a curve has zero steps, a cliff is
+        /// longer than the vesting duration, or the supply overflows.
+        #[cfg(feature = "emissions")]
+        #[ink(constructor)]
+        pub fn new_with_buckets(initial_supply: Balance, buckets: Vec<BucketConfig>) -> Self {
+            ink_lang::utils::initialize_contract(|contract: &mut Self| {
+                let caller = Self::env().caller();
+                let account = Self::env().account_id();
+                let start = Self::env().block_timestamp();
+                contract.init_roles(caller, caller);
+                contract.set_balance(&caller, initial_supply);
+                contract.emit_minted(caller, initial_supply);
+                let mut total_supply = initial_supply;
+                for config in buckets {
+                    assert!(
+                        !contract.buckets.contains(&config.name),
+                        "bucket names must be unique"
+                    );
+                    assert_ne!(config.curve, VestingCurve::Steps(0), "vesting needs steps");
+                    assert!(config.cliff <= config.duration, "cliff exceeds vesting");
+                    total_supply = total_supply
+                        .checked_add(config.amount)
+                        .expect("buckets overflow the total supply");
+                    let balance = contract.gons_of(&account);
+                    contract.set_balance(&account, balance + config.amount);
+                    contract.emit_minted(account, config.amount);
+                    contract.buckets.insert(
+                        &config.name,
+                        &Bucket {
+                            beneficiary: config.beneficiary,
+                            gons: config.amount,
+                            released_gons: 0,
+                            start,
+                            cliff: config.cliff,
+                            duration: config.duration,
+                            curve: config.curve,
+                        },
+                    );
+                }
+                contract.total_supply.set(&total_supply);
+            })
+        }
+
+        /// Create a new ERC-20 contract that takes over the balances of `holders`
+        /// from the deployment at `old_contract`.
+        ///
+        /// Balances are read through `balance_of` calls on the old contract and
+        /// re-minted here, so this works for upgrades that cannot use
+        /// `set_code_hash`. The old contract should be frozen beforehand.
+        #[cfg(feature = "migration")]
+        #[ink(constructor)]
+        pub fn new_from(old_contract: AccountId, holders: Vec<AccountId>) -> Self {
+            ink_lang::utils::initialize_contract(|contract: &mut Self| {
+                let balances = holders
+                    .into_iter()
+                    .map(|holder| (holder, Self::remote_balance_of(old_contract, holder)))
+                    .collect::<Vec<_>>();
+                let caller = Self::env().caller();
+                contract.init_roles(caller, caller);
+                contract.import_balances(balances);
+            })
+        }
+
+        /// Queries the balance of `owner` on another ERC-20 contract.
+        #[cfg(feature = "migration")]
+        fn remote_balance_of(contract: AccountId, owner: AccountId) -> Balance {
+            build_call::<Environment>()
+                .call_type(call::Call::new().callee(contract))
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink_lang::selector_bytes!("balance_of")))
+                        .push_arg(owner),
+                )
+                .returns::<Balance>()
+                .fire()
+                .expect("balance_of call to the old contract failed")
+        }
+
+        /// Mints the given balances into the fresh contract, skipping zero
+        /// balances and holders that have already been imported.
+        #[cfg(feature = "migration")]
+        fn import_balances(&mut self, balances: impl IntoIterator<Item = (AccountId, Balance)>) {
+            let mut total_supply = self.total_supply();
+            for (holder, value) in balances {
+                if value == 0 || self.balances.contains(holder) {
+                    continue
+                }
+                self.set_balance(&holder, value);
+                total_supply = total_supply
+                    .checked_add(value)
+                    .expect("imported balances overflow the total supply");
+                self.emit_minted(holder, value);
+            }
+            self.total_supply.set(&total_supply);
+        }
+
+        /// Returns the token name.
+        #[ink(message)]
+        pub fn name(&self) -> Option<String> {
+            self.name.get()
+        }
+
+        /// Returns the token symbol.
+        #[ink(message)]
+        pub fn symbol(&self) -> Option<String> {
+            self.symbol.get()
+        }
+
+        /// Returns the number of decimals used to display balances.
+        #[ink(message)]
+        pub fn decimals(&self) -> u8 {
+            self.decimals.get_or_default()
+        }
+
+        /// Returns the logo, description, website and tags of the token.
+        #[cfg(feature = "metadata")]
+        #[ink(message)]
+        pub fn token_metadata(&self) -> TokenMetadata {
+            self.token_metadata.get_or_default()
+        }
+
+        /// Replaces the logo, description, website and tags of the token.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "metadata")]
+        #[ink(message)]
+        pub fn set_token_metadata(&mut self, metadata: TokenMetadata) -> Result<()> {
+            self.ensure_owner()?;
+            self.token_metadata.set(&metadata);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(MetadataUpdated {
+                metadata,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Returns the token owner.
+        #[ink(message)]
+        pub fn owner(&self) -> AccountId {
+            self.owner.get_or_default()
+        }
+
+        /// Hands ownership to `new_owner`, e.g. to this contract's own account to
+        /// put the token under governance.
+        ///
+        /// Only the owner may call this.
+        #[ink(message)]
+        pub fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<()> {
+            self.ensure_owner()?;
+            self.set_owner(new_owner);
+            Ok(())
+        }
+
+        /// Hands ownership to `new_owner`, whose heartbeat starts now.
+        fn set_owner(&mut self, new_owner: AccountId) {
+            let previous_owner = self.owner();
+            self.owner.set(&new_owner);
+            #[cfg(feature = "recovery")]
+            {
+                let now = self.env().block_timestamp();
+                self.last_heartbeat.set(&now);
+            }
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(OwnershipTransferred {
+                previous_owner,
+                new_owner,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+        }
+
+        /// Returns the dead-man switch, if set.
+        #[cfg(feature = "recovery")]
+        #[ink(message)]
+        pub fn dead_man_switch(&self) -> Option<DeadManSwitch> {
+            self.dead_man_switch.get().flatten()
+        }
+
+        /// Returns the time of the owner's last heartbeat.
+        #[cfg(feature = "recovery")]
+        #[ink(message)]
+        pub fn last_heartbeat(&self) -> Timestamp {
+            self.last_heartbeat.get_or_default()
+        }
+
+        /// Sets or removes the dead-man switch, which lets its recovery
+        /// account take over ownership once the owner has not called
+        /// `heartbeat` for its window. Setting it counts as a heartbeat.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "recovery")]
+        #[ink(message)]
+        pub fn set_dead_man_switch(&mut self, switch: Option<DeadManSwitch>) -> Result<()> {
+            self.ensure_owner()?;
+            if switch.as_ref().is_some_and(|switch| switch.window == 0) {
+                return Err(Error::InvalidDeadManSwitch)
+            }
+            let old = self.dead_man_switch();
+            self.dead_man_switch.set(&switch);
+            let now = self.env().block_timestamp();
+            self.last_heartbeat.set(&now);
+            self.emit_config_changed(Setting::DeadManSwitch, None, old, switch);
+            Ok(())
+        }
+
+        /// Shows that the owner is still in control, restarting the window
+        /// of the dead-man switch.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "recovery")]
+        #[ink(message)]
+        pub fn heartbeat(&mut self) -> Result<()> {
+            self.ensure_owner()?;
+            let now = self.env().block_timestamp();
+            self.last_heartbeat.set(&now);
+            let window = self.dead_man_switch().map_or(0, |switch| switch.window);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(Heartbeat {
+                owner: self.owner(),
+                lapses_at: now.saturating_add(window),
+                block_number: self.env().block_number(),
+                timestamp: now,
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Takes over ownership once the owner's last heartbeat is longer ago
+        /// than the window of the dead-man switch. The switch is removed, so
+        /// the new owner may set up their own.
+        ///
+        /// Only the recovery account may call this.
+        #[cfg(feature = "recovery")]
+        #[ink(message)]
+        pub fn claim_ownership(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            let switch = self
+                .dead_man_switch()
+                .filter(|switch| switch.recovery == caller)
+                .ok_or(Error::NotRecoveryAccount)?;
+            let last_heartbeat = self.last_heartbeat();
+            if self.env().block_timestamp() < last_heartbeat.saturating_add(switch.window) {
+                return Err(Error::OwnerStillActive)
+            }
+            let previous_owner = self.owner();
+            self.dead_man_switch.set(&None);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(OwnershipClaimed {
+                previous_owner,
+                recovery: caller,
+                last_heartbeat,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            self.set_owner(caller);
+            Ok(())
+        }
+
+        /// Returns the account allowed to mint new tokens.
+        #[ink(message)]
+        pub fn minter(&self) -> AccountId {
+            self.minter.get_or_default()
+        }
+
+        /// Returns how much `account` may still mint, if it was added with
+        /// `add_minter`. The main minter is not capped.
+        #[cfg(feature = "mintable")]
+        #[ink(message)]
+        pub fn minter_cap(&self, account: AccountId) -> Option<Balance> {
+            self.minter_caps.get(account)
+        }
+
+        /// Allows `account` to mint up to `cap` tokens besides the main minter,
+        /// replacing any cap it had left.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "mintable")]
+        #[ink(message)]
+        pub fn add_minter(&mut self, account: AccountId, cap: Balance) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.minter_cap(account);
+            self.minter_caps.insert(account, &cap);
+            self.emit_minter_updated(account, Some(cap));
+            self.emit_config_changed(Setting::MinterCap, Some(account), old, cap);
+            Ok(())
+        }
+
+        /// Revokes the minting rights `account` got from `add_minter`.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "mintable")]
+        #[ink(message)]
+        pub fn remove_minter(&mut self, account: AccountId) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.minter_cap(account);
+            self.minter_caps.remove(account);
+            self.emit_minter_updated(account, None);
+            self.emit_config_changed(Setting::MinterCap, Some(account), old, ConfigValue::Unset);
+            Ok(())
+        }
+
+        #[cfg(feature = "mintable")]
+        fn emit_minter_updated(&mut self, account: AccountId, cap: Option<Balance>) {
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(MinterUpdated {
+                account,
+                cap,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+        }
+
+        /// Returns the most tokens that may be minted within one block.
+        #[cfg(feature = "mintable")]
+        #[ink(message)]
+        pub fn max_mint_per_block(&self) -> Option<Balance> {
+            self.max_mint_per_block.get().flatten()
+        }
+
+        /// Limits what may be minted within one block through any path, from
+        /// `mint` to sales and emissions, or lifts the limit.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "mintable")]
+        #[ink(message)]
+        pub fn set_max_mint_per_block(&mut self, amount: Option<Balance>) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.max_mint_per_block();
+            self.max_mint_per_block.set(&amount);
+            self.emit_config_changed(Setting::MaxMintPerBlock, None, old, amount);
+            Ok(())
+        }
+
+        /// Returns the tokens minted within the current block while the limit
+        /// was set.
+        #[cfg(feature = "mintable")]
+        #[ink(message)]
+        pub fn minted_this_block(&self) -> Balance {
+            match self.minted_in_block.get() {
+                Some((block, minted)) if block == self.env().block_number() => minted,
+                _ => 0,
+            }
+        }
+
+        /// Creates `value` new tokens for `to`.
+        ///
+        /// Only the minter, or a minter added with `add_minter` within its
+        /// cap, may call this. With mint approvers set, larger mints go
+        /// through `request_mint`.
+        #[cfg(feature = "mintable")]
+        #[ink(message)]
+        pub fn mint(&mut self, to: AccountId, value: Balance) -> Result<()> {
+            let caller = self.env().caller();
+            let remaining = self.remaining_minter_cap(caller, value)?;
+            if self
+                .mint_approval()
+                .is_some_and(|config| value > config.direct_limit)
+            {
+                return Err(Error::MintApprovalRequired)
+            }
+            self.mint_to(&to, value)?;
+            if let Some(remaining) = remaining {
+                self.minter_caps.insert(caller, &remaining);
+            }
+            Ok(())
+        }
+
+        /// Returns the cap `minter` has left after minting `value`, or `None`
+        /// for the main minter, which is not capped.
+        #[cfg(feature = "mintable")]
+        fn remaining_minter_cap(
+            &self,
+            minter: AccountId,
+            value: Balance,
+        ) -> Result<Option<Balance>> {
+            if minter == self.minter() {
+                return Ok(None)
+            }
+            let cap = self.minter_cap(minter).ok_or(Error::NotMinter)?;
+            cap.checked_sub(value)
+                .map(Some)
+                .ok_or(Error::MinterCapExceeded)
+        }
+
+        /// Creates `value` new tokens for `to`, within the wallet limit and
+        /// the mint limit of the block.
+        #[cfg(any(
+            feature = "bridge",
+            feature = "emissions",
+            feature = "mintable",
+            feature = "partitions",
+            feature = "sale",
+        ))]
+        fn mint_to(&mut self, to: &AccountId, value: Balance) -> Result<()> {
+            let total_supply = self
+                .total_supply()
+                .checked_add(value)
+                .ok_or(Error::SupplyOverflow)?;
+            #[cfg(feature = "limits")]
+            self.ensure_within_wallet_limit(to, value)?;
+            #[cfg(feature = "mintable")]
+            if let Some(max) = self.max_mint_per_block() {
+                let minted = self.minted_this_block().saturating_add(value);
+                if minted > max {
+                    return Err(Error::MintRateExceeded)
+                }
+                let block = self.env().block_number();
+                self.minted_in_block.set(&(block, minted));
+            }
+            let gons = self.gons_rate().to_gons(value);
+            if let Some(total_gons) = self.total_gons.get() {
+                let total_gons = total_gons.checked_add(gons).ok_or(Error::SupplyOverflow)?;
+                self.total_gons.set(&total_gons);
+            }
+            let to_gons = self.gons_of(to);
+            self.set_balance(to, to_gons + gons);
+            self.total_supply.set(&total_supply);
+            self.emit_minted(*to, value);
+            Ok(())
+        }
+
+        /// Returns the approvers that must confirm mints above the direct
+        /// limit, if any.
+        #[cfg(feature = "mintable")]
+        #[ink(message)]
+        pub fn mint_approval(&self) -> Option<MintApprovalConfig> {
+            self.mint_approval.get().flatten()
+        }
+
+        /// Requires mints above `config.direct_limit` to be confirmed by
+        /// `config.threshold` of its approvers, or lifts the requirement
+        /// with `None`. Pending requests are judged by the new approvers.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "mintable")]
+        #[ink(message)]
+        pub fn set_mint_approval(&mut self, config: Option<MintApprovalConfig>) -> Result<()> {
+            self.ensure_owner()?;
+            if let Some(config) = &config {
+                let approvers = &config.approvers;
+                let valid = config.threshold > 0
+                    && config.threshold as usize <= approvers.len()
+                    && approvers.len() <= MAX_MINT_APPROVERS as usize
+                    && config.request_ttl > 0
+                    && approvers
+                        .iter()
+                        .enumerate()
+                        .all(|(i, approver)| !approvers[..i].contains(approver));
+                if !valid {
+                    return Err(Error::InvalidMintApproval)
+                }
+            }
+            let old = self.mint_approval();
+            self.mint_approval.set(&config);
+            self.emit_config_changed(Setting::MintApproval, None, old, config);
+            Ok(())
+        }
+
+        /// Returns a mint request that is not executed or cancelled yet.
+        #[cfg(feature = "mintable")]
+        #[ink(message)]
+        pub fn mint_request(&self, id: MintRequestId) -> Option<MintRequest> {
+            self.mint_requests.get(id)
+        }
+
+        /// Requests a mint of `value` new tokens for `to` for the approvers
+        /// to confirm, and returns the identifier of the request.
+        ///
+        /// Only the minter, or a minter added with `add_minter` within its
+        /// cap, may call this. The cap is only spent once the mint is
+        /// executed.
+        #[cfg(feature = "mintable")]
+        #[ink(message)]
+        pub fn request_mint(&mut self, to: AccountId, value: Balance) -> Result<MintRequestId> {
+            let minter = self.env().caller();
+            self.remaining_minter_cap(minter, value)?;
+            let config = self.mint_approval().ok_or(Error::MintApprovalNotSet)?;
+            let expires_at = self
+                .env()
+                .block_timestamp()
+                .saturating_add(config.request_ttl);
+            let id = self.next_mint_request_id.get_or_default();
+            self.next_mint_request_id.set(&(id + 1));
+            self.mint_requests.insert(
+                id,
+                &MintRequest {
+                    minter,
+                    to,
+                    value,
+                    approvals: Vec::new(),
+                    expires_at,
+                },
+            );
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(MintRequested {
+                id,
+                minter,
+                to,
+                value,
+                expires_at,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(id)
+        }
+
+        /// Confirms a mint request.
+        ///
+        /// Only a mint approver may call this.
+        #[cfg(feature = "mintable")]
+        #[ink(message)]
+        pub fn approve_mint(&mut self, id: MintRequestId) -> Result<()> {
+            let approver = self.env().caller();
+            self.mint_approval()
+                .filter(|config| config.approvers.contains(&approver))
+                .ok_or(Error::NotMintApprover)?;
+            let mut request = self
+                .mint_requests
+                .get(id)
+                .ok_or(Error::MintRequestNotFound)?;
+            if self.env().block_timestamp() >= request.expires_at {
+                return Err(Error::MintRequestExpired)
+            }
+            if !request.approvals.contains(&approver) {
+                request.approvals.push(approver);
+            }
+            let approvals = request.approvals.len() as u32;
+            self.mint_requests.insert(id, &request);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(MintApproved {
+                id,
+                approver,
+                approvals,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Mints the tokens of a request once enough of the current
+        /// approvers confirmed it, before it expires. Anyone may call this.
+        ///
+        /// The requesting minter must still be allowed to mint the amount.
+        #[cfg(feature = "mintable")]
+        #[ink(message)]
+        pub fn execute_mint(&mut self, id: MintRequestId) -> Result<()> {
+            let request = self
+                .mint_requests
+                .get(id)
+                .ok_or(Error::MintRequestNotFound)?;
+            if self.env().block_timestamp() >= request.expires_at {
+                return Err(Error::MintRequestExpired)
+            }
+            // Without approvers, nothing is left to confirm.
+            if let Some(config) = self.mint_approval() {
+                let approvals = request
+                    .approvals
+                    .iter()
+                    .filter(|approver| config.approvers.contains(approver))
+                    .count();
+                if approvals < config.threshold as usize {
+                    return Err(Error::MintNotApproved)
+                }
+            }
+            let remaining = self.remaining_minter_cap(request.minter, request.value)?;
+            self.mint_to(&request.to, request.value)?;
+            if let Some(remaining) = remaining {
+                self.minter_caps.insert(request.minter, &remaining);
+            }
+            self.mint_requests.remove(id);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(MintExecuted {
+                id,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Drops a mint request.
+        ///
+        /// The requesting minter may call this at any time, anyone else once
+        /// the request has expired.
+        #[cfg(feature = "mintable")]
+        #[ink(message)]
+        pub fn cancel_mint_request(&mut self, id: MintRequestId) -> Result<()> {
+            let request = self
+                .mint_requests
+                .get(id)
+                .ok_or(Error::MintRequestNotFound)?;
+            let expired = self.env().block_timestamp() >= request.expires_at;
+            if self.env().caller() != request.minter && !expired {
+                return Err(Error::NotMinter)
+            }
+            self.mint_requests.remove(id);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(MintRequestCancelled {
+                id,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Returns whether balances are bound to their holders.
+        #[cfg(feature = "soulbound")]
+        #[ink(message)]
+        pub fn is_soulbound(&self) -> bool {
+            self.soulbound.get_or_default()
+        }
+
+        /// Destroys `value` of the caller's tokens.
+        #[cfg(feature = "burnable")]
+        #[ink(message)]
+        pub fn burn(&mut self, value: Balance) -> Result<()> {
+            let caller = self.env().caller();
+            self.burn_from(&caller, value)
+        }
+
+        /// Takes `value` of `from`'s spendable tokens out of supply.
+        #[cfg(any(feature = "bridge", feature = "burnable", feature = "sale"))]
+        fn burn_from(&mut self, from: &AccountId, value: Balance) -> Result<()> {
+            if self.balance_of_impl(from) < value {
+                return Err(Error::InsufficientBalance)
+            }
+            let gons = self.gons_rate().to_gons(value);
+            let from_gons = self.gons_of(from);
+            self.ensure_spendable(from, from_gons - gons)?;
+            self.set_balance(from, from_gons - gons);
+            if let Some(total_gons) = self.total_gons.get() {
+                self.total_gons.set(&(total_gons - gons));
+            }
+            let total_supply = self.total_supply() - value;
+            self.total_supply.set(&total_supply);
+            self.emit_burned(*from, value);
+            Ok(())
+        }
+
+        /// Returns the total token supply.
+        #[ink(message)]
+        pub fn total_supply(&self) -> Balance {
+            self.total_supply.get_or_default()
+        }
+
+        /// Returns the tokens taken out of supply by burns, transfer burns and
+        /// wipes so far.
+        #[ink(message)]
+        pub fn total_burned(&self) -> Balance {
+            self.total_burned.get_or_default()
+        }
+
+        /// Returns the total supply minus the balances of the accounts excluded
+        /// from circulation.
+        #[cfg(feature = "circulating")]
+        #[ink(message)]
+        pub fn circulating_supply(&self) -> Balance {
+            self.excluded_from_circulation()
+                .iter()
+                .fold(self.total_supply(), |supply, account| {
+                    supply.saturating_sub(self.balance_of_impl(account))
+                })
+        }
+
+        /// Returns whether the tokens of `account` are left out of the
+        /// circulating supply.
+        #[cfg(feature = "circulating")]
+        #[ink(message)]
+        pub fn is_excluded_from_circulation(&self, account: AccountId) -> bool {
+            self.excluded_indices.contains(account)
+        }
+
+        /// Returns the accounts excluded from the circulating supply.
+        #[cfg(feature = "circulating")]
+        #[ink(message)]
+        pub fn excluded_from_circulation(&self) -> Vec<AccountId> {
+            (0..self.excluded_count.get_or_default())
+                .filter_map(|index| self.excluded_accounts.get(index))
+                .collect()
+        }
+
+        /// Leaves the tokens of `account` out of the circulating supply or
+        /// counts them again, e.g. for the treasury, vesting contracts, bridge
+        /// escrows and burn addresses.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "circulating")]
+        #[ink(message)]
+        pub fn set_excluded_from_circulation(
+            &mut self,
+            account: AccountId,
+            excluded: bool,
+        ) -> Result<()> {
+            self.ensure_owner()?;
+            let count = self.excluded_count.get_or_default();
+            let index = self.excluded_indices.get(account);
+            match (index, excluded) {
+                (None, false) | (Some(_), true) => {}
+                (None, true) => {
+                    if count == MAX_EXCLUDED_ACCOUNTS {
+                        return Err(Error::TooManyExcludedAccounts)
+                    }
+                    self.excluded_accounts.insert(count, &account);
+                    self.excluded_indices.insert(account, &count);
+                    self.excluded_count.set(&(count + 1));
+                }
+                (Some(index), false) => {
+                    // Move the last account into the freed slot.
+                    let last = count - 1;
+                    if index != last {
+                        let moved = self
+                            .excluded_accounts
+                            .get(last)
+                            .expect("excluded account list is contiguous");
+                        self.excluded_accounts.insert(index, &moved);
+                        self.excluded_indices.insert(moved, &index);
+                    }
+                    self.excluded_accounts.remove(last);
+                    self.excluded_indices.remove(account);
+                    self.excluded_count.set(&last);
+                }
+            }
+            let setting = Setting::ExcludedFromCirculation;
+            self.emit_config_changed(setting, Some(account), index.is_some(), excluded);
+            Ok(())
+        }
+
+        /// Returns the account balance for the specified `owner`.
+        #[ink(message)]
+        pub fn balance_of(&self, owner: AccountId) -> Balance {
+            self.balance_of_impl(&owner)
+        }
+
+        /// Returns the balances of `accounts`, in the same order.
+        #[ink(message)]
+        pub fn balances_of(&self, accounts: Vec<AccountId>) -> Vec<Balance> {
+            accounts
+                .iter()
+                .map(|account| self.balance_of_impl(account))
+                .collect()
+        }
+
+        #[ink(message)]
+        pub fn transfer(&mut self, to: AccountId, value: Balance) -> Result<()> {
+            let from = self.env().caller();
+            self.transfer_from_to(&from, &to, value)
+        }
+
+        /// Transfers the caller's entire balance to `to` and returns the amount
+        /// sent. Burn and tax are taken out of it as usual, and gons left over
+        /// from rebase rounding go along, so the caller ends up with nothing.
+        #[ink(message)]
+        pub fn transfer_all(&mut self, to: AccountId) -> Result<Balance> {
+            let from = self.env().caller();
+            let value = self.balance_of_impl(&from);
+            self.transfer_from_to(&from, &to, value)?;
+            let dust = self.gons_of(&from);
+            if dust > 0 {
+                self.move_gons(&from, &to, dust);
+            }
+            Ok(value)
+        }
+
+        /// Returns the number of transfers the transfer log keeps.
+        #[cfg(feature = "transfer-log")]
+        #[ink(message)]
+        pub fn transfer_log_capacity(&self) -> u32 {
+            self.transfer_log_capacity.get_or_default()
+        }
+
+        /// Keeps the last `capacity` transfers in the contract for
+        /// `recent_transfers`, or stops logging with a capacity of zero.
+        /// Transfers logged so far are dropped.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "transfer-log")]
+        #[ink(message)]
+        pub fn set_transfer_log_capacity(&mut self, capacity: u32) -> Result<()> {
+            self.ensure_owner()?;
+            if capacity > MAX_TRANSFER_LOG {
+                return Err(Error::TransferLogTooLarge)
+            }
+            let old = self.transfer_log_capacity();
+            self.transfer_log_capacity.set(&capacity);
+            // Stale slots are out of reach and get overwritten.
+            self.transfer_log_count.set(&0);
+            self.emit_config_changed(Setting::TransferLogCapacity, None, old, capacity);
+            Ok(())
+        }
+
+        /// Returns up to `limit` logged transfers, skipping the `offset` most
+        /// recent ones, newest first.
+        #[cfg(feature = "transfer-log")]
+        #[ink(message)]
+        pub fn recent_transfers(&self, offset: u32, limit: u32) -> Vec<TransferRecord> {
+            let capacity = u64::from(self.transfer_log_capacity());
+            let count = self.transfer_log_count.get_or_default();
+            let logged = count.min(capacity);
+            let end = u64::from(offset).saturating_add(u64::from(limit)).min(logged);
+            (u64::from(offset)..end)
+                .filter_map(|back| {
+                    let slot = (count - 1 - back) % capacity;
+                    self.transfer_log.get(slot as u32)
+                })
+                .collect()
+        }
+
+        /// Adds a transfer to the transfer log, if it is enabled.
+        #[cfg(feature = "transfer-log")]
+        fn log_transfer(&mut self, from: &AccountId, to: &AccountId, value: Balance) {
+            let capacity = u64::from(self.transfer_log_capacity());
+            if capacity == 0 {
+                return
+            }
+            let count = self.transfer_log_count.get_or_default();
+            let record = TransferRecord {
+                from: *from,
+                to: *to,
+                value,
+                block: self.env().block_number(),
+            };
+            self.transfer_log.insert((count % capacity) as u32, &record);
+            self.transfer_log_count.set(&(count + 1));
+        }
+
+        /// Returns whether transfers update the per-account statistics.
+        #[cfg(feature = "stats")]
+        #[ink(message)]
+        pub fn stats_enabled(&self) -> bool {
+            self.stats_enabled.get_or_default()
+        }
+
+        /// Starts or stops keeping per-account statistics for `stats_of`.
+        /// Statistics kept so far stay, but miss the transfers while stopped.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "stats")]
+        #[ink(message)]
+        pub fn set_stats_enabled(&mut self, enabled: bool) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.stats_enabled();
+            self.stats_enabled.set(&enabled);
+            self.emit_config_changed(Setting::StatsEnabled, None, old, enabled);
+            Ok(())
+        }
+
+        /// Returns the transfer activity of `account` while statistics were
+        /// kept, or `None` if none was counted. Mints and burns are not
+        /// counted.
+        #[cfg(feature = "stats")]
+        #[ink(message)]
+        pub fn stats_of(&self, account: AccountId) -> Option<AccountStats> {
+            self.stats.get(account)
+        }
+
+        /// Counts a transfer in the statistics of both sides, if they are kept.
+        #[cfg(feature = "stats")]
+        fn record_stats(
+            &mut self,
+            from: &AccountId,
+            to: &AccountId,
+            sent: Balance,
+            received: Balance,
+        ) {
+            if !self.stats_enabled() {
+                return
+            }
+            let block = self.env().block_number();
+            let stats_of = |erc20: &Self, account| {
+                erc20.stats.get(account).unwrap_or(AccountStats {
+                    first_seen_block: block,
+                    ..Default::default()
+                })
+            };
+            let mut stats = stats_of(self, from);
+            stats.total_sent = stats.total_sent.saturating_add(sent);
+            stats.tx_count += 1;
+            if from == to {
+                stats.total_received = stats.total_received.saturating_add(received);
+                self.stats.insert(from, &stats);
+                return
+            }
+            self.stats.insert(from, &stats);
+            let mut stats = stats_of(self, to);
+            stats.total_received = stats.total_received.saturating_add(received);
+            stats.tx_count += 1;
+            self.stats.insert(to, &stats);
+        }
+
+        /// Returns why a transfer of `value` from `from` to `to` would be
+        /// rejected, as a `TransferRestriction` code; zero if it would not.
+        #[cfg(feature = "compliance")]
+        #[ink(message)]
+        pub fn detect_transfer_restriction(
+            &self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+        ) -> u8 {
+            match self.check_transfer(&from, &to, value) {
+                Ok(()) => TransferRestriction::None as u8,
+                Err(error) => TransferRestriction::from_error(&error) as u8,
+            }
+        }
+
+        /// Returns a human readable explanation of a restriction code.
+        #[cfg(feature = "compliance")]
+        #[ink(message)]
+        pub fn message_for_restriction(&self, code: u8) -> String {
+            String::from(
+                TransferRestriction::from_code(code)
+                    .map_or("Unknown restriction", TransferRestriction::message),
+            )
+        }
+
+        /// Returns how transfers of zero tokens are handled.
+        #[ink(message)]
+        pub fn zero_transfer_policy(&self) -> ZeroTransferPolicy {
+            self.zero_transfer_policy.get_or_default()
+        }
+
+        /// Sets how transfers of zero tokens are handled.
+        ///
+        /// Only the owner may call this.
+        #[ink(message)]
+        pub fn set_zero_transfer_policy(&mut self, policy: ZeroTransferPolicy) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.zero_transfer_policy();
+            self.zero_transfer_policy.set(&policy);
+            self.emit_config_changed(Setting::ZeroTransferPolicy, None, old, policy);
+            Ok(())
+        }
+
+        /// Returns whether `account` may neither send nor receive tokens.
+        #[cfg(feature = "compliance")]
+        #[ink(message)]
+        pub fn is_denylisted(&self, account: AccountId) -> bool {
+            self.denylisted.get(account).unwrap_or_default()
+        }
+
+        /// Bars `account` from sending and receiving tokens, or lifts the bar.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "compliance")]
+        #[ink(message)]
+        pub fn set_denylisted(&mut self, account: AccountId, denylisted: bool) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.is_denylisted(account);
+            self.denylisted.insert(account, &denylisted);
+            self.emit_config_changed(Setting::Denylisted, Some(account), old, denylisted);
+            Ok(())
+        }
+
+        /// Returns the limit on the volume transferred within a window.
+        #[cfg(feature = "pausable")]
+        #[ink(message)]
+        pub fn circuit_breaker(&self) -> Option<CircuitBreaker> {
+            self.circuit_breaker.get().flatten()
+        }
+
+        /// Sets or removes the limit on the volume transferred within a window.
+        /// Once a transfer takes the volume past the threshold, all further
+        /// transfers fail until the owner calls `reset_breaker`.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "pausable")]
+        #[ink(message)]
+        pub fn set_circuit_breaker(&mut self, breaker: Option<CircuitBreaker>) -> Result<()> {
+            self.ensure_owner()?;
+            if let Some(breaker) = &breaker {
+                if breaker.window == 0 || breaker.threshold == 0 || breaker.threshold > BASIS_POINTS
+                {
+                    return Err(Error::InvalidCircuitBreaker)
+                }
+            }
+            let old = self.circuit_breaker();
+            self.circuit_breaker.set(&breaker);
+            self.emit_config_changed(Setting::CircuitBreaker, None, old, breaker);
+            Ok(())
+        }
+
+        /// Returns whether the circuit breaker stopped all transfers.
+        #[cfg(feature = "pausable")]
+        #[ink(message)]
+        pub fn is_breaker_tripped(&self) -> bool {
+            self.breaker_tripped.get_or_default()
+        }
+
+        /// Returns the volume transferred within the current window.
+        #[cfg(feature = "pausable")]
+        #[ink(message)]
+        pub fn breaker_volume(&self) -> Balance {
+            match self.circuit_breaker() {
+                Some(breaker) => self.breaker_window_at(&breaker).volume,
+                None => 0,
+            }
+        }
+
+        /// Returns the account allowed to pause transfers.
+        #[cfg(feature = "pausable")]
+        #[ink(message)]
+        pub fn guardian(&self) -> Option<AccountId> {
+            self.guardian.get().flatten()
+        }
+
+        /// Sets or removes the guardian, which may pause transfers but not
+        /// resume them or change anything else, so it can be a hot key, e.g.
+        /// of a monitoring bot.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "pausable")]
+        #[ink(message)]
+        pub fn set_guardian(&mut self, guardian: Option<AccountId>) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.guardian();
+            self.guardian.set(&guardian);
+            self.emit_config_changed(Setting::Guardian, None, old, guardian);
+            Ok(())
+        }
+
+        /// Trips the circuit breaker, stopping all transfers until the owner
+        /// calls `reset_breaker`.
+        ///
+        /// Only the guardian or the owner may call this.
+        #[cfg(feature = "pausable")]
+        #[ink(message)]
+        pub fn pause(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            if self.guardian() != Some(caller) && caller != self.owner() {
+                return Err(Error::NotGuardian)
+            }
+            self.breaker_tripped.set(&true);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(Paused {
+                caller,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Lets transfers through again, after the breaker tripped or `pause`,
+        /// and starts a fresh window.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "pausable")]
+        #[ink(message)]
+        pub fn reset_breaker(&mut self) -> Result<()> {
+            self.ensure_owner()?;
+            self.breaker_tripped.set(&false);
+            let window = BreakerWindow {
+                start: self.env().block_timestamp(),
+                volume: 0,
+            };
+            self.breaker_window.set(&window);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(CircuitBreakerReset {
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Returns the breaker window as of now, a fresh one once the current
+        /// one is over.
+        #[cfg(feature = "pausable")]
+        fn breaker_window_at(&self, breaker: &CircuitBreaker) -> BreakerWindow {
+            let now = self.env().block_timestamp();
+            let window = self.breaker_window.get_or_default();
+            if now.saturating_sub(window.start) < breaker.window {
+                return window
+            }
+            BreakerWindow {
+                start: now,
+                volume: 0,
+            }
+        }
+
+        /// Adds `value` to the transferred volume and trips the breaker once
+        /// the volume passes the threshold.
+        #[cfg(feature = "pausable")]
+        fn record_volume(&mut self, value: Balance) {
+            let breaker = match self.circuit_breaker() {
+                Some(breaker) => breaker,
+                None => return,
+            };
+            let mut window = self.breaker_window_at(&breaker);
+            window.volume = window.volume.saturating_add(value);
+            self.breaker_window.set(&window);
+            let total_supply = self.total_supply();
+            if window.volume > Self::share_of(total_supply, breaker.threshold) {
+                self.breaker_tripped.set(&true);
+                let event_seq = self.next_event_seq();
+                self.env().emit_event(CircuitBreakerTripped {
+                    volume: window.volume,
+                    total_supply,
+                    block_number: self.env().block_number(),
+                    timestamp: self.env().block_timestamp(),
+                    event_seq,
+                });
+            }
+        }
+
+        /// Returns whether `account` is frozen.
+        #[cfg(feature = "compliance")]
+        #[ink(message)]
+        pub fn is_frozen(&self, account: AccountId) -> bool {
+            self.frozen.get(account).unwrap_or_default()
+        }
+
+        /// Bars `account` from sending and receiving tokens until it is
+        /// unfrozen, e.g. while a compliance case is investigated.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "compliance")]
+        #[ink(message)]
+        pub fn freeze(&mut self, account: AccountId) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.is_frozen(account);
+            self.frozen.insert(account, &true);
+            self.emit_config_changed(Setting::Frozen, Some(account), old, true);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(Frozen {
+                account,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Lifts the freeze of `account`.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "compliance")]
+        #[ink(message)]
+        pub fn unfreeze(&mut self, account: AccountId) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.is_frozen(account);
+            self.frozen.remove(account);
+            self.emit_config_changed(Setting::Frozen, Some(account), old, false);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(Unfrozen {
+                account,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Returns the account allowed to force transfers, if any.
+        #[cfg(feature = "compliance")]
+        #[ink(message)]
+        pub fn controller(&self) -> Option<AccountId> {
+            self.controller.get().flatten()
+        }
+
+        /// Sets or removes the controller.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "compliance")]
+        #[ink(message)]
+        pub fn set_controller(&mut self, controller: Option<AccountId>) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.controller();
+            self.controller.set(&controller);
+            self.emit_config_changed(Setting::Controller, None, old, controller);
+            Ok(())
+        }
+
+        /// Moves `value` tokens from `from` to `to` without an allowance, e.g.
+        /// to carry out a court order. `reason` is passed on in the
+        /// `ControllerTransfer` event.
+        ///
+        /// Bypasses the transfer restrictions, fees and limits, but not the
+        /// tokens `from` has locked. The contract itself and the splitter
+        /// hold tokens for others, so they cannot be taken from.
+        ///
+        /// Only the controller may call this.
+        #[cfg(feature = "compliance")]
+        #[ink(message)]
+        pub fn controller_transfer(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+            reason: Vec<u8>,
+        ) -> Result<()> {
+            self.ensure_controller()?;
+            self.ensure_unprotected(&from)?;
+            if self.balance_of_impl(&from) < value {
+                return Err(Error::InsufficientBalance)
+            }
+            let gons = self.gons_rate().to_gons(value);
+            self.ensure_spendable(&from, self.gons_of(&from) - gons)?;
+            self.move_gons(&from, &to, gons);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(ControllerTransfer {
+                controller: self.env().caller(),
+                from,
+                to,
+                value,
+                reason,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Burns the whole balance of the frozen `account`, locked tokens
+        /// included, and returns the amount burnt. Its token locks are
+        /// removed. The contract itself and the splitter cannot be wiped.
+        ///
+        /// Only the controller may call this.
+        #[cfg(feature = "compliance")]
+        #[ink(message)]
+        pub fn wipe(&mut self, account: AccountId) -> Result<Balance> {
+            self.ensure_controller()?;
+            self.ensure_unprotected(&account)?;
+            if !self.is_frozen(account) {
+                return Err(Error::AccountNotFrozen)
+            }
+            let value = self.balance_of_impl(&account);
+            let gons = self.gons_of(&account);
+            self.set_balance(&account, 0);
+            #[cfg(feature = "locks")]
+            {
+                for lock_id in 0..self.lock_count(account) {
+                    self.token_locks.remove((account, lock_id));
+                }
+                self.lock_counts.remove(account);
+                self.locked.remove(account);
+            }
+            #[cfg(feature = "partitions")]
+            {
+                for partition in self.partitions_of(account) {
+                    self.partition_balances.remove((account, partition));
+                }
+                self.holder_partitions.remove(account);
+                self.partitioned.remove(account);
+            }
+            #[cfg(feature = "vote-escrow")]
+            if self.active_ve_lock(&account).is_some() {
+                self.set_ve_lock(&account, VeLock::default());
+            }
+            if let Some(total_gons) = self.total_gons.get() {
+                self.total_gons.set(&(total_gons - gons));
+            }
+            let total_supply = self.total_supply() - value;
+            self.total_supply.set(&total_supply);
+            self.emit_burned(account, value);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(Wiped {
+                controller: self.env().caller(),
+                account,
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(value)
+        }
+
+        #[cfg(feature = "compliance")]
+        fn ensure_controller(&self) -> Result<()> {
+            if self.controller() != Some(self.env().caller()) {
+                return Err(Error::NotController)
+            }
+            Ok(())
+        }
+
+        /// Fails if `account` holds tokens on behalf of others.
+        #[cfg(feature = "compliance")]
+        fn ensure_unprotected(&self, account: &AccountId) -> Result<()> {
+            if *account == self.env().account_id() {
+                return Err(Error::ProtectedAccount)
+            }
+            #[cfg(feature = "splitter")]
+            if *account == self.splitter_account() {
+                return Err(Error::ProtectedAccount)
+            }
+            Ok(())
+        }
+
+        /// Returns the account allowed to airdrop tokens, if any.
+        #[cfg(feature = "airdrops")]
+        #[ink(message)]
+        pub fn distributor(&self) -> Option<AccountId> {
+            self.distributor.get().flatten()
+        }
+
+        /// Sets or removes the distributor.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "airdrops")]
+        #[ink(message)]
+        pub fn set_distributor(&mut self, distributor: Option<AccountId>) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.distributor();
+            self.distributor.set(&distributor);
+            self.emit_config_changed(Setting::Distributor, None, old, distributor);
+            Ok(())
+        }
+
+        /// Returns whether `account` has been paid in the airdrop `campaign`.
+        #[cfg(feature = "airdrops")]
+        #[ink(message)]
+        pub fn is_airdropped(&self, campaign: u32, account: AccountId) -> bool {
+            self.airdropped.get((campaign, account)).unwrap_or_default()
+        }
+
+        /// Transfers each of `recipients` its amount from the caller's balance
+        /// as part of the airdrop `campaign`, and returns how many entries were
+        /// processed. At most `MAX_AIRDROP_BATCH` entries are processed per
+        /// call, so a large list is sent in several calls, each resuming with
+        /// the entries after the processed ones. Recipients already paid in
+        /// the campaign are skipped, so resending entries never pays twice.
+        ///
+        /// Only the distributor may call this.
+        #[cfg(feature = "airdrops")]
+        #[ink(message)]
+        pub fn airdrop(
+            &mut self,
+            campaign: u32,
+            recipients: Vec<(AccountId, Balance)>,
+        ) -> Result<u32> {
+            let distributor = self.env().caller();
+            if self.distributor() != Some(distributor) {
+                return Err(Error::NotDistributor)
+            }
+            let mut processed = 0;
+            let (mut paid, mut value) = (0, 0);
+            for (recipient, amount) in recipients.into_iter().take(MAX_AIRDROP_BATCH as usize) {
+                processed += 1;
+                if self.is_airdropped(campaign, recipient) {
+                    continue
+                }
+                #[cfg(feature = "fees")]
+                let (burnt, tax) = self.transfer_fees(&distributor, &recipient, amount);
+                #[cfg(not(feature = "fees"))]
+                let (burnt, tax) = (0, 0);
+                self.transfer_from_to(&distributor, &recipient, amount)?;
+                self.airdropped.insert((campaign, recipient), &true);
+                paid += 1;
+                value += amount - burnt - tax;
+            }
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(Airdropped {
+                campaign,
+                recipients: paid,
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(processed)
+        }
+
+        /// Returns when claims on the Merkle airdrop open and close, if there
+        /// is one.
+        #[cfg(feature = "airdrops")]
+        #[ink(message)]
+        pub fn claim_window(&self) -> Option<(Timestamp, Timestamp)> {
+            self.merkle_airdrop
+                .get()
+                .flatten()
+                .map(|airdrop| (airdrop.starts_at, airdrop.deadline))
+        }
+
+        /// Returns the tokens of the Merkle airdrop not claimed yet.
+        #[cfg(feature = "airdrops")]
+        #[ink(message)]
+        pub fn unclaimed_amount(&self) -> Balance {
+            self.merkle_airdrop
+                .get()
+                .flatten()
+                .map(|airdrop| self.gons_rate().to_tokens(airdrop.gons))
+                .unwrap_or_default()
+        }
+
+        /// Moves `value` of the caller's tokens into an airdrop the accounts
+        /// of the Merkle tree with `root` claim from until `deadline`, see
+        /// `claim_airdrop`. Its leaves are the Blake2 hashes of the encoded
+        /// `(account, amount)` pairs, and every parent hashes its two
+        /// children in ascending order. Claims are recorded under `campaign`,
+        /// so accounts paid by `airdrop` in the same campaign cannot claim.
+        ///
+        /// Only the distributor may call this, once any earlier Merkle
+        /// airdrop has been swept.
+        #[cfg(feature = "airdrops")]
+        #[ink(message)]
+        pub fn start_merkle_airdrop(
+            &mut self,
+            campaign: u32,
+            root: [u8; 32],
+            value: Balance,
+            deadline: Timestamp,
+        ) -> Result<()> {
+            let distributor = self.env().caller();
+            if self.distributor() != Some(distributor) {
+                return Err(Error::NotDistributor)
+            }
+            if self.merkle_airdrop.get().flatten().is_some() {
+                return Err(Error::AirdropActive)
+            }
+            let now = self.env().block_timestamp();
+            if deadline <= now {
+                return Err(Error::AirdropEnded)
+            }
+            let gons = self.move_into_contract(&distributor, value)?;
+            self.merkle_airdrop.set(&Some(MerkleAirdrop {
+                campaign,
+                root,
+                starts_at: now,
+                deadline,
+                gons,
+            }));
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(MerkleAirdropStarted {
+                campaign,
+                root,
+                value,
+                deadline,
+                block_number: self.env().block_number(),
+                timestamp: now,
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Pays the caller `amount` from the Merkle airdrop, given the
+        /// sibling hashes from its leaf up to the root, before the deadline.
+        #[cfg(feature = "airdrops")]
+        #[ink(message)]
+        pub fn claim_airdrop(&mut self, amount: Balance, proof: Vec<[u8; 32]>) -> Result<()> {
+            let caller = self.env().caller();
+            let mut airdrop = self.merkle_airdrop.get().flatten().ok_or(Error::NoMerkleAirdrop)?;
+            if self.env().block_timestamp() >= airdrop.deadline {
+                return Err(Error::AirdropEnded)
+            }
+            if self.is_airdropped(airdrop.campaign, caller) {
+                return Err(Error::AlreadyAirdropped)
+            }
+            let leaf = self.env().hash_encoded::<Blake2x256, _>(&(caller, amount));
+            let root = proof.into_iter().fold(leaf, |node, sibling| {
+                let pair = if node <= sibling { (node, sibling) } else { (sibling, node) };
+                self.env().hash_encoded::<Blake2x256, _>(&pair)
+            });
+            if root != airdrop.root {
+                return Err(Error::InvalidMerkleProof)
+            }
+            let gons = self.gons_rate().to_gons(amount);
+            airdrop.gons = airdrop.gons.checked_sub(gons).ok_or(Error::InsufficientBalance)?;
+            let campaign = airdrop.campaign;
+            self.merkle_airdrop.set(&Some(airdrop));
+            self.airdropped.insert((campaign, caller), &true);
+            self.move_gons(&self.env().account_id(), &caller, gons);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(AirdropClaimed {
+                campaign,
+                account: caller,
+                value: amount,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Ends the Merkle airdrop once its deadline has passed, sends what
+        /// was not claimed to `to` and returns the amount.
+        ///
+        /// Only the distributor may call this.
+        #[cfg(feature = "airdrops")]
+        #[ink(message)]
+        pub fn sweep_unclaimed(&mut self, to: AccountId) -> Result<Balance> {
+            if self.distributor() != Some(self.env().caller()) {
+                return Err(Error::NotDistributor)
+            }
+            let airdrop = self.merkle_airdrop.get().flatten().ok_or(Error::NoMerkleAirdrop)?;
+            if self.env().block_timestamp() < airdrop.deadline {
+                return Err(Error::AirdropNotEnded)
+            }
+            self.merkle_airdrop.set(&None);
+            let value = self.gons_rate().to_tokens(airdrop.gons);
+            if airdrop.gons > 0 {
+                self.move_gons(&self.env().account_id(), &to, airdrop.gons);
+            }
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(UnclaimedSwept {
+                campaign: airdrop.campaign,
+                to,
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(value)
+        }
+
+        /// Returns the document called `name`.
+        #[cfg(feature = "documents")]
+        #[ink(message)]
+        pub fn get_document(&self, name: String) -> Option<Document> {
+            self.documents.get(&name)
+        }
+
+        /// Returns the number of documents attached to the token.
+        #[cfg(feature = "documents")]
+        #[ink(message)]
+        pub fn documents_count(&self) -> u32 {
+            self.documents_count.get_or_default()
+        }
+
+        /// Returns up to `limit` document names starting at `offset`.
+        /// Positions change as documents are removed.
+        #[cfg(feature = "documents")]
+        #[ink(message)]
+        pub fn document_names(&self, offset: u32, limit: u32) -> Vec<String> {
+            let end = offset.saturating_add(limit).min(self.documents_count());
+            (offset..end)
+                .filter_map(|index| self.document_names.get(index))
+                .collect()
+        }
+
+        /// Attaches the document at `uri` with content hash `hash` under
+        /// `name`, replacing any document of that name.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "documents")]
+        #[ink(message)]
+        pub fn set_document(&mut self, name: String, uri: String, hash: Hash) -> Result<()> {
+            self.ensure_owner()?;
+            if self.document_indices.get(&name).is_none() {
+                let count = self.documents_count();
+                self.document_names.insert(count, &name);
+                self.document_indices.insert(&name, &count);
+                self.documents_count.set(&(count + 1));
+            }
+            let document = Document {
+                uri: uri.clone(),
+                hash,
+                updated_at: self.env().block_timestamp(),
+            };
+            self.documents.insert(&name, &document);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(DocumentUpdated {
+                name,
+                uri,
+                hash,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Removes the document called `name`.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "documents")]
+        #[ink(message)]
+        pub fn remove_document(&mut self, name: String) -> Result<()> {
+            self.ensure_owner()?;
+            let index = self
+                .document_indices
+                .get(&name)
+                .ok_or(Error::DocumentNotFound)?;
+            // Move the last name into the freed slot.
+            let last = self.documents_count() - 1;
+            if index != last {
+                let moved = self
+                    .document_names
+                    .get(last)
+                    .expect("document list is contiguous");
+                self.document_names.insert(index, &moved);
+                self.document_indices.insert(&moved, &index);
+            }
+            self.document_names.remove(last);
+            self.document_indices.remove(&name);
+            self.documents_count.set(&last);
+            self.documents.remove(&name);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(DocumentRemoved {
+                name,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Returns the tokens `holder` has assigned to `partition`. They count
+        /// towards `balance_of` but move only through `transfer_by_partition`.
+        #[cfg(feature = "partitions")]
+        #[ink(message)]
+        pub fn balance_of_by_partition(&self, holder: AccountId, partition: String) -> Balance {
+            self.gons_rate()
+                .to_tokens(self.partition_gons(&holder, &partition))
+        }
+
+        /// Returns the partitions `holder` has a balance in.
+        #[cfg(feature = "partitions")]
+        #[ink(message)]
+        pub fn partitions_of(&self, holder: AccountId) -> Vec<String> {
+            self.holder_partitions.get(holder).unwrap_or_default()
+        }
+
+        /// Returns whether `operator` may transfer `holder`'s tokens out of
+        /// `partition`.
+        #[cfg(feature = "partitions")]
+        #[ink(message)]
+        pub fn is_operator_for_partition(
+            &self,
+            partition: String,
+            operator: AccountId,
+            holder: AccountId,
+        ) -> bool {
+            self.partition_operators
+                .get((holder, partition, operator))
+                .unwrap_or_default()
+        }
+
+        /// Allows `operator` to transfer the caller's tokens out of `partition`.
+        #[cfg(feature = "partitions")]
+        #[ink(message)]
+        pub fn authorize_operator_by_partition(
+            &mut self,
+            partition: String,
+            operator: AccountId,
+        ) -> Result<()> {
+            let holder = self.env().caller();
+            self.partition_operators
+                .insert((holder, &partition, operator), &true);
+            self.emit_partition_operator_updated(holder, operator, partition, true);
+            Ok(())
+        }
+
+        /// Revokes what `authorize_operator_by_partition` allowed `operator`.
+        #[cfg(feature = "partitions")]
+        #[ink(message)]
+        pub fn revoke_operator_by_partition(
+            &mut self,
+            partition: String,
+            operator: AccountId,
+        ) -> Result<()> {
+            let holder = self.env().caller();
+            self.partition_operators
+                .remove((holder, &partition, operator));
+            self.emit_partition_operator_updated(holder, operator, partition, false);
+            Ok(())
+        }
+
+        #[cfg(feature = "partitions")]
+        fn emit_partition_operator_updated(
+            &mut self,
+            holder: AccountId,
+            operator: AccountId,
+            partition: String,
+            authorized: bool,
+        ) {
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(PartitionOperatorUpdated {
+                holder,
+                operator,
+                partition,
+                authorized,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+        }
+
+        /// Transfers `value` of the caller's tokens in `partition` to the same
+        /// partition of `to`. Fees are taken as for `transfer`.
+        #[cfg(feature = "partitions")]
+        #[ink(message)]
+        pub fn transfer_by_partition(
+            &mut self,
+            partition: String,
+            to: AccountId,
+            value: Balance,
+        ) -> Result<()> {
+            let from = self.env().caller();
+            self.transfer_by_partition_impl(partition, None, &from, &to, value)
+        }
+
+        /// Transfers `value` of `from`'s tokens in `partition` to the same
+        /// partition of `to`.
+        ///
+        /// Only an operator `from` authorized for `partition` may call this.
+        #[cfg(feature = "partitions")]
+        #[ink(message)]
+        pub fn operator_transfer_by_partition(
+            &mut self,
+            partition: String,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+        ) -> Result<()> {
+            let operator = self.env().caller();
+            if !self.is_operator_for_partition(partition.clone(), operator, from) {
+                return Err(Error::NotPartitionOperator)
+            }
+            self.transfer_by_partition_impl(partition, Some(operator), &from, &to, value)
+        }
+
+        #[cfg(feature = "partitions")]
+        fn transfer_by_partition_impl(
+            &mut self,
+            partition: String,
+            operator: Option<AccountId>,
+            from: &AccountId,
+            to: &AccountId,
+            value: Balance,
+        ) -> Result<()> {
+            let rate = self.gons_rate();
+            let gons = rate.to_gons(value);
+            let from_gons = self.partition_gons(from, &partition);
+            if from_gons < gons {
+                return Err(Error::InsufficientPartitionBalance)
+            }
+            self.ensure_partition_slot(to, &partition)?;
+            #[cfg(feature = "fees")]
+            let (burnt, tax) = self.transfer_fees(from, to, value);
+            #[cfg(not(feature = "fees"))]
+            let (burnt, tax) = (0, 0);
+            // Release the tokens first so the transfer may spend them.
+            self.set_partition_gons(from, &partition, from_gons - gons);
+            if let Err(error) = self.transfer_from_to(from, to, value) {
+                self.set_partition_gons(from, &partition, from_gons);
+                return Err(error)
+            }
+            let received = gons - rate.to_gons(burnt) - rate.to_gons(tax);
+            let to_gons = self.partition_gons(to, &partition);
+            self.set_partition_gons(to, &partition, to_gons + received);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(TransferByPartition {
+                partition,
+                operator,
+                from: *from,
+                to: *to,
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Mints `value` new tokens for `to` straight into `partition`.
+        ///
+        /// Only the minter may call this.
+        #[cfg(feature = "partitions")]
+        #[ink(message)]
+        pub fn issue_by_partition(
+            &mut self,
+            partition: String,
+            to: AccountId,
+            value: Balance,
+        ) -> Result<()> {
+            if self.env().caller() != self.minter() {
+                return Err(Error::NotMinter)
+            }
+            self.ensure_partition_slot(&to, &partition)?;
+            self.mint_to(&to, value)?;
+            let gons = self.gons_rate().to_gons(value);
+            let to_gons = self.partition_gons(&to, &partition);
+            self.set_partition_gons(&to, &partition, to_gons + gons);
+            self.emit_partition_changed(to, None, Some(partition), value);
+            Ok(())
+        }
+
+        /// Reassigns `value` of `holder`'s tokens from partition `from` to
+        /// partition `to`, where `None` stands for the tokens in no partition.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "partitions")]
+        #[ink(message)]
+        pub fn change_partition(
+            &mut self,
+            holder: AccountId,
+            from: Option<String>,
+            to: Option<String>,
+            value: Balance,
+        ) -> Result<()> {
+            self.ensure_owner()?;
+            let gons = self.gons_rate().to_gons(value);
+            let available = match &from {
+                Some(partition) => self.partition_gons(&holder, partition),
+                None => {
+                    let partitioned = self.partitioned.get(holder).unwrap_or_default();
+                    self.gons_of(&holder).saturating_sub(partitioned)
+                }
+            };
+            if available < gons {
+                return Err(Error::InsufficientPartitionBalance)
+            }
+            if let Some(partition) = &to {
+                self.ensure_partition_slot(&holder, partition)?;
+            }
+            if let Some(partition) = &from {
+                self.set_partition_gons(&holder, partition, available - gons);
+            }
+            if let Some(partition) = &to {
+                let to_gons = self.partition_gons(&holder, partition);
+                self.set_partition_gons(&holder, partition, to_gons + gons);
+            }
+            self.emit_partition_changed(holder, from, to, value);
+            Ok(())
+        }
+
+        #[cfg(feature = "partitions")]
+        fn emit_partition_changed(
+            &mut self,
+            holder: AccountId,
+            from: Option<String>,
+            to: Option<String>,
+            value: Balance,
+        ) {
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(PartitionChanged {
+                holder,
+                from,
+                to,
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+        }
+
+        #[cfg(feature = "partitions")]
+        fn partition_gons(&self, holder: &AccountId, partition: &String) -> Balance {
+            self.partition_balances
+                .get((holder, partition))
+                .unwrap_or_default()
+        }
+
+        /// Fails if `holder` has no balance in `partition` and already uses
+        /// `MAX_PARTITIONS` others.
+        #[cfg(feature = "partitions")]
+        fn ensure_partition_slot(&self, holder: &AccountId, partition: &String) -> Result<()> {
+            let partitions = self.partitions_of(*holder);
+            if !partitions.contains(partition) && partitions.len() as u32 >= MAX_PARTITIONS {
+                return Err(Error::TooManyPartitions)
+            }
+            Ok(())
+        }
+
+        /// Writes `holder`'s gons in `partition` and keeps their total of
+        /// partitioned gons and their list of partitions in sync.
+        #[cfg(feature = "partitions")]
+        fn set_partition_gons(&mut self, holder: &AccountId, partition: &String, gons: Balance) {
+            let old = self.partition_gons(holder, partition);
+            let partitioned = self.partitioned.get(holder).unwrap_or_default() - old + gons;
+            if partitioned == 0 {
+                self.partitioned.remove(holder);
+            } else {
+                self.partitioned.insert(holder, &partitioned);
+            }
+            let mut partitions = self.partitions_of(*holder);
+            match (old, gons) {
+                (0, 0) | (1.., 1..) => {}
+                (0, _) => partitions.push(partition.clone()),
+                (_, 0) => partitions.retain(|other| other != partition),
+            }
+            if gons == 0 {
+                self.partition_balances.remove((holder, partition));
+            } else {
+                self.partition_balances.insert((holder, partition), &gons);
+            }
+            if partitions.is_empty() {
+                self.holder_partitions.remove(holder);
+            } else {
+                self.holder_partitions.insert(holder, &partitions);
+            }
+        }
+
+        /// Returns whether recipients of transfers must be accredited.
+        #[cfg(feature = "compliance")]
+        #[ink(message)]
+        pub fn accreditation_required(&self) -> bool {
+            self.accreditation_required.get_or_default()
+        }
+
+        /// Sets whether recipients of transfers must be accredited.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "compliance")]
+        #[ink(message)]
+        pub fn set_accreditation_required(&mut self, required: bool) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.accreditation_required();
+            self.accreditation_required.set(&required);
+            self.emit_config_changed(Setting::AccreditationRequired, None, old, required);
+            Ok(())
+        }
+
+        /// Returns whether `account` is accredited.
+        #[cfg(feature = "compliance")]
+        #[ink(message)]
+        pub fn is_accredited(&self, account: AccountId) -> bool {
+            self.accredited.get(account).unwrap_or_default()
+        }
+
+        /// Marks `account` as accredited or not.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "compliance")]
+        #[ink(message)]
+        pub fn set_accredited(&mut self, account: AccountId, accredited: bool) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.is_accredited(account);
+            self.accredited.insert(account, &accredited);
+            self.emit_config_changed(Setting::Accredited, Some(account), old, accredited);
+            Ok(())
+        }
+
+        /// Returns the contract transfer parties are checked against.
+        #[cfg(feature = "compliance")]
+        #[ink(message)]
+        pub fn kyc_registry(&self) -> Option<AccountId> {
+            self.kyc_registry.get().flatten()
+        }
+
+        /// Sets or removes the contract transfer parties are checked against.
+        /// The registry must provide `is_verified(account) -> bool`.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "compliance")]
+        #[ink(message)]
+        pub fn set_kyc_registry(&mut self, registry: Option<AccountId>) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.kyc_registry();
+            self.kyc_registry.set(&registry);
+            self.emit_config_changed(Setting::KycRegistry, None, old, registry);
+            Ok(())
+        }
+
+        /// Returns whether the registry confirms `account`, answering from
+        /// the cache for up to `KYC_CACHE_TTL` after the last confirmation.
+        #[cfg(feature = "compliance")]
+        fn is_kyc_verified(&self, registry: AccountId, account: AccountId) -> bool {
+            self.is_kyc_cached(registry, account)
+                || build_call::<Environment>()
+                    .call_type(call::Call::new().callee(registry))
+                    .exec_input(
+                        ExecutionInput::new(Selector::new(ink_lang::selector_bytes!(
+                            "is_verified"
+                        )))
+                        .push_arg(account),
+                    )
+                    .returns::<bool>()
+                    .fire()
+                    .unwrap_or(false)
+        }
+
+        #[cfg(feature = "compliance")]
+        fn is_kyc_cached(&self, registry: AccountId, account: AccountId) -> bool {
+            self.kyc_verified_at
+                .get((registry, account))
+                .is_some_and(|at| self.env().block_timestamp() < at.saturating_add(KYC_CACHE_TTL))
+        }
+
+        /// Remembers that the registry confirmed the parties of a transfer
+        /// that just passed `check_transfer`.
+        #[cfg(feature = "compliance")]
+        fn cache_kyc(&mut self, from: &AccountId, to: &AccountId) {
+            if let Some(registry) = self.kyc_registry() {
+                let now = self.env().block_timestamp();
+                for account in [*from, *to] {
+                    if !self.is_kyc_cached(registry, account) {
+                        self.kyc_verified_at.insert((registry, account), &now);
+                    }
+                }
+            }
+        }
+
+        /// Returns the launch protection settings.
+        #[cfg(feature = "limits")]
+        #[ink(message)]
+        pub fn launch_protection(&self) -> Option<LaunchProtection> {
+            self.launch_protection.get().flatten()
+        }
+
+        /// Enables launch protection from this block on, or disables it.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "limits")]
+        #[ink(message)]
+        pub fn set_launch_protection(
+            &mut self,
+            protection: Option<LaunchProtection>,
+        ) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.launch_protection();
+            self.launch_protection.set(&protection);
+            self.emit_config_changed(Setting::LaunchProtection, None, old, protection);
+            let now = self.env().block_number();
+            self.launched_at.set(&now);
+            Ok(())
+        }
+
+        /// Returns whether `account` was flagged for buying right at launch.
+        #[cfg(feature = "limits")]
+        #[ink(message)]
+        pub fn is_sniper(&self, account: AccountId) -> bool {
+            self.snipers.get(account).unwrap_or_default()
+        }
+
+        /// Clears the sniper flag of `account`.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "limits")]
+        #[ink(message)]
+        pub fn clear_sniper(&mut self, account: AccountId) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.is_sniper(account);
+            self.snipers.remove(account);
+            self.emit_config_changed(Setting::Sniper, Some(account), old, false);
+            Ok(())
+        }
+
+        /// Returns the launch protection while it is in force.
+        #[cfg(feature = "limits")]
+        fn active_launch_protection(&self) -> Option<LaunchProtection> {
+            self.launch_protection()
+                .filter(|protection| self.env().block_timestamp() < protection.ends_at)
+        }
+
+        /// Fails if launch protection forbids a transfer from `from` to `to`.
+        #[cfg(feature = "limits")]
+        fn ensure_launch_allowed(&self, from: &AccountId, to: &AccountId) -> Result<()> {
+            let protection = match self.active_launch_protection() {
+                Some(protection) => protection,
+                None => return Ok(()),
+            };
+            if self.is_sniper(*from) {
+                return Err(Error::FlaggedSniper)
+            }
+            if self.is_amm_pair(*to) {
+                if let Some(bought_at) = self.last_buy_block.get(from) {
+                    if self.env().block_number() < bought_at.saturating_add(protection.sell_delay) {
+                        return Err(Error::SellTooSoon)
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        /// Records a buy of `to` from a DEX pair for launch protection.
+        #[cfg(feature = "limits")]
+        fn record_launch_buy(&mut self, from: &AccountId, to: &AccountId) {
+            let protection = match self.active_launch_protection() {
+                Some(protection) => protection,
+                None => return,
+            };
+            if !self.is_amm_pair(*from) || self.is_amm_pair(*to) || *to == self.owner() {
+                return
+            }
+            let block = self.env().block_number();
+            self.last_buy_block.insert(to, &block);
+            let launched_at = self.launched_at.get_or_default();
+            if block < launched_at.saturating_add(protection.sniper_blocks) {
+                self.snipers.insert(to, &true);
+            }
+        }
+
+        /// Returns the burnt and taxed parts of a transfer of `value`.
+        #[cfg(feature = "fees")]
+        fn transfer_fees(
+            &self,
+            from: &AccountId,
+            to: &AccountId,
+            value: Balance,
+        ) -> (Balance, Balance) {
+            if self.in_swap.get_or_default() || self.is_fee_exempt(*from) || self.is_fee_exempt(*to)
+            {
+                return (0, 0)
+            }
+            (
+                Self::share_of(value, self.burn_rate()),
+                Self::share_of(value, self.tax_rate_for(from, to)),
+            )
+        }
+
+        /// Fails if `account` is denylisted or frozen.
+        #[cfg(any(
+            feature = "airdrops",
+            feature = "bridge",
+            feature = "escrow",
+            feature = "htlc",
+            feature = "raffles",
+            feature = "sale",
+            feature = "scheduled",
+            feature = "streams",
+            feature = "vouchers",
+        ))]
+        fn ensure_unrestricted(&self, account: &AccountId) -> Result<()> {
+            #[cfg(feature = "compliance")]
+            {
+                if self.is_denylisted(*account) {
+                    return Err(Error::Denylisted)
+                }
+                if self.is_frozen(*account) {
+                    return Err(Error::AccountFrozen)
+                }
+            }
+            let _ = account;
+            Ok(())
+        }
+
+        /// Fails if `from` may not send tokens at all, for paths that take
+        /// tokens out of its balance without a transfer, such as bridging or
+        /// deposits into the contract.
+        #[cfg(any(
+            feature = "airdrops",
+            feature = "bridge",
+            feature = "escrow",
+            feature = "htlc",
+            feature = "raffles",
+            feature = "sale",
+            feature = "scheduled",
+            feature = "streams",
+            feature = "vouchers",
+        ))]
+        fn ensure_can_send(&self, from: &AccountId) -> Result<()> {
+            #[cfg(feature = "soulbound")]
+            if self.is_soulbound() {
+                return Err(Error::NonTransferable)
+            }
+            #[cfg(feature = "pausable")]
+            if self.is_breaker_tripped() {
+                return Err(Error::CircuitBreakerTripped)
+            }
+            self.ensure_unrestricted(from)
+        }
+
+        /// Fails if a transfer of `value` from `from` to `to` is not allowed.
+        fn check_transfer(&self, from: &AccountId, to: &AccountId, value: Balance) -> Result<()> {
+            #[cfg(feature = "soulbound")]
+            if self.is_soulbound() {
+                return Err(Error::NonTransferable)
+            }
+            if value == 0 && self.zero_transfer_policy() == ZeroTransferPolicy::Reject {
+                return Err(Error::ZeroAmount)
+            }
+            #[cfg(feature = "pausable")]
+            if self.is_breaker_tripped() {
+                return Err(Error::CircuitBreakerTripped)
+            }
+            #[cfg(feature = "compliance")]
+            {
+                if self.is_denylisted(*from) || self.is_denylisted(*to) {
+                    return Err(Error::Denylisted)
+                }
+                if self.is_frozen(*from) || self.is_frozen(*to) {
+                    return Err(Error::AccountFrozen)
+                }
+                if self.accreditation_required() && !self.is_accredited(*to) {
+                    return Err(Error::NotAccredited)
+                }
+            }
+            #[cfg(feature = "limits")]
+            self.ensure_launch_allowed(from, to)?;
+            #[cfg(feature = "compliance")]
+            if let Some(registry) = self.kyc_registry() {
+                if !self.is_kyc_verified(registry, *from) || !self.is_kyc_verified(registry, *to) {
+                    return Err(Error::NotVerified)
+                }
+            }
+            if self.balance_of_impl(from) < value {
+                return Err(Error::InsufficientBalance)
+            }
+            #[cfg(feature = "limits")]
+            {
+                self.ensure_within_tx_limit(from, to, value)?;
+                if from != to {
+                    let (burnt, tax) = self.transfer_fees(from, to, value);
+                    self.ensure_within_wallet_limit(to, value - burnt - tax)?;
+                }
+            }
+            let value_gons = self.gons_rate().to_gons(value);
+            self.ensure_spendable(from, self.gons_of(from) - value_gons)
+        }
+
+        fn transfer_from_to(
+            &mut self,
+            from: &AccountId,
+            to: &AccountId,
+            value: Balance,
+        ) -> Result<()> {
+            self.check_transfer(from, to, value)?;
+            // Nothing moves, so skip the balance bookkeeping and transfer hooks.
+            if value == 0 {
+                if self.zero_transfer_policy() == ZeroTransferPolicy::Emit {
+                    let event_seq = self.next_event_seq();
+                    self.env().emit_event(Transfer {
+                        from: Some(*from),
+                        to: Some(*to),
+                        value,
+                        block_number: self.env().block_number(),
+                        timestamp: self.env().block_timestamp(),
+                        event_seq,
+                    });
+                }
+                return Ok(())
+            }
+            #[cfg(feature = "compliance")]
+            self.cache_kyc(from, to);
+            #[cfg(feature = "limits")]
+            self.record_launch_buy(from, to);
+            #[cfg(feature = "fees")]
+            let in_swap = self.in_swap.get_or_default();
+            #[cfg(feature = "fees")]
+            let (burnt, tax) = self.transfer_fees(from, to, value);
+            #[cfg(not(feature = "fees"))]
+            let (burnt, tax) = (0, 0);
+            let rate = self.gons_rate();
+            let (value_gons, burnt_gons, tax_gons) =
+                (rate.to_gons(value), rate.to_gons(burnt), rate.to_gons(tax));
+            let received_gons = value_gons - burnt_gons - tax_gons;
+            let from_gons = self.gons_of(from);
+            if from != to {
+                self.set_balance(from, from_gons - value_gons);
+            } else if received_gons != value_gons {
+                // A transfer to self only loses the fees.
+                self.set_balance(from, from_gons - burnt_gons - tax_gons);
+            }
+            if burnt > 0 {
+                let total_supply = self.total_supply() - burnt;
+                self.total_supply.set(&total_supply);
+                if let Some(total_gons) = self.total_gons.get() {
+                    self.total_gons.set(&(total_gons - burnt_gons));
+                }
+                self.emit_burned(*from, burnt);
+            }
+            #[cfg(feature = "fees")]
+            if tax > 0 {
+                self.collect_tax(from, tax, tax_gons);
+            }
+            let value = value - burnt - tax;
+            if from != to {
+                // Read after collecting the tax, which may go to `to`.
+                let to_gons = self.gons_of(to);
+                self.set_balance(to, to_gons + received_gons);
+            }
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(Transfer {
+                from: Some(*from),
+                to: Some(*to),
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            #[cfg(feature = "pausable")]
+            self.record_volume(value);
+            #[cfg(feature = "transfer-log")]
+            self.log_transfer(from, to, value);
+            #[cfg(feature = "stats")]
+            self.record_stats(from, to, value + burnt + tax, value);
+            // Selling into a pair while it is paying out a buy would reenter it.
+            #[cfg(feature = "fees")]
+            if !in_swap && !self.is_amm_pair(*from) {
+                self.swap_collected_tax();
+            }
+            Ok(())
+        }
+
+        /// Sells the collected tax through the router once it reaches the
+        /// threshold, sending the proceeds to the treasury, or to the contract
+        /// if there is none. A failed swap leaves the tax in place.
+        #[cfg(feature = "fees")]
+        fn swap_collected_tax(&mut self) {
+            let config = match self.swap_config() {
+                Some(config) => config,
+                None => return,
+            };
+            let gons = self.collected_tax.get_or_default();
+            let tokens = self.gons_rate().to_tokens(gons);
+            if tokens == 0 || tokens < config.threshold {
+                return
+            }
+            let contract = self.env().account_id();
+            let to = self.treasury().unwrap_or(contract);
+            self.in_swap.set(&true);
+            self.collected_tax.set(&0);
+            self.set_allowance(&contract, &config.router, tokens, None);
+            let result = build_call::<Environment>()
+                .call_type(call::Call::new().callee(config.router))
+                .call_flags(CallFlags::default().set_allow_reentry(true))
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink_lang::selector_bytes!(
+                        "swap_exact_tokens_for_native"
+                    )))
+                    .push_arg(tokens)
+                    .push_arg(0 as Balance)
+                    .push_arg(to),
+                )
+                .returns::<Balance>()
+                .fire();
+            self.set_allowance(&contract, &config.router, 0, None);
+            self.in_swap.set(&false);
+            match result {
+                Ok(proceeds) => {
+                    let event_seq = self.next_event_seq();
+                    self.env().emit_event(TaxSwapped {
+                        router: config.router,
+                        tokens,
+                        proceeds,
+                        to,
+                        block_number: self.env().block_number(),
+                        timestamp: self.env().block_timestamp(),
+                        event_seq,
+                    });
+                }
+                Err(_) => self.collected_tax.set(&gons),
+            }
+        }
+
+        /// Returns the router collected tax is sold through.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn swap_config(&self) -> Option<SwapConfig> {
+            self.swap_config.get().flatten()
+        }
+
+        /// Sets or disables selling collected tax through a DEX router.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn set_swap_config(&mut self, config: Option<SwapConfig>) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.swap_config();
+            self.swap_config.set(&config);
+            self.emit_config_changed(Setting::SwapConfig, None, old, config);
+            Ok(())
+        }
+
+        /// Returns `rate` basis points of `value`.
+        #[cfg(any(feature = "fees", feature = "governance", feature = "pausable"))]
+        fn share_of(value: Balance, rate: u16) -> Balance {
+            if rate == 0 {
+                return 0
+            }
+            // Split `value` to avoid overflowing on large amounts.
+            let basis_points = Balance::from(BASIS_POINTS);
+            let rate = Balance::from(rate);
+            value / basis_points * rate + value % basis_points * rate / basis_points
+        }
+
+        /// Emits the events for `value` new tokens credited to `to`.
+        fn emit_minted(&mut self, to: AccountId, value: Balance) {
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(Transfer {
+                from: None,
+                to: Some(to),
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(Minted {
+                to,
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+        }
+
+        /// Counts `value` tokens of `from` taken out of supply as burned and
+        /// emits the events for them. The total supply must already be reduced.
+        fn emit_burned(&mut self, from: AccountId, value: Balance) {
+            let total_burned = self.total_burned().saturating_add(value);
+            self.total_burned.set(&total_burned);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(Transfer {
+                from: Some(from),
+                to: None,
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(Burned {
+                from,
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(SupplyChanged {
+                total_supply: self.total_supply(),
+                total_burned,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+        }
+
+        /// Moves `gons` from `from` to `to` without any of the transfer checks.
+        /// The caller must make sure `from` holds them.
+        fn move_gons(&mut self, from: &AccountId, to: &AccountId, gons: Balance) {
+            let value = self.gons_rate().to_tokens(gons);
+            let from_gons = self.gons_of(from);
+            self.set_balance(from, from_gons - gons);
+            let to_gons = self.gons_of(to);
+            self.set_balance(to, to_gons + gons);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(Transfer {
+                from: Some(*from),
+                to: Some(*to),
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+        }
+
+        /// Credits `tax` to the contract's own balance and forwards everything
+        /// collected so far to the treasury once the threshold is reached.
+        #[cfg(feature = "fees")]
+        fn collect_tax(&mut self, from: &AccountId, tax: Balance, tax_gons: Balance) {
+            let contract = self.env().account_id();
+            let contract_gons = self.gons_of(&contract);
+            self.set_balance(&contract, contract_gons + tax_gons);
+            let protocol_gons = Self::share_of(tax_gons, self.protocol_fee_share());
+            if protocol_gons > 0 {
+                self.accrue_protocol_fee(FeeSource::TransferTax, protocol_gons);
+            }
+            let collected_gons = self.collected_tax.get_or_default() + tax_gons - protocol_gons;
+            self.collected_tax.set(&collected_gons);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(Transfer {
+                from: Some(*from),
+                to: Some(contract),
+                value: tax,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            if let (Some(treasury), Some(threshold)) =
+                (self.treasury(), self.treasury_threshold())
+            {
+                if self.gons_rate().to_tokens(collected_gons) >= threshold {
+                    self.collected_tax.set(&0);
+                    self.move_gons(&contract, &treasury, collected_gons);
+                }
+            }
+        }
+
+        /// Returns the share of every transfer collected as tax, in basis points.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn tax_rate(&self) -> u16 {
+            self.tax_rate.get_or_default()
+        }
+
+        /// Sets the share of every transfer collected as tax, in basis points.
+        ///
+        /// Only the owner may call this, and the rate is capped at `MAX_TAX_RATE`.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn set_tax_rate(&mut self, rate: u16) -> Result<()> {
+            self.ensure_owner()?;
+            if rate > MAX_TAX_RATE {
+                return Err(Error::TaxRateTooHigh)
+            }
+            let old = self.tax_rate();
+            self.tax_rate.set(&rate);
+            self.emit_config_changed(Setting::TaxRate, None, old, rate);
+            Ok(())
+        }
+
+        /// Returns the tax rate for transfers out of a DEX pair, in basis points.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn buy_tax_rate(&self) -> u16 {
+            self.buy_tax_rate.get_or_default()
+        }
+
+        /// Returns the tax rate for transfers into a DEX pair, in basis points.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn sell_tax_rate(&self) -> u16 {
+            self.sell_tax_rate.get_or_default()
+        }
+
+        /// Sets the tax rates for buys from and sells to DEX pairs, in basis points.
+        ///
+        /// Only the owner may call this, and both rates are capped at `MAX_TAX_RATE`.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn set_trade_tax_rates(&mut self, buy_rate: u16, sell_rate: u16) -> Result<()> {
+            self.ensure_owner()?;
+            if buy_rate > MAX_TAX_RATE || sell_rate > MAX_TAX_RATE {
+                return Err(Error::TaxRateTooHigh)
+            }
+            let (old_buy_rate, old_sell_rate) = (self.buy_tax_rate(), self.sell_tax_rate());
+            self.buy_tax_rate.set(&buy_rate);
+            self.sell_tax_rate.set(&sell_rate);
+            self.emit_config_changed(Setting::BuyTaxRate, None, old_buy_rate, buy_rate);
+            self.emit_config_changed(Setting::SellTaxRate, None, old_sell_rate, sell_rate);
+            Ok(())
+        }
+
+        /// Returns whether `account` is registered as a DEX pair.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn is_amm_pair(&self, account: AccountId) -> bool {
+            self.amm_pairs.get(account).unwrap_or_default()
+        }
+
+        /// Registers or unregisters `account` as a DEX pair.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn set_amm_pair(&mut self, account: AccountId, is_pair: bool) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.is_amm_pair(account);
+            self.emit_config_changed(Setting::AmmPair, Some(account), old, is_pair);
+            if is_pair {
+                self.amm_pairs.insert(account, &true);
+            } else {
+                self.amm_pairs.remove(account);
+            }
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(AmmPairUpdated {
+                pair: account,
+                is_pair,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Returns the tax rate that applies to a transfer between `from` and `to`:
+        /// the buy rate out of a DEX pair, the sell rate into one, and the
+        /// regular tax rate otherwise.
+        #[cfg(feature = "fees")]
+        fn tax_rate_for(&self, from: &AccountId, to: &AccountId) -> u16 {
+            if self.is_amm_pair(*from) {
+                self.buy_tax_rate()
+            } else if self.is_amm_pair(*to) {
+                self.sell_tax_rate()
+            } else {
+                self.tax_rate()
+            }
+        }
+
+        /// Returns whether transfers from or to `account` are free of transfer
+        /// fees and limits.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn is_fee_exempt(&self, account: AccountId) -> bool {
+            self.fee_exempt.get(account).unwrap_or_default()
+        }
+
+        /// Exempts transfers from or to `account` from the tax, the burn rate,
+        /// `max_tx_amount` and `max_wallet`, e.g. for routers, the treasury or
+        /// vesting and staking contracts.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn set_fee_exempt(&mut self, account: AccountId, exempt: bool) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.is_fee_exempt(account);
+            self.emit_config_changed(Setting::FeeExempt, Some(account), old, exempt);
+            if exempt {
+                self.fee_exempt.insert(account, &true);
+            } else {
+                self.fee_exempt.remove(account);
+            }
+            Ok(())
+        }
+
+        /// Returns the account accumulated taxes are forwarded to.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn treasury(&self) -> Option<AccountId> {
+            self.treasury.get().flatten()
+        }
+
+        /// Returns the accumulated tax at which it is forwarded to the treasury.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn treasury_threshold(&self) -> Option<Balance> {
+            self.treasury_threshold.get().flatten()
+        }
+
+        /// Sets the treasury and the accumulated tax at which it is forwarded
+        /// there automatically. Without a threshold, taxes stay in the contract
+        /// until `withdraw_treasury` is called.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn set_treasury(
+            &mut self,
+            treasury: Option<AccountId>,
+            threshold: Option<Balance>,
+        ) -> Result<()> {
+            self.ensure_owner()?;
+            let (old_treasury, old_threshold) = (self.treasury(), self.treasury_threshold());
+            self.treasury.set(&treasury);
+            self.treasury_threshold.set(&threshold);
+            self.emit_config_changed(Setting::Treasury, None, old_treasury, treasury);
+            self.emit_config_changed(Setting::TreasuryThreshold, None, old_threshold, threshold);
+            Ok(())
+        }
+
+        /// Returns the tax collected and not yet forwarded.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn accumulated_tax(&self) -> Balance {
+            self.gons_rate()
+                .to_tokens(self.collected_tax.get_or_default())
+        }
+
+        /// Sends all accumulated tax to `to`.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn withdraw_treasury(&mut self, to: AccountId) -> Result<()> {
+            self.ensure_owner()?;
+            let collected_gons = self.collected_tax.get_or_default();
+            if collected_gons > 0 {
+                self.collected_tax.set(&0);
+                self.move_gons(&self.env().account_id(), &to, collected_gons);
+            }
+            Ok(())
+        }
+
+        /// Returns the share of every fee kept as protocol fee rather than
+        /// going to the treasury, in basis points.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn protocol_fee_share(&self) -> u16 {
+            self.protocol_fee_share.get_or_default()
+        }
+
+        /// Sets the share of every fee kept as protocol fee, in basis points.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn set_protocol_fee_share(&mut self, share: u16) -> Result<()> {
+            self.ensure_owner()?;
+            if share > BASIS_POINTS {
+                return Err(Error::ProtocolFeeShareTooHigh)
+            }
+            let old = self.protocol_fee_share();
+            self.protocol_fee_share.set(&share);
+            self.emit_config_changed(Setting::ProtocolFeeShare, None, old, share);
+            Ok(())
+        }
+
+        /// Returns the account allowed to collect protocol fees.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn fee_collector(&self) -> Option<AccountId> {
+            self.fee_collector.get().flatten()
+        }
+
+        /// Sets or removes the fee collector.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn set_fee_collector(&mut self, collector: Option<AccountId>) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.fee_collector();
+            self.fee_collector.set(&collector);
+            self.emit_config_changed(Setting::FeeCollector, None, old, collector);
+            Ok(())
+        }
+
+        /// Returns the protocol fees accrued and not yet collected.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn protocol_fees(&self) -> Balance {
+            let gons = FeeSource::ALL
+                .iter()
+                .map(|source| self.protocol_fees.get(source).unwrap_or_default())
+                .sum();
+            self.gons_rate().to_tokens(gons)
+        }
+
+        /// Returns the protocol fees accrued on `source` and not yet collected.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn protocol_fees_by_source(&self, source: FeeSource) -> Balance {
+            self.gons_rate()
+                .to_tokens(self.protocol_fees.get(source).unwrap_or_default())
+        }
+
+        /// Sends all accrued protocol fees to `to` and returns the amount.
+        ///
+        /// Only the fee collector may call this.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn collect_protocol_fees(&mut self, to: AccountId) -> Result<Balance> {
+            let collector = self.env().caller();
+            if self.fee_collector() != Some(collector) {
+                return Err(Error::NotFeeCollector)
+            }
+            let mut gons = 0;
+            for source in FeeSource::ALL {
+                gons += self.protocol_fees.get(source).unwrap_or_default();
+                self.protocol_fees.remove(source);
+            }
+            let value = self.gons_rate().to_tokens(gons);
+            if gons > 0 {
+                self.move_gons(&self.env().account_id(), &to, gons);
+            }
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(ProtocolFeesCollected {
+                collector,
+                to,
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(value)
+        }
+
+        /// Books `gons` the contract already holds as protocol fee from `source`.
+        #[cfg(feature = "fees")]
+        fn accrue_protocol_fee(&mut self, source: FeeSource, gons: Balance) {
+            let fees = self.protocol_fees.get(source).unwrap_or_default() + gons;
+            self.protocol_fees.insert(source, &fees);
+        }
+
+        /// Returns the share of every transfer that is burnt, in basis points.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn burn_rate(&self) -> u16 {
+            self.burn_rate.get_or_default()
+        }
+
+        /// Sets the share of every transfer that is burnt, in basis points.
+        ///
+        /// Only the owner may call this, and the rate is capped at `MAX_BURN_RATE`.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn set_burn_rate(&mut self, rate: u16) -> Result<()> {
+            self.ensure_owner()?;
+            if rate > MAX_BURN_RATE {
+                return Err(Error::BurnRateTooHigh)
+            }
+            let old = self.burn_rate();
+            self.burn_rate.set(&rate);
+            self.emit_config_changed(Setting::BurnRate, None, old, rate);
+            Ok(())
+        }
+
+        /// Returns the largest amount a single transfer may move, if limited.
+        #[cfg(feature = "limits")]
+        #[ink(message)]
+        pub fn max_tx_amount(&self) -> Option<Balance> {
+            self.max_tx_amount.get().flatten()
+        }
+
+        /// Limits the amount a single transfer may move, or lifts the limit with `None`.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "limits")]
+        #[ink(message)]
+        pub fn set_max_tx_amount(&mut self, amount: Option<Balance>) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.max_tx_amount();
+            self.max_tx_amount.set(&amount);
+            self.emit_config_changed(Setting::MaxTxAmount, None, old, amount);
+            Ok(())
+        }
+
+        /// Returns whether transfers from or to `account` bypass `max_tx_amount`.
+        #[cfg(feature = "limits")]
+        #[ink(message)]
+        pub fn is_tx_limit_exempt(&self, account: AccountId) -> bool {
+            self.tx_limit_exempt.get(account).unwrap_or_default()
+        }
+
+        /// Exempts `account` from `max_tx_amount`, e.g. the treasury or a DEX pair.
+        ///
+        /// Only the owner may call this. The owner is always exempt.
+        #[cfg(feature = "limits")]
+        #[ink(message)]
+        pub fn set_tx_limit_exempt(&mut self, account: AccountId, exempt: bool) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.is_tx_limit_exempt(account);
+            self.emit_config_changed(Setting::TxLimitExempt, Some(account), old, exempt);
+            if exempt {
+                self.tx_limit_exempt.insert(account, &true);
+            } else {
+                self.tx_limit_exempt.remove(account);
+            }
+            Ok(())
+        }
+
+        #[cfg(feature = "limits")]
+        fn ensure_within_tx_limit(
+            &self,
+            from: &AccountId,
+            to: &AccountId,
+            value: Balance,
+        ) -> Result<()> {
+            let within_limit = match self.max_tx_amount() {
+                Some(max_tx_amount) => value <= max_tx_amount,
+                None => true,
+            };
+            if within_limit
+                || self.is_tx_limit_exempt(*from)
+                || self.is_tx_limit_exempt(*to)
+                || self.is_fee_exempt(*from)
+                || self.is_fee_exempt(*to)
+                || [*from, *to].contains(&self.owner())
+            {
+                return Ok(())
+            }
+            Err(Error::MaxTransactionExceeded)
+        }
+
+        /// Returns the largest balance an account may receive tokens up to, if limited.
+        #[cfg(feature = "limits")]
+        #[ink(message)]
+        pub fn max_wallet(&self) -> Option<Balance> {
+            self.max_wallet.get().flatten()
+        }
+
+        /// Limits the balance an account may receive tokens up to, or lifts the
+        /// limit with `None`.
+        ///
+        /// Only the owner may call this. Balances already above a new limit are
+        /// kept, but their holders cannot receive more.
+        #[cfg(feature = "limits")]
+        #[ink(message)]
+        pub fn set_max_wallet(&mut self, amount: Option<Balance>) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.max_wallet();
+            self.max_wallet.set(&amount);
+            self.emit_config_changed(Setting::MaxWallet, None, old, amount);
+            Ok(())
+        }
+
+        /// Returns whether `account` may hold more than `max_wallet`.
+        #[cfg(feature = "limits")]
+        #[ink(message)]
+        pub fn is_wallet_limit_exempt(&self, account: AccountId) -> bool {
+            self.wallet_limit_exempt.get(account).unwrap_or_default()
+        }
+
+        /// Exempts `account` from `max_wallet`, e.g. the treasury or a DEX pair.
+        ///
+        /// Only the owner may call this. The owner is always exempt.
+        #[cfg(feature = "limits")]
+        #[ink(message)]
+        pub fn set_wallet_limit_exempt(&mut self, account: AccountId, exempt: bool) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.is_wallet_limit_exempt(account);
+            self.emit_config_changed(Setting::WalletLimitExempt, Some(account), old, exempt);
+            if exempt {
+                self.wallet_limit_exempt.insert(account, &true);
+            } else {
+                self.wallet_limit_exempt.remove(account);
+            }
+            Ok(())
+        }
+
+        #[cfg(feature = "limits")]
+        fn ensure_within_wallet_limit(&self, to: &AccountId, value: Balance) -> Result<()> {
+            let max_wallet = match self.max_wallet() {
+                Some(max_wallet) => max_wallet,
+                None => return Ok(()),
+            };
+            let within_limit = self
+                .balance_of_impl(to)
+                .checked_add(value)
+                .is_some_and(|balance| balance <= max_wallet);
+            if within_limit
+                || self.is_wallet_limit_exempt(*to)
+                || self.is_fee_exempt(*to)
+                || *to == self.owner()
+            {
+                return Ok(())
+            }
+            Err(Error::MaxWalletExceeded)
+        }
+
+        fn ensure_owner(&self) -> Result<()> {
+            if self.env().caller() != self.owner() {
+                return Err(Error::NotOwner)
+            }
+            Ok(())
+        }
+
+        /// Reports a change of a privileged setting, of `account`'s entry for
+        /// per-account settings.
+        fn emit_config_changed(
+            &mut self,
+            setting: Setting,
+            account: Option<AccountId>,
+            old: impl Into<ConfigValue>,
+            new: impl Into<ConfigValue>,
+        ) {
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(ConfigChanged {
+                setting,
+                account,
+                old: old.into(),
+                new: new.into(),
+                caller: self.env().caller(),
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+        }
+
+        #[inline]
+        pub fn balance_of_impl(&self, owner: &AccountId) -> Balance {
+            self.gons_rate().to_tokens(self.gons_of(owner))
+        }
+
+        #[inline]
+        fn gons_of(&self, owner: &AccountId) -> Balance {
+            self.balances.get(owner).unwrap_or_default()
+        }
+
+        fn gons_rate(&self) -> GonsRate {
+            GonsRate {
+                rebased: self
+                    .total_gons
+                    .get()
+                    .map(|total_gons| (total_gons, self.total_supply())),
+            }
+        }
+
+        /// Writes the balance of `owner` in gons. Every balance change goes through here.
+        fn set_balance(&mut self, owner: &AccountId, gons: Balance) {
+            #[cfg(feature = "dividends")]
+            self.settle_dividends(owner);
+            #[cfg(feature = "holders")]
+            let index = self.holder_indices.get(owner);
+            #[cfg(feature = "holders")]
+            self.track_supply_audit(owner, index, gons);
+            // Drop empty entries so their storage deposit is refunded.
+            if gons == 0 {
+                self.balances.remove(owner);
+            } else {
+                self.balances.insert(owner, &gons);
+            }
+            #[cfg(feature = "holders")]
+            match (index, gons) {
+                (None, 0) | (Some(_), 1..) => {}
+                (None, _) => {
+                    let count = self.holders_count();
+                    self.holders.insert(count, owner);
+                    self.holder_indices.insert(owner, &count);
+                    self.holders_count.set(&(count + 1));
+                    let now = self.env().block_number();
+                    self.held_since.insert(owner, &now);
+                }
+                (Some(index), 0) => {
+                    // Move the last holder into the freed slot.
+                    let last = self.holders_count() - 1;
+                    if index != last {
+                        let moved = self.holders.get(last).expect("holder list is contiguous");
+                        self.holders.insert(index, &moved);
+                        self.holder_indices.insert(moved, &index);
+                    }
+                    self.holders.remove(last);
+                    self.holder_indices.remove(owner);
+                    self.holders_count.set(&last);
+                    self.held_since.remove(owner);
+                }
+            }
+        }
+
+        /// Keeps a running `verify_supply` in step when a holder it already
+        /// counted is about to hold `gons`. Holders not counted yet need
+        /// nothing, as the audit reads their balance when it gets to them.
+        #[cfg(feature = "holders")]
+        fn track_supply_audit(&mut self, owner: &AccountId, index: Option<u32>, gons: Balance) {
+            let audit = index.and_then(|index| {
+                self.supply_audit
+                    .get()
+                    .flatten()
+                    .filter(|audit| index < audit.next)
+            });
+            let mut audit = match audit {
+                Some(audit) => audit,
+                None => return,
+            };
+            audit.gons = audit.gons - self.gons_of(owner) + gons;
+            if gons == 0 {
+                // The last holder moves into the freed slot among those counted.
+                let last = self.holders_count() - 1;
+                if last < audit.next {
+                    audit.next = last;
+                } else {
+                    let moved = self.holders.get(last).expect("holder list is contiguous");
+                    audit.gons += self.gons_of(&moved);
+                }
+            }
+            self.supply_audit.set(&Some(audit));
+        }
+
+        /// Returns the number of accounts with a non-zero balance.
+        #[cfg(feature = "holders")]
+        #[ink(message)]
+        pub fn holders_count(&self) -> u32 {
+            self.holders_count.get_or_default()
+        }
+
+        /// Returns the holder at `index`. Positions change as accounts empty
+        /// their balance, so `index` is only stable within a block.
+        #[cfg(feature = "holders")]
+        #[ink(message)]
+        pub fn holder_at(&self, index: u32) -> Option<AccountId> {
+            self.holders.get(index)
+        }
+
+        /// Returns up to `limit` holders starting at `offset`.
+        #[cfg(feature = "holders")]
+        #[ink(message)]
+        pub fn holders(&self, offset: u32, limit: u32) -> Vec<AccountId> {
+            let end = offset.saturating_add(limit).min(self.holders_count());
+            (offset..end).filter_map(|index| self.holders.get(index)).collect()
+        }
+
+        /// Adds up the balances of the next `limit` holders and, once all of
+        /// them are checked, confirms the sum equals the total supply.
+        ///
+        /// Anyone may call this repeatedly until it reports `Verified`.
+        /// Balance changes in between are carried into the holders already
+        /// checked, so they don't start the audit over.
+        #[cfg(feature = "holders")]
+        #[ink(message)]
+        pub fn verify_supply(
+            &mut self,
+            limit: u32,
+        ) -> core::result::Result<AuditStatus, AuditError> {
+            let mut audit = self.supply_audit.get().flatten().unwrap_or_default();
+            let holders = self.holders_count();
+            let end = audit.next.saturating_add(limit).min(holders);
+            for index in audit.next..end {
+                if let Some(holder) = self.holders.get(index) {
+                    audit.gons += self.gons_of(&holder);
+                }
+            }
+            audit.next = end;
+            if end < holders {
+                self.supply_audit.set(&Some(audit));
+                return Ok(AuditStatus::InProgress {
+                    checked: end,
+                    holders,
+                })
+            }
+            self.supply_audit.set(&None);
+            let total_supply = self.total_supply();
+            let total_gons = self.total_gons.get().unwrap_or(total_supply);
+            if audit.gons != total_gons {
+                return Err(AuditError::Mismatch {
+                    recorded: self.gons_rate().to_tokens(audit.gons),
+                    total_supply,
+                })
+            }
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(SupplyVerified {
+                holders,
+                total_supply,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(AuditStatus::Verified)
+        }
+
+        /// Returns the balance below which holdings count as dust.
+        #[cfg(feature = "holders")]
+        #[ink(message)]
+        pub fn dust_threshold(&self) -> Option<Balance> {
+            self.dust_threshold.get().flatten()
+        }
+
+        /// Sets the balance below which `sweep_dust` clears holdings, or
+        /// stops sweeping with `None`.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "holders")]
+        #[ink(message)]
+        pub fn set_dust_threshold(&mut self, threshold: Option<Balance>) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.dust_threshold();
+            self.dust_threshold.set(&threshold);
+            self.emit_config_changed(Setting::DustThreshold, None, old, threshold);
+            Ok(())
+        }
+
+        /// Checks the next `limit` holders and clears the balances below the
+        /// dust threshold, sending them to the treasury or burning them if
+        /// there is none, and returns the number of balances cleared. Each
+        /// call carries on where the previous one stopped, starting over at
+        /// the end of the holder index.
+        ///
+        /// Balances the holder has partly held back, such as locked tokens,
+        /// and those of the contract, the splitter and the treasury are left
+        /// alone. Anyone may call this.
+        #[cfg(feature = "holders")]
+        #[ink(message)]
+        pub fn sweep_dust(&mut self, limit: u32) -> Result<u32> {
+            let threshold = self.dust_threshold().ok_or(Error::DustThresholdNotSet)?;
+            #[cfg(feature = "fees")]
+            let treasury = self.treasury();
+            #[cfg(not(feature = "fees"))]
+            let treasury = None;
+            #[cfg(feature = "splitter")]
+            let splitter = Some(self.splitter_account());
+            #[cfg(not(feature = "splitter"))]
+            let splitter = None;
+            let contract = self.env().account_id();
+            let mut index = self.dust_cursor.get_or_default();
+            let (mut swept, mut value) = (0, 0);
+            for _ in 0..limit {
+                let count = self.holders_count();
+                if count == 0 {
+                    break
+                }
+                if index >= count {
+                    index = 0;
+                }
+                let holder = self.holders.get(index).expect("holder list is contiguous");
+                let gons = self.gons_of(&holder);
+                let balance = self.gons_rate().to_tokens(gons);
+                if balance >= threshold
+                    || holder == contract
+                    || Some(holder) == treasury
+                    || Some(holder) == splitter
+                    || self.held_gons(&holder) > 0
+                {
+                    index += 1;
+                    continue
+                }
+                // The last holder moves into the cleared slot, so `index`
+                // is checked again.
+                match treasury {
+                    Some(treasury) => self.move_gons(&holder, &treasury, gons),
+                    None => {
+                        self.set_balance(&holder, 0);
+                        if let Some(total_gons) = self.total_gons.get() {
+                            self.total_gons.set(&(total_gons - gons));
+                        }
+                        let total_supply = self.total_supply() - balance;
+                        self.total_supply.set(&total_supply);
+                        self.emit_burned(holder, balance);
+                    }
+                }
+                swept += 1;
+                value += balance;
+            }
+            self.dust_cursor.set(&index);
+            if swept > 0 {
+                let event_seq = self.next_event_seq();
+                self.env().emit_event(DustSwept {
+                    holders: swept,
+                    value,
+                    treasury,
+                    block_number: self.env().block_number(),
+                    timestamp: self.env().block_timestamp(),
+                    event_seq,
+                });
+            }
+            Ok(swept)
+        }
+
+        /// Returns whether `account` holds at least `min_balance` tokens.
+        #[cfg(feature = "holders")]
+        #[ink(message)]
+        pub fn meets_threshold(&self, account: AccountId, min_balance: Balance) -> bool {
+            self.balance_of_impl(&account) >= min_balance
+        }
+
+        /// Returns the block `account` has held a non-zero balance since, or
+        /// `None` if it holds nothing. Holders from before the block was
+        /// tracked also report `None` until they empty their balance once.
+        #[cfg(feature = "holders")]
+        #[ink(message)]
+        pub fn held_since(&self, account: AccountId) -> Option<BlockNumber> {
+            self.held_since.get(account)
+        }
+
+        /// Returns the account besides the owner allowed to rebase.
+        #[cfg(feature = "elastic")]
+        #[ink(message)]
+        pub fn rebaser(&self) -> Option<AccountId> {
+            self.rebaser.get().flatten()
+        }
+
+        /// Sets the account besides the owner allowed to rebase.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "elastic")]
+        #[ink(message)]
+        pub fn set_rebaser(&mut self, rebaser: Option<AccountId>) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.rebaser();
+            self.rebaser.set(&rebaser);
+            self.emit_config_changed(Setting::Rebaser, None, old, rebaser);
+            Ok(())
+        }
+
+        /// Expands or contracts the total supply by `supply_delta` tokens, scaling
+        /// every balance proportionally, and returns the new total supply.
+        ///
+        /// Only the owner or the rebaser may call this.
+        #[cfg(feature = "elastic")]
+        #[ink(message)]
+        pub fn rebase(&mut self, supply_delta: i128) -> Result<Balance> {
+            let caller = self.env().caller();
+            if caller != self.owner() && Some(caller) != self.rebaser() {
+                return Err(Error::NotRebaser)
+            }
+            let total_supply = self.total_supply();
+            if total_supply == 0 {
+                return Err(Error::NoSupply)
+            }
+            let new_supply = if supply_delta >= 0 {
+                total_supply.checked_add(supply_delta.unsigned_abs())
+            } else {
+                total_supply.checked_sub(supply_delta.unsigned_abs())
+            }
+            .filter(|&supply| supply > 0)
+            .ok_or(Error::RebaseOutOfRange)?;
+            self.set_rebased_supply(new_supply);
+            Ok(new_supply)
+        }
+
+        /// Returns the shares `account` holds, the aToken scaled balance. Its
+        /// balance is its shares times `exchange_rate`, so shares only change
+        /// when tokens move. Not to be confused with the splitter `shares_of`.
+        #[cfg(feature = "elastic")]
+        #[ink(message)]
+        pub fn scaled_balance_of(&self, account: AccountId) -> Balance {
+            self.gons_of(&account)
+        }
+
+        /// Returns the tokens one share is worth, scaled by `EXCHANGE_RATE_SCALE`.
+        #[cfg(feature = "elastic")]
+        #[ink(message)]
+        pub fn exchange_rate(&self) -> u128 {
+            self.total_gons
+                .get()
+                .and_then(|total_gons| {
+                    mul_div(self.total_supply(), EXCHANGE_RATE_SCALE, total_gons)
+                })
+                .unwrap_or(EXCHANGE_RATE_SCALE)
+        }
+
+        /// Raises the exchange rate to `new_rate`, growing every balance by
+        /// the interest accrued since the last call, and returns the new total
+        /// supply. Like `rebase`, this writes no per-account storage.
+        ///
+        /// Only the owner or the rebaser may call this.
+        #[cfg(feature = "elastic")]
+        #[ink(message)]
+        pub fn accrue(&mut self, new_rate: u128) -> Result<Balance> {
+            let caller = self.env().caller();
+            if caller != self.owner() && Some(caller) != self.rebaser() {
+                return Err(Error::NotRebaser)
+            }
+            if new_rate < self.exchange_rate() {
+                return Err(Error::ExchangeRateDecrease)
+            }
+            let total_supply = self.total_supply();
+            if total_supply == 0 {
+                return Err(Error::NoSupply)
+            }
+            let total_gons = self.total_gons.get().unwrap_or(total_supply);
+            let new_supply = mul_div(total_gons, new_rate, EXCHANGE_RATE_SCALE)
+                .ok_or(Error::SupplyOverflow)?;
+            self.set_rebased_supply(new_supply);
+            Ok(new_supply)
+        }
+
+        /// Sets a new total supply all balances scale along with.
+        #[cfg(feature = "elastic")]
+        fn set_rebased_supply(&mut self, new_supply: Balance) {
+            if self.total_gons.get().is_none() {
+                let total_supply = self.total_supply();
+                self.total_gons.set(&total_supply);
+            }
+            self.total_supply.set(&new_supply);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(Rebase {
+                total_supply: new_supply,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+        }
+
+        /// Returns the relayer allowed to move tokens across the bridge.
+        #[cfg(feature = "bridge")]
+        #[ink(message)]
+        pub fn bridge(&self) -> Option<AccountId> {
+            self.bridge.get().flatten()
+        }
+
+        /// Sets or removes the relayer allowed to move tokens across the bridge.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "bridge")]
+        #[ink(message)]
+        pub fn set_bridge(&mut self, bridge: Option<AccountId>) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.bridge();
+            self.bridge.set(&bridge);
+            self.emit_config_changed(Setting::Bridge, None, old, bridge);
+            Ok(())
+        }
+
+        /// Returns whether the tokens of `source_tx` have been bridged in.
+        #[cfg(feature = "bridge")]
+        #[ink(message)]
+        pub fn is_bridged_in(&self, source_tx: Hash) -> bool {
+            self.bridged_in.get(source_tx).unwrap_or_default()
+        }
+
+        /// Burns `value` tokens of `from` to be released to `dest_address` on
+        /// `dest_chain`. Unless `from` is the bridge itself, this spends the
+        /// bridge's allowance like `transfer_from`.
+        ///
+        /// Only the bridge may call this.
+        #[cfg(feature = "bridge")]
+        #[ink(message)]
+        pub fn bridge_lock(
+            &mut self,
+            from: AccountId,
+            value: Balance,
+            dest_chain: u32,
+            dest_address: Vec<u8>,
+        ) -> Result<()> {
+            let caller = self.ensure_bridge()?;
+            self.ensure_can_send(&from)?;
+            if from != caller {
+                let (allowance, expires_at) = self.allowance_impl(&from, &caller)?;
+                if allowance < value {
+                    return Err(Error::InsufficientAllowance)
+                }
+                self.burn_from(&from, value)?;
+                self.set_allowance(&from, &caller, allowance - value, expires_at);
+            } else {
+                self.burn_from(&from, value)?;
+            }
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(BridgedOut {
+                from,
+                value,
+                dest_chain,
+                dest_address,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Mints `value` tokens to `to` for the tokens bridged out in
+        /// `source_tx` on another chain. Each source transaction is honoured
+        /// once.
+        ///
+        /// Only the bridge may call this.
+        #[cfg(feature = "bridge")]
+        #[ink(message)]
+        pub fn bridge_unlock(
+            &mut self,
+            to: AccountId,
+            value: Balance,
+            source_tx: Hash,
+        ) -> Result<()> {
+            self.ensure_bridge()?;
+            if self.is_bridged_in(source_tx) {
+                return Err(Error::AlreadyBridged)
+            }
+            self.mint_to(&to, value)?;
+            self.bridged_in.insert(source_tx, &true);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(BridgedIn {
+                to,
+                value,
+                source_tx,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Returns the chain extension function cross-chain transfers go through.
+        #[cfg(feature = "bridge")]
+        #[ink(message)]
+        pub fn xcm_config(&self) -> Option<XcmConfig> {
+            self.xcm.get().flatten()
+        }
+
+        /// Sets or disables the chain extension function cross-chain transfers
+        /// go through.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "bridge")]
+        #[ink(message)]
+        pub fn set_xcm_config(&mut self, config: Option<XcmConfig>) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.xcm_config();
+            self.xcm.set(&config);
+            self.emit_config_changed(Setting::XcmConfig, None, old, config);
+            Ok(())
+        }
+
+        /// Burns `value` of the caller's tokens and has the runtime send them
+        /// to `beneficiary` on sibling parachain `para_id` over XCM. Returns
+        /// the hash of the outbound message for tracking.
+        #[cfg(feature = "bridge")]
+        #[ink(message)]
+        pub fn transfer_cross_chain(
+            &mut self,
+            para_id: u32,
+            beneficiary: AccountId,
+            value: Balance,
+        ) -> Result<Hash> {
+            let config = self.xcm_config().ok_or(Error::XcmUnavailable)?;
+            let from = self.env().caller();
+            self.ensure_can_send(&from)?;
+            if self.balance_of_impl(&from) < value {
+                return Err(Error::InsufficientBalance)
+            }
+            let gons = self.gons_rate().to_gons(value);
+            self.ensure_spendable(&from, self.gons_of(&from) - gons)?;
+            #[cfg(feature = "xcm")]
+            let message_hash = crate::xcm::transfer(
+                config.extension_id,
+                config.function_id,
+                para_id,
+                *beneficiary.as_ref(),
+                value,
+            );
+            #[cfg(not(feature = "xcm"))]
+            let message_hash: Option<[u8; 32]> = {
+                let _ = config;
+                None
+            };
+            let message_hash = Hash::from(message_hash.ok_or(Error::XcmUnavailable)?);
+            self.burn_from(&from, value)?;
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(CrossChainTransfer {
+                from,
+                para_id,
+                beneficiary,
+                value,
+                message_hash,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(message_hash)
+        }
+
+        /// Returns the emission schedule set at deployment.
+        #[cfg(feature = "emissions")]
+        #[ink(message)]
+        pub fn emission_schedule(&self) -> Option<EmissionSchedule> {
+            self.emission.get().flatten()
+        }
+
+        /// Returns the tokens `drip` would release now.
+        #[cfg(feature = "emissions")]
+        #[ink(message)]
+        pub fn pending_emission(&self) -> Balance {
+            self.emission_schedule()
+                .map_or(0, |schedule| self.elapsed_periods(&schedule).0)
+        }
+
+        /// Mints the tokens of every period elapsed since the last call to the
+        /// schedule's recipient and returns the amount.
+        ///
+        /// Anyone may call this.
+        #[cfg(feature = "emissions")]
+        #[ink(message)]
+        pub fn drip(&mut self) -> Result<Balance> {
+            let schedule = self.emission_schedule().ok_or(Error::NoEmission)?;
+            let (value, emitted_until) = self.elapsed_periods(&schedule);
+            if value > 0 {
+                self.mint_to(&schedule.recipient, value)?;
+                self.emitted_until.set(&emitted_until);
+            }
+            Ok(value)
+        }
+
+        /// Returns the tokens of the periods elapsed since the last release
+        /// and the start of the first period after them.
+        #[cfg(feature = "emissions")]
+        fn elapsed_periods(&self, schedule: &EmissionSchedule) -> (Balance, Timestamp) {
+            let emitted_until = self.emitted_until.get_or_default();
+            let now = self.env().block_timestamp().min(schedule.end);
+            let periods = now.saturating_sub(emitted_until) / schedule.period;
+            let value = schedule
+                .amount_per_period
+                .saturating_mul(Balance::from(periods));
+            (value, emitted_until + periods * schedule.period)
+        }
+
+        /// Returns the inflation settings.
+        #[cfg(feature = "emissions")]
+        #[ink(message)]
+        pub fn inflation(&self) -> Option<InflationConfig> {
+            self.inflation.get().flatten()
+        }
+
+        /// Sets or disables inflation. Inflation accrued under the previous
+        /// settings is minted first.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "emissions")]
+        #[ink(message)]
+        pub fn set_inflation(&mut self, config: Option<InflationConfig>) -> Result<()> {
+            self.ensure_owner()?;
+            if matches!(&config, Some(config) if config.blocks_per_year == 0) {
+                return Err(Error::InvalidInflation)
+            }
+            let old = self.inflation();
+            if old.is_some() {
+                self.mint_inflation()?;
+            }
+            self.inflation.set(&config);
+            self.emit_config_changed(Setting::Inflation, None, old, config);
+            let now = self.env().block_number();
+            self.inflation_accrued_at.set(&now);
+            Ok(())
+        }
+
+        /// Returns the inflation accrued since it was last minted.
+        #[cfg(feature = "emissions")]
+        #[ink(message)]
+        pub fn accrued_inflation(&self) -> Balance {
+            self.inflation().map_or(0, |config| {
+                let blocks = self.env().block_number() - self.inflation_accrued_at.get_or_default();
+                config.accrued(self.total_supply(), blocks)
+            })
+        }
+
+        /// Mints the accrued inflation into the reward pool and returns the
+        /// amount.
+        ///
+        /// Anyone may call this.
+        #[cfg(feature = "emissions")]
+        #[ink(message)]
+        pub fn mint_inflation(&mut self) -> Result<Balance> {
+            let config = self.inflation().ok_or(Error::InvalidInflation)?;
+            let value = self.accrued_inflation();
+            if value > 0 {
+                self.mint_to(&config.reward_pool, value)?;
+            }
+            let now = self.env().block_number();
+            self.inflation_accrued_at.set(&now);
+            Ok(value)
+        }
+
+        /// Returns the caller if it is the bridge.
+        #[cfg(feature = "bridge")]
+        fn ensure_bridge(&self) -> Result<AccountId> {
+            let caller = self.env().caller();
+            if Some(caller) != self.bridge() {
+                return Err(Error::NotBridge)
+            }
+            Ok(caller)
+        }
+
+        /// Returns the dividend bookkeeping of `account` brought up to date with
+        /// its current balance.
+        #[cfg(feature = "dividends")]
+        fn dividend_account(&self, account: &AccountId) -> DividendAccount {
+            let entry = self.dividend_accounts.get(account).unwrap_or_default();
+            self.accrued(account, entry, self.dividends_per_token.get_or_default())
+        }
+
+        /// Returns the reward bookkeeping of `account` for `token` brought up
+        /// to date with its current balance.
+        #[cfg(feature = "dividends")]
+        fn reward_account(&self, account: &AccountId, token: &AccountId) -> DividendAccount {
+            let entry = self.reward_accounts.get((account, token)).unwrap_or_default();
+            self.accrued(account, entry, self.rewards_per_token.get(token).unwrap_or_default())
+        }
+
+        /// Credits `entry` with what the balance of `account` earned since the
+        /// accumulator stood at `entry.dividends_per_token`.
+        #[cfg(feature = "dividends")]
+        fn accrued(
+            &self,
+            account: &AccountId,
+            mut entry: DividendAccount,
+            dividends_per_token: u128,
+        ) -> DividendAccount {
+            if entry.dividends_per_token != dividends_per_token {
+                if self.earns_dividends(account) {
+                    let earned = mul_div(
+                        self.gons_of(account),
+                        dividends_per_token - entry.dividends_per_token,
+                        DIVIDEND_MAGNITUDE,
+                    )
+                    .expect("earned dividends are bounded by the deposits");
+                    entry.unclaimed += earned;
+                }
+                entry.dividends_per_token = dividends_per_token;
+            }
+            entry
+        }
+
+        /// Returns false for the contract itself and the splitter account,
+        /// whose balances are held for others and earn no dividends or
+        /// rewards.
+        #[cfg(feature = "dividends")]
+        fn earns_dividends(&self, account: &AccountId) -> bool {
+            #[cfg(feature = "splitter")]
+            if *account == self.splitter_account() {
+                return false
+            }
+            *account != self.env().account_id()
+        }
+
+        /// Returns the gons that earn dividends and rewards, i.e. all but
+        /// those of the accounts `earns_dividends` rules out.
+        #[cfg(feature = "dividends")]
+        fn dividend_gons(&self) -> Balance {
+            let held = self.gons_of(&self.env().account_id());
+            #[cfg(feature = "splitter")]
+            let held = held + self.gons_of(&self.splitter_account());
+            self.total_gons() - held
+        }
+
+        /// Records the dividends and rewards `account` earned with its current
+        /// balance. Must run before the balance changes.
+        #[cfg(feature = "dividends")]
+        fn settle_dividends(&mut self, account: &AccountId) {
+            if self.dividends_per_token.get_or_default() != 0 {
+                let entry = self.dividend_account(account);
+                self.dividend_accounts.insert(account, &entry);
+            }
+            for index in 0..self.reward_tokens_count.get_or_default() {
+                let token = self.reward_tokens.get(index).expect("reward token list is contiguous");
+                let entry = self.reward_account(account, &token);
+                self.reward_accounts.insert((account, &token), &entry);
+            }
+        }
+
+        /// Returns the PSP22 tokens rewards have been funded in.
+        #[cfg(feature = "dividends")]
+        #[ink(message)]
+        pub fn reward_tokens(&self) -> Vec<AccountId> {
+            (0..self.reward_tokens_count.get_or_default())
+                .filter_map(|index| self.reward_tokens.get(index))
+                .collect()
+        }
+
+        /// Pulls `value` of the PSP22 `token` from the caller, who must have
+        /// approved this contract, and distributes it to all token holders pro
+        /// rata to their balances.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "dividends")]
+        #[ink(message)]
+        pub fn fund_rewards(&mut self, token: AccountId, value: Balance) -> Result<()> {
+            self.ensure_owner()?;
+            let caller = self.env().caller();
+            build_call::<Environment>()
+                .call_type(call::Call::new().callee(token))
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink_lang::selector_bytes!(
+                        "PSP22::transfer_from"
+                    )))
+                    .push_arg(caller)
+                    .push_arg(self.env().account_id())
+                    .push_arg(value)
+                    .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<core::result::Result<(), u8>>()
+                .fire()
+                .ok()
+                .and_then(|result| result.ok())
+                .ok_or(Error::RewardTransferFailed)?;
+            self.distribute_rewards(token, value)
+        }
+
+        /// Adds `value` of `token` to the rewards of all token holders.
+        #[cfg(feature = "dividends")]
+        fn distribute_rewards(&mut self, token: AccountId, value: Balance) -> Result<()> {
+            let total_gons = self.dividend_gons();
+            if total_gons == 0 {
+                return Err(Error::NoSupply)
+            }
+            if !self.rewards_per_token.contains(token) {
+                let count = self.reward_tokens_count.get_or_default();
+                if count == MAX_REWARD_TOKENS {
+                    return Err(Error::TooManyRewardTokens)
+                }
+                self.reward_tokens.insert(count, &token);
+                self.reward_tokens_count.set(&(count + 1));
+            }
+            let pending = self.undistributed_rewards.get(token).unwrap_or_default() + value;
+            let increase = mul_div(pending, DIVIDEND_MAGNITUDE, total_gons)
+                .ok_or(Error::SupplyOverflow)?;
+            let distributed = mul_div(increase, total_gons, DIVIDEND_MAGNITUDE)
+                .expect("distributed rewards never exceed the funding");
+            let rewards_per_token = self
+                .rewards_per_token
+                .get(token)
+                .unwrap_or_default()
+                .checked_add(increase)
+                .ok_or(Error::SupplyOverflow)?;
+            self.rewards_per_token.insert(token, &rewards_per_token);
+            self.undistributed_rewards.insert(token, &(pending - distributed));
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(RewardsFunded {
+                token,
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Returns the rewards in `token` that `account` can claim.
+        #[cfg(feature = "dividends")]
+        #[ink(message)]
+        pub fn rewards_of(&self, account: AccountId, token: AccountId) -> Balance {
+            self.reward_account(&account, &token).unclaimed
+        }
+
+        /// Sends the caller's unclaimed rewards in `token` to the caller.
+        #[cfg(feature = "dividends")]
+        #[ink(message)]
+        pub fn claim(&mut self, token: AccountId) -> Result<Balance> {
+            let caller = self.env().caller();
+            let mut entry = self.reward_account(&caller, &token);
+            let value = entry.unclaimed;
+            if value == 0 {
+                return Err(Error::NoDividends)
+            }
+            entry.unclaimed = 0;
+            self.reward_accounts.insert((caller, token), &entry);
+            build_call::<Environment>()
+                .call_type(call::Call::new().callee(token))
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink_lang::selector_bytes!(
+                        "PSP22::transfer"
+                    )))
+                    .push_arg(caller)
+                    .push_arg(value)
+                    .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<core::result::Result<(), u8>>()
+                .fire()
+                .ok()
+                .and_then(|result| result.ok())
+                .ok_or(Error::RewardTransferFailed)?;
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(RewardsClaimed {
+                account: caller,
+                token,
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(value)
+        }
+
+        /// Distributes the transferred native currency to all token holders pro
+        /// rata to their balances.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "dividends")]
+        #[ink(message, payable)]
+        pub fn deposit_dividends(&mut self) -> Result<()> {
+            self.ensure_owner()?;
+            let total_gons = self.dividend_gons();
+            if total_gons == 0 {
+                return Err(Error::NoSupply)
+            }
+            let value = self.env().transferred_value();
+            let pending = self.undistributed_dividends.get_or_default() + value;
+            let increase = mul_div(pending, DIVIDEND_MAGNITUDE, total_gons)
+                .ok_or(Error::SupplyOverflow)?;
+            let distributed = mul_div(increase, total_gons, DIVIDEND_MAGNITUDE)
+                .expect("distributed dividends never exceed the deposit");
+            let dividends_per_token = self
+                .dividends_per_token
+                .get_or_default()
+                .checked_add(increase)
+                .ok_or(Error::SupplyOverflow)?;
+            self.dividends_per_token.set(&dividends_per_token);
+            self.undistributed_dividends.set(&(pending - distributed));
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(DividendsDeposited {
+                from: self.env().caller(),
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Returns the dividends `account` can claim.
+        #[cfg(feature = "dividends")]
+        #[ink(message)]
+        pub fn dividends_of(&self, account: AccountId) -> Balance {
+            self.dividend_account(&account).unclaimed
+        }
+
+        /// Sends the caller's unclaimed dividends to the caller.
+        #[cfg(feature = "dividends")]
+        #[ink(message)]
+        pub fn claim_dividends(&mut self) -> Result<Balance> {
+            let caller = self.env().caller();
+            let mut entry = self.dividend_account(&caller);
+            let value = entry.unclaimed;
+            if value == 0 {
+                return Err(Error::NoDividends)
+            }
+            entry.unclaimed = 0;
+            self.dividend_accounts.insert(caller, &entry);
+            self.env()
+                .transfer(caller, value)
+                .map_err(|_| Error::NativeTransferFailed)?;
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(DividendsClaimed {
+                account: caller,
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(value)
+        }
+
+        #[ink(message)]
+        pub fn approve(&mut self, spender: AccountId, value: Balance) -> Result<()> {
+            self.approve_impl(spender, value, None)
+        }
+
+        /// Like `approve`, but the allowance can no longer be spent from
+        /// `expires_at` on.
+        #[ink(message)]
+        pub fn approve_with_deadline(
+            &mut self,
+            spender: AccountId,
+            value: Balance,
+            expires_at: Timestamp,
+        ) -> Result<()> {
+            self.approve_impl(spender, value, Some(expires_at))
+        }
+
+        /// Allows `spender` to move up to `per_period_cap` of the caller's
+        /// tokens within every `period_ms` milliseconds, starting now, for as
+        /// long as the approval stands. Any later approval of `spender`
+        /// replaces the limit.
+        #[cfg(feature = "spending-limits")]
+        #[ink(message)]
+        pub fn approve_with_limit(
+            &mut self,
+            spender: AccountId,
+            per_period_cap: Balance,
+            period_ms: Timestamp,
+        ) -> Result<()> {
+            if period_ms == 0 {
+                return Err(Error::InvalidSpendingPeriod)
+            }
+            self.approve_impl(spender, Balance::MAX, None)?;
+            let owner = self.env().caller();
+            let limit = SpendingLimit {
+                cap: per_period_cap,
+                period: period_ms,
+                period_start: self.env().block_timestamp(),
+                spent: 0,
+            };
+            self.spending_limits.insert((owner, spender), &limit);
+            Ok(())
+        }
+
+        /// Returns the spending limit of `spender` on `owner`'s tokens as of
+        /// now, if it has one.
+        #[cfg(feature = "spending-limits")]
+        #[ink(message)]
+        pub fn spending_limit(
+            &self,
+            owner: AccountId,
+            spender: AccountId,
+        ) -> Option<SpendingLimit> {
+            self.spending_limits
+                .get((owner, spender))
+                .map(|limit| limit.at(self.env().block_timestamp()))
+        }
+
+        /// Returns the spending limit of `spender` on `owner`'s tokens with
+        /// `value` counted against it, if it has one.
+        #[cfg(feature = "spending-limits")]
+        fn spend_limit(
+            &self,
+            owner: AccountId,
+            spender: AccountId,
+            value: Balance,
+        ) -> Result<Option<SpendingLimit>> {
+            let mut limit = match self.spending_limit(owner, spender) {
+                Some(limit) => limit,
+                None => return Ok(None),
+            };
+            limit.spent = limit
+                .spent
+                .checked_add(value)
+                .filter(|spent| *spent <= limit.cap)
+                .ok_or(Error::SpendingLimitExceeded)?;
+            Ok(Some(limit))
+        }
+
+        /// Returns the session of `spender` on `owner`'s tokens, if it has one.
+        #[cfg(feature = "sessions")]
+        #[ink(message)]
+        pub fn session(&self, owner: AccountId, spender: AccountId) -> Option<Session> {
+            self.sessions.get((owner, spender))
+        }
+
+        /// Lets `spender` move up to `total_cap` of the caller's tokens through
+        /// `session_transfer_from`, at most `per_tx_cap` at a time and only
+        /// until `expires_at`. Sessions are separate from allowances, and a new
+        /// session replaces the spender's previous one.
+        #[cfg(feature = "sessions")]
+        #[ink(message)]
+        pub fn create_session(
+            &mut self,
+            spender: AccountId,
+            total_cap: Balance,
+            per_tx_cap: Balance,
+            expires_at: Timestamp,
+        ) -> Result<()> {
+            if expires_at <= self.env().block_timestamp() {
+                return Err(Error::SessionExpired)
+            }
+            let owner = self.env().caller();
+            let session = Session {
+                total_cap,
+                per_tx_cap,
+                expires_at,
+                spent: 0,
+            };
+            self.sessions.insert((owner, spender), &session);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(SessionCreated {
+                owner,
+                spender,
+                total_cap,
+                per_tx_cap,
+                expires_at,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Ends the session of `spender` on the caller's tokens.
+        #[cfg(feature = "sessions")]
+        #[ink(message)]
+        pub fn revoke_session(&mut self, spender: AccountId) -> Result<()> {
+            let owner = self.env().caller();
+            if !self.sessions.contains((owner, spender)) {
+                return Err(Error::SessionNotFound)
+            }
+            self.sessions.remove((owner, spender));
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(SessionRevoked {
+                owner,
+                spender,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Transfers `value` of `from`'s tokens to `to` within the caller's
+        /// session on them.
+        #[cfg(feature = "sessions")]
+        #[ink(message)]
+        pub fn session_transfer_from(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            let mut session = self
+                .sessions
+                .get((from, caller))
+                .ok_or(Error::SessionNotFound)?;
+            if self.env().block_timestamp() >= session.expires_at {
+                return Err(Error::SessionExpired)
+            }
+            session.spent = session
+                .spent
+                .checked_add(value)
+                .filter(|spent| value <= session.per_tx_cap && *spent <= session.total_cap)
+                .ok_or(Error::SessionCapExceeded)?;
+            self.transfer_from_to(&from, &to, value)?;
+            self.sessions.insert((from, caller), &session);
+            Ok(())
+        }
+
+        /// Returns a subscription that is not cancelled yet.
+        #[cfg(feature = "subscriptions")]
+        #[ink(message)]
+        pub fn subscription(&self, id: SubscriptionId) -> Option<Subscription> {
+            self.subscriptions.get(id)
+        }
+
+        /// Returns the time from which the next payment of a subscription
+        /// can be collected.
+        #[cfg(feature = "subscriptions")]
+        #[ink(message)]
+        pub fn next_collection(&self, id: SubscriptionId) -> Option<Timestamp> {
+            self.subscriptions
+                .get(id)
+                .map(|subscription| subscription.next_collection)
+        }
+
+        /// Authorizes `merchant` to collect `amount` of the caller's tokens
+        /// once per `interval`, starting now, and returns the identifier of
+        /// the subscription.
+        #[cfg(feature = "subscriptions")]
+        #[ink(message)]
+        pub fn create_subscription(
+            &mut self,
+            merchant: AccountId,
+            amount: Balance,
+            interval: Timestamp,
+        ) -> Result<SubscriptionId> {
+            if interval == 0 {
+                return Err(Error::InvalidSubscriptionInterval)
+            }
+            let payer = self.env().caller();
+            let now = self.env().block_timestamp();
+            let id = self.next_subscription_id.get_or_default();
+            self.next_subscription_id.set(&(id + 1));
+            self.subscriptions.insert(
+                id,
+                &Subscription {
+                    payer,
+                    merchant,
+                    amount,
+                    interval,
+                    next_collection: now,
+                },
+            );
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(SubscriptionCreated {
+                id,
+                payer,
+                merchant,
+                amount,
+                interval,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(id)
+        }
+
+        /// Transfers the payment of a subscription from its payer to its
+        /// merchant once it is due, and returns the amount. Anyone may call
+        /// this.
+        ///
+        /// At most one payment is collected at a time: intervals that passed
+        /// without a collection are skipped rather than charged later, and
+        /// the next payment is due at the start of the next interval.
+        #[cfg(feature = "subscriptions")]
+        #[ink(message)]
+        pub fn collect(&mut self, id: SubscriptionId) -> Result<Balance> {
+            let mut subscription = self
+                .subscriptions
+                .get(id)
+                .ok_or(Error::SubscriptionNotFound)?;
+            let now = self.env().block_timestamp();
+            if now < subscription.next_collection {
+                return Err(Error::SubscriptionNotDue)
+            }
+            let missed = (now - subscription.next_collection) / subscription.interval;
+            subscription.next_collection = subscription
+                .next_collection
+                .saturating_add((missed + 1).saturating_mul(subscription.interval));
+            let (payer, merchant) = (subscription.payer, subscription.merchant);
+            self.transfer_from_to(&payer, &merchant, subscription.amount)?;
+            self.subscriptions.insert(id, &subscription);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(SubscriptionCollected {
+                id,
+                collector: self.env().caller(),
+                amount: subscription.amount,
+                missed,
+                next_collection: subscription.next_collection,
+                block_number: self.env().block_number(),
+                timestamp: now,
+                event_seq,
+            });
+            Ok(subscription.amount)
+        }
+
+        /// Ends a subscription. Only its payer may call this.
+        #[cfg(feature = "subscriptions")]
+        #[ink(message)]
+        pub fn cancel_subscription(&mut self, id: SubscriptionId) -> Result<()> {
+            let subscription = self
+                .subscriptions
+                .get(id)
+                .ok_or(Error::SubscriptionNotFound)?;
+            if self.env().caller() != subscription.payer {
+                return Err(Error::NotSubscriptionPayer)
+            }
+            self.subscriptions.remove(id);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(SubscriptionCancelled {
+                id,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Returns the guardians `account` registered to recover its balance.
+        #[cfg(feature = "recovery")]
+        #[ink(message)]
+        pub fn recovery_config(&self, account: AccountId) -> Option<RecoveryConfig> {
+            self.recovery_configs.get(account)
+        }
+
+        /// Returns the recovery of `account`'s balance in progress.
+        #[cfg(feature = "recovery")]
+        #[ink(message)]
+        pub fn pending_recovery(&self, account: AccountId) -> Option<Recovery> {
+            self.recoveries.get(account)
+        }
+
+        /// Registers guardians that may together recover the caller's balance
+        /// to a new account if the caller loses its key, or opts out with
+        /// `None`. A recovery in progress is dropped.
+        #[cfg(feature = "recovery")]
+        #[ink(message)]
+        pub fn set_recovery_config(&mut self, config: Option<RecoveryConfig>) -> Result<()> {
+            let account = self.env().caller();
+            if let Some(config) = &config {
+                let guardians = &config.guardians;
+                let valid = config.threshold > 0
+                    && config.threshold as usize <= guardians.len()
+                    && guardians.len() <= MAX_RECOVERY_GUARDIANS as usize
+                    && !guardians.contains(&account)
+                    && guardians
+                        .iter()
+                        .enumerate()
+                        .all(|(i, guardian)| !guardians[..i].contains(guardian));
+                if !valid {
+                    return Err(Error::InvalidRecoveryConfig)
+                }
+            }
+            let old = self.recovery_config(account);
+            match &config {
+                Some(config) => self.recovery_configs.insert(account, config),
+                None => self.recovery_configs.remove(account),
+            }
+            self.recoveries.remove(account);
+            self.emit_config_changed(
+                Setting::RecoveryConfig,
+                Some(account),
+                old.map(|old| old.threshold),
+                config.map(|config| config.threshold),
+            );
+            Ok(())
+        }
+
+        /// Approves recovering `account`'s balance to `new_account`. Once
+        /// the threshold of guardians approved, the recovery can be carried
+        /// out with `recover_balance` after the delay, unless `account`
+        /// vetoes it.
+        ///
+        /// Only a guardian of `account` may call this.
+        #[cfg(feature = "recovery")]
+        #[ink(message)]
+        pub fn approve_recovery(
+            &mut self,
+            account: AccountId,
+            new_account: AccountId,
+        ) -> Result<()> {
+            let guardian = self.env().caller();
+            let config = self
+                .recovery_config(account)
+                .filter(|config| config.guardians.contains(&guardian))
+                .ok_or(Error::NotRecoveryGuardian)?;
+            let mut recovery = self.pending_recovery(account).unwrap_or(Recovery {
+                new_account,
+                ..Default::default()
+            });
+            if recovery.new_account != new_account {
+                return Err(Error::RecoveryInProgress)
+            }
+            if !recovery.approvals.contains(&guardian) {
+                recovery.approvals.push(guardian);
+            }
+            let approvals = recovery.approvals.len() as u32;
+            if recovery.executable_at.is_none() && approvals >= config.threshold {
+                let now = self.env().block_timestamp();
+                recovery.executable_at = Some(now.saturating_add(config.delay));
+            }
+            self.recoveries.insert(account, &recovery);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(RecoveryApproved {
+                account,
+                new_account,
+                guardian,
+                approvals,
+                executable_at: recovery.executable_at,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Cancels the recovery of the caller's balance in progress.
+        #[cfg(feature = "recovery")]
+        #[ink(message)]
+        pub fn veto_recovery(&mut self) -> Result<()> {
+            let account = self.env().caller();
+            let recovery = self
+                .pending_recovery(account)
+                .ok_or(Error::RecoveryNotFound)?;
+            self.recoveries.remove(account);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(RecoveryVetoed {
+                account,
+                new_account: recovery.new_account,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Moves `account`'s balance to `new_account` once its guardians
+        /// approved the recovery and the delay has passed, and returns the
+        /// amount moved. Anyone may call this.
+        ///
+        /// Bypasses the transfer fees and limits, but not freezes, the
+        /// denylist, or the tokens `account` holds back.
+        #[cfg(feature = "recovery")]
+        #[ink(message)]
+        pub fn recover_balance(
+            &mut self,
+            account: AccountId,
+            new_account: AccountId,
+        ) -> Result<Balance> {
+            let recovery = self
+                .pending_recovery(account)
+                .filter(|recovery| recovery.new_account == new_account)
+                .ok_or(Error::RecoveryNotFound)?;
+            let now = self.env().block_timestamp();
+            if recovery.executable_at.is_none_or(|at| at > now) {
+                return Err(Error::RecoveryNotReady)
+            }
+            #[cfg(feature = "compliance")]
+            {
+                if self.is_denylisted(account) || self.is_denylisted(new_account) {
+                    return Err(Error::Denylisted)
+                }
+                if self.is_frozen(account) || self.is_frozen(new_account) {
+                    return Err(Error::AccountFrozen)
+                }
+            }
+            let gons = self.gons_of(&account) - self.held_gons(&account);
+            let value = self.gons_rate().to_tokens(gons);
+            self.recoveries.remove(account);
+            self.move_gons(&account, &new_account, gons);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(BalanceRecovered {
+                account,
+                new_account,
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(value)
+        }
+
+        /// Sets several allowances of the caller at once, emitting one
+        /// `Approve` event per entry.
+        #[ink(message)]
+        pub fn approve_batch(&mut self, approvals: Vec<(AccountId, Balance)>) -> Result<()> {
+            for (spender, value) in approvals {
+                self.approve_impl(spender, value, None)?;
+            }
+            Ok(())
+        }
+
+        fn approve_impl(
+            &mut self,
+            spender: AccountId,
+            value: Balance,
+            expires_at: Option<Timestamp>,
+        ) -> Result<()> {
+            let owner = self.env().caller();
+            let previous = self.allowance(owner, spender);
+            self.set_allowance(&owner, &spender, value, expires_at);
+            #[cfg(feature = "spending-limits")]
+            self.spending_limits.remove((owner, spender));
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(Approve {
+                owner,
+                spender,
+                value,
+                previous,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Returns the allowance `spender` can still spend, which is zero once
+        /// it has expired.
+        #[ink(message)]
+        pub fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance {
+            self.allowance_impl(&owner, &spender)
+                .map(|(value, _)| value)
+                .unwrap_or_default()
+        }
+
+        /// Returns the deadline of an allowance, if it has one.
+        #[ink(message)]
+        pub fn allowance_expiry(&self, owner: AccountId, spender: AccountId) -> Option<Timestamp> {
+            self.allowances
+                .get((owner, spender))
+                .and_then(|allowance| allowance.expires_at)
+        }
+
+        /// Returns the allowance and its deadline, or `Err(AllowanceExpired)`
+        /// once the deadline has passed.
+        #[inline]
+        fn allowance_impl(
+            &self,
+            ownder: &AccountId,
+            spender: &AccountId,
+        ) -> Result<(Balance, Option<Timestamp>)> {
+            let Allowance { value, expires_at } = self
+                .allowances
+                .get((ownder, spender))
+                .unwrap_or_default();
+            match expires_at {
+                Some(expires_at) if self.env().block_timestamp() >= expires_at => {
+                    Err(Error::AllowanceExpired)
+                }
+                _ => Ok((value, expires_at)),
+            }
+        }
+
+        #[ink(message)]
+        pub fn transfer_from(&mut self, from: AccountId, to: AccountId, value: Balance) -> Result<()> {
+            let caller = self.env().caller();
+            let (allowance, expires_at) = self.allowance_impl(&from, &caller)?;
+            if allowance < value {
+                return Err(Error::InsufficientAllowance)
+            }
+            #[cfg(feature = "spending-limits")]
+            let limit = self.spend_limit(from, caller, value)?;
+            self.transfer_from_to(&from, &to, value)?;
+            self.spend_allowance(&from, &caller, allowance, value, expires_at);
+            #[cfg(feature = "spending-limits")]
+            if let Some(limit) = limit {
+                self.spending_limits.insert((from, caller), &limit);
+            }
+            Ok(())
+        }
+
+        /// Transfers from `from` to each of `recipients`, spending the
+        /// caller's allowance once for the sum. Fails as a whole if any
+        /// transfer fails.
+        #[ink(message)]
+        pub fn transfer_from_batch(
+            &mut self,
+            from: AccountId,
+            recipients: Vec<(AccountId, Balance)>,
+        ) -> Result<()> {
+            let caller = self.env().caller();
+            let (allowance, expires_at) = self.allowance_impl(&from, &caller)?;
+            let total = recipients
+                .iter()
+                .try_fold(0 as Balance, |total, (_, value)| total.checked_add(*value))
+                .filter(|total| *total <= allowance)
+                .ok_or(Error::InsufficientAllowance)?;
+            #[cfg(feature = "spending-limits")]
+            let limit = self.spend_limit(from, caller, total)?;
+            for (to, value) in recipients {
+                self.transfer_from_to(&from, &to, value)?;
+            }
+            self.spend_allowance(&from, &caller, allowance, total, expires_at);
+            #[cfg(feature = "spending-limits")]
+            if let Some(limit) = limit {
+                self.spending_limits.insert((from, caller), &limit);
+            }
+            Ok(())
+        }
+
+        /// Returns the nonce the next signed transfer of `owner` must carry.
+        #[cfg(feature = "signed-transfers")]
+        #[ink(message)]
+        pub fn nonce_of(&self, owner: AccountId) -> u64 {
+            self.nonces.get(owner).unwrap_or_default()
+        }
+
+        /// Returns the hash `from` signs to authorize a transfer through
+        /// `transfer_with_signature`. It commits to this contract, so a
+        /// signature cannot be replayed on another deployment.
+        #[cfg(feature = "signed-transfers")]
+        #[ink(message)]
+        pub fn transfer_signing_hash(
+            &self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+            nonce: u64,
+            deadline: Timestamp,
+        ) -> Hash {
+            Hash::from(self.transfer_digest(from, to, value, nonce, deadline))
+        }
+
+        #[cfg(feature = "signed-transfers")]
+        fn transfer_digest(
+            &self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+            nonce: u64,
+            deadline: Timestamp,
+        ) -> [u8; 32] {
+            let contract = self.env().account_id();
+            self.env()
+                .hash_encoded::<Blake2x256, _>(&(contract, from, to, value, nonce, deadline))
+        }
+
+        /// Transfers `value` tokens from `from` to `to` on behalf of `from`,
+        /// who signed `transfer_signing_hash` off-chain with their ECDSA key.
+        /// Anyone may submit the transfer, so `from` needs no native balance.
+        ///
+        /// `nonce` must be `from`'s next nonce and the block time must be
+        /// before `deadline`.
+        #[cfg(feature = "signed-transfers")]
+        #[ink(message)]
+        pub fn transfer_with_signature(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+            nonce: u64,
+            deadline: Timestamp,
+            signature: [u8; 65],
+        ) -> Result<()> {
+            if self.env().block_timestamp() >= deadline {
+                return Err(Error::SignatureExpired)
+            }
+            if nonce != self.nonce_of(from) {
+                return Err(Error::InvalidNonce)
+            }
+            let digest = self.transfer_digest(from, to, value, nonce, deadline);
+            let mut public_key = [0; 33];
+            ink_env::ecdsa_recover(&signature, &digest, &mut public_key)
+                .map_err(|_| Error::InvalidSignature)?;
+            let signer = AccountId::from(self.env().hash_bytes::<Blake2x256>(&public_key));
+            if signer != from {
+                return Err(Error::InvalidSignature)
+            }
+            self.transfer_from_to(&from, &to, value)?;
+            self.nonces.insert(from, &(nonce + 1));
+            Ok(())
+        }
+
+        /// Returns the account an EVM address maps to: the Blake2 hash of
+        /// `"evm:"` and the address, as pallet-evm's `HashedAddressMapping`
+        /// derives it.
+        #[cfg(feature = "evm")]
+        #[ink(message)]
+        pub fn h160_account(&self, address: [u8; 20]) -> AccountId {
+            let mut input = [0; 24];
+            input[..4].copy_from_slice(EVM_ADDRESS_PREFIX);
+            input[4..].copy_from_slice(&address);
+            AccountId::from(self.env().hash_bytes::<Blake2x256>(&input))
+        }
+
+        /// Returns the balance of the account an EVM address maps to.
+        #[cfg(feature = "evm")]
+        #[ink(message)]
+        pub fn balance_of_h160(&self, address: [u8; 20]) -> Balance {
+            self.balance_of(self.h160_account(address))
+        }
+
+        /// Transfers `value` tokens to the account an EVM address maps to.
+        #[cfg(feature = "evm")]
+        #[ink(message)]
+        pub fn transfer_to_h160(&mut self, address: [u8; 20], value: Balance) -> Result<()> {
+            let to = self.h160_account(address);
+            self.transfer(to, value)
+        }
+
+        /// Like `transfer`, but reports failure as `false` the way Solidity
+        /// ERC-20 tokens do. Failed calls change nothing and do not revert.
+        #[cfg(feature = "evm")]
+        #[ink(message)]
+        pub fn eth_transfer(&mut self, to: AccountId, value: Balance) -> bool {
+            self.transfer(to, value).is_ok()
+        }
+
+        /// Like `approve`, but reports failure as `false`.
+        #[cfg(feature = "evm")]
+        #[ink(message)]
+        pub fn eth_approve(&mut self, spender: AccountId, value: Balance) -> bool {
+            self.approve(spender, value).is_ok()
+        }
+
+        /// Like `transfer_from`, but reports failure as `false`.
+        #[cfg(feature = "evm")]
+        #[ink(message)]
+        pub fn eth_transfer_from(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+        ) -> bool {
+            self.transfer_from(from, to, value).is_ok()
+        }
+
+        /// Lowers `owner`'s allowance of `allowance` for `spender` by `value`,
+        /// which must not exceed it. Only the allowance entry is written
+        /// unless the allowance runs out.
+        fn spend_allowance(
+            &mut self,
+            owner: &AccountId,
+            spender: &AccountId,
+            allowance: Balance,
+            value: Balance,
+            expires_at: Option<Timestamp>,
+        ) {
+            match allowance - value {
+                _ if value == 0 => {}
+                0 => self.set_allowance(owner, spender, 0, expires_at),
+                value => {
+                    self.allowances
+                        .insert((owner, spender), &Allowance { value, expires_at })
+                }
+            }
+        }
+
+        /// Writes an allowance and keeps the owner's spender list in sync.
+        fn set_allowance(
+            &mut self,
+            owner: &AccountId,
+            spender: &AccountId,
+            value: Balance,
+            expires_at: Option<Timestamp>,
+        ) {
+            if value == 0 {
+                self.allowances.remove((owner, spender));
+            } else {
+                self.allowances
+                    .insert((owner, spender), &Allowance { value, expires_at });
+            }
+            match (self.spender_indices.get((owner, spender)), value) {
+                (None, 0) | (Some(_), 1..) => {}
+                (None, _) => {
+                    let count = self.spender_counts.get(owner).unwrap_or_default();
+                    self.spenders.insert((owner, count), spender);
+                    self.spender_indices.insert((owner, spender), &count);
+                    self.spender_counts.insert(owner, &(count + 1));
+                }
+                (Some(index), 0) => {
+                    // Move the last spender into the freed slot.
+                    let last = self.spender_counts.get(owner).unwrap_or_default() - 1;
+                    if index != last {
+                        let moved = self
+                            .spenders
+                            .get((owner, last))
+                            .expect("spender list is contiguous");
+                        self.spenders.insert((owner, index), &moved);
+                        self.spender_indices.insert((owner, &moved), &index);
+                    }
+                    self.spenders.remove((owner, last));
+                    self.spender_indices.remove((owner, spender));
+                    if last == 0 {
+                        self.spender_counts.remove(owner);
+                    } else {
+                        self.spender_counts.insert(owner, &last);
+                    }
+                }
+            }
+        }
+
+        /// Returns up to `limit` of `owner`'s outstanding allowances, starting
+        /// at `offset`. Expired allowances are listed with a value of zero.
+        #[ink(message)]
+        pub fn allowances_of(
+            &self,
+            owner: AccountId,
+            offset: u32,
+            limit: u32,
+        ) -> Vec<(AccountId, Balance)> {
+            let count = self.spender_counts.get(owner).unwrap_or_default();
+            let end = offset.saturating_add(limit).min(count);
+            (offset..end)
+                .filter_map(|index| self.spenders.get((owner, index)))
+                .map(|spender| (spender, self.allowance(owner, spender)))
+                .collect()
+        }
+
+        /// Returns the allowances of the given `(owner, spender)` pairs, in the
+        /// same order.
+        #[ink(message)]
+        pub fn allowances_of_pairs(&self, pairs: Vec<(AccountId, AccountId)>) -> Vec<Balance> {
+            pairs
+                .into_iter()
+                .map(|(owner, spender)| self.allowance(owner, spender))
+                .collect()
+        }
+
+        /// Executes `calls` in order on behalf of the caller. If any of them
+        /// fails, the whole batch is reverted and its error returned.
+        #[cfg(feature = "multicall")]
+        #[ink(message)]
+        pub fn multicall(&mut self, calls: Vec<Call>) -> Result<()> {
+            for call in calls {
+                match call {
+                    Call::Transfer { to, value } => self.transfer(to, value)?,
+                    Call::TransferFrom { from, to, value } => self.transfer_from(from, to, value)?,
+                    Call::Approve { spender, value } => self.approve(spender, value)?,
+                    Call::ApproveWithDeadline {
+                        spender,
+                        value,
+                        expires_at,
+                    } => self.approve_with_deadline(spender, value, expires_at)?,
+                    Call::ClaimDividends => {
+                        self.claim_dividends()?;
+                    }
+                    Call::Lock { value, unlock_at } => {
+                        self.lock(value, unlock_at)?;
+                    }
+                    Call::Unlock { lock_id } => {
+                        self.unlock(lock_id)?;
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        /// Returns the parameters of the built-in governor.
+        #[cfg(feature = "governance")]
+        #[ink(message)]
+        pub fn governance_config(&self) -> GovernanceConfig {
+            self.governance_config.get_or_default()
+        }
+
+        /// Sets the parameters of the built-in governor.
+        ///
+        /// Only the owner may call this. Running proposals keep their voting
+        /// period and quorum.
+        #[cfg(feature = "governance")]
+        #[ink(message)]
+        pub fn set_governance_config(&mut self, config: GovernanceConfig) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.governance_config();
+            self.governance_config.set(&config);
+            self.emit_config_changed(Setting::GovernanceConfig, None, old, config);
+            Ok(())
+        }
+
+        /// Puts a call on `target` to a vote and returns the proposal identifier.
+        ///
+        /// `call_data` is the selector of the message to call followed by its
+        /// SCALE encoded arguments. To administer this token through governance,
+        /// make this contract its own owner and propose calls on itself. Only
+        /// the messages in `GOVERNABLE_SELECTORS` may be called on it, and
+        /// none on the PSP22 tokens it holds as rewards.
+        #[cfg(feature = "governance")]
+        #[ink(message)]
+        pub fn propose(
+            &mut self,
+            target: AccountId,
+            call_data: Vec<u8>,
+            description_hash: Hash,
+        ) -> Result<ProposalId> {
+            let config = self.governance_config();
+            if config.voting_period == 0 {
+                return Err(Error::GovernanceDisabled)
+            }
+            let proposer = self.env().caller();
+            if self.balance_of_impl(&proposer) < config.proposal_threshold {
+                return Err(Error::BelowProposalThreshold)
+            }
+            self.ensure_governable(target, &call_data)?;
+            let proposal_id = self.next_proposal_id.get_or_default();
+            let vote_end = self.env().block_number() + config.voting_period;
+            let proposal = Proposal {
+                proposer,
+                target,
+                call_data,
+                description_hash,
+                vote_end,
+                quorum_gons: Self::share_of(self.total_gons(), config.quorum),
+                for_gons: 0,
+                against_gons: 0,
+                eta: None,
+                executed: false,
+            };
+            self.proposals.insert(proposal_id, &proposal);
+            self.next_proposal_id.set(&(proposal_id + 1));
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(ProposalCreated {
+                proposal_id,
+                proposer,
+                target,
+                description_hash,
+                vote_end,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(proposal_id)
+        }
+
+        /// Proposes to replace the contract code with `code_hash`. Once passed,
+        /// queued and past its timelock, executing the proposal calls `set_code`.
+        #[cfg(feature = "governance")]
+        #[ink(message)]
+        pub fn propose_upgrade(
+            &mut self,
+            code_hash: [u8; 32],
+            description_hash: Hash,
+        ) -> Result<ProposalId> {
+            let mut call_data = ink_lang::selector_bytes!("set_code").to_vec();
+            call_data.extend_from_slice(&code_hash);
+            self.propose(self.env().account_id(), call_data, description_hash)
+        }
+
+        /// Returns the code hash and earliest execution time of the queued
+        /// upgrade, if there is one.
+        #[cfg(feature = "governance")]
+        #[ink(message)]
+        pub fn pending_upgrade(&self) -> Option<(Hash, Timestamp)> {
+            let proposal = self.proposals.get(self.pending_upgrade.get().flatten()?)?;
+            if self.state_of(&proposal) != ProposalState::Queued {
+                return None
+            }
+            Some((self.upgrade_hash_of(&proposal)?, proposal.eta?))
+        }
+
+        /// Returns the new code hash if `proposal` calls `set_code` on this contract.
+        #[cfg(feature = "governance")]
+        fn upgrade_hash_of(&self, proposal: &Proposal) -> Option<Hash> {
+            if proposal.target != self.env().account_id() {
+                return None
+            }
+            let (selector, code_hash) = proposal.call_data.split_at(4.min(proposal.call_data.len()));
+            if selector != ink_lang::selector_bytes!("set_code") {
+                return None
+            }
+            <[u8; 32]>::try_from(code_hash).ok().map(Hash::from)
+        }
+
+        /// Returns the proposal with the given identifier.
+        #[cfg(feature = "governance")]
+        #[ink(message)]
+        pub fn proposal(&self, proposal_id: ProposalId) -> Option<Proposal> {
+            self.proposals.get(proposal_id)
+        }
+
+        /// Returns the lifecycle state of the proposal with the given identifier.
+        #[cfg(feature = "governance")]
+        #[ink(message)]
+        pub fn proposal_state(&self, proposal_id: ProposalId) -> Option<ProposalState> {
+            self.proposals
+                .get(proposal_id)
+                .map(|proposal| self.state_of(&proposal))
+        }
+
+        /// Returns whether `voter` has voted on the given proposal.
+        #[cfg(feature = "governance")]
+        #[ink(message)]
+        pub fn has_voted(&self, proposal_id: ProposalId, voter: AccountId) -> bool {
+            self.votes.get((proposal_id, voter)).unwrap_or_default()
+        }
+
+        /// Votes for or against a proposal with the caller's whole balance.
+        ///
+        /// The balance backing the vote cannot be transferred out until voting
+        /// on the proposal ends, so the same tokens cannot vote twice.
+        #[cfg(feature = "governance")]
+        #[ink(message)]
+        pub fn cast_vote(&mut self, proposal_id: ProposalId, support: bool) -> Result<()> {
+            let voter = self.env().caller();
+            let mut proposal = self.proposals.get(proposal_id).ok_or(Error::ProposalNotFound)?;
+            if self.state_of(&proposal) != ProposalState::Active {
+                return Err(Error::InvalidProposalState)
+            }
+            if self.has_voted(proposal_id, voter) {
+                return Err(Error::AlreadyVoted)
+            }
+            let weight = self.gons_of(&voter);
+            if weight == 0 {
+                return Err(Error::NoVotingPower)
+            }
+            if support {
+                proposal.for_gons += weight;
+            } else {
+                proposal.against_gons += weight;
+            }
+            self.proposals.insert(proposal_id, &proposal);
+            self.votes.insert((proposal_id, voter), &true);
+            let mut lock = self.active_vote_lock(&voter).unwrap_or_default();
+            lock.gons = lock.gons.max(weight);
+            lock.until = lock.until.max(proposal.vote_end);
+            self.vote_locks.insert(voter, &lock);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(VoteCast {
+                proposal_id,
+                voter,
+                support,
+                weight: self.gons_rate().to_tokens(weight),
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Starts the timelock of a proposal that passed.
+        #[cfg(feature = "governance")]
+        #[ink(message)]
+        pub fn queue(&mut self, proposal_id: ProposalId) -> Result<Timestamp> {
+            let mut proposal = self.proposals.get(proposal_id).ok_or(Error::ProposalNotFound)?;
+            if self.state_of(&proposal) != ProposalState::Succeeded {
+                return Err(Error::InvalidProposalState)
+            }
+            let eta = self.env().block_timestamp() + self.governance_config().timelock;
+            proposal.eta = Some(eta);
+            self.proposals.insert(proposal_id, &proposal);
+            if self.upgrade_hash_of(&proposal).is_some() {
+                self.pending_upgrade.set(&Some(proposal_id));
+            }
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(ProposalQueued {
+                proposal_id,
+                eta,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(eta)
+        }
+
+        /// Makes the call of a queued proposal once its timelock has passed.
+        #[cfg(feature = "governance")]
+        #[ink(message)]
+        pub fn execute(&mut self, proposal_id: ProposalId) -> Result<()> {
+            let mut proposal = self.proposals.get(proposal_id).ok_or(Error::ProposalNotFound)?;
+            if self.state_of(&proposal) != ProposalState::Queued {
+                return Err(Error::InvalidProposalState)
+            }
+            if proposal.eta > Some(self.env().block_timestamp()) {
+                return Err(Error::TimelockNotExpired)
+            }
+            self.ensure_governable(proposal.target, &proposal.call_data)?;
+            proposal.executed = true;
+            self.proposals.insert(proposal_id, &proposal);
+            if proposal.call_data.len() < 4 {
+                return Err(Error::ProposalCallFailed)
+            }
+            let (selector, args) = proposal.call_data.split_at(4);
+            build_call::<Environment>()
+                .call_type(call::Call::new().callee(proposal.target))
+                .call_flags(CallFlags::default().set_allow_reentry(true))
+                .exec_input(
+                    ExecutionInput::new(Selector::new([
+                        selector[0],
+                        selector[1],
+                        selector[2],
+                        selector[3],
+                    ]))
+                    .push_arg(EncodedArgs(args)),
+                )
+                .returns::<()>()
+                .fire()
+                .map_err(|_| Error::ProposalCallFailed)?;
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(ProposalExecuted {
+                proposal_id,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Fails if a proposal calling `call_data` on `target` would call a
+        /// message of this contract that is not in `GOVERNABLE_SELECTORS`, or
+        /// a reward token, whose balance the contract holds for its holders.
+        #[cfg(feature = "governance")]
+        fn ensure_governable(&self, target: AccountId, call_data: &[u8]) -> Result<()> {
+            if target != self.env().account_id() {
+                #[cfg(feature = "dividends")]
+                if self.reward_tokens().contains(&target) {
+                    return Err(Error::TargetNotGovernable)
+                }
+                return Ok(())
+            }
+            let selector = call_data.get(..4).unwrap_or_default();
+            if !GOVERNABLE_SELECTORS.iter().any(|allowed| allowed == selector) {
+                return Err(Error::SelectorNotGovernable)
+            }
+            Ok(())
+        }
+
+        #[cfg(feature = "governance")]
+        fn state_of(&self, proposal: &Proposal) -> ProposalState {
+            if proposal.executed {
+                ProposalState::Executed
+            } else if self.env().block_number() <= proposal.vote_end {
+                ProposalState::Active
+            } else if proposal.for_gons <= proposal.against_gons
+                || proposal.for_gons < proposal.quorum_gons
+            {
+                ProposalState::Defeated
+            } else if proposal.eta.is_some() {
+                ProposalState::Queued
+            } else {
+                ProposalState::Succeeded
+            }
+        }
+
+        #[cfg(feature = "governance")]
+        fn active_vote_lock(&self, account: &AccountId) -> Option<VoteLock> {
+            self.vote_locks
+                .get(account)
+                .filter(|lock| lock.until >= self.env().block_number())
+        }
+
+        /// Returns the gons `account` has locked, locked into the vote escrow,
+        /// assigned to partitions and pledged to running votes, each with the
+        /// error spending them fails with. They overlap, so the largest of them
+        /// is held back.
+        // Each source only exists with its feature.
+        #[allow(unused_mut, clippy::vec_init_then_push)]
+        fn encumbrances(&self, account: &AccountId) -> Vec<(Balance, Error)> {
+            let mut encumbrances = Vec::new();
+            #[cfg(feature = "locks")]
+            encumbrances.push((self.locked.get(account).unwrap_or_default(), Error::TokensLocked));
+            #[cfg(feature = "vote-escrow")]
+            encumbrances.push((
+                self.active_ve_lock(account)
+                    .map(|lock| lock.gons)
+                    .unwrap_or_default(),
+                Error::TokensLocked,
+            ));
+            #[cfg(feature = "partitions")]
+            encumbrances.push((
+                self.partitioned.get(account).unwrap_or_default(),
+                Error::TokensPartitioned,
+            ));
+            #[cfg(feature = "governance")]
+            encumbrances.push((
+                self.active_vote_lock(account)
+                    .map(|lock| lock.gons)
+                    .unwrap_or_default(),
+                Error::TokensLockedByVote,
+            ));
+            encumbrances
+        }
+
+        /// Fails if `account` would be left with fewer gons than any of its
+        /// encumbrances.
+        fn ensure_spendable(&self, account: &AccountId, remaining: Balance) -> Result<()> {
+            match self
+                .encumbrances(account)
+                .into_iter()
+                .find(|(gons, _)| remaining < *gons)
+            {
+                Some((_, error)) => Err(error),
+                None => Ok(()),
+            }
+        }
+
+        /// Returns the gons of `account` that cannot be transferred: all of
+        /// them while it is frozen, the largest encumbrance otherwise.
+        #[cfg(any(feature = "holders", feature = "locks", feature = "recovery"))]
+        fn held_gons(&self, account: &AccountId) -> Balance {
+            let gons = self.gons_of(account);
+            #[cfg(feature = "compliance")]
+            if self.is_frozen(*account) {
+                return gons
+            }
+            self.encumbrances(account)
+                .iter()
+                .map(|(held, _)| *held)
+                .max()
+                .unwrap_or_default()
+                .min(gons)
+        }
+
+        /// Moves `value` of `from`'s spendable tokens into the contract's own
+        /// balance and returns the amount in gons.
+        #[cfg(any(
+            feature = "airdrops",
+            feature = "escrow",
+            feature = "htlc",
+            feature = "raffles",
+            feature = "sale",
+            feature = "scheduled",
+            feature = "streams",
+            feature = "vouchers",
+        ))]
+        fn move_into_contract(&mut self, from: &AccountId, value: Balance) -> Result<Balance> {
+            self.ensure_can_send(from)?;
+            if self.balance_of_impl(from) < value {
+                return Err(Error::InsufficientBalance)
+            }
+            let gons = self.gons_rate().to_gons(value);
+            self.ensure_spendable(from, self.gons_of(from) - gons)?;
+            self.move_gons(from, &self.env().account_id(), gons);
+            Ok(gons)
+        }
+
+        /// Returns where the oracle price is read from.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn oracle_config(&self) -> Option<OracleConfig> {
+            self.oracle.get().flatten()
+        }
+
+        /// Sets where the oracle price is read from, or disables the oracle.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn set_oracle_config(&mut self, config: Option<OracleConfig>) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.oracle_config();
+            self.oracle.set(&config);
+            self.emit_config_changed(Setting::OracleConfig, None, old, config);
+            Ok(())
+        }
+
+        /// Returns the current price of the configured feed, as reported by
+        /// the runtime.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn oracle_price(&self) -> Result<Balance> {
+            let config = self.oracle_config().ok_or(Error::OracleUnavailable)?;
+            #[cfg(feature = "oracle")]
+            let price = crate::oracle::price(config.extension_id, config.function_id, config.feed);
+            #[cfg(not(feature = "oracle"))]
+            let price = {
+                let _ = config;
+                None
+            };
+            price.ok_or(Error::OracleUnavailable)
+        }
+
+        /// Returns the settings of the oracle-driven tax rate.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn fee_controller(&self) -> Option<FeeController> {
+            self.fee_controller.get().flatten()
+        }
+
+        /// Sets or disables the oracle-driven tax rate. The bounds must be
+        /// ordered and within `MAX_TAX_RATE`.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn set_fee_controller(&mut self, controller: Option<FeeController>) -> Result<()> {
+            self.ensure_owner()?;
+            if let Some(controller) = &controller {
+                if controller.target_price == 0
+                    || controller.min_rate > controller.max_rate
+                    || controller.max_rate > MAX_TAX_RATE
+                {
+                    return Err(Error::InvalidFeeController)
+                }
+            }
+            let old = self.fee_controller();
+            self.fee_controller.set(&controller);
+            self.emit_config_changed(Setting::FeeController, None, old, controller);
+            Ok(())
+        }
+
+        /// Sets the tax rate from the current oracle price and returns it.
+        ///
+        /// Anyone may call this, at most once per block.
+        #[cfg(feature = "fees")]
+        #[ink(message)]
+        pub fn update_fee_from_oracle(&mut self) -> Result<u16> {
+            let controller = self.fee_controller().ok_or(Error::InvalidFeeController)?;
+            let block = self.env().block_number();
+            if self.fee_updated_at.get().flatten() == Some(block) {
+                return Err(Error::FeeUpdateTooSoon)
+            }
+            let price = self.oracle_price()?;
+            let old_rate = self.tax_rate();
+            let new_rate = controller.rate_for(price);
+            self.tax_rate.set(&new_rate);
+            self.fee_updated_at.set(&Some(block));
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(TaxRateUpdated {
+                old_rate,
+                new_rate,
+                price,
+                block_number: block,
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(new_rate)
+        }
+
+        /// Returns the layout version of the data in storage.
+        #[ink(message)]
+        pub fn storage_version(&self) -> u32 {
+            self.storage_version.get_or_default()
+        }
+
+        /// Returns the version of the event formats, see `EVENT_SCHEMA_VERSION`.
+        #[ink(message)]
+        pub fn event_schema_version(&self) -> u32 {
+            EVENT_SCHEMA_VERSION
+        }
+
+        /// Returns the sequence number the next event will carry, which is
+        /// also the number of events emitted so far. Indexers that see a gap in
+        /// `event_seq` have missed events.
+        #[ink(message)]
+        pub fn event_seq(&self) -> u64 {
+            self.event_seq.get_or_default()
+        }
+
+        /// Returns the sequence number for an event about to be emitted.
+        fn next_event_seq(&mut self) -> u64 {
+            let seq = self.event_seq();
+            self.event_seq.set(&(seq + 1));
+            seq
+        }
+
+        /// Replaces the code of the contract while keeping its storage. Call
+        /// `migrate` afterwards if the new code expects a newer storage version.
+        ///
+        /// Only an executed governance proposal may call this, see
+        /// `propose_upgrade`. Without the `governance` feature the owner
+        /// calls it directly.
+        #[ink(message)]
+        pub fn set_code(&mut self, code_hash: [u8; 32]) -> Result<()> {
+            #[cfg(feature = "governance")]
+            if self.env().caller() != self.env().account_id() {
+                return Err(Error::UpgradeNotApproved)
+            }
+            #[cfg(not(feature = "governance"))]
+            self.ensure_owner()?;
+            let old = self.code_hash();
+            replace_code(&code_hash)?;
+            #[cfg(feature = "governance")]
+            self.pending_upgrade.set(&None);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(CodeUpdated {
+                old,
+                new: Hash::from(code_hash),
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Removes the contract, sending its native balance and storage
+        /// deposit to `beneficiary`, once the total supply is zero.
+        ///
+        /// Only the owner or a passed governance proposal, which calls in as
+        /// the contract itself, may call this.
+        #[ink(message)]
+        pub fn terminate(&mut self, beneficiary: AccountId) -> Result<()> {
+            if self.env().caller() != self.env().account_id() {
+                self.ensure_owner()?;
+            }
+            if self.total_supply() != 0 {
+                return Err(Error::SupplyNotZero)
+            }
+            self.env().terminate_contract(beneficiary)
+        }
+
+        /// Returns the hash of the code the contract currently runs.
+        #[ink(message)]
+        pub fn code_hash(&self) -> Hash {
+            own_code_hash()
+        }
+
+        /// Upgrades storage written by older code to `STORAGE_VERSION`, once.
+        ///
+        /// Version 1 is the first layout this code reads, so there is nothing
+        /// to convert yet. Storage from before versioning, which kept
+        /// allowances without a deadline and the total supply in the storage
+        /// root, cannot be upgraded.
+        ///
+        /// Only the owner may call this.
+        #[ink(message)]
+        pub fn migrate(&mut self) -> Result<()> {
+            self.ensure_owner()?;
+            let from_version = self.storage_version();
+            if from_version >= STORAGE_VERSION {
+                return Err(Error::AlreadyMigrated)
+            }
+            if from_version == 0 {
+                return Err(Error::UnsupportedStorageVersion)
+            }
+            // Steps from each older version go here once there are any.
+            self.storage_version.set(&STORAGE_VERSION);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(StorageMigrated {
+                from_version,
+                to_version: STORAGE_VERSION,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Returns the part of `owner`'s balance that cannot be transferred:
+        /// all of it while frozen, otherwise the largest of its token locks,
+        /// vote-escrow lock, partitioned tokens and tokens pledged to running
+        /// votes.
+        #[cfg(feature = "locks")]
+        #[ink(message)]
+        pub fn locked_balance_of(&self, owner: AccountId) -> Balance {
+            self.gons_rate().to_tokens(self.held_gons(&owner))
+        }
+
+        /// Returns the part of `owner`'s balance a transfer can move, which is
+        /// the balance minus `locked_balance_of`.
+        #[cfg(feature = "locks")]
+        #[ink(message)]
+        pub fn spendable_balance_of(&self, owner: AccountId) -> Balance {
+            self.gons_rate()
+                .to_tokens(self.gons_of(&owner) - self.held_gons(&owner))
+        }
+
+        /// Returns the number of locks `owner` has created, which is also the
+        /// identifier of their next lock.
+        #[cfg(feature = "locks")]
+        #[ink(message)]
+        pub fn lock_count(&self, owner: AccountId) -> LockId {
+            self.lock_counts.get(owner).unwrap_or_default()
+        }
+
+        /// Returns the amount and unlock time of one of `owner`'s locks.
+        #[cfg(feature = "locks")]
+        #[ink(message)]
+        pub fn token_lock(&self, owner: AccountId, lock_id: LockId) -> Option<(Balance, Timestamp)> {
+            self.token_locks
+                .get((owner, lock_id))
+                .map(|lock| (self.gons_rate().to_tokens(lock.gons), lock.unlock_at))
+        }
+
+        /// Locks `value` of the caller's tokens until `unlock_at` and returns
+        /// the lock identifier. Locked tokens stay in the balance but cannot be
+        /// transferred.
+        #[cfg(feature = "locks")]
+        #[ink(message)]
+        pub fn lock(&mut self, value: Balance, unlock_at: Timestamp) -> Result<LockId> {
+            let owner = self.env().caller();
+            let gons = self.gons_rate().to_gons(value);
+            let locked = self.locked.get(owner).unwrap_or_default() + gons;
+            if self.balance_of_impl(&owner) < value || self.gons_of(&owner) < locked {
+                return Err(Error::InsufficientBalance)
+            }
+            let lock_id = self.lock_count(owner);
+            self.token_locks
+                .insert((owner, lock_id), &TokenLock { gons, unlock_at });
+            self.lock_counts.insert(owner, &(lock_id + 1));
+            self.locked.insert(owner, &locked);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(TokensLocked {
+                account: owner,
+                lock_id,
+                value,
+                unlock_at,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(lock_id)
+        }
+
+        /// Releases one of the caller's locks once its unlock time has passed.
+        #[cfg(feature = "locks")]
+        #[ink(message)]
+        pub fn unlock(&mut self, lock_id: LockId) -> Result<Balance> {
+            let owner = self.env().caller();
+            let lock = self
+                .token_locks
+                .get((owner, lock_id))
+                .ok_or(Error::LockNotFound)?;
+            if self.env().block_timestamp() < lock.unlock_at {
+                return Err(Error::LockNotExpired)
+            }
+            let locked = self
+                .locked
+                .get(owner)
+                .unwrap_or_default()
+                .checked_sub(lock.gons)
+                .ok_or(Error::LockedTotalMismatch)?;
+            self.token_locks.remove((owner, lock_id));
+            if locked == 0 {
+                self.locked.remove(owner);
+            } else {
+                self.locked.insert(owner, &locked);
+            }
+            let value = self.gons_rate().to_tokens(lock.gons);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(TokensUnlocked {
+                account: owner,
+                lock_id,
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(value)
+        }
+
+        /// Returns the tokens `account` has locked into the vote escrow and
+        /// when they unlock, unless the lock has expired.
+        #[cfg(feature = "vote-escrow")]
+        #[ink(message)]
+        pub fn ve_lock(&self, account: AccountId) -> Option<(Balance, Timestamp)> {
+            self.active_ve_lock(&account)
+                .map(|lock| (self.gons_rate().to_tokens(lock.gons), lock.end))
+        }
+
+        /// Locks `value` of the caller's tokens into the vote escrow until
+        /// `unlock_time`. They stay in the caller's balance but cannot be
+        /// transferred until then, and give voting power that decays with the
+        /// time left on the lock, see `voting_power`.
+        #[cfg(feature = "vote-escrow")]
+        #[ink(message)]
+        pub fn create_lock(&mut self, value: Balance, unlock_time: Timestamp) -> Result<()> {
+            let account = self.env().caller();
+            if self.active_ve_lock(&account).is_some() {
+                return Err(Error::VeLockExists)
+            }
+            self.ensure_unlock_time(unlock_time, 0)?;
+            let gons = self.gons_rate().to_gons(value);
+            self.update_ve_lock(&account, VeLock { gons, end: unlock_time })
+        }
+
+        /// Adds `value` of the caller's tokens to their vote-escrow lock.
+        #[cfg(feature = "vote-escrow")]
+        #[ink(message)]
+        pub fn increase_lock_amount(&mut self, value: Balance) -> Result<()> {
+            let account = self.env().caller();
+            let mut lock = self.active_ve_lock(&account).ok_or(Error::VeLockNotFound)?;
+            lock.gons = lock
+                .gons
+                .checked_add(self.gons_rate().to_gons(value))
+                .ok_or(Error::InsufficientBalance)?;
+            self.update_ve_lock(&account, lock)
+        }
+
+        /// Moves the end of the caller's vote-escrow lock out to `unlock_time`.
+        #[cfg(feature = "vote-escrow")]
+        #[ink(message)]
+        pub fn extend_lock(&mut self, unlock_time: Timestamp) -> Result<()> {
+            let account = self.env().caller();
+            let mut lock = self.active_ve_lock(&account).ok_or(Error::VeLockNotFound)?;
+            self.ensure_unlock_time(unlock_time, lock.end + 1)?;
+            lock.end = unlock_time;
+            self.update_ve_lock(&account, lock)
+        }
+
+        /// Returns the voting power `account` had or will have at `at` from
+        /// its vote-escrow lock: the locked tokens scaled by the time left on
+        /// the lock over `MAX_VE_LOCK_TIME`.
+        #[cfg(feature = "vote-escrow")]
+        #[ink(message)]
+        pub fn voting_power(&self, account: AccountId, at: Timestamp) -> Balance {
+            let count = self.ve_checkpoint_counts.get(account).unwrap_or_default();
+            // Binary search for the last checkpoint at or before `at`.
+            let (mut low, mut high) = (0, count);
+            while low < high {
+                let middle = low + (high - low) / 2;
+                let (since, _) = self
+                    .ve_checkpoints
+                    .get((account, middle))
+                    .expect("checkpoint list is contiguous");
+                if since <= at {
+                    low = middle + 1;
+                } else {
+                    high = middle;
+                }
+            }
+            if low == 0 {
+                return 0
+            }
+            let (_, lock) = self
+                .ve_checkpoints
+                .get((account, low - 1))
+                .expect("checkpoint list is contiguous");
+            self.gons_rate().to_tokens(lock.power_at(at))
+        }
+
+        #[cfg(feature = "vote-escrow")]
+        fn active_ve_lock(&self, account: &AccountId) -> Option<VeLock> {
+            self.ve_locks
+                .get(account)
+                .filter(|lock| self.env().block_timestamp() < lock.end)
+        }
+
+        /// Fails unless `unlock_time` is at least `earliest`, in the future
+        /// and at most `MAX_VE_LOCK_TIME` ahead.
+        #[cfg(feature = "vote-escrow")]
+        fn ensure_unlock_time(&self, unlock_time: Timestamp, earliest: Timestamp) -> Result<()> {
+            let now = self.env().block_timestamp();
+            if unlock_time <= now
+                || unlock_time < earliest
+                || unlock_time > now.saturating_add(MAX_VE_LOCK_TIME)
+            {
+                return Err(Error::InvalidUnlockTime)
+            }
+            Ok(())
+        }
+
+        /// Sets the caller's vote-escrow lock after checking their balance
+        /// covers it.
+        #[cfg(feature = "vote-escrow")]
+        fn update_ve_lock(&mut self, account: &AccountId, lock: VeLock) -> Result<()> {
+            if lock.gons > self.gons_of(account) {
+                return Err(Error::InsufficientBalance)
+            }
+            let (value, unlock_time) = (self.gons_rate().to_tokens(lock.gons), lock.end);
+            self.set_ve_lock(account, lock);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(VeLockUpdated {
+                account: *account,
+                value,
+                unlock_time,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Stores `lock` and checkpoints it from now on.
+        #[cfg(feature = "vote-escrow")]
+        fn set_ve_lock(&mut self, account: &AccountId, lock: VeLock) {
+            let now = self.env().block_timestamp();
+            let count = self.ve_checkpoint_counts.get(account).unwrap_or_default();
+            // Changes within the same block replace the block's checkpoint.
+            let last = count
+                .checked_sub(1)
+                .and_then(|last| self.ve_checkpoints.get((account, last)));
+            let index = match last {
+                Some((since, _)) if since == now => count - 1,
+                _ => {
+                    self.ve_checkpoint_counts.insert(account, &(count + 1));
+                    count
+                }
+            };
+            self.ve_checkpoints.insert((account, index), &(now, lock.clone()));
+            self.ve_locks.insert(account, &lock);
+        }
+
+        /// Returns an escrow, including resolved ones.
+        #[cfg(feature = "escrow")]
+        #[ink(message)]
+        pub fn escrow(&self, escrow_id: EscrowId) -> Option<Escrow> {
+            self.escrows.get(escrow_id)
+        }
+
+        /// Returns the amount held by an escrow.
+        #[cfg(feature = "escrow")]
+        #[ink(message)]
+        pub fn escrow_value(&self, escrow_id: EscrowId) -> Balance {
+            self.escrows
+                .get(escrow_id)
+                .map(|escrow| self.gons_rate().to_tokens(escrow.gons))
+                .unwrap_or_default()
+        }
+
+        /// Moves `value` of the caller's tokens into an escrow that `arbiter`
+        /// resolves, and returns its identifier.
+        ///
+        /// From `expires_at` on, the caller may also refund it themselves.
+        #[cfg(feature = "escrow")]
+        #[ink(message)]
+        pub fn create_escrow(
+            &mut self,
+            beneficiary: AccountId,
+            arbiter: AccountId,
+            value: Balance,
+            expires_at: Timestamp,
+        ) -> Result<EscrowId> {
+            let depositor = self.env().caller();
+            let gons = self.move_into_contract(&depositor, value)?;
+            let escrow_id = self.next_escrow_id.get_or_default();
+            self.next_escrow_id.set(&(escrow_id + 1));
+            self.escrows.insert(
+                escrow_id,
+                &Escrow {
+                    depositor,
+                    beneficiary,
+                    arbiter,
+                    gons,
+                    expires_at,
+                    released: None,
+                },
+            );
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(EscrowCreated {
+                escrow_id,
+                depositor,
+                beneficiary,
+                arbiter,
+                value,
+                expires_at,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(escrow_id)
+        }
+
+        /// Pays an escrow out to its beneficiary.
+        ///
+        /// Only the arbiter may call this.
+        #[cfg(feature = "escrow")]
+        #[ink(message)]
+        pub fn release(&mut self, escrow_id: EscrowId) -> Result<Balance> {
+            let escrow = self.escrows.get(escrow_id).ok_or(Error::EscrowNotFound)?;
+            if self.env().caller() != escrow.arbiter {
+                return Err(Error::NotArbiter)
+            }
+            self.resolve_escrow(escrow_id, escrow, true)
+        }
+
+        /// Returns an escrow to its depositor.
+        ///
+        /// The arbiter may call this at any time, the depositor once the
+        /// escrow has expired.
+        #[cfg(feature = "escrow")]
+        #[ink(message)]
+        pub fn refund_escrow(&mut self, escrow_id: EscrowId) -> Result<Balance> {
+            let escrow = self.escrows.get(escrow_id).ok_or(Error::EscrowNotFound)?;
+            let caller = self.env().caller();
+            let expired = self.env().block_timestamp() >= escrow.expires_at;
+            if caller != escrow.arbiter && !(caller == escrow.depositor && expired) {
+                return Err(Error::NotArbiter)
+            }
+            self.resolve_escrow(escrow_id, escrow, false)
+        }
+
+        #[cfg(feature = "escrow")]
+        fn resolve_escrow(
+            &mut self,
+            escrow_id: EscrowId,
+            mut escrow: Escrow,
+            release: bool,
+        ) -> Result<Balance> {
+            if escrow.released.is_some() {
+                return Err(Error::EscrowResolved)
+            }
+            let to = if release {
+                escrow.beneficiary
+            } else {
+                escrow.depositor
+            };
+            self.ensure_unrestricted(&escrow.depositor)?;
+            self.ensure_unrestricted(&to)?;
+            escrow.released = Some(release);
+            self.escrows.insert(escrow_id, &escrow);
+            let value = self.gons_rate().to_tokens(escrow.gons);
+            self.move_gons(&self.env().account_id(), &to, escrow.gons);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(EscrowResolved {
+                escrow_id,
+                to,
+                value,
+                released: release,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(value)
+        }
+
+        /// Returns a scheduled transfer that is not executed or cancelled yet.
+        #[cfg(feature = "scheduled")]
+        #[ink(message)]
+        pub fn scheduled_transfer(&self, id: ScheduledId) -> Option<ScheduledTransfer> {
+            self.scheduled.get(id)
+        }
+
+        /// Returns the amounts a scheduled transfer pays to its recipient
+        /// and to its executor.
+        #[cfg(feature = "scheduled")]
+        #[ink(message)]
+        pub fn scheduled_value(&self, id: ScheduledId) -> (Balance, Balance) {
+            self.scheduled
+                .get(id)
+                .map(|scheduled| {
+                    let rate = self.gons_rate();
+                    (rate.to_tokens(scheduled.gons), rate.to_tokens(scheduled.tip_gons))
+                })
+                .unwrap_or_default()
+        }
+
+        /// Moves `value` of the caller's tokens, plus `tip`, into the
+        /// contract until anyone executes the transfer to `to` from
+        /// `execute_after` on, and returns its identifier. The executor gets
+        /// the tip.
+        #[cfg(feature = "scheduled")]
+        #[ink(message)]
+        pub fn schedule_transfer(
+            &mut self,
+            to: AccountId,
+            value: Balance,
+            execute_after: Timestamp,
+            tip: Balance,
+        ) -> Result<ScheduledId> {
+            let sender = self.env().caller();
+            let total = value.checked_add(tip).ok_or(Error::InsufficientBalance)?;
+            let gons = self.move_into_contract(&sender, total)?;
+            let tip_gons = self.gons_rate().to_gons(tip).min(gons);
+            let id = self.next_scheduled_id.get_or_default();
+            self.next_scheduled_id.set(&(id + 1));
+            self.scheduled.insert(
+                id,
+                &ScheduledTransfer {
+                    sender,
+                    to,
+                    gons: gons - tip_gons,
+                    tip_gons,
+                    execute_after,
+                },
+            );
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(TransferScheduled {
+                id,
+                sender,
+                to,
+                value,
+                tip,
+                execute_after,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(id)
+        }
+
+        /// Pays a scheduled transfer out to its recipient once it is due,
+        /// and its tip to the caller, and returns the amount transferred.
+        /// Anyone may call this.
+        #[cfg(feature = "scheduled")]
+        #[ink(message)]
+        pub fn execute_scheduled(&mut self, id: ScheduledId) -> Result<Balance> {
+            let scheduled = self
+                .scheduled
+                .get(id)
+                .ok_or(Error::ScheduledTransferNotFound)?;
+            if self.env().block_timestamp() < scheduled.execute_after {
+                return Err(Error::ScheduledTransferNotDue)
+            }
+            self.ensure_unrestricted(&scheduled.sender)?;
+            self.ensure_unrestricted(&scheduled.to)?;
+            self.scheduled.remove(id);
+            let contract = self.env().account_id();
+            let executor = self.env().caller();
+            let rate = self.gons_rate();
+            let (value, tip) = (rate.to_tokens(scheduled.gons), rate.to_tokens(scheduled.tip_gons));
+            self.move_gons(&contract, &scheduled.to, scheduled.gons);
+            if scheduled.tip_gons > 0 {
+                self.move_gons(&contract, &executor, scheduled.tip_gons);
+            }
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(ScheduledTransferExecuted {
+                id,
+                executor,
+                value,
+                tip,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(value)
+        }
+
+        /// Returns a scheduled transfer that was not executed yet, tip
+        /// included, to its sender, and returns the amount.
+        ///
+        /// Only the sender may call this.
+        #[cfg(feature = "scheduled")]
+        #[ink(message)]
+        pub fn cancel_scheduled(&mut self, id: ScheduledId) -> Result<Balance> {
+            let scheduled = self
+                .scheduled
+                .get(id)
+                .ok_or(Error::ScheduledTransferNotFound)?;
+            if self.env().caller() != scheduled.sender {
+                return Err(Error::NotScheduledSender)
+            }
+            self.scheduled.remove(id);
+            let gons = scheduled.gons + scheduled.tip_gons;
+            let value = self.gons_rate().to_tokens(gons);
+            self.move_gons(&self.env().account_id(), &scheduled.sender, gons);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(ScheduledTransferCancelled {
+                id,
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(value)
+        }
+
+        /// Returns a hashlocked transfer that is not claimed or refunded yet.
+        #[cfg(feature = "htlc")]
+        #[ink(message)]
+        pub fn htlc(&self, id: HtlcId) -> Option<Htlc> {
+            self.htlcs.get(id)
+        }
+
+        /// Returns the amount held by a hashlocked transfer.
+        #[cfg(feature = "htlc")]
+        #[ink(message)]
+        pub fn htlc_value(&self, id: HtlcId) -> Balance {
+            self.htlcs
+                .get(id)
+                .map(|htlc| self.gons_rate().to_tokens(htlc.gons))
+                .unwrap_or_default()
+        }
+
+        /// Moves `value` of the caller's tokens into the contract until the
+        /// preimage of `hashlock` releases them to `recipient`, and returns
+        /// the identifier of the hashlocked transfer. From `timelock` on,
+        /// the tokens can only be refunded to the caller.
+        ///
+        /// The hashlock is the SHA-256 hash of the preimage, so it can lock
+        /// the other side of a swap on chains using the same construction.
+        #[cfg(feature = "htlc")]
+        #[ink(message)]
+        pub fn create_htlc(
+            &mut self,
+            recipient: AccountId,
+            value: Balance,
+            hashlock: [u8; 32],
+            timelock: Timestamp,
+        ) -> Result<HtlcId> {
+            if timelock <= self.env().block_timestamp() {
+                return Err(Error::HtlcExpired)
+            }
+            let sender = self.env().caller();
+            let gons = self.move_into_contract(&sender, value)?;
+            let id = self.next_htlc_id.get_or_default();
+            self.next_htlc_id.set(&(id + 1));
+            self.htlcs.insert(
+                id,
+                &Htlc {
+                    sender,
+                    recipient,
+                    gons,
+                    hashlock,
+                    timelock,
+                },
+            );
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(HtlcCreated {
+                id,
+                sender,
+                recipient,
+                value,
+                hashlock,
+                timelock,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(id)
+        }
+
+        /// Pays a hashlocked transfer out to its recipient given the
+        /// preimage of its hashlock, before its timelock, and returns the
+        /// amount. Anyone may call this.
+        #[cfg(feature = "htlc")]
+        #[ink(message)]
+        pub fn claim_htlc(&mut self, id: HtlcId, preimage: Vec<u8>) -> Result<Balance> {
+            let htlc = self.htlcs.get(id).ok_or(Error::HtlcNotFound)?;
+            if self.env().block_timestamp() >= htlc.timelock {
+                return Err(Error::HtlcExpired)
+            }
+            if self.env().hash_bytes::<Sha2x256>(&preimage) != htlc.hashlock {
+                return Err(Error::InvalidPreimage)
+            }
+            self.ensure_unrestricted(&htlc.sender)?;
+            self.ensure_unrestricted(&htlc.recipient)?;
+            self.htlcs.remove(id);
+            let value = self.gons_rate().to_tokens(htlc.gons);
+            self.move_gons(&self.env().account_id(), &htlc.recipient, htlc.gons);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(HtlcClaimed {
+                id,
+                value,
+                preimage,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(value)
+        }
+
+        /// Returns a hashlocked transfer that was not claimed before its
+        /// timelock to its sender, and returns the amount. Anyone may call
+        /// this.
+        #[cfg(feature = "htlc")]
+        #[ink(message)]
+        pub fn refund_htlc(&mut self, id: HtlcId) -> Result<Balance> {
+            let htlc = self.htlcs.get(id).ok_or(Error::HtlcNotFound)?;
+            if self.env().block_timestamp() < htlc.timelock {
+                return Err(Error::HtlcNotExpired)
+            }
+            self.htlcs.remove(id);
+            let value = self.gons_rate().to_tokens(htlc.gons);
+            self.move_gons(&self.env().account_id(), &htlc.sender, htlc.gons);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(HtlcRefunded {
+                id,
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(value)
+        }
+
+        /// Returns a voucher that is not redeemed or reclaimed yet.
+        #[cfg(feature = "vouchers")]
+        #[ink(message)]
+        pub fn voucher(&self, id: VoucherId) -> Option<Voucher> {
+            self.vouchers.get(id)
+        }
+
+        /// Returns the amount held by a voucher.
+        #[cfg(feature = "vouchers")]
+        #[ink(message)]
+        pub fn voucher_value(&self, id: VoucherId) -> Balance {
+            self.vouchers
+                .get(id)
+                .map(|voucher| self.gons_rate().to_tokens(voucher.gons))
+                .unwrap_or_default()
+        }
+
+        /// Returns the hash the signer of a voucher signs to redeem voucher
+        /// `id` to `to`. It commits to the recipient, so a
+        /// signature cannot be redirected.
+        #[cfg(feature = "vouchers")]
+        #[ink(message)]
+        pub fn voucher_signing_hash(&self, id: VoucherId, to: AccountId) -> Hash {
+            Hash::from(self.voucher_digest(id, to))
+        }
+
+        #[cfg(feature = "vouchers")]
+        fn voucher_digest(&self, id: VoucherId, to: AccountId) -> [u8; 32] {
+            let contract = self.env().account_id();
+            self.env().hash_encoded::<Blake2x256, _>(&(contract, id, to))
+        }
+
+        /// Moves `value` of the caller's tokens into a voucher that whoever
+        /// holds the key of `signer` may redeem to any account before
+        /// `expires_at`, and returns its identifier. The recipient needs no
+        /// account yet when the voucher is created, so it can be handed out
+        /// as a link: the sender derives the key from a random code, takes
+        /// its Blake2 hash as the secret, and shares the code. Redeeming
+        /// only reveals a signature for one recipient, never the code.
+        #[cfg(feature = "vouchers")]
+        #[ink(message)]
+        pub fn create_voucher(
+            &mut self,
+            value: Balance,
+            signer: AccountId,
+            expires_at: Timestamp,
+        ) -> Result<VoucherId> {
+            if expires_at <= self.env().block_timestamp() {
+                return Err(Error::VoucherExpired)
+            }
+            let sender = self.env().caller();
+            let gons = self.move_into_contract(&sender, value)?;
+            let id = self.next_voucher_id.get_or_default();
+            self.next_voucher_id.set(&(id + 1));
+            self.vouchers.insert(
+                id,
+                &Voucher {
+                    sender,
+                    signer,
+                    gons,
+                    expires_at,
+                },
+            );
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(VoucherCreated {
+                id,
+                sender,
+                value,
+                expires_at,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(id)
+        }
+
+        /// Pays a voucher out to `to` given a `signature` of
+        /// `voucher_signing_hash(id, to)` by its signer, before it expires,
+        /// and returns the amount. Anyone may call this.
+        #[cfg(feature = "vouchers")]
+        #[ink(message)]
+        pub fn redeem_voucher(
+            &mut self,
+            id: VoucherId,
+            signature: [u8; 65],
+            to: AccountId,
+        ) -> Result<Balance> {
+            let voucher = self.vouchers.get(id).ok_or(Error::VoucherNotFound)?;
+            if self.env().block_timestamp() >= voucher.expires_at {
+                return Err(Error::VoucherExpired)
+            }
+            let digest = self.voucher_digest(id, to);
+            let mut public_key = [0; 33];
+            if ink_env::ecdsa_recover(&signature, &digest, &mut public_key).is_err()
+                || AccountId::from(self.env().hash_bytes::<Blake2x256>(&public_key))
+                    != voucher.signer
+            {
+                return Err(Error::InvalidVoucherProof)
+            }
+            self.ensure_unrestricted(&voucher.sender)?;
+            self.ensure_unrestricted(&to)?;
+            self.vouchers.remove(id);
+            let value = self.gons_rate().to_tokens(voucher.gons);
+            self.move_gons(&self.env().account_id(), &to, voucher.gons);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(VoucherRedeemed {
+                id,
+                to,
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(value)
+        }
+
+        /// Returns an expired voucher to its sender, and returns the amount.
+        ///
+        /// Only the sender may call this.
+        #[cfg(feature = "vouchers")]
+        #[ink(message)]
+        pub fn reclaim_voucher(&mut self, id: VoucherId) -> Result<Balance> {
+            let voucher = self.vouchers.get(id).ok_or(Error::VoucherNotFound)?;
+            if self.env().caller() != voucher.sender {
+                return Err(Error::NotVoucherSender)
+            }
+            if self.env().block_timestamp() < voucher.expires_at {
+                return Err(Error::VoucherNotExpired)
+            }
+            self.vouchers.remove(id);
+            let value = self.gons_rate().to_tokens(voucher.gons);
+            self.move_gons(&self.env().account_id(), &voucher.sender, voucher.gons);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(VoucherReclaimed {
+                id,
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(value)
+        }
+
+        /// Returns the account whose incoming tokens are split between the
+        /// payees. Nobody holds its key; tokens only leave it through
+        /// `release_payment`.
+        #[cfg(feature = "splitter")]
+        #[ink(message)]
+        pub fn splitter_account(&self) -> AccountId {
+            let contract = self.env().account_id();
+            AccountId::from(self.env().hash_encoded::<Blake2x256, _>(&(contract, SPLITTER_SALT)))
+        }
+
+        /// Returns the splitter shares of `payee`.
+        #[cfg(feature = "splitter")]
+        #[ink(message)]
+        pub fn shares_of(&self, payee: AccountId) -> u32 {
+            self.payee_shares.get(payee).unwrap_or_default()
+        }
+
+        /// Returns the sum of all payee shares.
+        #[cfg(feature = "splitter")]
+        #[ink(message)]
+        pub fn total_shares(&self) -> u32 {
+            self.total_shares.get_or_default()
+        }
+
+        /// Returns the tokens paid out of the splitter to `payee` so far.
+        #[cfg(feature = "splitter")]
+        #[ink(message)]
+        pub fn released_to(&self, payee: AccountId) -> Balance {
+            self.gons_rate()
+                .to_tokens(self.released_gons.get(payee).unwrap_or_default())
+        }
+
+        /// Returns the tokens `payee` can currently release from the splitter.
+        #[cfg(feature = "splitter")]
+        #[ink(message)]
+        pub fn releasable(&self, payee: AccountId) -> Balance {
+            self.gons_rate().to_tokens(self.releasable_gons(&payee))
+        }
+
+        /// Registers `payee` with `shares` of everything the splitter receives,
+        /// including tokens received before they were added. Payees can only
+        /// be added before the first payment is released, since the shares
+        /// already paid out could not be taken back.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "splitter")]
+        #[ink(message)]
+        pub fn add_payee(&mut self, payee: AccountId, shares: u32) -> Result<()> {
+            self.ensure_owner()?;
+            if shares == 0 || self.payee_shares.contains(payee) {
+                return Err(Error::InvalidPayee)
+            }
+            if self.total_released_gons.get_or_default() > 0 {
+                return Err(Error::PayoutsStarted)
+            }
+            let total_shares = self
+                .total_shares()
+                .checked_add(shares)
+                .ok_or(Error::InvalidPayee)?;
+            self.payee_shares.insert(payee, &shares);
+            self.total_shares.set(&total_shares);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(PayeeAdded {
+                payee,
+                shares,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Pays `payee` their share of the splitter's receipts that has not
+        /// been paid out yet. Anyone may call this.
+        #[cfg(feature = "splitter")]
+        #[ink(message)]
+        pub fn release_payment(&mut self, payee: AccountId) -> Result<Balance> {
+            let gons = self.releasable_gons(&payee);
+            if gons == 0 {
+                return Err(Error::NothingToClaim)
+            }
+            let released = self.released_gons.get(payee).unwrap_or_default() + gons;
+            self.released_gons.insert(payee, &released);
+            let total_released_gons = self.total_released_gons.get_or_default() + gons;
+            self.total_released_gons.set(&total_released_gons);
+            self.move_gons(&self.splitter_account(), &payee, gons);
+            let value = self.gons_rate().to_tokens(gons);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(PaymentReleased {
+                payee,
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(value)
+        }
+
+        #[cfg(feature = "splitter")]
+        fn releasable_gons(&self, payee: &AccountId) -> Balance {
+            let shares = self.shares_of(*payee);
+            if shares == 0 {
+                return 0
+            }
+            let received = self.gons_of(&self.splitter_account())
+                + self.total_released_gons.get_or_default();
+            let due = mul_div(received, shares.into(), self.total_shares().into())
+                .expect("shares never exceed total shares");
+            due.saturating_sub(self.released_gons.get(payee).unwrap_or_default())
+        }
+
+        /// Returns a running stream.
+        #[cfg(feature = "streams")]
+        #[ink(message)]
+        pub fn stream(&self, stream_id: StreamId) -> Option<Stream> {
+            self.streams.get(stream_id)
+        }
+
+        /// Returns the tokens of a stream that have vested but not been
+        /// withdrawn, and those still left to stream.
+        #[cfg(feature = "streams")]
+        #[ink(message)]
+        pub fn stream_balances(&self, stream_id: StreamId) -> (Balance, Balance) {
+            let rate = self.gons_rate();
+            self.streams
+                .get(stream_id)
+                .map(|stream| {
+                    let vested = self.vested_gons(&stream);
+                    (
+                        rate.to_tokens(vested - stream.withdrawn_gons),
+                        rate.to_tokens(stream.deposit_gons - vested),
+                    )
+                })
+                .unwrap_or_default()
+        }
+
+        /// Moves `deposit` of the caller's tokens into a stream that pays them
+        /// out to `recipient` evenly between `start` and `stop`, and returns
+        /// its identifier.
+        #[cfg(feature = "streams")]
+        #[ink(message)]
+        pub fn create_stream(
+            &mut self,
+            recipient: AccountId,
+            deposit: Balance,
+            start: Timestamp,
+            stop: Timestamp,
+        ) -> Result<StreamId> {
+            if deposit == 0 || stop <= start {
+                return Err(Error::InvalidStream)
+            }
+            let sender = self.env().caller();
+            let deposit_gons = self.move_into_contract(&sender, deposit)?;
+            let stream_id = self.next_stream_id.get_or_default();
+            self.next_stream_id.set(&(stream_id + 1));
+            self.streams.insert(
+                stream_id,
+                &Stream {
+                    sender,
+                    recipient,
+                    deposit_gons,
+                    withdrawn_gons: 0,
+                    start,
+                    stop,
+                },
+            );
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(StreamCreated {
+                stream_id,
+                sender,
+                recipient,
+                deposit,
+                start,
+                stop,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(stream_id)
+        }
+
+        /// Withdraws `value` of the vested tokens of a stream.
+        ///
+        /// Only the recipient may call this.
+        #[cfg(feature = "streams")]
+        #[ink(message)]
+        pub fn withdraw_from_stream(&mut self, stream_id: StreamId, value: Balance) -> Result<()> {
+            let mut stream = self.streams.get(stream_id).ok_or(Error::StreamNotFound)?;
+            if self.env().caller() != stream.recipient {
+                return Err(Error::NotStreamParty)
+            }
+            let rate = self.gons_rate();
+            let available = self.vested_gons(&stream) - stream.withdrawn_gons;
+            if rate.to_tokens(available) < value {
+                return Err(Error::InsufficientBalance)
+            }
+            self.ensure_unrestricted(&stream.sender)?;
+            self.ensure_unrestricted(&stream.recipient)?;
+            let gons = rate.to_gons(value).min(available);
+            stream.withdrawn_gons += gons;
+            if stream.withdrawn_gons == stream.deposit_gons {
+                self.streams.remove(stream_id);
+            } else {
+                self.streams.insert(stream_id, &stream);
+            }
+            self.move_gons(&self.env().account_id(), &stream.recipient, gons);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(StreamWithdrawn {
+                stream_id,
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Ends a stream, paying the recipient what has vested and returning
+        /// the rest to the sender.
+        ///
+        /// Either the sender or the recipient may call this.
+        #[cfg(feature = "streams")]
+        #[ink(message)]
+        pub fn cancel_stream(&mut self, stream_id: StreamId) -> Result<()> {
+            let stream = self.streams.get(stream_id).ok_or(Error::StreamNotFound)?;
+            let caller = self.env().caller();
+            if caller != stream.sender && caller != stream.recipient {
+                return Err(Error::NotStreamParty)
+            }
+            self.streams.remove(stream_id);
+            let vested = self.vested_gons(&stream);
+            let recipient_gons = vested - stream.withdrawn_gons;
+            let sender_gons = stream.deposit_gons - vested;
+            let contract = self.env().account_id();
+            if recipient_gons > 0 {
+                self.move_gons(&contract, &stream.recipient, recipient_gons);
+            }
+            if sender_gons > 0 {
+                self.move_gons(&contract, &stream.sender, sender_gons);
+            }
+            let rate = self.gons_rate();
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(StreamCanceled {
+                stream_id,
+                recipient_value: rate.to_tokens(recipient_gons),
+                sender_value: rate.to_tokens(sender_gons),
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Returns an allocation bucket.
+        #[cfg(feature = "emissions")]
+        #[ink(message)]
+        pub fn bucket_info(&self, name: String) -> Option<Bucket> {
+            self.buckets.get(name)
+        }
+
+        /// Returns the tokens of a bucket that have vested but not been
+        /// released, and those still to vest.
+        #[cfg(feature = "emissions")]
+        #[ink(message)]
+        pub fn bucket_balances(&self, name: String) -> (Balance, Balance) {
+            let rate = self.gons_rate();
+            self.buckets
+                .get(name)
+                .map(|bucket| {
+                    let vested = bucket.vested_gons(self.env().block_timestamp());
+                    (
+                        rate.to_tokens(vested - bucket.released_gons),
+                        rate.to_tokens(bucket.gons - vested),
+                    )
+                })
+                .unwrap_or_default()
+        }
+
+        /// Pays the vested tokens of a bucket out to its beneficiary and
+        /// returns the amount.
+        ///
+        /// Anyone may call this.
+        #[cfg(feature = "emissions")]
+        #[ink(message)]
+        pub fn release_bucket(&mut self, name: String) -> Result<Balance> {
+            let mut bucket = self.buckets.get(&name).ok_or(Error::BucketNotFound)?;
+            let vested = bucket.vested_gons(self.env().block_timestamp());
+            let gons = vested - bucket.released_gons;
+            if gons == 0 {
+                return Err(Error::NothingToClaim)
+            }
+            bucket.released_gons = vested;
+            self.buckets.insert(&name, &bucket);
+            self.move_gons(&self.env().account_id(), &bucket.beneficiary, gons);
+            let value = self.gons_rate().to_tokens(gons);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(BucketReleased {
+                name,
+                beneficiary: bucket.beneficiary,
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(value)
+        }
+
+        /// Returns the gons of a stream vested by now, withdrawn or not.
+        #[cfg(feature = "streams")]
+        fn vested_gons(&self, stream: &Stream) -> Balance {
+            let now = self.env().block_timestamp().clamp(stream.start, stream.stop);
+            mul_div(
+                stream.deposit_gons,
+                (now - stream.start).into(),
+                (stream.stop - stream.start).into(),
+            )
+            .expect("elapsed time never exceeds the stream duration")
+        }
+
+        /// Returns the token sale, if one was started.
+        #[cfg(feature = "sale")]
+        #[ink(message)]
+        pub fn sale(&self) -> Option<Sale> {
+            self.sale.get()
+        }
+
+        /// Returns the native currency `buyer` contributed to the sale.
+        #[cfg(feature = "sale")]
+        #[ink(message)]
+        pub fn sale_contribution_of(&self, buyer: AccountId) -> Balance {
+            self.sale_contributions.get(buyer).unwrap_or_default()
+        }
+
+        /// Starts a token sale, moving enough of the caller's tokens into the
+        /// contract to cover the hard cap at the sale rate.
+        ///
+        /// Only the owner may call this, once.
+        #[cfg(feature = "sale")]
+        #[ink(message)]
+        pub fn start_sale(&mut self, config: SaleConfig) -> Result<()> {
+            self.ensure_owner()?;
+            if self.sale.get().is_some() {
+                return Err(Error::SaleAlreadyStarted)
+            }
+            let seller = self.env().caller();
+            let tokens = config
+                .hard_cap
+                .checked_mul(config.rate)
+                .ok_or(Error::SupplyOverflow)?;
+            let gons = self.move_into_contract(&seller, tokens)?;
+            self.sale.set(&Sale {
+                config,
+                seller,
+                raised: 0,
+                succeeded: None,
+                gons,
+            });
+            Ok(())
+        }
+
+        /// Buys sale tokens with the transferred native currency.
+        ///
+        /// The tokens can be claimed once the sale has been finalized successfully.
+        #[cfg(feature = "sale")]
+        #[ink(message, payable)]
+        pub fn buy(&mut self) -> Result<Balance> {
+            let mut sale = self.sale.get().ok_or(Error::NoSale)?;
+            let now = self.env().block_timestamp();
+            if now < sale.config.start || now >= sale.config.end || sale.succeeded.is_some() {
+                return Err(Error::SaleNotOpen)
+            }
+            let buyer = self.env().caller();
+            let value = self.env().transferred_value();
+            let contribution = self.sale_contribution_of(buyer) + value;
+            if value < sale.config.min_purchase || contribution > sale.config.max_purchase {
+                return Err(Error::PurchaseOutOfBounds)
+            }
+            if sale.raised + value > sale.config.hard_cap {
+                return Err(Error::HardCapExceeded)
+            }
+            sale.raised += value;
+            self.sale.set(&sale);
+            self.sale_contributions.insert(buyer, &contribution);
+            let tokens = value * sale.config.rate;
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(TokensPurchased {
+                buyer,
+                value,
+                tokens,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(tokens)
+        }
+
+        /// Closes the sale once its window ended or the hard cap was reached.
+        ///
+        /// If the soft cap was reached, the raised funds go to the beneficiary
+        /// and unsold tokens back to the seller. Otherwise all tokens go back
+        /// to the seller and buyers can reclaim their funds with `refund`.
+        #[cfg(feature = "sale")]
+        #[ink(message)]
+        pub fn finalize_sale(&mut self) -> Result<()> {
+            let mut sale = self.sale.get().ok_or(Error::NoSale)?;
+            let closed = self.env().block_timestamp() >= sale.config.end
+                || sale.raised == sale.config.hard_cap;
+            if !closed || sale.succeeded.is_some() {
+                return Err(Error::SaleNotClosed)
+            }
+            let succeeded = sale.raised >= sale.config.soft_cap;
+            let sold_gons = if succeeded && sale.raised > 0 {
+                mul_div(sale.gons, sale.raised, sale.config.hard_cap)
+                    .expect("raised never exceeds the hard cap")
+            } else {
+                0
+            };
+            let unsold_gons = sale.gons - sold_gons;
+            sale.succeeded = Some(succeeded);
+            sale.gons = sold_gons;
+            self.sale.set(&sale);
+            if unsold_gons > 0 {
+                self.move_gons(&self.env().account_id(), &sale.seller, unsold_gons);
+            }
+            if succeeded && sale.raised > 0 {
+                self.env()
+                    .transfer(sale.config.beneficiary, sale.raised)
+                    .map_err(|_| Error::NativeTransferFailed)?;
+            }
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(SaleFinalized {
+                raised: sale.raised,
+                succeeded,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Sends the caller the tokens they bought in a successful sale: their
+        /// share of the tokens sold, pro rata to their contribution, so it
+        /// follows any rebase since the sale started.
+        #[cfg(feature = "sale")]
+        #[ink(message)]
+        pub fn claim_sale_tokens(&mut self) -> Result<Balance> {
+            let sale = self.sale.get().ok_or(Error::NoSale)?;
+            let buyer = self.env().caller();
+            let contribution = self.sale_contribution_of(buyer);
+            if sale.succeeded != Some(true) || contribution == 0 {
+                return Err(Error::NothingToClaim)
+            }
+            self.ensure_unrestricted(&buyer)?;
+            self.sale_contributions.remove(buyer);
+            let gons = mul_div(sale.gons, contribution, sale.raised)
+                .expect("contributions never exceed the amount raised");
+            self.move_gons(&self.env().account_id(), &buyer, gons);
+            Ok(self.gons_rate().to_tokens(gons))
+        }
+
+        /// Returns the caller's contribution to a sale that missed its soft cap.
+        #[cfg(feature = "sale")]
+        #[ink(message)]
+        pub fn refund(&mut self) -> Result<Balance> {
+            let sale = self.sale.get().ok_or(Error::NoSale)?;
+            let buyer = self.env().caller();
+            let contribution = self.sale_contribution_of(buyer);
+            if sale.succeeded != Some(false) || contribution == 0 {
+                return Err(Error::NothingToClaim)
+            }
+            self.sale_contributions.remove(buyer);
+            self.env()
+                .transfer(buyer, contribution)
+                .map_err(|_| Error::NativeTransferFailed)?;
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(SaleRefunded {
+                buyer,
+                value: contribution,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(contribution)
+        }
+
+        /// Returns the terms tokens are minted for native currency on, if enabled.
+        #[cfg(feature = "sale")]
+        #[ink(message)]
+        pub fn mint_sale(&self) -> Option<MintSale> {
+            self.mint_sale.get().flatten()
+        }
+
+        /// Sets or disables the terms of `purchase`.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "sale")]
+        #[ink(message)]
+        pub fn set_mint_sale(&mut self, sale: Option<MintSale>) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.mint_sale();
+            self.mint_sale.set(&sale);
+            self.emit_config_changed(Setting::MintSale, None, old, sale);
+            Ok(())
+        }
+
+        /// Returns the tokens `account` has bought through `purchase`.
+        #[cfg(feature = "sale")]
+        #[ink(message)]
+        pub fn mint_sale_purchased(&self, account: AccountId) -> Balance {
+            self.mint_sale_purchases.get(account).unwrap_or_default()
+        }
+
+        /// Returns the tokens bought through `purchase` in total.
+        #[cfg(feature = "sale")]
+        #[ink(message)]
+        pub fn mint_sale_sold(&self) -> Balance {
+            self.mint_sale_sold.get_or_default()
+        }
+
+        /// Returns the native reserve: currency paid for minted tokens or
+        /// deposited with `fund_reserve` that was neither withdrawn nor redeemed.
+        #[cfg(feature = "sale")]
+        #[ink(message)]
+        pub fn mint_proceeds(&self) -> Balance {
+            self.mint_proceeds.get_or_default()
+        }
+
+        /// Adds the transferred native currency to the reserve redemptions are
+        /// paid from.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "sale")]
+        #[ink(message, payable)]
+        pub fn fund_reserve(&mut self) -> Result<()> {
+            self.ensure_owner()?;
+            let proceeds = self.mint_proceeds() + self.env().transferred_value();
+            self.mint_proceeds.set(&proceeds);
+            Ok(())
+        }
+
+        /// Returns the tokens redeemed per unit of native currency, if
+        /// redemptions are enabled.
+        #[cfg(feature = "sale")]
+        #[ink(message)]
+        pub fn redeem_rate(&self) -> Option<Balance> {
+            self.redeem_rate.get().flatten()
+        }
+
+        /// Sets or disables the rate of `redeem`.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "sale")]
+        #[ink(message)]
+        pub fn set_redeem_rate(&mut self, rate: Option<Balance>) -> Result<()> {
+            self.ensure_owner()?;
+            if rate == Some(0) {
+                return Err(Error::RedemptionDisabled)
+            }
+            let old = self.redeem_rate();
+            self.redeem_rate.set(&rate);
+            self.emit_config_changed(Setting::RedeemRate, None, old, rate);
+            Ok(())
+        }
+
+        /// Burns up to `value` of the caller's tokens and pays them out of the
+        /// native reserve at the redeem rate. Tokens worth less than one unit of
+        /// native currency are left with the caller. Returns the native amount
+        /// paid.
+        #[cfg(feature = "sale")]
+        #[ink(message)]
+        pub fn redeem(&mut self, value: Balance) -> Result<Balance> {
+            let rate = self.redeem_rate().ok_or(Error::RedemptionDisabled)?;
+            let account = self.env().caller();
+            let payout = value / rate;
+            if payout == 0 {
+                return Err(Error::NothingToClaim)
+            }
+            let reserve = self.mint_proceeds();
+            if payout > reserve {
+                return Err(Error::InsufficientReserve)
+            }
+            let tokens = payout * rate;
+            self.burn_from(&account, tokens)?;
+            self.mint_proceeds.set(&(reserve - payout));
+            self.env()
+                .transfer(account, payout)
+                .map_err(|_| Error::NativeTransferFailed)?;
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(TokensRedeemed {
+                account,
+                tokens,
+                value: payout,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(payout)
+        }
+
+        /// Mints the transferred native currency times the mint sale rate in
+        /// tokens to the caller and returns the amount minted.
+        #[cfg(feature = "sale")]
+        #[ink(message, payable)]
+        pub fn purchase(&mut self) -> Result<Balance> {
+            self.purchase_impl(None)
+        }
+
+        /// Like `purchase`, and additionally mints `referrer` the referral
+        /// rate of the purchased tokens. Referral rewards do not count
+        /// towards the mint sale caps.
+        #[cfg(feature = "sale")]
+        #[ink(message, payable)]
+        pub fn purchase_with_referral(&mut self, referrer: AccountId) -> Result<Balance> {
+            if referrer == self.env().caller() {
+                return Err(Error::SelfReferral)
+            }
+            self.purchase_impl(Some(referrer))
+        }
+
+        /// Returns the share of purchased tokens minted to the referrer, in
+        /// basis points.
+        #[cfg(feature = "sale")]
+        #[ink(message)]
+        pub fn referral_rate(&self) -> u16 {
+            self.referral_rate.get_or_default()
+        }
+
+        /// Sets the share of purchased tokens minted to the referrer, in basis
+        /// points.
+        ///
+        /// Only the owner may call this, and the rate is capped at `MAX_REFERRAL_RATE`.
+        #[cfg(feature = "sale")]
+        #[ink(message)]
+        pub fn set_referral_rate(&mut self, rate: u16) -> Result<()> {
+            self.ensure_owner()?;
+            if rate > MAX_REFERRAL_RATE {
+                return Err(Error::ReferralRateTooHigh)
+            }
+            let old = self.referral_rate();
+            self.referral_rate.set(&rate);
+            self.emit_config_changed(Setting::ReferralRate, None, old, rate);
+            Ok(())
+        }
+
+        /// Returns the tokens `referrer` has been credited with and the number
+        /// of purchases they referred.
+        #[cfg(feature = "sale")]
+        #[ink(message)]
+        pub fn referral_stats(&self, referrer: AccountId) -> (Balance, u32) {
+            (
+                self.referral_rewards.get(referrer).unwrap_or_default(),
+                self.referral_counts.get(referrer).unwrap_or_default(),
+            )
+        }
+
+        #[cfg(feature = "sale")]
+        fn purchase_impl(&mut self, referrer: Option<AccountId>) -> Result<Balance> {
+            let sale = self.mint_sale().ok_or(Error::NoSale)?;
+            let buyer = self.env().caller();
+            let value = self.env().transferred_value();
+            let tokens = value.checked_mul(sale.rate).ok_or(Error::SupplyOverflow)?;
+            let purchased = self.mint_sale_purchased(buyer) + tokens;
+            if tokens == 0 || sale.max_per_account.is_some_and(|max| purchased > max) {
+                return Err(Error::PurchaseOutOfBounds)
+            }
+            let sold = self.mint_sale_sold() + tokens;
+            if sale.max_total.is_some_and(|max| sold > max) {
+                return Err(Error::HardCapExceeded)
+            }
+            self.mint_to(&buyer, tokens)?;
+            if let Some(referrer) = referrer {
+                self.pay_referral(referrer, buyer, tokens)?;
+            }
+            self.mint_sale_purchases.insert(buyer, &purchased);
+            self.mint_sale_sold.set(&sold);
+            let proceeds = self.mint_proceeds() + value;
+            self.mint_proceeds.set(&proceeds);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(TokensPurchased {
+                buyer,
+                value,
+                tokens,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(tokens)
+        }
+
+        /// Mints `referrer` the referral rate of `tokens` bought by `buyer`.
+        #[cfg(feature = "sale")]
+        fn pay_referral(
+            &mut self,
+            referrer: AccountId,
+            buyer: AccountId,
+            tokens: Balance,
+        ) -> Result<()> {
+            let value = mul_div(tokens, self.referral_rate().into(), BASIS_POINTS.into())
+                .expect("basis point share of the purchase fits");
+            if value == 0 {
+                return Ok(())
+            }
+            self.mint_to(&referrer, value)?;
+            let (rewards, count) = self.referral_stats(referrer);
+            self.referral_rewards.insert(referrer, &(rewards + value));
+            self.referral_counts.insert(referrer, &(count + 1));
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(ReferralPaid {
+                referrer,
+                buyer,
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Sends the native currency paid for minted tokens to the treasury,
+        /// or to the owner if there is none, and returns the amount sent.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "sale")]
+        #[ink(message)]
+        pub fn withdraw_proceeds(&mut self) -> Result<Balance> {
+            self.ensure_owner()?;
+            let value = self.mint_proceeds();
+            if value == 0 {
+                return Err(Error::NothingToClaim)
+            }
+            #[cfg(feature = "fees")]
+            let to = self.treasury().unwrap_or_else(|| self.env().caller());
+            #[cfg(not(feature = "fees"))]
+            let to = self.env().caller();
+            self.mint_proceeds.set(&0);
+            self.env()
+                .transfer(to, value)
+                .map_err(|_| Error::NativeTransferFailed)?;
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(ProceedsWithdrawn {
+                to,
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(value)
+        }
+
+        /// Returns the curve tokens are bought and sold along, if enabled.
+        #[cfg(feature = "sale")]
+        #[ink(message)]
+        pub fn bonding_curve(&self) -> Option<BondingCurve> {
+            self.bonding_curve.get().flatten()
+        }
+
+        /// Sets or disables the bonding curve. The curve can only change while
+        /// no tokens bought along it are outstanding, since their refunds are
+        /// backed by the current curve.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "sale")]
+        #[ink(message)]
+        pub fn set_bonding_curve(&mut self, curve: Option<BondingCurve>) -> Result<()> {
+            self.ensure_owner()?;
+            let invalid = curve
+                .as_ref()
+                .is_some_and(|curve| curve.scale == 0 || curve.exponent > MAX_CURVE_EXPONENT);
+            if invalid || self.curve_supply() > 0 {
+                return Err(Error::InvalidBondingCurve)
+            }
+            let old = self.bonding_curve();
+            self.bonding_curve.set(&curve);
+            self.emit_config_changed(Setting::BondingCurve, None, old, curve);
+            Ok(())
+        }
+
+        /// Returns the tokens bought along the bonding curve and not sold back yet.
+        #[cfg(feature = "sale")]
+        #[ink(message)]
+        pub fn curve_supply(&self) -> Balance {
+            self.curve_supply.get_or_default()
+        }
+
+        /// Returns the native currency `buy_on_curve` charges for `value` tokens.
+        #[cfg(feature = "sale")]
+        #[ink(message)]
+        pub fn price_to_mint(&self, value: Balance) -> Result<Balance> {
+            let curve = self.bonding_curve().ok_or(Error::NoBondingCurve)?;
+            let supply = self.curve_supply();
+            let after = supply
+                .checked_add(value)
+                .and_then(|supply| curve.reserve_at(supply))
+                .ok_or(Error::SupplyOverflow)?;
+            let before = curve.reserve_at(supply).expect("reserve of the curve supply fits");
+            Ok(after - before)
+        }
+
+        /// Returns the native currency `sell_on_curve` pays for `value` tokens.
+        #[cfg(feature = "sale")]
+        #[ink(message)]
+        pub fn refund_for_burn(&self, value: Balance) -> Result<Balance> {
+            let curve = self.bonding_curve().ok_or(Error::NoBondingCurve)?;
+            let supply = self.curve_supply();
+            if value > supply {
+                return Err(Error::InsufficientReserve)
+            }
+            let before = curve.reserve_at(supply).expect("reserve of the curve supply fits");
+            let after = curve.reserve_at(supply - value).expect("smaller supplies fit");
+            Ok(before - after)
+        }
+
+        /// Mints `value` tokens to the caller at the bonding curve price. Any
+        /// native currency transferred beyond the price is sent back. Returns
+        /// the price paid.
+        #[cfg(feature = "sale")]
+        #[ink(message, payable)]
+        pub fn buy_on_curve(&mut self, value: Balance) -> Result<Balance> {
+            let price = self.price_to_mint(value)?;
+            let buyer = self.env().caller();
+            let paid = self.env().transferred_value();
+            if value == 0 || paid < price {
+                return Err(Error::PurchaseOutOfBounds)
+            }
+            self.mint_to(&buyer, value)?;
+            let curve_supply = self.curve_supply() + value;
+            self.curve_supply.set(&curve_supply);
+            if paid > price {
+                self.env()
+                    .transfer(buyer, paid - price)
+                    .map_err(|_| Error::NativeTransferFailed)?;
+            }
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(TokensPurchased {
+                buyer,
+                value: price,
+                tokens: value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(price)
+        }
+
+        /// Burns `value` of the caller's tokens and refunds them along the
+        /// bonding curve. Returns the refund paid.
+        #[cfg(feature = "sale")]
+        #[ink(message)]
+        pub fn sell_on_curve(&mut self, value: Balance) -> Result<Balance> {
+            let refund = self.refund_for_burn(value)?;
+            let account = self.env().caller();
+            self.burn_from(&account, value)?;
+            let curve_supply = self.curve_supply() - value;
+            self.curve_supply.set(&curve_supply);
+            self.env()
+                .transfer(account, refund)
+                .map_err(|_| Error::NativeTransferFailed)?;
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(TokensRedeemed {
+                account,
+                tokens: value,
+                value: refund,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(refund)
+        }
+
+        /// Returns where raffle draws read randomness from.
+        #[cfg(feature = "raffles")]
+        #[ink(message)]
+        pub fn randomness_config(&self) -> Option<RandomnessConfig> {
+            self.randomness.get().flatten()
+        }
+
+        /// Sets or disables the chain extension function raffle draws read
+        /// randomness from.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "raffles")]
+        #[ink(message)]
+        pub fn set_randomness_config(&mut self, config: Option<RandomnessConfig>) -> Result<()> {
+            self.ensure_owner()?;
+            let old = self.randomness_config();
+            self.randomness.set(&config);
+            self.emit_config_changed(Setting::RandomnessConfig, None, old, config);
+            Ok(())
+        }
+
+        /// Starts a raffle selling tickets until `ends_at`, and returns its
+        /// identifier.
+        ///
+        /// Winners are drawn with the randomness chain extension, or, if
+        /// `commitment` is given, from the secret hashing to it, which the
+        /// owner reveals at the draw.
+        ///
+        /// Only the owner may call this.
+        #[cfg(feature = "raffles")]
+        #[ink(message)]
+        pub fn start_raffle(
+            &mut self,
+            ticket_price: Balance,
+            ends_at: Timestamp,
+            winners: u32,
+            commitment: Option<Hash>,
+        ) -> Result<RaffleId> {
+            self.ensure_owner()?;
+            if ticket_price == 0 || winners == 0 || winners > MAX_RAFFLE_WINNERS {
+                return Err(Error::InvalidRaffle)
+            }
+            let raffle_id = self.next_raffle_id.get_or_default();
+            self.next_raffle_id.set(&(raffle_id + 1));
+            self.raffles.insert(
+                raffle_id,
+                &Raffle {
+                    ticket_price,
+                    ends_at,
+                    winners,
+                    commitment,
+                    ..Default::default()
+                },
+            );
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(RaffleStarted {
+                raffle_id,
+                ticket_price,
+                ends_at,
+                winners,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(raffle_id)
+        }
+
+        /// Returns a raffle.
+        #[cfg(feature = "raffles")]
+        #[ink(message)]
+        pub fn raffle(&self, raffle_id: RaffleId) -> Option<Raffle> {
+            self.raffles.get(raffle_id)
+        }
+
+        /// Returns the pooled stakes of a raffle, which its winners share.
+        #[cfg(feature = "raffles")]
+        #[ink(message)]
+        pub fn raffle_prize(&self, raffle_id: RaffleId) -> Balance {
+            self.raffles
+                .get(raffle_id)
+                .map(|raffle| self.gons_rate().to_tokens(raffle.pot_gons))
+                .unwrap_or_default()
+        }
+
+        /// Returns the number of tickets `account` holds in a raffle.
+        #[cfg(feature = "raffles")]
+        #[ink(message)]
+        pub fn raffle_tickets_of(&self, raffle_id: RaffleId, account: AccountId) -> u32 {
+            self.raffle_tickets.get((raffle_id, account)).unwrap_or_default()
+        }
+
+        /// Returns the holder of ticket number `ticket` of a raffle.
+        #[cfg(feature = "raffles")]
+        #[ink(message)]
+        pub fn raffle_ticket_holder(&self, raffle_id: RaffleId, ticket: u32) -> Option<AccountId> {
+            let raffle = self.raffles.get(raffle_id)?;
+            if ticket >= raffle.tickets {
+                return None
+            }
+            // Find the last purchase starting at or before the ticket.
+            let (mut low, mut high) = (0, raffle.entries);
+            while high - low > 1 {
+                let middle = low + (high - low) / 2;
+                let (_, first) = self.raffle_entries.get((raffle_id, middle))?;
+                if first <= ticket {
+                    low = middle;
+                } else {
+                    high = middle;
+                }
+            }
+            self.raffle_entries
+                .get((raffle_id, low))
+                .map(|(buyer, _)| buyer)
+        }
+
+        /// Returns the drawn winners of a raffle, in draw order.
+        #[cfg(feature = "raffles")]
+        #[ink(message)]
+        pub fn raffle_winners(&self, raffle_id: RaffleId) -> Vec<AccountId> {
+            (0..)
+                .map_while(|position| self.raffle_winners.get((raffle_id, position)))
+                .collect()
+        }
+
+        /// Stakes the caller's tokens on `count` tickets of a raffle.
+        #[cfg(feature = "raffles")]
+        #[ink(message)]
+        pub fn buy_tickets(&mut self, raffle_id: RaffleId, count: u32) -> Result<()> {
+            let mut raffle = self.raffles.get(raffle_id).ok_or(Error::RaffleNotFound)?;
+            if self.env().block_timestamp() >= raffle.ends_at {
+                return Err(Error::RaffleClosed)
+            }
+            let tickets = raffle.tickets.checked_add(count).filter(|_| count > 0);
+            let tickets = tickets.ok_or(Error::InvalidRaffle)?;
+            // More than any balance can cover.
+            let value = raffle
+                .ticket_price
+                .checked_mul(count.into())
+                .ok_or(Error::InsufficientBalance)?;
+            let buyer = self.env().caller();
+            let gons = self.move_into_contract(&buyer, value)?;
+            self.raffle_entries
+                .insert((raffle_id, raffle.entries), &(buyer, raffle.tickets));
+            let held = self.raffle_tickets_of(raffle_id, buyer) + count;
+            self.raffle_tickets.insert((raffle_id, buyer), &held);
+            raffle.entries += 1;
+            raffle.tickets = tickets;
+            raffle.pot_gons += gons;
+            self.raffles.insert(raffle_id, &raffle);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(TicketsBought {
+                raffle_id,
+                buyer,
+                count,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(())
+        }
+
+        /// Draws the winners of an ended raffle, each from a distinct ticket,
+        /// and shares the pooled stakes among them. The first winner also gets
+        /// the rounding remainder. Returns the winners.
+        ///
+        /// This is possible for `RAFFLE_DRAW_PERIOD` after the raffle ended;
+        /// afterwards buyers take their stakes back with `refund_raffle_tickets`.
+        ///
+        /// Raffles with a commitment need the secret hashing to it, which only
+        /// the owner may reveal. Others read the randomness chain extension and
+        /// can be drawn by anyone.
+        #[cfg(feature = "raffles")]
+        #[ink(message)]
+        pub fn draw_raffle(
+            &mut self,
+            raffle_id: RaffleId,
+            reveal: Option<Hash>,
+        ) -> Result<Vec<AccountId>> {
+            let mut raffle = self.raffles.get(raffle_id).ok_or(Error::RaffleNotFound)?;
+            if raffle.drawn {
+                return Err(Error::RaffleClosed)
+            }
+            let now = self.env().block_timestamp();
+            if now < raffle.ends_at {
+                return Err(Error::RaffleNotEnded)
+            }
+            if now >= raffle.ends_at.saturating_add(RAFFLE_DRAW_PERIOD) {
+                return Err(Error::RaffleClosed)
+            }
+            let seed = self.raffle_seed(raffle_id, &raffle, reveal)?;
+            let count = raffle.winners.min(raffle.tickets);
+            let mut tickets = Vec::new();
+            let mut nonce = 0u32;
+            while (tickets.len() as u32) < count {
+                let draw = self.env().hash_encoded::<Blake2x256, _>(&(seed, nonce));
+                nonce += 1;
+                let mut bytes = [0; 4];
+                bytes.copy_from_slice(&draw[..4]);
+                let ticket = u32::from_le_bytes(bytes) % raffle.tickets;
+                if !tickets.contains(&ticket) {
+                    tickets.push(ticket);
+                }
+            }
+            let winners: Vec<AccountId> = tickets
+                .iter()
+                .map(|ticket| {
+                    self.raffle_ticket_holder(raffle_id, *ticket)
+                        .expect("drawn tickets were sold")
+                })
+                .collect();
+            raffle.drawn = true;
+            self.raffles.insert(raffle_id, &raffle);
+            let contract = self.env().account_id();
+            let share = raffle.pot_gons.checked_div(count.into()).unwrap_or_default();
+            for (position, winner) in winners.iter().enumerate() {
+                self.raffle_winners.insert((raffle_id, position as u32), winner);
+                let remainder = if position == 0 {
+                    raffle.pot_gons - share * Balance::from(count)
+                } else {
+                    0
+                };
+                self.move_gons(&contract, winner, share + remainder);
+            }
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(RaffleDrawn {
+                raffle_id,
+                winners: winners.clone(),
+                prize: self.gons_rate().to_tokens(share),
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(winners)
+        }
+
+        /// Returns the caller's stakes in a raffle that was not drawn within
+        /// `RAFFLE_DRAW_PERIOD` after it ended, and returns the amount. Each
+        /// refund takes the caller's share of what is left in the pot.
+        #[cfg(feature = "raffles")]
+        #[ink(message)]
+        pub fn refund_raffle_tickets(&mut self, raffle_id: RaffleId) -> Result<Balance> {
+            let mut raffle = self.raffles.get(raffle_id).ok_or(Error::RaffleNotFound)?;
+            if raffle.drawn {
+                return Err(Error::RaffleClosed)
+            }
+            if self.env().block_timestamp() < raffle.ends_at.saturating_add(RAFFLE_DRAW_PERIOD) {
+                return Err(Error::RaffleNotEnded)
+            }
+            let buyer = self.env().caller();
+            let tickets = self.raffle_tickets_of(raffle_id, buyer);
+            if tickets == 0 {
+                return Err(Error::NothingToClaim)
+            }
+            self.ensure_unrestricted(&buyer)?;
+            let gons = mul_div(
+                raffle.pot_gons,
+                tickets.into(),
+                (raffle.tickets - raffle.refunded).into(),
+            )
+            .expect("held tickets are among those not refunded");
+            self.raffle_tickets.remove((raffle_id, buyer));
+            raffle.refunded += tickets;
+            raffle.pot_gons -= gons;
+            self.raffles.insert(raffle_id, &raffle);
+            self.move_gons(&self.env().account_id(), &buyer, gons);
+            let value = self.gons_rate().to_tokens(gons);
+            let event_seq = self.next_event_seq();
+            self.env().emit_event(RaffleRefunded {
+                raffle_id,
+                buyer,
+                tickets,
+                value,
+                block_number: self.env().block_number(),
+                timestamp: self.env().block_timestamp(),
+                event_seq,
+            });
+            Ok(value)
+        }
+
+        /// Returns the random seed winners of a raffle are drawn from.
+        #[cfg(feature = "raffles")]
+        fn raffle_seed(
+            &self,
+            raffle_id: RaffleId,
+            raffle: &Raffle,
+            reveal: Option<Hash>,
+        ) -> Result<[u8; 32]> {
+            if let Some(commitment) = raffle.commitment {
+                self.ensure_owner()?;
+                let reveal = reveal.ok_or(Error::InvalidReveal)?;
+                let hash = self.env().hash_encoded::<Blake2x256, _>(&reveal);
+                if Hash::from(hash) != commitment {
+                    return Err(Error::InvalidReveal)
+                }
+                // Mixing in the sales keeps the outcome open until the draw for
+                // everyone but the owner.
+                return Ok(self
+                    .env()
+                    .hash_encoded::<Blake2x256, _>(&(reveal, raffle_id, raffle.tickets)))
+            }
+            let config = self.randomness_config().ok_or(Error::RandomnessUnavailable)?;
+            let subject = self
+                .env()
+                .hash_encoded::<Blake2x256, _>(&(self.env().account_id(), raffle_id));
+            #[cfg(feature = "randomness")]
+            let random =
+                crate::randomness::random(config.extension_id, config.function_id, subject);
+            #[cfg(not(feature = "randomness"))]
+            let random: Option<[u8; 32]> = {
+                let _ = (config, subject);
+                None
+            };
+            random.ok_or(Error::RandomnessUnavailable)
+        }
+
+        #[cfg(any(feature = "dividends", feature = "governance"))]
+        fn total_gons(&self) -> Balance {
+            self.total_gons
+                .get()
+                .unwrap_or_else(|| self.total_supply())
+        }
+    }
+
+    #[cfg(not(test))]
+    fn own_code_hash() -> Hash {
+        ink_env::own_code_hash::<Environment>().expect("a running contract always has code")
+    }
+
+    #[cfg(not(test))]
+    fn replace_code(code_hash: &[u8; 32]) -> Result<()> {
+        ink_env::set_code_hash(code_hash).map_err(|_| Error::CodeUpdateFailed)
+    }
+
+    // The off-chain environment keeps no code, so tests track the hash the
+    // contract runs themselves, starting from the zero hash.
+    #[cfg(test)]
+    thread_local! {
+        static CODE_HASH: core::cell::Cell<[u8; 32]> = const { core::cell::Cell::new([0; 32]) };
+    }
+
+    #[cfg(test)]
+    fn own_code_hash() -> Hash {
+        Hash::from(CODE_HASH.with(core::cell::Cell::get))
+    }
+
+    #[cfg(test)]
+    fn replace_code(code_hash: &[u8; 32]) -> Result<()> {
+        CODE_HASH.with(|cell| cell.set(*code_hash));
+        Ok(())
+    }
+
+    /// Decoding and checking of the events recorded by the off-chain environment.
+    #[cfg(test)]
+    mod test_events {
+        use super::*;
+
+        pub use crate::testing::{decode, last_event, recorded, Event};
+        use ink_env::{hash::Blake2x256, test::EmittedEvent, topics::PrefixedValue};
+        use scale::Encode;
+
+        /// Returns the topic ink! records for a field: the encoded prefix and
+        /// value if they fit, their hash otherwise.
+        fn topic<T: Encode>(prefix: &[u8], value: &T) -> Vec<u8> {
+            let mut encoded = PrefixedValue { prefix, value }.encode();
+            if encoded.len() <= 32 {
+                encoded.resize(32, 0);
+                return encoded
+            }
+            let mut hash = [0; 32];
+            ink_env::hash_bytes::<Blake2x256>(&encoded, &mut hash);
+            hash.to_vec()
+        }
+
+        /// Asserts that `event` is a `Transfer` with the given fields and topics.
+        pub fn assert_transfer(
+            event: &EmittedEvent,
+            from: Option<AccountId>,
+            to: Option<AccountId>,
+            value: Balance,
+        ) {
+            match decode(event) {
+                Event::Transfer(transfer) => {
+                    assert_eq!(transfer.from, from, "unexpected Transfer::from");
+                    assert_eq!(transfer.to, to, "unexpected Transfer::to");
+                    assert_eq!(transfer.value, value, "unexpected Transfer::value");
+                }
+                _ => panic!("expected a Transfer event"),
+            }
+            let topics = vec![
+                topic(b"", b"Erc20::Transfer"),
+                topic(b"Erc20::Transfer::from", &from),
+                topic(b"Erc20::Transfer::to", &to),
+            ];
+            assert_eq!(event.topics, topics, "unexpected Transfer topics");
+        }
+
+        /// Asserts that `event` is an `Approve` with the given fields and topics.
+        pub fn assert_approve(
+            event: &EmittedEvent,
+            owner: AccountId,
+            spender: AccountId,
+            value: Balance,
+        ) {
+            match decode(event) {
+                Event::Approve(approve) => {
+                    assert_eq!(approve.owner, owner, "unexpected Approve::owner");
+                    assert_eq!(approve.spender, spender, "unexpected Approve::spender");
+                    assert_eq!(approve.value, value, "unexpected Approve::value");
+                }
+                _ => panic!("expected an Approve event"),
+            }
+            let topics = vec![
+                topic(b"", b"Erc20::Approve"),
+                topic(b"Erc20::Approve::owner", &owner),
+                topic(b"Erc20::Approve::spender", &spender),
+            ];
+            assert_eq!(event.topics, topics, "unexpected Approve topics");
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::test_events::{
+            assert_approve, assert_transfer, decode, last_event, recorded, Event,
+        };
+        use super::*;
+        use crate::testing::{advance_blocks, Erc20TestBuilder};
+
+        use ink_lang as ink;
+
+        #[ink::test]
+        fn new_works() {
+            let contract = Erc20::new(777);
+            assert_eq!(contract.total_supply(), 777);
+            let events = recorded();
+            assert_eq!(events.len(), 2);
+            assert_transfer(&events[0], None, Some(AccountId::from([0x1; 32])), 777);
+            match test_events::decode(&events[1]) {
+                Event::Minted(minted) => assert_eq!(minted.value, 777),
+                _ => panic!("expected a Minted event"),
+            }
+        }
+
+        #[ink::test]
+        fn balance_works() {
+            let contract = Erc20::new(100);
+            assert_eq!(contract.total_supply(), 100);
+            assert_eq!(contract.balance_of(AccountId::from([0x1; 32])), 100);
+            assert_eq!(contract.balance_of(AccountId::from([0x0; 32])), 0);
+        }
+
+        #[ink::test]
+        fn transfer_works() {
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.balance_of(AccountId::from([0x0; 32])), 0);
+            assert_eq!(erc20.transfer(AccountId::from([0x0; 32]), 10), Ok(()));
+            assert_eq!(erc20.balance_of(AccountId::from([0x0; 32])), 10);
+            let events = recorded();
+            assert_eq!(events.len(), 3);
+            assert_transfer(
+                &events[2],
+                Some(AccountId::from([0x1; 32])),
+                Some(AccountId::from([0x0; 32])),
+                10,
+            );
+        }
+
+        #[ink::test]
+        fn failed_transfer_emits_no_event() {
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(
+                erc20.transfer(AccountId::from([0x0; 32]), 101),
+                Err(Error::InsufficientBalance)
+            );
+            assert_eq!(recorded().len(), 2);
+        }
+
+        #[ink::test]
+        fn transfer_from_works() {
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.balance_of(AccountId::from([0x1; 32])), 100);
+            let _ = erc20.approve(AccountId::from([0x1; 32]), 20);
+            let _ = erc20.transfer_from(AccountId::from([0x1; 32]), AccountId::from([0x0; 32]), 10);
+            assert_eq!(erc20.balance_of(AccountId::from([0x0; 32])), 10);
+            let events = recorded();
+            assert_eq!(events.len(), 4);
+            assert_approve(&events[2], AccountId::from([0x1; 32]), AccountId::from([0x1; 32]), 20);
+            assert_transfer(
+                &events[3],
+                Some(AccountId::from([0x1; 32])),
+                Some(AccountId::from([0x0; 32])),
+                10,
+            );
+        }
+
+        #[ink::test]
+        fn allowances_works() {
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.balance_of(AccountId::from([0x1; 32])), 100);
+            let _ = erc20.approve(AccountId::from([0x1; 32]), 200);
+            assert_eq!(erc20.allowance(AccountId::from([0x1; 32]), AccountId::from([0x1; 32])), 200);
+
+            let _ = erc20.transfer_from(AccountId::from([0x1; 32]), AccountId::from([0x0; 32]), 50);
+            assert_eq!(erc20.balance_of(AccountId::from([0x0; 32])), 50);
+            assert_eq!(erc20.allowance(AccountId::from([0x1; 32]), AccountId::from([0x1; 32])), 150);
+
+            let _ = erc20.transfer_from(AccountId::from([0x1; 32]), AccountId::from([0x0; 32]), 100);
+            assert_eq!(erc20.balance_of(AccountId::from([0x0; 32])), 50);
+            assert_eq!(erc20.allowance(AccountId::from([0x1; 32]), AccountId::from([0x1; 32])), 150);
+            // The failed transfer emitted nothing.
+            let events = recorded();
+            assert_eq!(events.len(), 4);
+            assert_approve(&events[2], AccountId::from([0x1; 32]), AccountId::from([0x1; 32]), 200);
+            assert_transfer(
+                &events[3],
+                Some(AccountId::from([0x1; 32])),
+                Some(AccountId::from([0x0; 32])),
+                50,
+            );
+        }
+
+        #[ink::test]
+        fn new_with_metadata_works() {
+            let owner = AccountId::from([0x2; 32]);
+            let minter = AccountId::from([0x3; 32]);
+            let erc20 = Erc20::new_with_metadata(
+                Some(String::from("Token")),
+                Some(String::from("TKN")),
+                12,
+                100,
+                owner,
+                minter,
+            );
+            assert_eq!(erc20.name(), Some(String::from("Token")));
+            assert_eq!(erc20.symbol(), Some(String::from("TKN")));
+            assert_eq!(erc20.decimals(), 12);
+            assert_eq!(erc20.owner(), owner);
+            assert_eq!(erc20.minter(), minter);
+            assert_eq!(erc20.balance_of(owner), 100);
+            assert_eq!(erc20.balance_of(AccountId::from([0x1; 32])), 0);
+        }
+
+        #[cfg(feature = "mintable")]
+        #[ink::test]
+        fn mint_works() {
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.mint(AccountId::from([0x2; 32]), 50), Ok(()));
+            assert_eq!(erc20.balance_of(AccountId::from([0x2; 32])), 50);
+            assert_eq!(erc20.total_supply(), 150);
+            assert_eq!(
+                erc20.mint(AccountId::from([0x2; 32]), Balance::MAX),
+                Err(Error::SupplyOverflow)
+            );
+        }
+
+        #[cfg(feature = "mintable")]
+        #[ink::test]
+        fn mint_requires_minter() {
+            let mut erc20 = Erc20::new_with_metadata(
+                None,
+                None,
+                0,
+                100,
+                AccountId::from([0x2; 32]),
+                AccountId::from([0x3; 32]),
+            );
+            assert_eq!(erc20.mint(AccountId::from([0x1; 32]), 50), Err(Error::NotMinter));
+            assert_eq!(erc20.total_supply(), 100);
+        }
+
+        #[cfg(feature = "fees")]
+        #[ink::test]
+        fn transfer_burns_at_burn_rate() {
+            let mut erc20 = Erc20::new(100_000);
+            assert_eq!(erc20.set_burn_rate(250), Ok(()));
+            assert_eq!(erc20.transfer(AccountId::from([0x2; 32]), 10_000), Ok(()));
+            assert_eq!(erc20.balance_of(AccountId::from([0x1; 32])), 90_000);
+            assert_eq!(erc20.balance_of(AccountId::from([0x2; 32])), 9_750);
+            assert_eq!(erc20.total_supply(), 99_750);
+            assert_eq!(erc20.total_burned(), 250);
+            // Transfer and Minted on deployment, ConfigChanged for the burn
+            // rate, Transfer, Burned and SupplyChanged for the burn, then the
+            // transfer itself.
+            let events = recorded();
+            assert_eq!(events.len(), 7);
+            match decode(&events[5]) {
+                Event::SupplyChanged(changed) => {
+                    assert_eq!((changed.total_supply, changed.total_burned), (99_750, 250));
+                }
+                _ => panic!("expected a SupplyChanged event"),
+            }
+        }
+
+        #[cfg(feature = "circulating")]
+        #[ink::test]
+        fn circulating_supply_leaves_out_excluded_accounts() {
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            let mut erc20 = Erc20TestBuilder::new()
+                .initial_supply(500)
+                .balance(bob, 300)
+                .balance(charlie, 200)
+                .build();
+            assert_eq!(erc20.circulating_supply(), 1_000);
+
+            assert_eq!(erc20.set_excluded_from_circulation(bob, true), Ok(()));
+            assert_eq!(erc20.set_excluded_from_circulation(charlie, true), Ok(()));
+            assert_eq!(erc20.set_excluded_from_circulation(charlie, true), Ok(()));
+            assert_eq!(erc20.excluded_from_circulation(), vec![bob, charlie]);
+            assert_eq!(erc20.circulating_supply(), 500);
+
+            assert_eq!(erc20.set_excluded_from_circulation(bob, false), Ok(()));
+            assert!(!erc20.is_excluded_from_circulation(bob));
+            assert_eq!(erc20.excluded_from_circulation(), vec![charlie]);
+            assert_eq!(erc20.circulating_supply(), 800);
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.set_excluded_from_circulation(bob, true), Err(Error::NotOwner));
+        }
+
+        #[cfg(feature = "circulating")]
+        #[ink::test]
+        fn excluded_accounts_are_bounded() {
+            let mut erc20 = Erc20::new(1_000);
+            for account in 0..MAX_EXCLUDED_ACCOUNTS as u8 {
+                let account = AccountId::from([account; 32]);
+                assert_eq!(erc20.set_excluded_from_circulation(account, true), Ok(()));
+            }
+            assert_eq!(
+                erc20.set_excluded_from_circulation(AccountId::from([0xee; 32]), true),
+                Err(Error::TooManyExcludedAccounts)
+            );
+            assert_eq!(erc20.circulating_supply(), 0);
+        }
+
+        #[cfg(feature = "mintable")]
+        #[ink::test]
+        fn added_minters_are_capped() {
+            let mut erc20 = Erc20::new(0);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            assert_eq!(erc20.add_minter(bob, 100), Ok(()));
+            assert_eq!(erc20.minter_cap(bob), Some(100));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.add_minter(charlie, 100), Err(Error::NotOwner));
+            assert_eq!(erc20.mint(charlie, 60), Ok(()));
+            assert_eq!(erc20.minter_cap(bob), Some(40));
+            assert_eq!(erc20.mint(charlie, 41), Err(Error::MinterCapExceeded));
+            assert_eq!(erc20.mint(charlie, 40), Ok(()));
+            assert_eq!(erc20.balance_of(charlie), 100);
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(AccountId::from([0x1; 32]));
+            assert_eq!(erc20.remove_minter(bob), Ok(()));
+            assert_eq!(erc20.minter_cap(bob), None);
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.mint(charlie, 0), Err(Error::NotMinter));
+        }
+
+        #[cfg(feature = "mintable")]
+        #[ink::test]
+        fn large_mints_need_approvals() {
+            let mut erc20 = Erc20::new(0);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            let dave = AccountId::from([0x4; 32]);
+            let config = MintApprovalConfig {
+                approvers: vec![bob, charlie, dave],
+                threshold: 2,
+                direct_limit: 100,
+                request_ttl: 12,
+            };
+            assert_eq!(erc20.request_mint(alice, 500), Err(Error::MintApprovalNotSet));
+            assert_eq!(
+                erc20.set_mint_approval(Some(MintApprovalConfig {
+                    approvers: vec![bob, bob],
+                    ..config.clone()
+                })),
+                Err(Error::InvalidMintApproval)
+            );
+            assert_eq!(erc20.set_mint_approval(Some(config)), Ok(()));
+            assert_eq!(erc20.mint(alice, 100), Ok(()));
+            assert_eq!(erc20.mint(alice, 101), Err(Error::MintApprovalRequired));
+            assert_eq!(erc20.request_mint(alice, 500), Ok(0));
+            assert_eq!(erc20.request_mint(alice, 500), Ok(1));
+            assert_eq!(erc20.approve_mint(0), Err(Error::NotMintApprover));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.request_mint(bob, 500), Err(Error::NotMinter));
+            assert_eq!(erc20.approve_mint(0), Ok(()));
+            assert_eq!(erc20.approve_mint(0), Ok(()));
+            assert_eq!(erc20.execute_mint(0), Err(Error::MintNotApproved));
+            assert_eq!(erc20.cancel_mint_request(1), Err(Error::NotMinter));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            assert_eq!(erc20.approve_mint(0), Ok(()));
+            assert_eq!(erc20.execute_mint(0), Ok(()));
+            assert_eq!(erc20.balance_of(alice), 600);
+            assert_eq!(erc20.mint_request(0), None);
+
+            // Stale requests can no longer be executed, and anyone may drop
+            // them.
+            assert_eq!(erc20.approve_mint(1), Ok(()));
+            advance_blocks(2);
+            assert_eq!(erc20.approve_mint(1), Err(Error::MintRequestExpired));
+            assert_eq!(erc20.execute_mint(1), Err(Error::MintRequestExpired));
+            assert_eq!(erc20.cancel_mint_request(1), Ok(()));
+            assert!(matches!(last_event(), Event::MintRequestCancelled(_)));
+            assert_eq!(erc20.execute_mint(1), Err(Error::MintRequestNotFound));
+        }
+
+        #[cfg(feature = "emissions")]
+        #[ink::test]
+        fn drip_releases_elapsed_periods() {
+            let bob = AccountId::from([0x2; 32]);
+            let schedule = EmissionSchedule {
+                recipient: bob,
+                amount_per_period: 10,
+                period: 10,
+                end: 50,
+            };
+            let mut erc20 = Erc20::new_with_emission(1_000, schedule);
+            assert_eq!(erc20.drip(), Ok(0));
+
+            // Four blocks of 6 ms each: two full periods.
+            advance_blocks(4);
+            assert_eq!(erc20.pending_emission(), 20);
+            assert_eq!(erc20.drip(), Ok(20));
+            assert_eq!(erc20.drip(), Ok(0));
+
+            advance_blocks(20);
+            assert_eq!(erc20.drip(), Ok(30));
+            assert_eq!(erc20.balance_of(bob), 50);
+            assert_eq!(erc20.total_supply(), 1_050);
+            assert_eq!(erc20.pending_emission(), 0);
+        }
+
+        #[cfg(feature = "emissions")]
+        #[ink::test]
+        fn inflation_accrues_per_block() {
+            let mut erc20 = Erc20::new(1_000_000);
+            let pool = AccountId::from([0x7; 32]);
+            assert_eq!(erc20.mint_inflation(), Err(Error::InvalidInflation));
+            let config = InflationConfig {
+                reward_pool: pool,
+                annual_rate: 500,
+                blocks_per_year: 100,
+            };
+            assert_eq!(
+                erc20.set_inflation(Some(InflationConfig {
+                    blocks_per_year: 0,
+                    ..config.clone()
+                })),
+                Err(Error::InvalidInflation)
+            );
+            assert_eq!(erc20.set_inflation(Some(config)), Ok(()));
+            assert_eq!(erc20.accrued_inflation(), 0);
+
+            advance_blocks(10);
+            assert_eq!(erc20.accrued_inflation(), 5_000);
+            assert_eq!(erc20.mint_inflation(), Ok(5_000));
+            assert_eq!(erc20.accrued_inflation(), 0);
+            assert_eq!(erc20.balance_of(pool), 5_000);
+
+            advance_blocks(10);
+            assert_eq!(erc20.set_inflation(None), Ok(()));
+            assert_eq!(erc20.balance_of(pool), 10_025);
+            advance_blocks(10);
+            assert_eq!(erc20.accrued_inflation(), 0);
+        }
+
+        #[cfg(feature = "emissions")]
+        #[ink::test]
+        fn drip_requires_schedule() {
+            let mut erc20 = Erc20::new(1_000);
+            assert_eq!(erc20.emission_schedule(), None);
+            assert_eq!(erc20.drip(), Err(Error::NoEmission));
+        }
+
+        #[cfg(feature = "mintable")]
+        #[ink::test]
+        fn mint_emits_minted_event() {
+            let mut erc20 = Erc20::new(0);
+            let bob = AccountId::from([0x2; 32]);
+            assert_eq!(erc20.mint(bob, 50), Ok(()));
+            match last_event() {
+                Event::Minted(minted) => {
+                    assert_eq!(minted.to, bob);
+                    assert_eq!(minted.value, 50);
+                }
+                _ => panic!("expected a Minted event"),
+            }
+        }
+
+        #[cfg(feature = "limits")]
+        #[ink::test]
+        fn setters_emit_config_changed() {
+            let mut erc20 = Erc20::new(100);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            assert_eq!(erc20.set_burn_rate(100), Ok(()));
+            assert_eq!(erc20.set_burn_rate(200), Ok(()));
+            match last_event() {
+                Event::ConfigChanged(changed) => {
+                    assert_eq!(changed.setting, Setting::BurnRate);
+                    assert_eq!(changed.account, None);
+                    assert_eq!(changed.old, ConfigValue::Rate(100));
+                    assert_eq!(changed.new, ConfigValue::Rate(200));
+                    assert_eq!(changed.caller, alice);
+                }
+                _ => panic!("expected a ConfigChanged event"),
+            }
+
+            assert_eq!(erc20.set_tx_limit_exempt(bob, true), Ok(()));
+            match last_event() {
+                Event::ConfigChanged(changed) => {
+                    assert_eq!(changed.setting, Setting::TxLimitExempt);
+                    assert_eq!(changed.account, Some(bob));
+                    assert_eq!(changed.old, ConfigValue::Flag(false));
+                    assert_eq!(changed.new, ConfigValue::Flag(true));
+                }
+                _ => panic!("expected a ConfigChanged event"),
+            }
+
+            assert_eq!(erc20.set_max_wallet(Some(50)), Ok(()));
+            match last_event() {
+                Event::ConfigChanged(changed) => {
+                    assert_eq!(changed.old, ConfigValue::Unset);
+                    assert_eq!(changed.new, ConfigValue::Amount(50));
+                }
+                _ => panic!("expected a ConfigChanged event"),
+            }
+        }
+
+        #[test]
+        fn error_codes_are_stable() {
+            assert_eq!(u32::from(Error::InsufficientBalance), 1);
+            assert_eq!(u32::from(Error::NothingToClaim), 30);
+            assert_eq!(u32::from(Error::AllowanceExpired), 45);
+            assert_eq!(u32::from(Error::FlaggedSniper), 66);
+            assert_eq!(Error::NotOwner.to_string(), "caller is not the owner");
+        }
+
+        /// Regenerate the snapshot with `UPDATE_STORAGE_LAYOUT=1 cargo test storage_layout`
+        /// after deliberately changing the storage layout.
+        #[cfg(feature = "full")]
+        #[test]
+        fn storage_layout_matches_snapshot() {
+            use ink_storage::traits::StorageLayout;
+            use scale_info::IntoPortable;
+
+            let path = concat!(env!("CARGO_MANIFEST_DIR"), "/storage_layout.json");
+            let root_key = ink_primitives::Key::new([0x00; 32]);
+            let layout = Erc20::layout(&mut root_key.into())
+                .into_portable(&mut scale_info::Registry::new());
+            let json = serde_json::to_string_pretty(&layout).unwrap() + "\n";
+            if std::env::var_os("UPDATE_STORAGE_LAYOUT").is_some() {
+                std::fs::write(path, &json).unwrap();
+            }
+            let snapshot = std::fs::read_to_string(path).unwrap();
+            assert!(json == snapshot, "storage layout differs from storage_layout.json");
+        }
+
+        #[cfg(feature = "fees")]
+        #[ink::test]
+        fn set_burn_rate_is_restricted() {
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.set_burn_rate(MAX_BURN_RATE + 1), Err(Error::BurnRateTooHigh));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(AccountId::from([0x2; 32]));
+            assert_eq!(erc20.set_burn_rate(100), Err(Error::NotOwner));
+            assert_eq!(erc20.burn_rate(), 0);
+        }
+
+        #[cfg(feature = "limits")]
+        #[ink::test]
+        fn max_tx_amount_limits_transfers() {
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            let mut erc20 = Erc20TestBuilder::new()
+                .initial_supply(500)
+                .balance(bob, 500)
+                .configure(|erc20| erc20.set_max_tx_amount(Some(100)))
+                .build();
+            // The owner is exempt.
+            assert_eq!(erc20.transfer(bob, 200), Ok(()));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.transfer(charlie, 101), Err(Error::MaxTransactionExceeded));
+            assert_eq!(erc20.transfer(charlie, 100), Ok(()));
+            assert_eq!(erc20.set_tx_limit_exempt(charlie, true), Err(Error::NotOwner));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(AccountId::from([0x1; 32]));
+            assert_eq!(erc20.set_tx_limit_exempt(charlie, true), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.transfer(charlie, 500), Ok(()));
+            assert_eq!(erc20.balance_of(charlie), 600);
+        }
+
+        #[cfg(all(feature = "limits", feature = "mintable"))]
+        #[ink::test]
+        fn max_wallet_limits_recipients() {
+            let mut erc20 = Erc20::new(1_000);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            assert_eq!(erc20.set_max_wallet(Some(100)), Ok(()));
+            assert_eq!(erc20.transfer(bob, 100), Ok(()));
+            assert_eq!(erc20.transfer(bob, 1), Err(Error::MaxWalletExceeded));
+            assert_eq!(erc20.mint(bob, 1), Err(Error::MaxWalletExceeded));
+            assert_eq!(erc20.balance_of(bob), 100);
+
+            // Sending to yourself does not grow your balance.
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.transfer(bob, 100), Ok(()));
+            // The owner is exempt.
+            assert_eq!(erc20.transfer(AccountId::from([0x1; 32]), 50), Ok(()));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(AccountId::from([0x1; 32]));
+            assert_eq!(erc20.set_wallet_limit_exempt(charlie, true), Ok(()));
+            assert_eq!(erc20.transfer(charlie, 500), Ok(()));
+            assert_eq!(erc20.balance_of(charlie), 500);
+        }
+
+        #[cfg(feature = "fees")]
+        #[ink::test]
+        fn transfer_collects_tax() {
+            // Keep the contract account apart from the default test accounts.
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(AccountId::from([0xff; 32]));
+            let mut erc20 = Erc20::new(100_000);
+            let bob = AccountId::from([0x2; 32]);
+            let treasury = AccountId::from([0x5; 32]);
+            assert_eq!(erc20.set_tax_rate(MAX_TAX_RATE + 1), Err(Error::TaxRateTooHigh));
+            assert_eq!(erc20.set_tax_rate(100), Ok(()));
+            assert_eq!(erc20.transfer(bob, 10_000), Ok(()));
+            assert_eq!(erc20.balance_of(bob), 9_900);
+            assert_eq!(erc20.accumulated_tax(), 100);
+            assert_eq!(erc20.total_supply(), 100_000);
+
+            assert_eq!(erc20.withdraw_treasury(treasury), Ok(()));
+            assert_eq!(erc20.balance_of(treasury), 100);
+            assert_eq!(erc20.accumulated_tax(), 0);
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.withdraw_treasury(bob), Err(Error::NotOwner));
+        }
+
+        #[cfg(feature = "fees")]
+        #[ink::test]
+        fn accumulated_tax_is_forwarded_at_threshold() {
+            // Keep the contract account apart from the default test accounts.
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(AccountId::from([0xff; 32]));
+            let mut erc20 = Erc20::new(100_000);
+            let bob = AccountId::from([0x2; 32]);
+            let treasury = AccountId::from([0x5; 32]);
+            assert_eq!(erc20.set_tax_rate(100), Ok(()));
+            assert_eq!(erc20.set_treasury(Some(treasury), Some(150)), Ok(()));
+
+            assert_eq!(erc20.transfer(bob, 10_000), Ok(()));
+            assert_eq!(erc20.accumulated_tax(), 100);
+            assert_eq!(erc20.balance_of(treasury), 0);
+
+            assert_eq!(erc20.transfer(bob, 10_000), Ok(()));
+            assert_eq!(erc20.accumulated_tax(), 0);
+            assert_eq!(erc20.balance_of(treasury), 200);
+        }
+
+        #[cfg(feature = "limits")]
+        #[ink::test]
+        fn fee_exempt_accounts_skip_fees_and_limits() {
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(AccountId::from([0xff; 32]));
+            let mut erc20 = Erc20::new(100_000);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            let router = AccountId::from([0x6; 32]);
+            assert_eq!(erc20.set_tax_rate(100), Ok(()));
+            assert_eq!(erc20.set_burn_rate(100), Ok(()));
+            assert_eq!(erc20.set_max_tx_amount(Some(1_000)), Ok(()));
+            assert_eq!(erc20.set_max_wallet(Some(2_000)), Ok(()));
+            assert_eq!(erc20.set_fee_exempt(router, true), Ok(()));
+            assert!(erc20.is_fee_exempt(router));
+            match last_event() {
+                Event::ConfigChanged(changed) => {
+                    assert_eq!(changed.setting, Setting::FeeExempt);
+                    assert_eq!(changed.account, Some(router));
+                }
+                _ => panic!("expected a ConfigChanged event"),
+            }
+
+            assert_eq!(erc20.transfer(router, 10_000), Ok(()));
+            assert_eq!(erc20.balance_of(router), 10_000);
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(router);
+            assert_eq!(erc20.transfer(bob, 1_500), Ok(()));
+            assert_eq!(erc20.balance_of(bob), 1_500);
+            assert_eq!(erc20.accumulated_tax(), 0);
+            assert_eq!(erc20.total_supply(), 100_000);
+
+            // Other transfers still pay and stay within the limits.
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.transfer(charlie, 1_001), Err(Error::MaxTransactionExceeded));
+            assert_eq!(erc20.transfer(charlie, 1_000), Ok(()));
+            assert_eq!(erc20.balance_of(charlie), 980);
+            assert_eq!(erc20.set_fee_exempt(bob, true), Err(Error::NotOwner));
+        }
+
+        #[cfg(feature = "fees")]
+        #[ink::test]
+        fn transfer_to_self_only_pays_fees() {
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(AccountId::from([0xff; 32]));
+            let mut erc20 = Erc20::new(10_000);
+            let alice = AccountId::from([0x1; 32]);
+            assert_eq!(erc20.transfer(alice, 1_000), Ok(()));
+            assert_eq!(erc20.balance_of(alice), 10_000);
+            assert_transfer(recorded().last().unwrap(), Some(alice), Some(alice), 1_000);
+
+            assert_eq!(erc20.set_tax_rate(100), Ok(()));
+            assert_eq!(erc20.set_burn_rate(100), Ok(()));
+            assert_eq!(erc20.transfer(alice, 1_000), Ok(()));
+            assert_eq!(erc20.balance_of(alice), 9_980);
+            assert_eq!(erc20.accumulated_tax(), 10);
+            assert_eq!(erc20.total_supply(), 9_990);
+        }
+
+        #[ink::test]
+        fn test_builder_sets_up_the_token() {
+            use crate::testing;
+
+            let accounts = testing::accounts();
+            let erc20 = Erc20TestBuilder::new()
+                .initial_supply(1_000)
+                .balance(accounts.bob, 300)
+                .balance(accounts.charlie, 200)
+                .allowance(accounts.bob, accounts.django, 50)
+                .configure(|erc20| erc20.set_zero_transfer_policy(ZeroTransferPolicy::Silent))
+                .caller(accounts.bob)
+                .build();
+            assert_eq!(erc20.total_supply(), 1_500);
+            assert_eq!(erc20.balance_of(accounts.alice), 1_000);
+            assert_eq!(erc20.balance_of(accounts.bob), 300);
+            assert_eq!(erc20.balance_of(accounts.charlie), 200);
+            assert_eq!(erc20.allowance(accounts.bob, accounts.django), 50);
+            assert_eq!(erc20.zero_transfer_policy(), ZeroTransferPolicy::Silent);
+            assert_eq!(ink_env::caller::<ink_env::DefaultEnvironment>(), accounts.bob);
+            match testing::expect_event(|event| matches!(event, Event::Approve(_))) {
+                Event::Approve(approve) => assert_eq!(approve.spender, accounts.django),
+                _ => unreachable!(),
+            }
+        }
+
+        #[cfg(feature = "fees")]
+        #[ink::test]
+        fn amm_pair_transfers_use_trade_tax_rates() {
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(AccountId::from([0xff; 32]));
+            let mut erc20 = Erc20::new(100_000);
+            let bob = AccountId::from([0x2; 32]);
+            let pair = AccountId::from([0x6; 32]);
+            assert_eq!(erc20.set_tax_rate(100), Ok(()));
+            assert_eq!(erc20.set_trade_tax_rates(200, 500), Ok(()));
+            assert_eq!(erc20.set_trade_tax_rates(200, MAX_TAX_RATE + 1), Err(Error::TaxRateTooHigh));
+            assert_eq!(erc20.set_amm_pair(pair, true), Ok(()));
+            assert!(erc20.is_amm_pair(pair));
+
+            // Sell: 5%.
+            assert_eq!(erc20.transfer(pair, 10_000), Ok(()));
+            assert_eq!(erc20.balance_of(pair), 9_500);
+            // Buy: 2%.
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(pair);
+            assert_eq!(erc20.transfer(bob, 5_000), Ok(()));
+            assert_eq!(erc20.balance_of(bob), 4_900);
+            // Regular transfer: 1%.
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.transfer(AccountId::from([0x3; 32]), 1_000), Ok(()));
+            assert_eq!(erc20.accumulated_tax(), 500 + 100 + 10);
+        }
+
+        #[cfg(feature = "dividends")]
+        #[ink::test]
+        fn psp22_rewards_are_tracked_per_token() {
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(AccountId::from([0xff; 32]));
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let stable = AccountId::from([0x8; 32]);
+            let other = AccountId::from([0x9; 32]);
+            assert_eq!(erc20.transfer(bob, 250), Ok(()));
+            assert_eq!(erc20.distribute_rewards(stable, 1_000), Ok(()));
+            assert_eq!(erc20.distribute_rewards(other, 2_000), Ok(()));
+            assert_eq!(erc20.reward_tokens(), vec![stable, other]);
+            assert_eq!(erc20.rewards_of(alice, stable), 750);
+            assert_eq!(erc20.rewards_of(bob, other), 500);
+
+            // Rewards earned so far stay with the account after a transfer.
+            assert_eq!(erc20.transfer(bob, 250), Ok(()));
+            assert_eq!(erc20.distribute_rewards(stable, 1_000), Ok(()));
+            assert_eq!(erc20.rewards_of(alice, stable), 750 + 500);
+            assert_eq!(erc20.rewards_of(bob, stable), 250 + 500);
+            assert_eq!(erc20.rewards_of(bob, other), 500);
+            assert_eq!(erc20.dividends_of(bob), 0);
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.fund_rewards(stable, 1), Err(Error::NotOwner));
+            assert_eq!(erc20.claim(AccountId::from([0x7; 32])), Err(Error::NoDividends));
+        }
+
+        #[cfg(feature = "dividends")]
+        #[ink::test]
+        fn reward_tokens_are_bounded() {
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(AccountId::from([0xff; 32]));
+            let mut erc20 = Erc20::new(1_000);
+            for token in 0..MAX_REWARD_TOKENS as u8 {
+                assert_eq!(erc20.distribute_rewards(AccountId::from([token; 32]), 10), Ok(()));
+            }
+            assert_eq!(
+                erc20.distribute_rewards(AccountId::from([0xee; 32]), 10),
+                Err(Error::TooManyRewardTokens)
+            );
+            assert_eq!(erc20.distribute_rewards(AccountId::from([0; 32]), 10), Ok(()));
+        }
+
+        #[cfg(all(feature = "dividends", feature = "splitter", feature = "vouchers"))]
+        #[ink::test]
+        fn dividends_are_shared_pro_rata() {
+            let contract = AccountId::from([0xff; 32]);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            let mut erc20 = Erc20::new(1_000);
+            assert_eq!(erc20.transfer(bob, 250), Ok(()));
+
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 1_000);
+            ink_env::test::set_value_transferred::<ink_env::DefaultEnvironment>(1_000);
+            assert_eq!(erc20.deposit_dividends(), Ok(()));
+            assert_eq!(erc20.dividends_of(alice), 750);
+            assert_eq!(erc20.dividends_of(bob), 250);
+
+            // Dividends earned so far stay with the account after a transfer.
+            assert_eq!(erc20.transfer(bob, 250), Ok(()));
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 2_000);
+            assert_eq!(erc20.deposit_dividends(), Ok(()));
+            assert_eq!(erc20.dividends_of(alice), 750 + 500);
+            assert_eq!(erc20.dividends_of(bob), 250 + 500);
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            let before = ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(bob)
+                .unwrap_or_default();
+            assert_eq!(erc20.claim_dividends(), Ok(750));
+            assert_eq!(
+                ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(bob),
+                Ok(before + 750)
+            );
+            assert_eq!(erc20.dividends_of(bob), 0);
+            assert_eq!(erc20.claim_dividends(), Err(Error::NoDividends));
+            assert_eq!(erc20.deposit_dividends(), Err(Error::NotOwner));
+
+            // Tokens held in escrow or by the splitter earn nothing.
+            let splitter = erc20.splitter_account();
+            assert_eq!(erc20.transfer(splitter, 250), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.create_voucher(250, bob, 12), Ok(0));
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 3_000);
+            assert_eq!(erc20.deposit_dividends(), Ok(()));
+            assert_eq!(erc20.dividends_of(alice), 1_250 + 500);
+            assert_eq!(erc20.dividends_of(bob), 500);
+            assert_eq!(erc20.dividends_of(contract), 0);
+            assert_eq!(erc20.dividends_of(splitter), 0);
+        }
+
+        #[cfg(all(feature = "elastic", feature = "mintable"))]
+        #[ink::test]
+        fn rebase_scales_balances() {
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let mut erc20 = Erc20TestBuilder::new().initial_supply(750).balance(bob, 250).build();
+
+            assert_eq!(erc20.rebase(1_000), Ok(2_000));
+            assert_eq!(erc20.total_supply(), 2_000);
+            assert_eq!(erc20.balance_of(alice), 1_500);
+            assert_eq!(erc20.balance_of(bob), 500);
+
+            assert_eq!(erc20.transfer(bob, 500), Ok(()));
+            assert_eq!(erc20.balance_of(alice), 1_000);
+            assert_eq!(erc20.balance_of(bob), 1_000);
+
+            assert_eq!(erc20.rebase(-1_500), Ok(500));
+            assert_eq!(erc20.balance_of(alice), 250);
+            assert_eq!(erc20.balance_of(bob), 250);
+            assert_eq!(erc20.mint(bob, 250), Ok(()));
+            assert_eq!(erc20.balance_of(bob), 500);
+            assert_eq!(erc20.total_supply(), 750);
+            assert_eq!(erc20.rebase(-750), Err(Error::RebaseOutOfRange));
+        }
+
+        #[cfg(all(feature = "burnable", feature = "elastic"))]
+        #[ink::test]
+        fn empty_rebased_supply_is_worth_nothing() {
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            assert_eq!(erc20.rebase(1_000), Ok(2_000));
+            assert_eq!(erc20.burn(2_000), Ok(()));
+            assert_eq!(erc20.total_supply(), 0);
+            assert_eq!(erc20.balance_of(alice), 0);
+            assert_eq!(GonsRate { rebased: Some((0, 0)) }.to_tokens(0), 0);
+        }
+
+        #[cfg(feature = "elastic")]
+        #[ink::test]
+        fn accrue_grows_balances_through_the_exchange_rate() {
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            assert_eq!(erc20.transfer(bob, 400), Ok(()));
+            assert_eq!(erc20.exchange_rate(), EXCHANGE_RATE_SCALE);
+
+            let rate = EXCHANGE_RATE_SCALE / 100 * 105;
+            assert_eq!(erc20.accrue(rate), Ok(1_050));
+            assert_eq!(erc20.exchange_rate(), rate);
+            assert_eq!(erc20.balance_of(alice), 630);
+            assert_eq!(erc20.balance_of(bob), 420);
+            assert_eq!(erc20.scaled_balance_of(bob), 400);
+            assert_eq!(
+                erc20.accrue(EXCHANGE_RATE_SCALE),
+                Err(Error::ExchangeRateDecrease)
+            );
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.accrue(rate * 2), Err(Error::NotRebaser));
+            assert_eq!(erc20.transfer(alice, 210), Ok(()));
+            assert_eq!(erc20.scaled_balance_of(bob), 200);
+        }
+
+        #[cfg(feature = "fees")]
+        #[ink::test]
+        fn protocol_fees_are_split_off_the_tax() {
+            let mut erc20 = Erc20::new(10_000);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            assert_eq!(erc20.set_tax_rate(1_000), Ok(()));
+            assert_eq!(
+                erc20.set_protocol_fee_share(BASIS_POINTS + 1),
+                Err(Error::ProtocolFeeShareTooHigh)
+            );
+            assert_eq!(erc20.set_protocol_fee_share(2_500), Ok(()));
+            assert_eq!(erc20.transfer(bob, 4_000), Ok(()));
+            assert_eq!(erc20.accumulated_tax(), 300);
+            assert_eq!(erc20.protocol_fees(), 100);
+            assert_eq!(erc20.protocol_fees_by_source(FeeSource::TransferTax), 100);
+
+            assert_eq!(erc20.collect_protocol_fees(charlie), Err(Error::NotFeeCollector));
+            assert_eq!(erc20.set_fee_collector(Some(charlie)), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            assert_eq!(erc20.collect_protocol_fees(charlie), Ok(100));
+            assert_eq!(erc20.balance_of(charlie), 100);
+            assert_eq!(erc20.protocol_fees(), 0);
+            assert_eq!(erc20.accumulated_tax(), 300);
+        }
+
+        #[cfg(all(feature = "compliance", feature = "pausable"))]
+        #[ink::test]
+        fn circuit_breaker_halts_transfers_after_heavy_volume() {
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let breaker = CircuitBreaker {
+                threshold: 2_000,
+                window: 60,
+            };
+            assert_eq!(
+                erc20.set_circuit_breaker(Some(CircuitBreaker {
+                    threshold: 0,
+                    window: 60
+                })),
+                Err(Error::InvalidCircuitBreaker)
+            );
+            assert_eq!(erc20.set_circuit_breaker(Some(breaker)), Ok(()));
+            assert_eq!(erc20.transfer(bob, 150), Ok(()));
+            assert_eq!(erc20.breaker_volume(), 150);
+
+            // A new window starts the count over.
+            advance_blocks(10);
+            assert_eq!(erc20.breaker_volume(), 0);
+            assert_eq!(erc20.transfer(bob, 150), Ok(()));
+            assert!(!erc20.is_breaker_tripped());
+            // The transfer that passes the threshold still goes through.
+            assert_eq!(erc20.transfer(bob, 51), Ok(()));
+            assert!(erc20.is_breaker_tripped());
+            assert!(matches!(last_event(), Event::CircuitBreakerTripped(_)));
+            assert_eq!(erc20.transfer(bob, 1), Err(Error::CircuitBreakerTripped));
+            assert_eq!(erc20.detect_transfer_restriction(alice, bob, 1), 12);
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.reset_breaker(), Err(Error::NotOwner));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.reset_breaker(), Ok(()));
+            assert_eq!(erc20.breaker_volume(), 0);
+            assert_eq!(erc20.transfer(bob, 1), Ok(()));
+        }
+
+        #[cfg(all(feature = "fees", feature = "pausable"))]
+        #[ink::test]
+        fn guardian_can_only_pause() {
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let guardian = AccountId::from([0x4; 32]);
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(guardian);
+            assert_eq!(erc20.pause(), Err(Error::NotGuardian));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.set_guardian(Some(guardian)), Ok(()));
+            assert_eq!(erc20.guardian(), Some(guardian));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(guardian);
+            assert_eq!(erc20.pause(), Ok(()));
+            match last_event() {
+                Event::Paused(paused) => assert_eq!(paused.caller, guardian),
+                _ => panic!("expected a Paused event"),
+            }
+            assert!(erc20.is_breaker_tripped());
+            assert_eq!(erc20.reset_breaker(), Err(Error::NotOwner));
+            assert_eq!(erc20.set_guardian(None), Err(Error::NotOwner));
+            assert_eq!(erc20.set_tax_rate(100), Err(Error::NotOwner));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.transfer(bob, 1), Err(Error::CircuitBreakerTripped));
+            assert_eq!(erc20.reset_breaker(), Ok(()));
+            assert_eq!(erc20.transfer(bob, 1), Ok(()));
+        }
+
+        #[cfg(feature = "recovery")]
+        #[ink::test]
+        fn recovery_account_claims_ownership_after_missed_heartbeats() {
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let recovery = AccountId::from([0x4; 32]);
+            let switch = DeadManSwitch {
+                recovery,
+                window: 60,
+            };
+            assert_eq!(
+                erc20.set_dead_man_switch(Some(DeadManSwitch {
+                    recovery,
+                    window: 0
+                })),
+                Err(Error::InvalidDeadManSwitch)
+            );
+            assert_eq!(erc20.set_dead_man_switch(Some(switch.clone())), Ok(()));
+            assert_eq!(erc20.dead_man_switch(), Some(switch));
+
+            // Every heartbeat restarts the window.
+            advance_blocks(9);
+            assert_eq!(erc20.heartbeat(), Ok(()));
+            match last_event() {
+                Event::Heartbeat(heartbeat) => {
+                    assert_eq!(heartbeat.lapses_at, erc20.last_heartbeat() + 60)
+                }
+                _ => panic!("expected a Heartbeat event"),
+            }
+            advance_blocks(9);
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(recovery);
+            assert_eq!(erc20.heartbeat(), Err(Error::NotOwner));
+            assert_eq!(erc20.claim_ownership(), Err(Error::OwnerStillActive));
+            advance_blocks(1);
+            assert_eq!(erc20.claim_ownership(), Ok(()));
+            let events = recorded();
+            match decode(&events[events.len() - 2]) {
+                Event::OwnershipClaimed(claimed) => {
+                    assert_eq!(claimed.previous_owner, alice);
+                    assert_eq!(claimed.recovery, recovery);
+                }
+                _ => panic!("expected an OwnershipClaimed event"),
+            }
+            assert_eq!(erc20.owner(), recovery);
+            assert_eq!(erc20.dead_man_switch(), None);
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.claim_ownership(), Err(Error::NotRecoveryAccount));
+        }
+
+        #[cfg(feature = "mintable")]
+        #[ink::test]
+        fn mints_are_limited_per_block() {
+            let mut erc20 = Erc20::new(1_000);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            assert_eq!(erc20.set_max_mint_per_block(Some(100)), Ok(()));
+            assert_eq!(erc20.add_minter(charlie, 1_000), Ok(()));
+            assert_eq!(erc20.mint(bob, 60), Ok(()));
+            assert_eq!(erc20.minted_this_block(), 60);
+
+            // The limit holds for every minter within its cap.
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            assert_eq!(erc20.mint(bob, 41), Err(Error::MintRateExceeded));
+            assert_eq!(erc20.minter_cap(charlie), Some(1_000));
+            assert_eq!(erc20.mint(bob, 40), Ok(()));
+
+            advance_blocks(1);
+            assert_eq!(erc20.minted_this_block(), 0);
+            assert_eq!(erc20.mint(bob, 100), Ok(()));
+            assert_eq!(erc20.balance_of(bob), 200);
+        }
+
+        #[ink::test]
+        fn transfer_from_batch_spends_allowance_once() {
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            let dave = AccountId::from([0x4; 32]);
+            let mut erc20 = Erc20TestBuilder::new()
+                .initial_supply(1_000)
+                .allowance(alice, bob, 300)
+                .caller(bob)
+                .build();
+            assert_eq!(
+                erc20.transfer_from_batch(alice, vec![(charlie, 200), (dave, 101)]),
+                Err(Error::InsufficientAllowance)
+            );
+            assert_eq!(
+                erc20.transfer_from_batch(alice, vec![(charlie, 1), (dave, Balance::MAX)]),
+                Err(Error::InsufficientAllowance)
+            );
+            let before = recorded().len();
+            assert_eq!(
+                erc20.transfer_from_batch(alice, vec![(charlie, 200), (dave, 50)]),
+                Ok(())
+            );
+            let events = recorded();
+            assert_eq!(events.len(), before + 2);
+            assert_transfer(&events[before], Some(alice), Some(charlie), 200);
+            assert_transfer(&events[before + 1], Some(alice), Some(dave), 50);
+            assert_eq!(erc20.allowance(alice, bob), 50);
+            assert_eq!(erc20.balance_of(alice), 750);
+        }
+
+        #[cfg(feature = "compliance")]
+        #[ink::test]
+        fn transfer_restrictions_are_reported() {
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            assert_eq!(erc20.detect_transfer_restriction(alice, bob, 100), 0);
+            assert_eq!(erc20.detect_transfer_restriction(alice, bob, 1_001), 4);
+
+            assert_eq!(erc20.set_denylisted(bob, true), Ok(()));
+            assert_eq!(erc20.detect_transfer_restriction(alice, bob, 100), 1);
+            assert_eq!(erc20.transfer(bob, 100), Err(Error::Denylisted));
+            assert_eq!(erc20.set_denylisted(bob, false), Ok(()));
+
+            assert_eq!(erc20.set_accreditation_required(true), Ok(()));
+            assert_eq!(erc20.detect_transfer_restriction(alice, bob, 100), 3);
+            assert_eq!(erc20.transfer(bob, 100), Err(Error::NotAccredited));
+            assert_eq!(erc20.set_accredited(bob, true), Ok(()));
+            assert_eq!(erc20.transfer(bob, 100), Ok(()));
+
+            assert_eq!(erc20.message_for_restriction(1), "Sender or recipient is denylisted");
+            assert_eq!(erc20.message_for_restriction(200), "Unknown restriction");
+        }
+
+        #[cfg(feature = "compliance")]
+        #[ink::test]
+        fn kyc_answers_are_cached() {
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let registry = AccountId::from([0x9; 32]);
+            assert_eq!(erc20.set_kyc_registry(Some(registry)), Ok(()));
+            // Stand in for answers of the registry, which cannot be called off-chain.
+            erc20.kyc_verified_at.insert((registry, alice), &0);
+            erc20.kyc_verified_at.insert((registry, bob), &0);
+            assert_eq!(erc20.transfer(bob, 100), Ok(()));
+            assert_eq!(erc20.detect_transfer_restriction(alice, bob, 100), 0);
+            assert_eq!(erc20.message_for_restriction(7), "Sender or recipient has not passed KYC");
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.set_kyc_registry(None), Err(Error::NotOwner));
+        }
+
+        #[cfg(all(
+            feature = "burnable",
+            feature = "compliance",
+            feature = "mintable",
+            feature = "soulbound",
+            feature = "streams",
+        ))]
+        #[ink::test]
+        fn soulbound_tokens_only_mint_and_burn() {
+            let mut erc20 = Erc20::new_soulbound(100);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            assert!(erc20.is_soulbound());
+            assert_eq!(erc20.transfer(bob, 10), Err(Error::NonTransferable));
+            assert_eq!(erc20.approve(alice, 10), Ok(()));
+            assert_eq!(erc20.transfer_from(alice, bob, 10), Err(Error::NonTransferable));
+            assert_eq!(erc20.create_stream(bob, 10, 0, 10), Err(Error::NonTransferable));
+            assert_eq!(erc20.detect_transfer_restriction(alice, bob, 10), 8);
+
+            assert_eq!(erc20.mint(bob, 50), Ok(()));
+            assert_eq!(erc20.burn(30), Ok(()));
+            assert_eq!(erc20.balance_of(alice), 70);
+            assert_eq!(erc20.total_supply(), 120);
+            assert_eq!(erc20.total_burned(), 30);
+        }
+
+        #[cfg(all(feature = "compliance", feature = "limits"))]
+        #[ink::test]
+        fn launch_protection_stops_snipers_and_flips() {
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(AccountId::from([0xff; 32]));
+            let mut erc20 = Erc20::new(100_000);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            let pair = AccountId::from([0x6; 32]);
+            assert_eq!(erc20.set_amm_pair(pair, true), Ok(()));
+            assert_eq!(erc20.transfer(pair, 10_000), Ok(()));
+            let protection = LaunchProtection {
+                sell_delay: 2,
+                sniper_blocks: 1,
+                ends_at: 1_000,
+            };
+            assert_eq!(erc20.set_launch_protection(Some(protection)), Ok(()));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(pair);
+            assert_eq!(erc20.transfer(bob, 100), Ok(()));
+            assert!(erc20.is_sniper(bob));
+            advance_blocks(1);
+            assert_eq!(erc20.transfer(charlie, 100), Ok(()));
+            assert!(!erc20.is_sniper(charlie));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.transfer(charlie, 10), Err(Error::FlaggedSniper));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            assert_eq!(erc20.transfer(pair, 10), Err(Error::SellTooSoon));
+            assert_eq!(erc20.detect_transfer_restriction(charlie, pair, 10), 9);
+            assert_eq!(erc20.transfer(bob, 10), Ok(()));
+            advance_blocks(2);
+            assert_eq!(erc20.transfer(pair, 10), Ok(()));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(AccountId::from([0x1; 32]));
+            assert_eq!(erc20.clear_sniper(bob), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.transfer(charlie, 10), Ok(()));
+        }
+
+        #[cfg(feature = "evm")]
+        #[ink::test]
+        fn h160_addresses_map_to_hashed_accounts() {
+            let mut erc20 = Erc20::new(100);
+            let address = [0xab; 20];
+            let mut expected = [0; 32];
+            ink_env::hash_bytes::<Blake2x256>(&[&b"evm:"[..], &address].concat(), &mut expected);
+            assert_eq!(erc20.h160_account(address), AccountId::from(expected));
+
+            assert_eq!(erc20.transfer_to_h160(address, 40), Ok(()));
+            assert_eq!(erc20.balance_of_h160(address), 40);
+            assert_eq!(erc20.balance_of(AccountId::from(expected)), 40);
+        }
+
+        #[cfg(all(feature = "compliance", feature = "locks"))]
+        #[ink::test]
+        fn frozen_accounts_neither_send_nor_receive() {
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let mut erc20 = Erc20TestBuilder::new().initial_supply(50).balance(bob, 50).build();
+            assert_eq!(erc20.freeze(bob), Ok(()));
+            match last_event() {
+                Event::Frozen(frozen) => assert_eq!(frozen.account, bob),
+                _ => panic!("expected a Frozen event"),
+            }
+            assert!(erc20.is_frozen(bob));
+            assert_eq!(erc20.transfer(bob, 10), Err(Error::AccountFrozen));
+            assert_eq!(erc20.detect_transfer_restriction(alice, bob, 10), 11);
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.transfer(alice, 10), Err(Error::AccountFrozen));
+            assert_eq!(erc20.lock(10, 60), Ok(0));
+            assert_eq!(erc20.unfreeze(bob), Err(Error::NotOwner));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.unfreeze(bob), Ok(()));
+            assert!(!erc20.is_frozen(bob));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.transfer(alice, 10), Ok(()));
+        }
+
+        #[cfg(feature = "compliance")]
+        #[ink::test]
+        fn controller_transfer_moves_tokens_without_allowance() {
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            let mut erc20 = Erc20TestBuilder::new().initial_supply(50).balance(bob, 50).build();
+            assert_eq!(
+                erc20.controller_transfer(bob, charlie, 10, Vec::new()),
+                Err(Error::NotController)
+            );
+            assert_eq!(erc20.set_controller(Some(charlie)), Ok(()));
+            assert_eq!(erc20.freeze(bob), Ok(()));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            assert_eq!(
+                erc20.controller_transfer(bob, alice, 51, Vec::new()),
+                Err(Error::InsufficientBalance)
+            );
+            assert_eq!(erc20.controller_transfer(bob, alice, 30, b"order 7".to_vec()), Ok(()));
+            match last_event() {
+                Event::ControllerTransfer(event) => {
+                    assert_eq!(event.controller, charlie);
+                    assert_eq!((event.from, event.to, event.value), (bob, alice, 30));
+                    assert_eq!(event.reason, b"order 7".to_vec());
+                }
+                _ => panic!("expected a ControllerTransfer event"),
+            }
+            let events = recorded();
+            assert_transfer(&events[events.len() - 2], Some(bob), Some(alice), 30);
+            assert_eq!(erc20.balance_of(bob), 20);
+            assert_eq!(erc20.balance_of(alice), 80);
+            assert_eq!(erc20.allowance(bob, charlie), 0);
+        }
+
+        #[cfg(feature = "compliance")]
+        #[ink::test]
+        fn controller_cannot_take_from_protected_accounts() {
+            let alice = AccountId::from([0x1; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            let contract = AccountId::from([0xff; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            let mut erc20 = Erc20TestBuilder::new().balance(contract, 50).build();
+            assert_eq!(erc20.set_controller(Some(charlie)), Ok(()));
+            assert_eq!(erc20.freeze(contract), Ok(()));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            assert_eq!(
+                erc20.controller_transfer(contract, alice, 10, Vec::new()),
+                Err(Error::ProtectedAccount)
+            );
+            assert_eq!(erc20.wipe(contract), Err(Error::ProtectedAccount));
+            #[cfg(feature = "splitter")]
+            assert_eq!(
+                erc20.controller_transfer(erc20.splitter_account(), alice, 0, Vec::new()),
+                Err(Error::ProtectedAccount)
+            );
+            assert_eq!(erc20.balance_of(contract), 50);
+        }
+
+        #[cfg(all(feature = "compliance", feature = "locks"))]
+        #[ink::test]
+        fn wipe_burns_balance_of_frozen_accounts() {
+            let mut erc20 = Erc20::new(100);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            assert_eq!(erc20.transfer(bob, 40), Ok(()));
+            assert_eq!(erc20.set_controller(Some(charlie)), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.lock(15, 60), Ok(0));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            assert_eq!(erc20.wipe(bob), Err(Error::AccountNotFrozen));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.freeze(bob), Ok(()));
+            assert_eq!(erc20.wipe(bob), Err(Error::NotController));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            assert_eq!(erc20.wipe(bob), Ok(40));
+            match last_event() {
+                Event::Wiped(wiped) => assert_eq!((wiped.account, wiped.value), (bob, 40)),
+                _ => panic!("expected a Wiped event"),
+            }
+            let events = recorded();
+            assert_transfer(&events[events.len() - 4], Some(bob), None, 40);
+            assert_eq!(erc20.total_burned(), 40);
+            assert_eq!(erc20.balance_of(bob), 0);
+            assert_eq!(erc20.locked_balance_of(bob), 0);
+            assert_eq!(erc20.total_supply(), 60);
+
+            assert_eq!(erc20.lock_count(bob), 0);
+            assert_eq!(erc20.token_lock(bob, 0), None);
+
+            // Locks made after the wipe hold their tokens back in full.
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.unfreeze(bob), Ok(()));
+            assert_eq!(erc20.transfer(bob, 20), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.lock(10, 60), Ok(0));
+            advance_blocks(10);
+            assert_eq!(erc20.locked_balance_of(bob), 10);
+            assert_eq!(erc20.unlock(0), Ok(10));
+            assert_eq!(erc20.locked_balance_of(bob), 0);
+            assert_eq!(erc20.unlock(0), Err(Error::LockNotFound));
+        }
+
+        #[cfg(feature = "documents")]
+        #[ink::test]
+        fn documents_can_be_set_listed_and_removed() {
+            let mut erc20 = Erc20::new(100);
+            let name = |name: &str| String::from(name);
+            let hash = Hash::from([0x7; 32]);
+            assert_eq!(erc20.set_document(name("terms"), name("ipfs://terms"), hash), Ok(()));
+            assert_eq!(erc20.set_document(name("prospectus"), name("ipfs://p1"), hash), Ok(()));
+            assert_eq!(erc20.set_document(name("prospectus"), name("ipfs://p2"), hash), Ok(()));
+            match last_event() {
+                Event::DocumentUpdated(event) => assert_eq!(event.uri, "ipfs://p2"),
+                _ => panic!("expected a DocumentUpdated event"),
+            }
+            assert_eq!(
+                erc20.get_document(name("prospectus")).map(|document| document.uri),
+                Some(name("ipfs://p2"))
+            );
+            assert_eq!(
+                erc20.document_names(0, 10),
+                vec![name("terms"), name("prospectus")]
+            );
+
+            assert_eq!(erc20.remove_document(name("terms")), Ok(()));
+            assert!(matches!(last_event(), Event::DocumentRemoved(_)));
+            assert_eq!(erc20.get_document(name("terms")), None);
+            assert_eq!(erc20.document_names(0, 10), vec![name("prospectus")]);
+            assert_eq!(erc20.remove_document(name("terms")), Err(Error::DocumentNotFound));
+
+            let bob = AccountId::from([0x2; 32]);
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(
+                erc20.set_document(name("terms"), name("ipfs://fake"), hash),
+                Err(Error::NotOwner)
+            );
+            assert_eq!(erc20.remove_document(name("prospectus")), Err(Error::NotOwner));
+        }
+
+        #[cfg(all(feature = "compliance", feature = "partitions"))]
+        #[ink::test]
+        fn partitioned_tokens_move_only_by_partition() {
+            let mut erc20 = Erc20::new(100);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            let restricted = || String::from("restricted");
+            assert_eq!(erc20.issue_by_partition(restricted(), bob, 30), Ok(()));
+            assert!(matches!(last_event(), Event::PartitionChanged(_)));
+            assert_eq!(erc20.transfer(bob, 10), Ok(()));
+            assert_eq!(erc20.balance_of(bob), 40);
+            assert_eq!(erc20.balance_of_by_partition(bob, restricted()), 30);
+            assert_eq!(erc20.partitions_of(bob), vec![restricted()]);
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.transfer(alice, 11), Err(Error::TokensPartitioned));
+            assert_eq!(erc20.detect_transfer_restriction(bob, alice, 11), 2);
+            assert_eq!(
+                erc20.transfer_by_partition(restricted(), alice, 31),
+                Err(Error::InsufficientPartitionBalance)
+            );
+            assert_eq!(erc20.transfer_by_partition(restricted(), alice, 20), Ok(()));
+            assert_eq!(erc20.balance_of_by_partition(bob, restricted()), 10);
+            assert_eq!(erc20.balance_of_by_partition(alice, restricted()), 20);
+            assert_eq!(erc20.balance_of(alice), 110);
+
+            // Operators are scoped to a single partition.
+            assert_eq!(
+                erc20.authorize_operator_by_partition(restricted(), charlie),
+                Ok(())
+            );
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            assert_eq!(
+                erc20.operator_transfer_by_partition(String::from("free"), bob, charlie, 1),
+                Err(Error::NotPartitionOperator)
+            );
+            assert_eq!(
+                erc20.operator_transfer_by_partition(restricted(), bob, charlie, 10),
+                Ok(())
+            );
+            match last_event() {
+                Event::TransferByPartition(event) => assert_eq!(event.operator, Some(charlie)),
+                _ => panic!("expected a TransferByPartition event"),
+            }
+            assert_eq!(erc20.partitions_of(bob), Vec::<String>::new());
+            assert_eq!(erc20.balance_of(bob), 10);
+
+            // The owner moves tokens between partitions and the free balance.
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(
+                erc20.change_partition(charlie, Some(restricted()), None, 4),
+                Ok(())
+            );
+            assert_eq!(erc20.balance_of_by_partition(charlie, restricted()), 6);
+            assert_eq!(
+                erc20.change_partition(charlie, None, Some(restricted()), 5),
+                Err(Error::InsufficientPartitionBalance)
+            );
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            assert_eq!(erc20.transfer(alice, 4), Ok(()));
+            assert_eq!(erc20.transfer(alice, 1), Err(Error::TokensPartitioned));
+        }
+
+        #[cfg(all(feature = "fees", feature = "holders", feature = "locks"))]
+        #[ink::test]
+        fn sweep_dust_clears_small_balances_in_pages() {
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(AccountId::from([0xff; 32]));
+            let mut erc20 = Erc20::new(100);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            let django = AccountId::from([0x4; 32]);
+            let treasury = AccountId::from([0x5; 32]);
+            assert_eq!(erc20.sweep_dust(10), Err(Error::DustThresholdNotSet));
+            assert_eq!(erc20.transfer(bob, 5), Ok(()));
+            assert_eq!(erc20.transfer(charlie, 3), Ok(()));
+            assert_eq!(erc20.transfer(django, 2), Ok(()));
+            assert_eq!(erc20.set_dust_threshold(Some(10)), Ok(()));
+
+            // Alice and Bob are checked; Charlie takes Bob's slot.
+            assert_eq!(erc20.sweep_dust(2), Ok(1));
+            assert_eq!(erc20.balance_of(bob), 0);
+            assert_eq!(erc20.total_supply(), 95);
+            assert_eq!(erc20.holders_count(), 3);
+            match last_event() {
+                Event::DustSwept(swept) => {
+                    assert_eq!((swept.holders, swept.value, swept.treasury), (1, 5, None))
+                }
+                _ => panic!("expected a DustSwept event"),
+            }
+
+            // Locked dust is left alone; the rest goes to the treasury.
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            assert_eq!(erc20.lock(1, 1_000), Ok(0));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.set_treasury(Some(treasury), None), Ok(()));
+            assert_eq!(erc20.sweep_dust(5), Ok(1));
+            assert_eq!(erc20.balance_of(charlie), 3);
+            assert_eq!(erc20.balance_of(django), 0);
+            assert_eq!(erc20.balance_of(treasury), 2);
+            assert_eq!(erc20.total_supply(), 95);
+            assert_eq!(erc20.holders(0, 10), [alice, charlie, treasury]);
+        }
+
+        #[cfg(all(feature = "holders", feature = "splitter"))]
+        #[ink::test]
+        fn sweep_dust_leaves_splitter_alone() {
+            let mut erc20 = Erc20::new(100);
+            let bob = AccountId::from([0x2; 32]);
+            let splitter = erc20.splitter_account();
+            assert_eq!(erc20.add_payee(bob, 1), Ok(()));
+            assert_eq!(erc20.transfer(splitter, 5), Ok(()));
+            assert_eq!(erc20.set_dust_threshold(Some(10)), Ok(()));
+
+            assert_eq!(erc20.sweep_dust(10), Ok(0));
+            assert_eq!(erc20.balance_of(splitter), 5);
+            assert_eq!(erc20.release_payment(bob), Ok(5));
+        }
+
+        #[cfg(feature = "holders")]
+        #[ink::test]
+        fn verify_supply_walks_holders_in_pages() {
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            let mut erc20 = Erc20TestBuilder::new()
+                .initial_supply(50)
+                .balance(bob, 30)
+                .balance(charlie, 20)
+                .build();
+            assert_eq!(
+                erc20.verify_supply(2),
+                Ok(AuditStatus::InProgress {
+                    checked: 2,
+                    holders: 3
+                })
+            );
+            assert_eq!(erc20.verify_supply(2), Ok(AuditStatus::Verified));
+            assert!(matches!(last_event(), Event::SupplyVerified(_)));
+
+            // Balance changes in between carry on with the audit.
+            assert_eq!(erc20.verify_supply(2).map(|_| ()), Ok(()));
+            assert_eq!(erc20.transfer(charlie, 1), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            // Charlie moves into Bob's slot, among the holders checked.
+            assert_eq!(erc20.transfer(charlie, 30), Ok(()));
+            assert_eq!(erc20.verify_supply(1), Ok(AuditStatus::Verified));
+
+            erc20.balances.insert(charlie, &56);
+            assert_eq!(
+                erc20.verify_supply(3),
+                Err(AuditError::Mismatch {
+                    recorded: 105,
+                    total_supply: 100
+                })
+            );
+        }
+
+        #[cfg(feature = "spending-limits")]
+        #[ink::test]
+        fn spending_limits_cap_each_period() {
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            assert_eq!(erc20.approve_with_limit(bob, 100, 0), Err(Error::InvalidSpendingPeriod));
+            assert_eq!(erc20.allowance(alice, bob), 0);
+            assert_eq!(erc20.spending_limit(alice, bob), None);
+            assert_eq!(erc20.approve_with_limit(bob, 100, 60), Ok(()));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.transfer_from(alice, charlie, 70), Ok(()));
+            assert_eq!(
+                erc20.transfer_from(alice, charlie, 31),
+                Err(Error::SpendingLimitExceeded)
+            );
+            assert_eq!(erc20.transfer_from(alice, charlie, 30), Ok(()));
+            assert_eq!(erc20.spending_limit(alice, bob).map(|limit| limit.spent), Some(100));
+
+            advance_blocks(10);
+            let limit = erc20.spending_limit(alice, bob).unwrap();
+            assert_eq!((limit.period_start, limit.spent), (60, 0));
+            assert_eq!(erc20.transfer_from(alice, charlie, 100), Ok(()));
+            assert_eq!(erc20.balance_of(charlie), 200);
+
+            // A plain approval replaces the limit.
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.approve(bob, 500), Ok(()));
+            assert_eq!(erc20.spending_limit(alice, bob), None);
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.transfer_from(alice, charlie, 500), Ok(()));
+        }
+
+        #[cfg(feature = "holders")]
+        #[ink::test]
+        fn held_since_tracks_first_acquisition() {
+            let mut erc20 = Erc20::new(100);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            assert_eq!(erc20.held_since(alice), Some(0));
+            assert_eq!(erc20.held_since(bob), None);
+            advance_blocks(2);
+            assert_eq!(erc20.transfer(bob, 10), Ok(()));
+            advance_blocks(2);
+            assert_eq!(erc20.transfer(bob, 10), Ok(()));
+            assert_eq!(erc20.held_since(bob), Some(2));
+            assert!(erc20.meets_threshold(bob, 20));
+            assert!(!erc20.meets_threshold(bob, 21));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.transfer(alice, 20), Ok(()));
+            assert_eq!(erc20.held_since(bob), None);
+            assert_eq!(erc20.held_since(alice), Some(0));
+        }
+
+        #[ink::test]
+        fn zero_transfer_policy_controls_zero_transfers() {
+            let mut erc20 = Erc20::new(100);
+            let bob = AccountId::from([0x2; 32]);
+            let events = || ink_env::test::recorded_events().count();
+            assert_eq!(erc20.zero_transfer_policy(), ZeroTransferPolicy::Emit);
+            let before = events();
+            assert_eq!(erc20.transfer(bob, 0), Ok(()));
+            assert_eq!(events(), before + 1);
+            let alice = AccountId::from([0x1; 32]);
+            assert_transfer(recorded().last().unwrap(), Some(alice), Some(bob), 0);
+            #[cfg(feature = "holders")]
+            assert_eq!(erc20.holders_count(), 1);
+
+            assert_eq!(erc20.set_zero_transfer_policy(ZeroTransferPolicy::Silent), Ok(()));
+            let before = events();
+            assert_eq!(erc20.transfer(bob, 0), Ok(()));
+            assert_eq!(events(), before);
+
+            assert_eq!(erc20.set_zero_transfer_policy(ZeroTransferPolicy::Reject), Ok(()));
+            assert_eq!(erc20.transfer(bob, 0), Err(Error::ZeroAmount));
+            #[cfg(feature = "compliance")]
+            assert_eq!(erc20.detect_transfer_restriction(bob, bob, 0), 10);
+            assert_eq!(erc20.transfer(bob, 1), Ok(()));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(
+                erc20.set_zero_transfer_policy(ZeroTransferPolicy::Emit),
+                Err(Error::NotOwner)
+            );
+        }
+
+        #[cfg(feature = "evm")]
+        #[ink::test]
+        fn eth_facade_returns_bool() {
+            let mut erc20 = Erc20::new(100);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            assert!(erc20.eth_transfer(bob, 10));
+            assert!(!erc20.eth_transfer(bob, 91));
+            assert!(erc20.eth_approve(alice, 20));
+            assert!(erc20.eth_transfer_from(alice, bob, 20));
+            assert!(!erc20.eth_transfer_from(alice, bob, 1));
+            assert_eq!(erc20.balance_of(bob), 30);
+        }
+
+        #[cfg(all(feature = "elastic", feature = "fees", feature = "holders"))]
+        #[ink::test]
+        fn transfer_all_empties_the_account() {
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            assert_eq!(erc20.transfer(bob, 333), Ok(()));
+            assert_eq!(erc20.rebase(-1), Ok(999));
+            assert_eq!(erc20.set_burn_rate(100), Ok(()));
+            assert_eq!(erc20.holders_count(), 2);
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.transfer_all(alice), Ok(332));
+            // The dust left by rounding moves with its own transfer event.
+            let events = recorded();
+            assert_transfer(&events[events.len() - 1], Some(bob), Some(alice), 0);
+            assert_eq!(erc20.gons_of(&bob), 0);
+            assert_eq!(erc20.holders_count(), 1);
+            assert_eq!(erc20.balance_of(alice), erc20.total_supply());
+        }
+
+        #[cfg(feature = "elastic")]
+        #[ink::test]
+        fn rebase_is_restricted() {
+            let mut erc20 = Erc20::new(1_000);
+            let bob = AccountId::from([0x2; 32]);
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.rebase(1), Err(Error::NotRebaser));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(AccountId::from([0x1; 32]));
+            assert_eq!(erc20.set_rebaser(Some(bob)), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.rebase(1), Ok(1_001));
+        }
+
+        #[cfg(feature = "bridge")]
+        #[ink::test]
+        fn bridge_round_trip() {
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            assert_eq!(erc20.bridge_lock(alice, 100, 7, vec![0xab; 20]), Err(Error::NotBridge));
+            assert_eq!(erc20.set_bridge(Some(bob)), Ok(()));
+            assert_eq!(erc20.approve(bob, 100), Ok(()));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(
+                erc20.bridge_lock(alice, 101, 7, vec![0xab; 20]),
+                Err(Error::InsufficientAllowance)
+            );
+            assert_eq!(erc20.bridge_lock(alice, 100, 7, vec![0xab; 20]), Ok(()));
+            assert_eq!(erc20.balance_of(alice), 900);
+            assert_eq!(erc20.total_supply(), 900);
+            assert_eq!(erc20.allowance(alice, bob), 0);
+
+            let source_tx = Hash::from([0x5; 32]);
+            assert!(!erc20.is_bridged_in(source_tx));
+            assert_eq!(erc20.bridge_unlock(alice, 40, source_tx), Ok(()));
+            assert!(erc20.is_bridged_in(source_tx));
+            assert_eq!(erc20.bridge_unlock(alice, 40, source_tx), Err(Error::AlreadyBridged));
+            assert_eq!(erc20.balance_of(alice), 940);
+            assert_eq!(erc20.total_supply(), 940);
+
+            match last_event() {
+                Event::BridgedIn(bridged) => {
+                    assert_eq!(bridged.to, alice);
+                    assert_eq!(bridged.source_tx, source_tx);
+                }
+                _ => panic!("expected a BridgedIn event"),
+            }
+        }
+
+        /// Signs `hash` with `secret` the way `transfer_with_signature` expects
+        /// and returns the signature with the signer's account.
+        #[cfg(any(feature = "signed-transfers", feature = "vouchers"))]
+        fn sign(secret: [u8; 32], hash: Hash) -> ([u8; 65], AccountId) {
+            let secp = secp256k1::Secp256k1::new();
+            let secret = secp256k1::SecretKey::from_slice(&secret).unwrap();
+            let message = secp256k1::Message::from_slice(hash.as_ref()).unwrap();
+            let (recovery_id, compact) = secp
+                .sign_ecdsa_recoverable(&message, &secret)
+                .serialize_compact();
+            let mut signature = [0; 65];
+            signature[..64].copy_from_slice(&compact);
+            signature[64] = recovery_id.to_i32() as u8;
+            let public_key = secp256k1::PublicKey::from_secret_key(&secp, &secret).serialize();
+            let mut account = [0; 32];
+            ink_env::hash_bytes::<Blake2x256>(&public_key, &mut account);
+            (signature, AccountId::from(account))
+        }
+
+        #[cfg(feature = "signed-transfers")]
+        #[ink::test]
+        fn transfer_with_signature_works_once() {
+            let mut erc20 = Erc20::new(1_000);
+            let bob = AccountId::from([0x2; 32]);
+            let (_, signer) = sign([0x42; 32], Hash::default());
+            assert_eq!(erc20.transfer(signer, 100), Ok(()));
+
+            let hash = erc20.transfer_signing_hash(signer, bob, 30, 0, 1_000);
+            let (signature, _) = sign([0x42; 32], hash);
+            assert_eq!(
+                erc20.transfer_with_signature(signer, bob, 31, 0, 1_000, signature),
+                Err(Error::InvalidSignature)
+            );
+            assert_eq!(erc20.transfer_with_signature(signer, bob, 30, 0, 1_000, signature), Ok(()));
+            assert_eq!(erc20.balance_of(signer), 70);
+            assert_eq!(erc20.balance_of(bob), 30);
+            assert_eq!(erc20.nonce_of(signer), 1);
+            assert_eq!(
+                erc20.transfer_with_signature(signer, bob, 30, 0, 1_000, signature),
+                Err(Error::InvalidNonce)
+            );
+        }
+
+        #[cfg(feature = "signed-transfers")]
+        #[ink::test]
+        fn transfer_with_signature_checks_deadline_and_signer() {
+            let mut erc20 = Erc20::new(1_000);
+            let bob = AccountId::from([0x2; 32]);
+            let (_, signer) = sign([0x42; 32], Hash::default());
+            assert_eq!(erc20.transfer(signer, 100), Ok(()));
+
+            let hash = erc20.transfer_signing_hash(signer, bob, 30, 0, 1_000);
+            let (signature, _) = sign([0x43; 32], hash);
+            assert_eq!(
+                erc20.transfer_with_signature(signer, bob, 30, 0, 1_000, signature),
+                Err(Error::InvalidSignature)
+            );
+            let hash = erc20.transfer_signing_hash(signer, bob, 30, 0, 0);
+            let (signature, _) = sign([0x42; 32], hash);
+            assert_eq!(
+                erc20.transfer_with_signature(signer, bob, 30, 0, 0, signature),
+                Err(Error::SignatureExpired)
+            );
+            assert_eq!(erc20.nonce_of(signer), 0);
+        }
+
+
+        #[cfg(feature = "governance")]
+        #[ink::test]
+        fn upgrades_go_through_governance() {
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(AccountId::from([0xff; 32]));
+            let mut erc20 = Erc20::new(1_000);
+            assert_eq!(erc20.set_code([7; 32]), Err(Error::UpgradeNotApproved));
+            let config = GovernanceConfig {
+                voting_period: 10,
+                quorum: 4_000,
+                timelock: 1_000,
+                proposal_threshold: 0,
+            };
+            assert_eq!(erc20.set_governance_config(config), Ok(()));
+            assert_eq!(erc20.propose_upgrade([7; 32], Hash::default()), Ok(0));
+            assert_eq!(erc20.cast_vote(0, true), Ok(()));
+            assert_eq!(erc20.pending_upgrade(), None);
+
+            advance_blocks(11);
+            let eta = erc20.queue(0).unwrap();
+            assert_eq!(erc20.pending_upgrade(), Some((Hash::from([7; 32]), eta)));
+            assert_eq!(erc20.execute(0), Err(Error::TimelockNotExpired));
+        }
+
+        #[ink::test]
+        fn set_code_replaces_the_code_hash() {
+            let contract = AccountId::from([0xff; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            let mut erc20 = Erc20::new(1_000);
+            assert_eq!(erc20.code_hash(), Hash::default());
+
+            // As an executed proposal would.
+            #[cfg(feature = "governance")]
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(contract);
+            assert_eq!(erc20.set_code([7; 32]), Ok(()));
+            assert_eq!(erc20.code_hash(), Hash::from([7; 32]));
+            match last_event() {
+                Event::CodeUpdated(updated) => {
+                    assert_eq!(updated.old, Hash::default());
+                    assert_eq!(updated.new, Hash::from([7; 32]));
+                }
+                _ => panic!("expected a CodeUpdated event"),
+            }
+        }
+
+        #[cfg(feature = "governance")]
+        #[ink::test]
+        fn governance_proposal_lifecycle() {
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            let mut erc20 = Erc20TestBuilder::new()
+                .initial_supply(600)
+                .balance(bob, 300)
+                .balance(charlie, 100)
+                .build();
+            assert_eq!(
+                erc20.propose(charlie, vec![0; 4], Hash::default()),
+                Err(Error::GovernanceDisabled)
+            );
+            let config = GovernanceConfig {
+                voting_period: 10,
+                quorum: 4_000,
+                timelock: 1_000,
+                proposal_threshold: 200,
+            };
+            assert_eq!(erc20.set_governance_config(config), Ok(()));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            assert_eq!(
+                erc20.propose(charlie, vec![0; 4], Hash::default()),
+                Err(Error::BelowProposalThreshold)
+            );
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.propose(charlie, vec![0; 4], Hash::default()), Ok(0));
+            assert_eq!(erc20.proposal_state(0), Some(ProposalState::Active));
+            assert_eq!(erc20.cast_vote(0, true), Ok(()));
+            assert_eq!(erc20.cast_vote(0, true), Err(Error::AlreadyVoted));
+            // Tokens backing the vote stay put until voting ends.
+            assert_eq!(erc20.transfer(alice, 1), Err(Error::TokensLockedByVote));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            assert_eq!(erc20.cast_vote(0, false), Ok(()));
+            // Quorum is 400 tokens and only 300 voted in favour.
+            advance_blocks(11);
+            assert_eq!(erc20.proposal_state(0), Some(ProposalState::Defeated));
+            assert_eq!(erc20.queue(0), Err(Error::InvalidProposalState));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.transfer(alice, 1), Ok(()));
+            assert_eq!(erc20.propose(charlie, vec![0; 4], Hash::default()), Ok(1));
+            assert_eq!(erc20.cast_vote(1, true), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.cast_vote(1, true), Ok(()));
+            advance_blocks(11);
+            assert_eq!(erc20.proposal_state(1), Some(ProposalState::Succeeded));
+            assert!(erc20.queue(1).is_ok());
+            assert_eq!(erc20.proposal_state(1), Some(ProposalState::Queued));
+            assert_eq!(erc20.execute(1), Err(Error::TimelockNotExpired));
+        }
+
+        #[cfg(feature = "governance")]
+        #[ink::test]
+        fn governance_cannot_move_the_contracts_tokens() {
+            let contract = AccountId::from([0xff; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            let mut erc20 = Erc20::new(1_000);
+            let config = GovernanceConfig {
+                voting_period: 10,
+                quorum: 4_000,
+                timelock: 1_000,
+                proposal_threshold: 0,
+            };
+            assert_eq!(erc20.set_governance_config(config), Ok(()));
+            let mut drain = ink_lang::selector_bytes!("transfer").to_vec();
+            drain.extend_from_slice(&scale::Encode::encode(&(AccountId::from([0x2; 32]), 1u128)));
+            assert_eq!(
+                erc20.propose(contract, drain.clone(), Hash::default()),
+                Err(Error::SelectorNotGovernable)
+            );
+            assert_eq!(
+                erc20.propose(contract, vec![0; 2], Hash::default()),
+                Err(Error::SelectorNotGovernable)
+            );
+            // Other contracts may still be called with any message.
+            assert_eq!(
+                erc20.propose(AccountId::from([0x3; 32]), drain.clone(), Hash::default()),
+                Ok(0)
+            );
+            let mut set_tax = ink_lang::selector_bytes!("set_tax_rate").to_vec();
+            set_tax.extend_from_slice(&scale::Encode::encode(&100u16));
+            assert_eq!(erc20.propose(contract, set_tax, Hash::default()), Ok(1));
+            // Except for the reward tokens the contract holds for its holders.
+            #[cfg(feature = "dividends")]
+            {
+                let reward = AccountId::from([0x8; 32]);
+                assert_eq!(erc20.distribute_rewards(reward, 100), Ok(()));
+                assert_eq!(
+                    erc20.propose(reward, drain, Hash::default()),
+                    Err(Error::TargetNotGovernable)
+                );
+            }
+        }
+
+        #[cfg(feature = "sale")]
+        fn sale_config(beneficiary: AccountId) -> SaleConfig {
+            SaleConfig {
+                rate: 10,
+                start: 0,
+                // Ten blocks of the off-chain environment.
+                end: 60,
+                min_purchase: 10,
+                max_purchase: 60,
+                soft_cap: 50,
+                hard_cap: 100,
+                beneficiary,
+            }
+        }
+
+        #[cfg(feature = "sale")]
+        #[ink::test]
+        fn successful_sale_delivers_tokens() {
+            let contract = AccountId::from([0xff; 32]);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let beneficiary = AccountId::from([0x7; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            let mut erc20 = Erc20::new(5_000);
+            assert_eq!(erc20.start_sale(sale_config(beneficiary)), Ok(()));
+            assert_eq!(erc20.balance_of(alice), 4_000);
+            assert_eq!(erc20.start_sale(sale_config(beneficiary)), Err(Error::SaleAlreadyStarted));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            ink_env::test::set_value_transferred::<ink_env::DefaultEnvironment>(5);
+            assert_eq!(erc20.buy(), Err(Error::PurchaseOutOfBounds));
+            ink_env::test::set_value_transferred::<ink_env::DefaultEnvironment>(60);
+            assert_eq!(erc20.buy(), Ok(600));
+            ink_env::test::set_value_transferred::<ink_env::DefaultEnvironment>(10);
+            assert_eq!(erc20.buy(), Err(Error::PurchaseOutOfBounds));
+            assert_eq!(erc20.finalize_sale(), Err(Error::SaleNotClosed));
+            assert_eq!(erc20.claim_sale_tokens(), Err(Error::NothingToClaim));
+
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 60);
+            advance_blocks(10);
+            assert_eq!(erc20.buy(), Err(Error::SaleNotOpen));
+            assert_eq!(erc20.finalize_sale(), Ok(()));
+            assert_eq!(
+                ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(beneficiary),
+                Ok(60)
+            );
+            // Unsold tokens went back to the seller.
+            assert_eq!(erc20.balance_of(alice), 4_400);
+            assert_eq!(erc20.claim_sale_tokens(), Ok(600));
+            assert_eq!(erc20.balance_of(bob), 600);
+            assert_eq!(erc20.balance_of(contract), 0);
+            assert_eq!(erc20.refund(), Err(Error::NothingToClaim));
+        }
+
+        #[cfg(all(feature = "sale", feature = "elastic"))]
+        #[ink::test]
+        fn sale_tokens_follow_rebases() {
+            let contract = AccountId::from([0xff; 32]);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            let mut erc20 = Erc20::new(5_000);
+            assert_eq!(erc20.start_sale(sale_config(AccountId::from([0x7; 32]))), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            ink_env::test::set_value_transferred::<ink_env::DefaultEnvironment>(60);
+            assert_eq!(erc20.buy(), Ok(600));
+
+            // Doubling every balance doubles what the sale holds for buyers.
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.rebase(5_000), Ok(10_000));
+            assert_eq!(erc20.balance_of(contract), 2_000);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 60);
+            advance_blocks(10);
+            assert_eq!(erc20.finalize_sale(), Ok(()));
+            assert_eq!(erc20.balance_of(alice), 8_000 + 800);
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.claim_sale_tokens(), Ok(1_200));
+            assert_eq!(erc20.balance_of(contract), 0);
+        }
+
+        #[cfg(all(feature = "sale", feature = "compliance"))]
+        #[ink::test]
+        fn frozen_buyers_cannot_claim_sale_tokens() {
+            let contract = AccountId::from([0xff; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            let mut erc20 = Erc20::new(5_000);
+            assert_eq!(erc20.start_sale(sale_config(AccountId::from([0x7; 32]))), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            ink_env::test::set_value_transferred::<ink_env::DefaultEnvironment>(60);
+            assert_eq!(erc20.buy(), Ok(600));
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 60);
+            advance_blocks(10);
+            assert_eq!(erc20.finalize_sale(), Ok(()));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(AccountId::from([0x1; 32]));
+            assert_eq!(erc20.freeze(bob), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.claim_sale_tokens(), Err(Error::AccountFrozen));
+            assert_eq!(erc20.balance_of(contract), 600);
+        }
+
+        #[cfg(feature = "sale")]
+        #[ink::test]
+        fn failed_sale_refunds_buyers() {
+            let contract = AccountId::from([0xff; 32]);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            let mut erc20 = Erc20::new(5_000);
+            assert_eq!(erc20.start_sale(sale_config(alice)), Ok(()));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            ink_env::test::set_value_transferred::<ink_env::DefaultEnvironment>(40);
+            assert_eq!(erc20.buy(), Ok(400));
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 40);
+            advance_blocks(10);
+            assert_eq!(erc20.finalize_sale(), Ok(()));
+            assert_eq!(erc20.balance_of(alice), 5_000);
+            assert_eq!(erc20.claim_sale_tokens(), Err(Error::NothingToClaim));
+
+            let before = ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(bob)
+                .unwrap_or_default();
+            assert_eq!(erc20.refund(), Ok(40));
+            assert_eq!(
+                ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(bob),
+                Ok(before + 40)
+            );
+            assert_eq!(erc20.refund(), Err(Error::NothingToClaim));
+        }
+
+        #[cfg(all(feature = "fees", feature = "sale"))]
+        #[ink::test]
+        fn purchase_mints_within_caps() {
+            let contract = AccountId::from([0xff; 32]);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let treasury = AccountId::from([0x7; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            let mut erc20 = Erc20::new(1_000);
+            ink_env::test::set_value_transferred::<ink_env::DefaultEnvironment>(10);
+            assert_eq!(erc20.purchase(), Err(Error::NoSale));
+            let sale = MintSale {
+                rate: 10,
+                max_per_account: Some(300),
+                max_total: Some(500),
+            };
+            assert_eq!(erc20.set_mint_sale(Some(sale)), Ok(()));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.purchase(), Ok(100));
+            assert_eq!(erc20.balance_of(bob), 100);
+            assert_eq!(erc20.total_supply(), 1_100);
+            ink_env::test::set_value_transferred::<ink_env::DefaultEnvironment>(25);
+            assert_eq!(erc20.purchase(), Err(Error::PurchaseOutOfBounds));
+            ink_env::test::set_value_transferred::<ink_env::DefaultEnvironment>(0);
+            assert_eq!(erc20.purchase(), Err(Error::PurchaseOutOfBounds));
+            ink_env::test::set_value_transferred::<ink_env::DefaultEnvironment>(20);
+            assert_eq!(erc20.purchase(), Ok(200));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            ink_env::test::set_value_transferred::<ink_env::DefaultEnvironment>(21);
+            assert_eq!(erc20.purchase(), Err(Error::HardCapExceeded));
+            assert_eq!(erc20.mint_sale_purchased(bob), 300);
+            assert_eq!(erc20.mint_sale_sold(), 300);
+            assert_eq!(erc20.mint_proceeds(), 30);
+
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 30);
+            assert_eq!(erc20.set_treasury(Some(treasury), None), Ok(()));
+            assert_eq!(erc20.withdraw_proceeds(), Ok(30));
+            assert_eq!(
+                ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(treasury),
+                Ok(30)
+            );
+            assert_eq!(erc20.withdraw_proceeds(), Err(Error::NothingToClaim));
+        }
+
+        #[cfg(feature = "raffles")]
+        #[ink::test]
+        fn raffle_winners_share_the_pot() {
+            let contract = AccountId::from([0xff; 32]);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            let mut erc20 = Erc20::new(1_000);
+            assert_eq!(erc20.transfer(bob, 300), Ok(()));
+            assert_eq!(erc20.transfer(charlie, 300), Ok(()));
+            let secret = Hash::from([0x42; 32]);
+            let mut commitment = [0; 32];
+            ink_env::hash_encoded::<Blake2x256, _>(&secret, &mut commitment);
+            let commitment = Hash::from(commitment);
+            assert_eq!(erc20.start_raffle(0, 60, 2, None), Err(Error::InvalidRaffle));
+            assert_eq!(
+                erc20.start_raffle(10, 60, MAX_RAFFLE_WINNERS + 1, None),
+                Err(Error::InvalidRaffle)
+            );
+            assert_eq!(erc20.start_raffle(10, 60, 2, Some(commitment)), Ok(0));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.buy_tickets(1, 1), Err(Error::RaffleNotFound));
+            assert_eq!(erc20.buy_tickets(0, 0), Err(Error::InvalidRaffle));
+            assert_eq!(erc20.buy_tickets(0, 3), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            assert_eq!(erc20.buy_tickets(0, 31), Err(Error::InsufficientBalance));
+            assert_eq!(erc20.buy_tickets(0, 2), Ok(()));
+            assert_eq!(erc20.buy_tickets(0, 1), Ok(()));
+            assert_eq!(erc20.raffle_tickets_of(0, charlie), 3);
+            assert_eq!(erc20.raffle_ticket_holder(0, 2), Some(bob));
+            assert_eq!(erc20.raffle_ticket_holder(0, 3), Some(charlie));
+            assert_eq!(erc20.raffle_ticket_holder(0, 5), Some(charlie));
+            assert_eq!(erc20.raffle_ticket_holder(0, 6), None);
+            assert_eq!(erc20.raffle_prize(0), 60);
+            assert_eq!(erc20.draw_raffle(0, Some(secret)), Err(Error::RaffleNotEnded));
+
+            advance_blocks(10);
+            assert_eq!(erc20.buy_tickets(0, 1), Err(Error::RaffleClosed));
+            assert_eq!(erc20.draw_raffle(0, Some(secret)), Err(Error::NotOwner));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.draw_raffle(0, Some(commitment)), Err(Error::InvalidReveal));
+            let winners = erc20.draw_raffle(0, Some(secret)).unwrap();
+            assert_eq!(winners.len(), 2);
+            assert_eq!(erc20.raffle_winners(0), winners);
+            assert_eq!(erc20.draw_raffle(0, Some(secret)), Err(Error::RaffleClosed));
+            assert_eq!(erc20.balance_of(contract), 0);
+            assert_eq!(erc20.balance_of(bob) + erc20.balance_of(charlie), 600);
+            assert!(erc20.raffle(0).unwrap().drawn);
+        }
+
+        #[cfg(feature = "raffles")]
+        #[ink::test]
+        fn undrawn_raffles_refund_their_tickets() {
+            let contract = AccountId::from([0xff; 32]);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            let mut erc20 = Erc20::new(1_000);
+            assert_eq!(erc20.transfer(bob, 300), Ok(()));
+            assert_eq!(erc20.transfer(charlie, 300), Ok(()));
+            assert_eq!(erc20.start_raffle(10, 60, 1, Some(Hash::from([0x42; 32]))), Ok(0));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.buy_tickets(0, 3), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            assert_eq!(erc20.buy_tickets(0, 1), Ok(()));
+
+            advance_blocks(10);
+            assert_eq!(erc20.refund_raffle_tickets(0), Err(Error::RaffleNotEnded));
+            // The owner never reveals the secret.
+            advance_blocks((RAFFLE_DRAW_PERIOD / 6) as u32);
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.draw_raffle(0, None), Err(Error::RaffleClosed));
+            assert_eq!(erc20.refund_raffle_tickets(0), Err(Error::NothingToClaim));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.refund_raffle_tickets(0), Ok(30));
+            assert_eq!(erc20.refund_raffle_tickets(0), Err(Error::NothingToClaim));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            assert_eq!(erc20.refund_raffle_tickets(0), Ok(10));
+            assert_eq!(erc20.balance_of(bob), 300);
+            assert_eq!(erc20.balance_of(charlie), 300);
+            assert_eq!(erc20.balance_of(contract), 0);
+            assert_eq!(erc20.raffle(0).unwrap().refunded, 4);
+        }
+
+        #[cfg(feature = "raffles")]
+        #[ink::test]
+        fn raffle_without_commitment_needs_randomness() {
+            let mut erc20 = Erc20::new(1_000);
+            assert_eq!(erc20.start_raffle(10, 0, 1, None), Ok(0));
+            assert_eq!(erc20.draw_raffle(0, None), Err(Error::RandomnessUnavailable));
+            let config = RandomnessConfig {
+                extension_id: 9,
+                function_id: 1,
+            };
+            assert_eq!(erc20.set_randomness_config(Some(config)), Ok(()));
+            #[cfg(not(feature = "randomness"))]
+            assert_eq!(erc20.draw_raffle(0, None), Err(Error::RandomnessUnavailable));
+        }
+
+        #[cfg(feature = "sale")]
+        #[ink::test]
+        fn referrers_are_credited_on_purchases() {
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            let mut erc20 = Erc20::new(1_000);
+            let sale = MintSale {
+                rate: 10,
+                max_per_account: None,
+                max_total: None,
+            };
+            assert_eq!(erc20.set_mint_sale(Some(sale)), Ok(()));
+            assert_eq!(
+                erc20.set_referral_rate(MAX_REFERRAL_RATE + 1),
+                Err(Error::ReferralRateTooHigh)
+            );
+            assert_eq!(erc20.set_referral_rate(500), Ok(()));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            ink_env::test::set_value_transferred::<ink_env::DefaultEnvironment>(20);
+            assert_eq!(erc20.purchase_with_referral(bob), Err(Error::SelfReferral));
+            assert_eq!(erc20.purchase_with_referral(charlie), Ok(200));
+            match last_event() {
+                Event::TokensPurchased(purchased) => assert_eq!(purchased.tokens, 200),
+                _ => panic!("expected a TokensPurchased event"),
+            }
+            assert_eq!(erc20.purchase_with_referral(charlie), Ok(200));
+            assert_eq!(erc20.balance_of(bob), 400);
+            assert_eq!(erc20.balance_of(charlie), 20);
+            assert_eq!(erc20.referral_stats(charlie), (20, 2));
+            assert_eq!(erc20.referral_stats(alice), (0, 0));
+            assert_eq!(erc20.total_supply(), 1_420);
+            let paid = recorded()
+                .iter()
+                .filter(|event| matches!(test_events::decode(event), Event::ReferralPaid(_)))
+                .count();
+            assert_eq!(paid, 2);
+        }
+
+        #[cfg(feature = "sale")]
+        #[ink::test]
+        fn redeem_pays_out_of_reserve() {
+            let contract = AccountId::from([0xff; 32]);
+            let alice = AccountId::from([0x1; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            let mut erc20 = Erc20::new(1_000);
+            assert_eq!(erc20.redeem(100), Err(Error::RedemptionDisabled));
+            assert_eq!(erc20.set_redeem_rate(Some(0)), Err(Error::RedemptionDisabled));
+            assert_eq!(erc20.set_redeem_rate(Some(10)), Ok(()));
+            ink_env::test::set_value_transferred::<ink_env::DefaultEnvironment>(20);
+            assert_eq!(erc20.fund_reserve(), Ok(()));
+            assert_eq!(erc20.mint_proceeds(), 20);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 20);
+
+            assert_eq!(erc20.redeem(9), Err(Error::NothingToClaim));
+            assert_eq!(erc20.redeem(210), Err(Error::InsufficientReserve));
+            let before = ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(alice)
+                .unwrap_or_default();
+            assert_eq!(erc20.redeem(155), Ok(15));
+            assert_eq!(erc20.balance_of(alice), 850);
+            assert_eq!(erc20.total_supply(), 850);
+            assert_eq!(erc20.mint_proceeds(), 5);
+            assert_eq!(
+                ink_env::test::get_account_balance::<ink_env::DefaultEnvironment>(alice),
+                Ok(before + 15)
+            );
+        }
+
+        #[cfg(feature = "sale")]
+        #[test]
+        fn bonding_curve_reserve_is_the_area_under_the_curve() {
+            let linear = BondingCurve {
+                base_price: 10,
+                slope: 2,
+                scale: 1,
+                exponent: 1,
+            };
+            // 10 * x + 2 * x^2 / 2
+            assert_eq!(linear.reserve_at(0), Some(0));
+            assert_eq!(linear.reserve_at(5), Some(75));
+            let quadratic = BondingCurve {
+                base_price: 0,
+                slope: 3,
+                scale: 10,
+                exponent: 2,
+            };
+            // 3 * x^3 / (3 * 10^2) / 10
+            assert_eq!(quadratic.reserve_at(100), Some(1_000));
+            assert_eq!(linear.reserve_at(Balance::MAX), None);
+        }
+
+        #[cfg(feature = "sale")]
+        #[ink::test]
+        fn tokens_are_bought_and_sold_along_the_curve() {
+            let contract = AccountId::from([0xff; 32]);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            let mut erc20 = Erc20::new(1_000);
+            assert_eq!(erc20.price_to_mint(5), Err(Error::NoBondingCurve));
+            let curve = BondingCurve {
+                base_price: 10,
+                slope: 2,
+                scale: 1,
+                exponent: 1,
+            };
+            let invalid = BondingCurve {
+                exponent: MAX_CURVE_EXPONENT + 1,
+                ..curve.clone()
+            };
+            assert_eq!(erc20.set_bonding_curve(Some(invalid)), Err(Error::InvalidBondingCurve));
+            assert_eq!(erc20.set_bonding_curve(Some(curve)), Ok(()));
+            assert_eq!(erc20.price_to_mint(5), Ok(75));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 100);
+            ink_env::test::set_value_transferred::<ink_env::DefaultEnvironment>(70);
+            assert_eq!(erc20.buy_on_curve(5), Err(Error::PurchaseOutOfBounds));
+            ink_env::test::set_value_transferred::<ink_env::DefaultEnvironment>(100);
+            assert_eq!(erc20.buy_on_curve(5), Ok(75));
+            assert_eq!(erc20.balance_of(bob), 5);
+            assert_eq!(erc20.curve_supply(), 5);
+            // The next tokens cost more.
+            assert_eq!(erc20.price_to_mint(5), Ok(125));
+            assert_eq!(erc20.refund_for_burn(2), Ok(36));
+            assert_eq!(erc20.refund_for_burn(6), Err(Error::InsufficientReserve));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.set_bonding_curve(None), Err(Error::InvalidBondingCurve));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.sell_on_curve(5), Ok(75));
+            assert_eq!(erc20.balance_of(bob), 0);
+            assert_eq!(erc20.curve_supply(), 0);
+        }
+
+        #[cfg(feature = "locks")]
+        #[ink::test]
+        fn locked_tokens_cannot_be_spent() {
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            assert_eq!(erc20.lock(600, 12), Ok(0));
+            assert_eq!(erc20.lock(300, 60), Ok(1));
+            assert_eq!(erc20.lock(200, 60), Err(Error::InsufficientBalance));
+            assert_eq!(erc20.locked_balance_of(alice), 900);
+            assert_eq!(erc20.token_lock(alice, 1), Some((300, 60)));
+            assert_eq!(erc20.transfer(bob, 101), Err(Error::TokensLocked));
+            assert_eq!(erc20.transfer(bob, 100), Ok(()));
+
+            assert_eq!(erc20.unlock(0), Err(Error::LockNotExpired));
+            advance_blocks(2);
+            assert_eq!(erc20.unlock(0), Ok(600));
+            assert_eq!(erc20.unlock(0), Err(Error::LockNotFound));
+            assert_eq!(erc20.locked_balance_of(alice), 300);
+            assert_eq!(erc20.transfer(bob, 600), Ok(()));
+            assert_eq!(erc20.transfer(bob, 1), Err(Error::TokensLocked));
+            assert_eq!(erc20.balance_of(alice), 300);
+        }
+
+        #[cfg(all(feature = "compliance", feature = "locks", feature = "partitions"))]
+        #[ink::test]
+        fn spendable_balance_matches_enforcement() {
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            assert_eq!(erc20.lock(300, 60), Ok(0));
+            assert_eq!(
+                erc20.change_partition(alice, None, Some(String::from("restricted")), 500),
+                Ok(())
+            );
+            // Locks and partitions overlap, so the larger one is held back.
+            assert_eq!(erc20.locked_balance_of(alice), 500);
+            assert_eq!(erc20.spendable_balance_of(alice), 500);
+            assert_eq!(erc20.transfer(bob, 501), Err(Error::TokensPartitioned));
+            assert_eq!(erc20.transfer(bob, 500), Ok(()));
+            assert_eq!(erc20.spendable_balance_of(alice), 0);
+
+            assert_eq!(erc20.spendable_balance_of(bob), 500);
+            assert_eq!(erc20.freeze(bob), Ok(()));
+            assert_eq!(erc20.locked_balance_of(bob), 500);
+            assert_eq!(erc20.spendable_balance_of(bob), 0);
+        }
+
+        #[cfg(feature = "transfer-log")]
+        #[ink::test]
+        fn transfer_log_keeps_the_last_transfers() {
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let record = |value, block| TransferRecord { from: alice, to: bob, value, block };
+            assert_eq!(erc20.transfer(bob, 1), Ok(()));
+            assert_eq!(erc20.recent_transfers(0, 10), vec![]);
+
+            assert_eq!(
+                erc20.set_transfer_log_capacity(MAX_TRANSFER_LOG + 1),
+                Err(Error::TransferLogTooLarge)
+            );
+            assert_eq!(erc20.set_transfer_log_capacity(3), Ok(()));
+            for value in 1..=4 {
+                advance_blocks(1);
+                assert_eq!(erc20.transfer(bob, value), Ok(()));
+            }
+            assert_eq!(
+                erc20.recent_transfers(0, 10),
+                vec![record(4, 4), record(3, 3), record(2, 2)]
+            );
+            assert_eq!(erc20.recent_transfers(1, 1), vec![record(3, 3)]);
+            assert_eq!(erc20.recent_transfers(3, 1), vec![]);
+
+            assert_eq!(erc20.set_transfer_log_capacity(2), Ok(()));
+            assert_eq!(erc20.recent_transfers(0, 10), vec![]);
+            assert_eq!(erc20.transfer(bob, 5), Ok(()));
+            assert_eq!(erc20.recent_transfers(0, 10), vec![record(5, 4)]);
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.set_transfer_log_capacity(0), Err(Error::NotOwner));
+        }
+
+        #[cfg(all(feature = "fees", feature = "stats"))]
+        #[ink::test]
+        fn stats_count_transfers_while_enabled() {
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(AccountId::from([0xff; 32]));
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            assert_eq!(erc20.transfer(bob, 100), Ok(()));
+            assert_eq!(erc20.stats_of(bob), None);
+
+            assert_eq!(erc20.set_stats_enabled(true), Ok(()));
+            assert_eq!(erc20.set_tax_rate(100), Ok(()));
+            advance_blocks(2);
+            assert_eq!(erc20.transfer(bob, 100), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.transfer(bob, 10), Ok(()));
+            assert_eq!(
+                erc20.stats_of(alice),
+                Some(AccountStats {
+                    total_sent: 100,
+                    total_received: 0,
+                    tx_count: 1,
+                    first_seen_block: 2,
+                })
+            );
+            assert_eq!(
+                erc20.stats_of(bob),
+                Some(AccountStats {
+                    total_sent: 10,
+                    total_received: 99 + 10,
+                    tx_count: 2,
+                    first_seen_block: 2,
+                })
+            );
+            assert_eq!(erc20.set_stats_enabled(false), Err(Error::NotOwner));
+        }
+
+        #[cfg(feature = "metadata")]
+        #[ink::test]
+        fn owner_sets_token_metadata() {
+            let mut erc20 = Erc20::new(100);
+            let metadata = TokenMetadata {
+                logo_uri: String::from("ipfs://logo"),
+                description: String::from("A token"),
+                website: String::from("https://example.com"),
+                tags: vec![String::from("defi"), String::from("governance")],
+            };
+            assert_eq!(erc20.token_metadata(), TokenMetadata::default());
+            assert_eq!(erc20.set_token_metadata(metadata.clone()), Ok(()));
+            assert_eq!(erc20.token_metadata(), metadata);
+            match last_event() {
+                Event::MetadataUpdated(updated) => assert_eq!(updated.metadata, metadata),
+                _ => panic!("expected a MetadataUpdated event"),
+            }
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(AccountId::from([0x2; 32]));
+            assert_eq!(
+                erc20.set_token_metadata(TokenMetadata::default()),
+                Err(Error::NotOwner)
+            );
+        }
+
+        #[cfg(feature = "airdrops")]
+        #[ink::test]
+        fn airdrops_resume_without_paying_twice() {
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let recipients: Vec<_> = (0..=MAX_AIRDROP_BATCH)
+                .map(|index| (AccountId::from([index as u8 + 0x10; 32]), 2))
+                .collect();
+            assert_eq!(erc20.airdrop(0, recipients.clone()), Err(Error::NotDistributor));
+            assert_eq!(erc20.set_distributor(Some(alice)), Ok(()));
+
+            assert_eq!(erc20.airdrop(0, recipients.clone()), Ok(MAX_AIRDROP_BATCH));
+            assert!(erc20.is_airdropped(0, recipients[0].0));
+            assert!(!erc20.is_airdropped(0, recipients[MAX_AIRDROP_BATCH as usize].0));
+            // Resending processed entries skips them.
+            assert_eq!(erc20.airdrop(0, recipients[99..].to_vec()), Ok(2));
+            assert_eq!(erc20.balance_of(recipients[99].0), 2);
+            assert_eq!(erc20.balance_of(recipients[100].0), 2);
+            assert_eq!(erc20.balance_of(alice), 1_000 - 2 * 101);
+            match last_event() {
+                Event::Airdropped(airdropped) => {
+                    assert_eq!((airdropped.recipients, airdropped.value), (1, 2));
+                }
+                _ => panic!("expected an Airdropped event"),
+            }
+
+            // A new campaign pays everyone again.
+            assert_eq!(erc20.airdrop(1, recipients[..1].to_vec()), Ok(1));
+            assert_eq!(erc20.balance_of(recipients[0].0), 4);
+        }
+
+        #[cfg(all(feature = "airdrops", feature = "fees"))]
+        #[ink::test]
+        fn airdrops_report_what_recipients_received() {
+            let mut erc20 = Erc20::new(100_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            assert_eq!(erc20.set_distributor(Some(alice)), Ok(()));
+            assert_eq!(erc20.set_burn_rate(250), Ok(()));
+            assert_eq!(erc20.airdrop(0, vec![(bob, 10_000), (charlie, 2_000)]), Ok(2));
+            assert_eq!(erc20.balance_of(bob), 9_750);
+            assert_eq!(erc20.balance_of(charlie), 1_950);
+            match last_event() {
+                Event::Airdropped(airdropped) => assert_eq!(airdropped.value, 11_700),
+                _ => panic!("expected an Airdropped event"),
+            }
+        }
+
+        #[cfg(feature = "airdrops")]
+        #[ink::test]
+        fn merkle_airdrop_is_claimed_until_the_deadline() {
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(AccountId::from([0xff; 32]));
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            let dave = AccountId::from([0x4; 32]);
+            let hash = |value: &dyn Fn(&mut [u8; 32])| {
+                let mut output = [0; 32];
+                value(&mut output);
+                output
+            };
+            let leaf = |account: AccountId, amount: Balance| {
+                hash(&|output| ink_env::hash_encoded::<Blake2x256, _>(&(account, amount), output))
+            };
+            let node = |a: [u8; 32], b: [u8; 32]| {
+                let pair = if a <= b { (a, b) } else { (b, a) };
+                hash(&|output| ink_env::hash_encoded::<Blake2x256, _>(&pair, output))
+            };
+            let (bob_leaf, charlie_leaf) = (leaf(bob, 100), leaf(charlie, 200));
+            let dave_leaf = leaf(dave, 300);
+            let root = node(node(bob_leaf, charlie_leaf), dave_leaf);
+
+            assert_eq!(erc20.start_merkle_airdrop(7, root, 600, 12), Err(Error::NotDistributor));
+            assert_eq!(erc20.set_distributor(Some(alice)), Ok(()));
+            assert_eq!(erc20.start_merkle_airdrop(7, root, 600, 0), Err(Error::AirdropEnded));
+            assert_eq!(erc20.start_merkle_airdrop(7, root, 600, 12), Ok(()));
+            assert_eq!(erc20.start_merkle_airdrop(8, root, 1, 12), Err(Error::AirdropActive));
+            assert_eq!(erc20.balance_of(alice), 400);
+            assert_eq!(erc20.claim_window(), Some((0, 12)));
+            assert_eq!(erc20.unclaimed_amount(), 600);
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            let proof = vec![charlie_leaf, dave_leaf];
+            assert_eq!(erc20.claim_airdrop(200, proof.clone()), Err(Error::InvalidMerkleProof));
+            assert_eq!(erc20.claim_airdrop(100, proof.clone()), Ok(()));
+            assert_eq!(erc20.claim_airdrop(100, proof), Err(Error::AlreadyAirdropped));
+            assert!(erc20.is_airdropped(7, bob));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            assert_eq!(erc20.claim_airdrop(200, vec![bob_leaf, dave_leaf]), Ok(()));
+            assert_eq!((erc20.balance_of(bob), erc20.balance_of(charlie)), (100, 200));
+            assert_eq!(erc20.unclaimed_amount(), 300);
+            assert_eq!(erc20.sweep_unclaimed(charlie), Err(Error::NotDistributor));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.sweep_unclaimed(alice), Err(Error::AirdropNotEnded));
+            advance_blocks(2);
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(dave);
+            let proof = vec![node(bob_leaf, charlie_leaf)];
+            assert_eq!(erc20.claim_airdrop(300, proof), Err(Error::AirdropEnded));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.sweep_unclaimed(alice), Ok(300));
+            assert_eq!(erc20.balance_of(alice), 700);
+            assert_eq!(erc20.claim_window(), None);
+            assert_eq!(erc20.unclaimed_amount(), 0);
+            assert_eq!(erc20.sweep_unclaimed(alice), Err(Error::NoMerkleAirdrop));
+        }
+
+        #[cfg(feature = "vote-escrow")]
+        #[ink::test]
+        fn vote_escrow_power_decays_until_unlock() {
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let quarter = MAX_VE_LOCK_TIME / 4;
+            assert_eq!(erc20.increase_lock_amount(1), Err(Error::VeLockNotFound));
+            assert_eq!(
+                erc20.create_lock(100, MAX_VE_LOCK_TIME + 1),
+                Err(Error::InvalidUnlockTime)
+            );
+            assert_eq!(erc20.create_lock(1_001, quarter), Err(Error::InsufficientBalance));
+            assert_eq!(erc20.create_lock(400, 2 * quarter), Ok(()));
+            assert_eq!(erc20.create_lock(400, 2 * quarter), Err(Error::VeLockExists));
+            assert_eq!(erc20.ve_lock(alice), Some((400, 2 * quarter)));
+            assert_eq!(erc20.voting_power(alice, 0), 200);
+            assert_eq!(erc20.voting_power(alice, quarter), 100);
+            assert_eq!(erc20.transfer(bob, 601), Err(Error::TokensLocked));
+
+            advance_blocks(1);
+            assert_eq!(erc20.increase_lock_amount(400), Ok(()));
+            assert_eq!(erc20.extend_lock(2 * quarter), Err(Error::InvalidUnlockTime));
+            assert_eq!(erc20.extend_lock(4 * quarter), Ok(()));
+            assert_eq!(erc20.voting_power(alice, 2 * quarter), 400);
+            assert_eq!(erc20.voting_power(alice, 4 * quarter), 0);
+            // Past power follows the lock as it was then.
+            assert_eq!(erc20.voting_power(alice, 0), 200);
+            assert_eq!(erc20.voting_power(bob, 0), 0);
+
+            // Locks expire on their own.
+            assert_eq!(erc20.transfer(bob, 200), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.create_lock(200, 18), Ok(()));
+            assert_eq!(erc20.transfer(alice, 1), Err(Error::TokensLocked));
+            advance_blocks(2);
+            assert_eq!(erc20.ve_lock(bob), None);
+            assert_eq!(erc20.transfer(alice, 200), Ok(()));
+        }
+
+        #[cfg(feature = "escrow")]
+        #[ink::test]
+        fn escrow_is_resolved_by_arbiter() {
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(AccountId::from([0xff; 32]));
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            assert_eq!(erc20.create_escrow(bob, charlie, 1_001, 0), Err(Error::InsufficientBalance));
+            assert_eq!(erc20.create_escrow(bob, charlie, 300, 1_000), Ok(0));
+            assert_eq!(erc20.balance_of(alice), 700);
+            assert_eq!(erc20.escrow_value(0), 300);
+            assert_eq!(erc20.release(0), Err(Error::NotArbiter));
+            assert_eq!(erc20.refund_escrow(0), Err(Error::NotArbiter));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            assert_eq!(erc20.release(0), Ok(300));
+            assert_eq!(erc20.refund_escrow(0), Err(Error::EscrowResolved));
+            assert_eq!(erc20.balance_of(bob), 300);
+            assert_eq!(erc20.escrow(0).unwrap().released, Some(true));
+            assert_eq!(erc20.release(1), Err(Error::EscrowNotFound));
+        }
+
+        #[cfg(feature = "escrow")]
+        #[ink::test]
+        fn depositor_can_refund_expired_escrow() {
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(AccountId::from([0xff; 32]));
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            assert_eq!(erc20.create_escrow(bob, charlie, 400, 12), Ok(0));
+            assert_eq!(erc20.refund_escrow(0), Err(Error::NotArbiter));
+            advance_blocks(2);
+            assert_eq!(erc20.refund_escrow(0), Ok(400));
+            assert_eq!(erc20.balance_of(alice), 1_000);
+            assert_eq!(erc20.escrow(0).unwrap().released, Some(false));
+        }
+
+        #[cfg(all(feature = "escrow", feature = "compliance"))]
+        #[ink::test]
+        fn escrow_is_not_released_to_or_from_restricted_accounts() {
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(AccountId::from([0xff; 32]));
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            assert_eq!(erc20.create_escrow(bob, charlie, 300, 1_000), Ok(0));
+            assert_eq!(erc20.freeze(alice), Ok(()));
+            assert_eq!(erc20.set_denylisted(bob, true), Ok(()));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            assert_eq!(erc20.release(0), Err(Error::AccountFrozen));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.unfreeze(alice), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            assert_eq!(erc20.release(0), Err(Error::Denylisted));
+            assert_eq!(erc20.refund_escrow(0), Ok(300));
+            assert_eq!(erc20.balance_of(alice), 1_000);
+        }
+
+        #[cfg(feature = "scheduled")]
+        #[ink::test]
+        fn scheduled_transfers_pay_out_when_due() {
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(AccountId::from([0xff; 32]));
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            assert_eq!(
+                erc20.schedule_transfer(bob, 1_000, 12, 1),
+                Err(Error::InsufficientBalance)
+            );
+            assert_eq!(erc20.schedule_transfer(bob, 300, 12, 10), Ok(0));
+            assert_eq!(erc20.schedule_transfer(bob, 200, 12, 0), Ok(1));
+            assert_eq!(erc20.balance_of(alice), 490);
+            assert_eq!(erc20.scheduled_value(0), (300, 10));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            assert_eq!(erc20.execute_scheduled(0), Err(Error::ScheduledTransferNotDue));
+            assert_eq!(erc20.cancel_scheduled(1), Err(Error::NotScheduledSender));
+            advance_blocks(2);
+            assert_eq!(erc20.execute_scheduled(0), Ok(300));
+            assert_eq!(erc20.balance_of(bob), 300);
+            assert_eq!(erc20.balance_of(charlie), 10);
+            assert_eq!(erc20.scheduled_transfer(0), None);
+            assert_eq!(erc20.execute_scheduled(0), Err(Error::ScheduledTransferNotFound));
+
+            // Due transfers can still be cancelled until they are executed.
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.cancel_scheduled(1), Ok(200));
+            assert!(matches!(last_event(), Event::ScheduledTransferCancelled(_)));
+            assert_eq!(erc20.balance_of(alice), 690);
+            assert_eq!(erc20.cancel_scheduled(1), Err(Error::ScheduledTransferNotFound));
+        }
+
+        #[cfg(feature = "subscriptions")]
+        #[ink::test]
+        fn subscriptions_are_collected_once_per_interval() {
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            assert_eq!(
+                erc20.create_subscription(bob, 100, 0),
+                Err(Error::InvalidSubscriptionInterval)
+            );
+            assert_eq!(erc20.create_subscription(bob, 100, 10), Ok(0));
+
+            // The first payment is due right away, and anyone may collect.
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            assert_eq!(erc20.collect(0), Ok(100));
+            assert_eq!(erc20.next_collection(0), Some(10));
+            advance_blocks(1);
+            assert_eq!(erc20.collect(0), Err(Error::SubscriptionNotDue));
+            advance_blocks(1);
+            assert_eq!(erc20.collect(0), Ok(100));
+            assert_eq!(erc20.next_collection(0), Some(20));
+
+            // Missed intervals are skipped rather than charged.
+            advance_blocks(5);
+            assert_eq!(erc20.collect(0), Ok(100));
+            assert!(matches!(
+                last_event(),
+                Event::SubscriptionCollected(SubscriptionCollected { missed: 2, .. })
+            ));
+            assert_eq!(erc20.next_collection(0), Some(50));
+            assert_eq!(erc20.balance_of(alice), 700);
+            assert_eq!(erc20.balance_of(bob), 300);
+
+            assert_eq!(erc20.cancel_subscription(0), Err(Error::NotSubscriptionPayer));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.cancel_subscription(0), Ok(()));
+            assert_eq!(erc20.next_collection(0), None);
+            assert_eq!(erc20.collect(0), Err(Error::SubscriptionNotFound));
+        }
+
+        #[cfg(feature = "htlc")]
+        #[ink::test]
+        fn htlcs_are_claimed_with_the_preimage_or_refunded() {
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(AccountId::from([0xff; 32]));
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let mut hashlock = [0; 32];
+            ink_env::hash_bytes::<Sha2x256>(b"secret", &mut hashlock);
+            assert_eq!(erc20.create_htlc(bob, 100, hashlock, 0), Err(Error::HtlcExpired));
+            assert_eq!(erc20.create_htlc(bob, 100, hashlock, 12), Ok(0));
+            assert_eq!(erc20.create_htlc(bob, 200, hashlock, 12), Ok(1));
+            assert_eq!(erc20.balance_of(alice), 700);
+            assert_eq!(erc20.htlc_value(1), 200);
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.claim_htlc(0, b"guess".to_vec()), Err(Error::InvalidPreimage));
+            assert_eq!(erc20.refund_htlc(0), Err(Error::HtlcNotExpired));
+            assert_eq!(erc20.claim_htlc(0, b"secret".to_vec()), Ok(100));
+            assert!(matches!(
+                last_event(),
+                Event::HtlcClaimed(HtlcClaimed { preimage, .. }) if preimage == b"secret"
+            ));
+            assert_eq!(erc20.balance_of(bob), 100);
+            assert_eq!(erc20.claim_htlc(0, b"secret".to_vec()), Err(Error::HtlcNotFound));
+
+            advance_blocks(2);
+            assert_eq!(erc20.claim_htlc(1, b"secret".to_vec()), Err(Error::HtlcExpired));
+            assert_eq!(erc20.refund_htlc(1), Ok(200));
+            assert_eq!(erc20.balance_of(alice), 900);
+            assert_eq!(erc20.htlc(1), None);
+        }
+
+        #[cfg(all(feature = "htlc", feature = "compliance"))]
+        #[ink::test]
+        fn htlcs_check_both_ends() {
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(AccountId::from([0xff; 32]));
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let mut hashlock = [0; 32];
+            ink_env::hash_bytes::<Sha2x256>(b"secret", &mut hashlock);
+            assert_eq!(erc20.create_htlc(bob, 100, hashlock, 12), Ok(0));
+            assert_eq!(erc20.set_denylisted(alice, true), Ok(()));
+            assert_eq!(erc20.create_htlc(bob, 100, hashlock, 12), Err(Error::Denylisted));
+            assert_eq!(erc20.claim_htlc(0, b"secret".to_vec()), Err(Error::Denylisted));
+
+            assert_eq!(erc20.set_denylisted(alice, false), Ok(()));
+            assert_eq!(erc20.freeze(bob), Ok(()));
+            assert_eq!(erc20.claim_htlc(0, b"secret".to_vec()), Err(Error::AccountFrozen));
+            #[cfg(feature = "pausable")]
+            {
+                assert_eq!(erc20.pause(), Ok(()));
+                assert_eq!(
+                    erc20.create_htlc(bob, 100, hashlock, 12),
+                    Err(Error::CircuitBreakerTripped)
+                );
+            }
+            assert_eq!(erc20.balance_of(alice), 900);
+        }
+
+        #[cfg(feature = "vouchers")]
+        #[ink::test]
+        fn vouchers_are_redeemed_with_a_signature_or_code() {
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(AccountId::from([0xff; 32]));
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            let (_, signer) = sign([0x42; 32], Hash::default());
+            let mut code_secret = [0; 32];
+            ink_env::hash_bytes::<Blake2x256>(b"gift", &mut code_secret);
+            let (_, by_code) = sign(code_secret, Hash::default());
+            assert_eq!(erc20.create_voucher(100, by_code, 0), Err(Error::VoucherExpired));
+            assert_eq!(erc20.create_voucher(100, signer, 12), Ok(0));
+            assert_eq!(erc20.create_voucher(200, by_code, 12), Ok(1));
+            assert_eq!(erc20.create_voucher(300, by_code, 12), Ok(2));
+            assert_eq!(erc20.balance_of(alice), 400);
+
+            // The signature is bound to the recipient.
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            let (signature, _) = sign([0x42; 32], erc20.voucher_signing_hash(0, bob));
+            assert_eq!(
+                erc20.redeem_voucher(0, signature, charlie),
+                Err(Error::InvalidVoucherProof)
+            );
+            assert_eq!(erc20.redeem_voucher(0, signature, bob), Ok(100));
+
+            // A redemption made with the code only reveals a signature for
+            // bob, which cannot redirect the payout to whoever sees it.
+            let (guess, _) = sign([0x43; 32], erc20.voucher_signing_hash(1, bob));
+            assert_eq!(erc20.redeem_voucher(1, guess, bob), Err(Error::InvalidVoucherProof));
+            let (signature, _) = sign(code_secret, erc20.voucher_signing_hash(1, bob));
+            assert_eq!(
+                erc20.redeem_voucher(1, signature, charlie),
+                Err(Error::InvalidVoucherProof)
+            );
+            assert_eq!(erc20.redeem_voucher(1, signature, bob), Ok(200));
+            assert_eq!(erc20.balance_of(bob), 300);
+            assert_eq!(erc20.balance_of(charlie), 0);
+            assert_eq!(erc20.voucher(1), None);
+
+            assert_eq!(erc20.reclaim_voucher(2), Err(Error::NotVoucherSender));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.reclaim_voucher(2), Err(Error::VoucherNotExpired));
+            advance_blocks(2);
+            let (signature, _) = sign(code_secret, erc20.voucher_signing_hash(2, bob));
+            assert_eq!(erc20.redeem_voucher(2, signature, bob), Err(Error::VoucherExpired));
+            assert_eq!(erc20.reclaim_voucher(2), Ok(300));
+            assert_eq!(erc20.balance_of(alice), 700);
+        }
+
+        #[cfg(feature = "splitter")]
+        #[ink::test]
+        fn splitter_pays_out_pro_rata() {
+            let mut erc20 = Erc20::new(1_000);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            let splitter = erc20.splitter_account();
+            assert_eq!(erc20.add_payee(bob, 1), Ok(()));
+            assert_eq!(erc20.add_payee(charlie, 3), Ok(()));
+            assert_eq!(erc20.add_payee(charlie, 1), Err(Error::InvalidPayee));
+
+            assert_eq!(erc20.transfer(splitter, 400), Ok(()));
+            assert_eq!(erc20.releasable(bob), 100);
+            assert_eq!(erc20.release_payment(bob), Ok(100));
+            assert_eq!(erc20.release_payment(bob), Err(Error::NothingToClaim));
+
+            assert_eq!(erc20.transfer(splitter, 200), Ok(()));
+            assert_eq!(erc20.release_payment(bob), Ok(50));
+            assert_eq!(erc20.release_payment(charlie), Ok(450));
+            assert_eq!(erc20.released_to(bob), 150);
+            assert_eq!(erc20.balance_of(splitter), 0);
+        }
+
+        #[cfg(feature = "splitter")]
+        #[ink::test]
+        fn splitter_takes_no_payees_after_paying_out() {
+            let mut erc20 = Erc20::new(1_000);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            let splitter = erc20.splitter_account();
+            assert_eq!(erc20.add_payee(bob, 1), Ok(()));
+            assert_eq!(erc20.transfer(splitter, 100), Ok(()));
+            assert_eq!(erc20.release_payment(bob), Ok(100));
+
+            // Charlie's half of the 100 is already with Bob.
+            assert_eq!(erc20.add_payee(charlie, 1), Err(Error::PayoutsStarted));
+            assert_eq!(erc20.transfer(splitter, 100), Ok(()));
+            assert_eq!(erc20.release_payment(bob), Ok(100));
+            assert_eq!(erc20.release_payment(charlie), Err(Error::NothingToClaim));
+        }
 
-    /// Create storage for a simple ERC-20 contract.
-    #[ink(storage)]
-    #[derive(SpreadAllocate)]
-    pub struct Erc20 {
-        /// Total token supply.
-        total_supply: Balance,
-        /// Mapping from owner to number of owned tokens.
-        balances: Mapping<AccountId, Balance>,
-        /// Balances that can be transferred by non-owners: (owner, spender) -> allowed
-        allowances: ink_storage::Mapping<(AccountId, AccountId), Balance>,
-    }
+        #[cfg(feature = "streams")]
+        #[ink::test]
+        fn stream_vests_linearly() {
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(AccountId::from([0xff; 32]));
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            assert_eq!(erc20.create_stream(bob, 600, 12, 12), Err(Error::InvalidStream));
+            assert_eq!(erc20.create_stream(bob, 600, 0, 60), Ok(0));
+            assert_eq!(erc20.balance_of(alice), 400);
 
-    /// Splecify ERC-20 error type
-    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
-    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
-    pub enum Error {
-        /// Return if the balance cannot fulfill a request
-        InsufficientBalance,
-        InsufficientAllowance,
-    }
+            advance_blocks(3);
+            assert_eq!(erc20.stream_balances(0), (180, 420));
+            assert_eq!(erc20.withdraw_from_stream(0, 100), Err(Error::NotStreamParty));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.withdraw_from_stream(0, 181), Err(Error::InsufficientBalance));
+            assert_eq!(erc20.withdraw_from_stream(0, 100), Ok(()));
+            assert_eq!(erc20.balance_of(bob), 100);
 
-    #[ink(event)]
-    pub struct Transfer {
-        #[ink(topic)]
-        from: Option<AccountId>,
-        #[ink(topic)]
-        to: Option<AccountId>,
-        value: Balance,
-    }
+            advance_blocks(2);
+            assert_eq!(erc20.cancel_stream(0), Ok(()));
+            assert_eq!(erc20.balance_of(bob), 300);
+            assert_eq!(erc20.balance_of(alice), 700);
+            assert_eq!(erc20.stream(0), None);
+        }
 
-    #[ink(event)]
-    pub struct Approve {
-        #[ink(topic)]
-        owner: AccountId,
-        #[ink(topic)]
-        spender: AccountId,
-        value: Balance,
-    }
+        #[cfg(all(feature = "streams", feature = "compliance"))]
+        #[ink::test]
+        fn streams_of_frozen_senders_cannot_be_withdrawn() {
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(AccountId::from([0xff; 32]));
+            let mut erc20 = Erc20::new(1_000);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            assert_eq!(erc20.create_stream(bob, 600, 0, 60), Ok(0));
+            assert_eq!(erc20.freeze(alice), Ok(()));
+            advance_blocks(3);
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.withdraw_from_stream(0, 100), Err(Error::AccountFrozen));
+            assert_eq!(erc20.balance_of(bob), 0);
+        }
 
-    /// Specify the ERC-20 result type
-    pub type Result<T> = core::result::Result<T, Error>;
+        #[ink::test]
+        fn approve_batch_sets_every_allowance() {
+            let mut erc20 = Erc20::new(100);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            assert_eq!(erc20.approve_batch(vec![(bob, 10), (charlie, 20), (bob, 30)]), Ok(()));
+            assert_eq!(erc20.allowance(alice, bob), 30);
+            assert_eq!(erc20.allowance(alice, charlie), 20);
+            assert_eq!(ink_env::test::recorded_events().count(), 5);
+        }
 
-    impl Erc20 {
-        /// Create a new ERC-20 contract with an initial supply.
-        #[ink(constructor)]
-        pub fn new(initial_supply: Balance) -> Self {
-            // Initialize mapping for the contract.
-            ink_lang::utils::initialize_contract(|contract| {
-                Self::new_init(contract, initial_supply)
-            })
+        #[ink::test]
+        fn approve_event_carries_previous_allowance() {
+            let mut erc20 = Erc20::new(100);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let previous = |erc20: &mut Erc20, value| {
+                assert_eq!(erc20.approve_with_deadline(bob, value, 12), Ok(()));
+                match last_event() {
+                    Event::Approve(approve) => approve.previous,
+                    _ => panic!("expected an Approve event"),
+                }
+            };
+            assert_eq!(previous(&mut erc20, 10), 0);
+            assert_eq!(previous(&mut erc20, 50), 10);
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.transfer_from(alice, bob, 20), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(previous(&mut erc20, 5), 30);
+
+            // An expired allowance can no longer be spent.
+            advance_blocks(2);
+            assert_eq!(previous(&mut erc20, 5), 0);
         }
 
-        /// Initialize the ERC-20 contract with the specified initial supply.
-        fn new_init(&mut self, initial_supply: Balance) {
-            let caller = Self::env().caller();
-            self.balances.insert(&caller, &initial_supply);
-            self.total_supply = initial_supply;
-            self.env().emit_event(Transfer {
-                from: None,
-                to: Some(caller),
-                value: initial_supply,
-            });
+        #[ink::test]
+        fn balances_and_allowances_are_queried_in_bulk() {
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            let erc20 = Erc20TestBuilder::new()
+                .initial_supply(70)
+                .balance(bob, 30)
+                .allowance(alice, charlie, 5)
+                .build();
+            assert_eq!(erc20.balances_of(vec![bob, charlie, alice, bob]), vec![30, 0, 70, 30]);
+            assert_eq!(
+                erc20.allowances_of_pairs(vec![(alice, charlie), (charlie, alice), (alice, bob)]),
+                vec![5, 0, 0]
+            );
+            assert_eq!(erc20.balances_of(vec![]), vec![]);
         }
 
-        /// Returns the total token supply.
-        #[ink(message)]
-        pub fn total_supply(&self) -> Balance {
-            self.total_supply
+        #[ink::test]
+        fn emptied_entries_free_their_storage() {
+            let contract = ink_env::test::callee::<ink_env::DefaultEnvironment>();
+            let cells = || {
+                ink_env::test::count_used_storage_cells::<ink_env::DefaultEnvironment>(&contract)
+                    .unwrap()
+            };
+            let mut erc20 = Erc20::new(100);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let used = cells();
+            assert_eq!(erc20.approve(bob, 10), Ok(()));
+            assert_eq!(erc20.approve(bob, 0), Ok(()));
+            assert_eq!(cells(), used);
+
+            assert_eq!(erc20.transfer(bob, 100), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.transfer(alice, 100), Ok(()));
+            assert_eq!(cells(), used);
         }
 
-        /// Returns the account balance for the specified `owner`.
-        #[ink(message)]
-        pub fn balance_of(&self, owner: AccountId) -> Balance {
-            self.balances.get(owner).unwrap_or_default()
+        #[cfg(feature = "sessions")]
+        #[ink::test]
+        fn sessions_are_bounded_by_caps_and_expiry() {
+            let mut erc20 = Erc20::new(100);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            assert_eq!(erc20.create_session(bob, 25, 10, 0), Err(Error::SessionExpired));
+            assert_eq!(erc20.create_session(bob, 25, 10, 12), Ok(()));
+            assert_eq!(erc20.allowance(alice, bob), 0);
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(
+                erc20.session_transfer_from(alice, charlie, 11),
+                Err(Error::SessionCapExceeded)
+            );
+            assert_eq!(erc20.session_transfer_from(alice, charlie, 10), Ok(()));
+            assert_eq!(erc20.session_transfer_from(alice, charlie, 10), Ok(()));
+            assert_eq!(
+                erc20.session_transfer_from(alice, charlie, 6),
+                Err(Error::SessionCapExceeded)
+            );
+            assert_eq!(erc20.session_transfer_from(alice, charlie, 5), Ok(()));
+            assert_eq!(erc20.session(alice, bob).map(|session| session.spent), Some(25));
+            assert_eq!(erc20.session_transfer_from(charlie, bob, 1), Err(Error::SessionNotFound));
+            assert_eq!(erc20.balance_of(charlie), 25);
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.create_session(bob, 50, 50, 12), Ok(()));
+            advance_blocks(2);
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.session_transfer_from(alice, charlie, 1), Err(Error::SessionExpired));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.revoke_session(bob), Ok(()));
+            assert_eq!(erc20.revoke_session(bob), Err(Error::SessionNotFound));
+            assert_eq!(erc20.session(alice, bob), None);
         }
 
-        #[ink(message)]
-        pub fn transfer(&mut self, to: AccountId, value: Balance) -> Result<()> {
-            let from = self.env().caller();
-            self.transfer_from_to(&from, &to, value)
+        #[cfg(feature = "recovery")]
+        #[ink::test]
+        fn guardians_recover_balance_unless_vetoed() {
+            let mut erc20 = Erc20::new(100);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            let new_account = AccountId::from([0x5; 32]);
+            let config = |guardians: Vec<AccountId>, threshold| RecoveryConfig {
+                guardians,
+                threshold,
+                delay: 12,
+            };
+            for invalid in [
+                config(vec![bob, charlie], 0),
+                config(vec![bob, charlie], 3),
+                config(vec![bob, bob], 2),
+                config(vec![alice, bob], 1),
+            ] {
+                assert_eq!(
+                    erc20.set_recovery_config(Some(invalid)),
+                    Err(Error::InvalidRecoveryConfig)
+                );
+            }
+            let config = config(vec![bob, charlie], 2);
+            assert_eq!(erc20.set_recovery_config(Some(config.clone())), Ok(()));
+            assert_eq!(erc20.recovery_config(alice), Some(config));
+            assert_eq!(erc20.approve_recovery(alice, new_account), Err(Error::NotRecoveryGuardian));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.approve_recovery(alice, new_account), Ok(()));
+            assert_eq!(erc20.recover_balance(alice, new_account), Err(Error::RecoveryNotReady));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            assert_eq!(erc20.approve_recovery(alice, bob), Err(Error::RecoveryInProgress));
+            assert_eq!(erc20.approve_recovery(alice, new_account), Ok(()));
+            let recovery = erc20.pending_recovery(alice).unwrap();
+            assert_eq!(recovery.approvals, [bob, charlie]);
+            assert!(recovery.executable_at.is_some());
+
+            // The holder still has the key and vetoes.
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.veto_recovery(), Ok(()));
+            assert!(matches!(last_event(), Event::RecoveryVetoed(_)));
+            assert_eq!(erc20.veto_recovery(), Err(Error::RecoveryNotFound));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.approve_recovery(alice, new_account), Ok(()));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(charlie);
+            assert_eq!(erc20.approve_recovery(alice, new_account), Ok(()));
+            advance_blocks(1);
+            assert_eq!(erc20.recover_balance(alice, new_account), Err(Error::RecoveryNotReady));
+            advance_blocks(1);
+            assert_eq!(erc20.recover_balance(alice, bob), Err(Error::RecoveryNotFound));
+            assert_eq!(erc20.recover_balance(alice, new_account), Ok(100));
+            assert_eq!(erc20.balance_of(alice), 0);
+            assert_eq!(erc20.balance_of(new_account), 100);
+            assert_eq!(erc20.pending_recovery(alice), None);
         }
 
-        fn transfer_from_to(
-            &mut self,
-            from: &AccountId,
-            to: &AccountId,
-            value: Balance,
-        ) -> Result<()> {
-            let from_balance = self.balance_of_impl(from);
-            if from_balance < value {
-                return Err(Error::InsufficientBalance);
+        #[cfg(feature = "multicall")]
+        #[ink::test]
+        fn multicall_runs_calls_in_order() {
+            let mut erc20 = Erc20::new(100);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let calls = vec![
+                Call::Lock { value: 60, unlock_at: 1_000 },
+                Call::Approve { spender: bob, value: 25 },
+                Call::Transfer { to: bob, value: 40 },
+            ];
+            assert_eq!(erc20.multicall(calls), Ok(()));
+            assert_eq!(erc20.balance_of(bob), 40);
+            assert_eq!(erc20.allowance(alice, bob), 25);
+            assert_eq!(
+                erc20.multicall(vec![Call::Transfer { to: bob, value: 1 }]),
+                Err(Error::TokensLocked)
+            );
+        }
+
+        #[cfg(feature = "holders")]
+        #[ink::test]
+        fn holders_are_enumerable() {
+            let mut erc20 = Erc20::new(100);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            assert_eq!(erc20.holders_count(), 1);
+            assert_eq!(erc20.transfer(bob, 10), Ok(()));
+            assert_eq!(erc20.transfer(charlie, 10), Ok(()));
+            assert_eq!(erc20.transfer(bob, 0), Ok(()));
+            assert_eq!(erc20.holders(0, 10), vec![alice, bob, charlie]);
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.transfer(alice, 10), Ok(()));
+            assert_eq!(erc20.holders_count(), 2);
+            assert_eq!(erc20.holder_at(1), Some(charlie));
+            assert_eq!(erc20.holders(1, 5), vec![charlie]);
+            assert_eq!(erc20.holders(u32::MAX, u32::MAX), vec![]);
+        }
+
+        #[ink::test]
+        fn events_carry_consecutive_sequence_numbers() {
+            let mut erc20 = Erc20::new(100);
+            let bob = AccountId::from([0x2; 32]);
+            // With fees the transfer also burns, which emits events of its own.
+            #[cfg(feature = "fees")]
+            assert_eq!(erc20.set_burn_rate(100), Ok(()));
+            #[cfg(not(feature = "fees"))]
+            assert_eq!(erc20.set_zero_transfer_policy(ZeroTransferPolicy::Silent), Ok(()));
+            assert_eq!(erc20.transfer(bob, 100), Ok(()));
+            assert_eq!(erc20.approve(bob, 10), Ok(()));
+            let seqs: Vec<u64> = recorded()
+                .iter()
+                .map(|event| match decode(event) {
+                    Event::Transfer(event) => event.event_seq,
+                    Event::Minted(event) => event.event_seq,
+                    Event::Burned(event) => event.event_seq,
+                    Event::SupplyChanged(event) => event.event_seq,
+                    Event::ConfigChanged(event) => event.event_seq,
+                    Event::Approve(event) => event.event_seq,
+                    _ => panic!("unexpected event"),
+                })
+                .collect();
+            let emitted = if cfg!(feature = "fees") { 8 } else { 5 };
+            assert_eq!(seqs, (0..emitted).collect::<Vec<_>>());
+            assert_eq!(erc20.event_seq(), emitted);
+            assert_eq!(erc20.event_schema_version(), EVENT_SCHEMA_VERSION);
+        }
+
+        #[ink::test]
+        fn events_carry_block_number_and_timestamp() {
+            let mut erc20 = Erc20::new(100);
+            advance_blocks(2);
+            assert_eq!(erc20.transfer(AccountId::from([0x2; 32]), 10), Ok(()));
+            match last_event() {
+                Event::Transfer(transfer) => {
+                    assert_eq!(transfer.block_number, 2);
+                    assert_eq!(transfer.timestamp, 12);
+                }
+                _ => panic!("expected a Transfer event"),
             }
-            let to_balance = self.balance_of_impl(to);
-            self.balances.insert(&from, &(from_balance - value));
-            self.balances.insert(&to, &(to_balance + value));
-            self.env().emit_event(Transfer {
-                from: Some(*from),
-                to: Some(*to),
-                value,
-            });
-            Ok(())
         }
 
-        #[inline]
-        pub fn balance_of_impl(&self, owner: &AccountId) -> Balance {
-            self.balances.get(owner).unwrap_or_default()
+        #[ink::test]
+        fn allowances_of_lists_outstanding_approvals() {
+            let mut erc20 = Erc20::new(100);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            let dave = AccountId::from([0x4; 32]);
+            assert_eq!(erc20.approve_batch(vec![(bob, 10), (charlie, 20), (dave, 0)]), Ok(()));
+            assert_eq!(erc20.allowances_of(alice, 0, 10), vec![(bob, 10), (charlie, 20)]);
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.transfer_from(alice, bob, 10), Ok(()));
+            assert_eq!(erc20.allowances_of(alice, 0, 10), vec![(charlie, 20)]);
+            assert_eq!(erc20.allowances_of(alice, 1, 10), vec![]);
         }
 
-        #[ink(message)]
-        pub fn approve(&mut self, spender: AccountId, value: Balance) -> Result<()> {
-            let owner = self.env().caller();
-            self.allowances.insert((&owner, &spender), &value);
-            self.env().emit_event(Approve {
-                owner,
-                spender,
-                value,
-            });
-            Ok(())
+        #[ink::test]
+        fn terminate_requires_zero_supply() {
+            let contract = AccountId::from([0xff; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(contract);
+            ink_env::test::set_account_balance::<ink_env::DefaultEnvironment>(contract, 50);
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.terminate(bob), Err(Error::SupplyNotZero));
+            // Governance calls in as the contract and is bound by it as well.
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(contract);
+            assert_eq!(erc20.terminate(bob), Err(Error::SupplyNotZero));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(AccountId::from([0x1; 32]));
+            let mut erc20 = Erc20::new(0);
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.terminate(bob), Err(Error::NotOwner));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(AccountId::from([0x1; 32]));
+            ink_env::test::assert_contract_termination::<ink_env::DefaultEnvironment, _>(
+                move || {
+                    let _ = erc20.terminate(bob);
+                },
+                bob,
+                50,
+            );
         }
 
-        #[ink(message)]
-        pub fn allowance(&self, owner: AccountId, spender: AccountId) -> Balance {
-            self.allowance_impl(&owner, &spender)
+        #[cfg(feature = "fees")]
+        #[ink::test]
+        fn oracle_price_needs_configuration() {
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.oracle_price(), Err(Error::OracleUnavailable));
+            let config = OracleConfig {
+                extension_id: 7,
+                function_id: 1,
+                feed: 42,
+            };
+            assert_eq!(erc20.set_oracle_config(Some(config.clone())), Ok(()));
+            assert_eq!(erc20.oracle_config(), Some(config));
         }
 
-        #[inline]
-        fn allowance_impl(&self, ownder: &AccountId, spender: &AccountId) -> Balance {
-            self.allowances.get((ownder, spender)).unwrap_or_default()
+        #[cfg(feature = "fees")]
+        #[test]
+        fn fee_controller_tracks_peg_deviation() {
+            let controller = FeeController {
+                target_price: 1_000,
+                min_rate: 50,
+                max_rate: 800,
+            };
+            assert_eq!(controller.rate_for(1_000), 50);
+            assert_eq!(controller.rate_for(1_030), 300);
+            assert_eq!(controller.rate_for(970), 300);
+            assert_eq!(controller.rate_for(2_000), 800);
+            assert_eq!(controller.rate_for(Balance::MAX), 800);
         }
 
-        #[ink(message)]
-        pub fn transfer_from(&mut self, from: AccountId, to: AccountId, value: Balance) -> Result<()> {
-            let caller = self.env().caller();
-            let allowance = self.allowances.get((from, caller)).unwrap_or_default();
-            if allowance < value {
-                return Err(Error::InsufficientAllowance)
+        #[cfg(feature = "fees")]
+        #[ink::test]
+        fn set_fee_controller_checks_bounds() {
+            let mut erc20 = Erc20::new(100);
+            let controller = FeeController {
+                target_price: 1_000,
+                min_rate: 50,
+                max_rate: MAX_TAX_RATE + 1,
+            };
+            assert_eq!(
+                erc20.set_fee_controller(Some(controller)),
+                Err(Error::InvalidFeeController)
+            );
+            assert_eq!(erc20.update_fee_from_oracle(), Err(Error::InvalidFeeController));
+        }
+
+        #[cfg(feature = "oracle")]
+        struct MockOracle(Option<Balance>);
+
+        #[cfg(feature = "oracle")]
+        impl ink_engine::ChainExtension for MockOracle {
+            fn func_id(&self) -> u32 {
+                7 << 16 | 1
+            }
+
+            fn call(&mut self, input: &[u8], output: &mut Vec<u8>) -> u32 {
+                // The off-chain engine hands over the encoded input as a byte vector.
+                let input = <Vec<u8> as scale::Decode>::decode(&mut &input[..]).unwrap();
+                assert_eq!(<u32 as scale::Decode>::decode(&mut &input[..]), Ok(42));
+                scale::Encode::encode_to(&self.0, output);
+                0
             }
-            self.transfer_from_to(&from, &to, value)?;
-            self.allowances.insert((&from, &caller), &(allowance - value));
-            Ok(())
         }
-    }
 
-    #[cfg(test)]
-    mod tests {
-        use super::*;
+        #[cfg(feature = "fees")]
+        #[cfg(feature = "oracle")]
+        #[ink::test]
+        fn oracle_price_reads_chain_extension() {
+            ink_env::test::register_chain_extension(MockOracle(Some(1_250)));
+            let mut erc20 = Erc20::new(100);
+            let config = OracleConfig {
+                extension_id: 7,
+                function_id: 1,
+                feed: 42,
+            };
+            assert_eq!(erc20.set_oracle_config(Some(config)), Ok(()));
+            assert_eq!(erc20.oracle_price(), Ok(1_250));
 
-        use ink_lang as ink;
+            let controller = FeeController {
+                target_price: 1_000,
+                min_rate: 0,
+                max_rate: 1_000,
+            };
+            assert_eq!(erc20.set_fee_controller(Some(controller)), Ok(()));
+            assert_eq!(erc20.update_fee_from_oracle(), Ok(1_000));
+            assert_eq!(erc20.tax_rate(), 1_000);
+            assert_eq!(erc20.update_fee_from_oracle(), Err(Error::FeeUpdateTooSoon));
+            advance_blocks(1);
+            assert_eq!(erc20.update_fee_from_oracle(), Ok(1_000));
+        }
 
+        #[cfg(feature = "bridge")]
         #[ink::test]
-        fn new_works() {
-            let contract = Erc20::new(777);
-            assert_eq!(contract.total_supply(), 777);
+        fn cross_chain_transfer_requires_xcm() {
+            let mut erc20 = Erc20::new(100);
+            let bob = AccountId::from([0x2; 32]);
+            assert_eq!(erc20.transfer_cross_chain(2_000, bob, 10), Err(Error::XcmUnavailable));
+            let config = XcmConfig {
+                extension_id: 8,
+                function_id: 1,
+            };
+            assert_eq!(erc20.set_xcm_config(Some(config)), Ok(()));
+            assert_eq!(
+                erc20.transfer_cross_chain(2_000, bob, 101),
+                Err(Error::InsufficientBalance)
+            );
+            assert_eq!(erc20.total_supply(), 100);
+        }
+
+        /// Makes alice her own bridge and asserts that she can bridge none of
+        /// her tokens out, either way, because of `error`.
+        #[cfg(all(
+            feature = "bridge",
+            any(feature = "compliance", feature = "pausable", feature = "soulbound")
+        ))]
+        fn assert_bridging_fails(erc20: &mut Erc20, error: Error) {
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let supply = erc20.total_supply();
+            assert_eq!(erc20.set_bridge(Some(alice)), Ok(()));
+            let config = XcmConfig {
+                extension_id: 8,
+                function_id: 1,
+            };
+            assert_eq!(erc20.set_xcm_config(Some(config)), Ok(()));
+            let locked = erc20.bridge_lock(alice, 10, 7, vec![0xab; 20]);
+            assert_eq!(locked.err().as_ref(), Some(&error));
+            assert_eq!(erc20.transfer_cross_chain(2_000, bob, 10).err(), Some(error));
+            assert_eq!(erc20.total_supply(), supply);
         }
 
+        #[cfg(all(feature = "bridge", feature = "compliance"))]
         #[ink::test]
-        fn balance_works() {
-            let contract = Erc20::new(100);
-            assert_eq!(contract.total_supply(), 100);
-            assert_eq!(contract.balance_of(AccountId::from([0x1; 32])), 100);
-            assert_eq!(contract.balance_of(AccountId::from([0x0; 32])), 0);
+        fn frozen_account_cannot_bridge_out() {
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.freeze(AccountId::from([0x1; 32])), Ok(()));
+            assert_bridging_fails(&mut erc20, Error::AccountFrozen);
         }
 
+        #[cfg(all(feature = "bridge", feature = "compliance"))]
         #[ink::test]
-        fn transfer_works() {
+        fn denylisted_account_cannot_bridge_out() {
             let mut erc20 = Erc20::new(100);
-            assert_eq!(erc20.balance_of(AccountId::from([0x0; 32])), 0);
-            assert_eq!(erc20.transfer(AccountId::from([0x0; 32]), 10), Ok(()));
-            assert_eq!(erc20.balance_of(AccountId::from([0x0; 32])), 10);
+            assert_eq!(erc20.set_denylisted(AccountId::from([0x1; 32]), true), Ok(()));
+            assert_bridging_fails(&mut erc20, Error::Denylisted);
         }
 
+        #[cfg(all(feature = "bridge", feature = "soulbound"))]
         #[ink::test]
-        fn transfer_from_works() {
+        fn soulbound_tokens_cannot_bridge_out() {
+            let mut erc20 = Erc20::new_soulbound(100);
+            assert_bridging_fails(&mut erc20, Error::NonTransferable);
+        }
+
+        #[cfg(all(feature = "bridge", feature = "pausable"))]
+        #[ink::test]
+        fn tripped_breaker_stops_bridging_out() {
             let mut erc20 = Erc20::new(100);
-            assert_eq!(erc20.balance_of(AccountId::from([0x1; 32])), 100);
-            let _ = erc20.approve(AccountId::from([0x1; 32]), 20);
-            let _ = erc20.transfer_from(AccountId::from([0x1; 32]), AccountId::from([0x0; 32]), 10);
-            assert_eq!(erc20.balance_of(AccountId::from([0x0; 32])), 10);
+            assert_eq!(erc20.pause(), Ok(()));
+            assert_bridging_fails(&mut erc20, Error::CircuitBreakerTripped);
+        }
+
+        #[cfg(feature = "xcm")]
+        struct MockXcm;
+
+        #[cfg(feature = "xcm")]
+        impl ink_engine::ChainExtension for MockXcm {
+            fn func_id(&self) -> u32 {
+                8 << 16 | 1
+            }
+
+            fn call(&mut self, input: &[u8], output: &mut Vec<u8>) -> u32 {
+                let input = <Vec<u8> as scale::Decode>::decode(&mut &input[..]).unwrap();
+                let (para_id, _, amount) =
+                    <(u32, [u8; 32], u128) as scale::Decode>::decode(&mut &input[..]).unwrap();
+                assert_eq!((para_id, amount), (2_000, 10));
+                scale::Encode::encode_to(&Some([0xcc_u8; 32]), output);
+                0
+            }
         }
 
+        #[cfg(feature = "bridge")]
+        #[cfg(feature = "xcm")]
         #[ink::test]
-        fn allowances_works() {
+        fn cross_chain_transfer_burns_and_reports_message() {
+            ink_env::test::register_chain_extension(MockXcm);
             let mut erc20 = Erc20::new(100);
-            assert_eq!(erc20.balance_of(AccountId::from([0x1; 32])), 100);
-            let _ = erc20.approve(AccountId::from([0x1; 32]), 200);
-            assert_eq!(erc20.allowance(AccountId::from([0x1; 32]), AccountId::from([0x1; 32])), 200);
+            let config = XcmConfig {
+                extension_id: 8,
+                function_id: 1,
+            };
+            assert_eq!(erc20.set_xcm_config(Some(config)), Ok(()));
+            let bob = AccountId::from([0x2; 32]);
+            assert_eq!(erc20.transfer_cross_chain(2_000, bob, 10), Ok(Hash::from([0xcc; 32])));
+            assert_eq!(erc20.balance_of(AccountId::from([0x1; 32])), 90);
+            assert_eq!(erc20.total_supply(), 90);
+        }
 
-            let _ = erc20.transfer_from(AccountId::from([0x1; 32]), AccountId::from([0x0; 32]), 50);
-            assert_eq!(erc20.balance_of(AccountId::from([0x0; 32])), 50);
-            assert_eq!(erc20.allowance(AccountId::from([0x1; 32]), AccountId::from([0x1; 32])), 150);
+        #[cfg(feature = "randomness")]
+        struct MockRandomness;
 
-            let _ = erc20.transfer_from(AccountId::from([0x1; 32]), AccountId::from([0x0; 32]), 100);
-            assert_eq!(erc20.balance_of(AccountId::from([0x0; 32])), 50);
-            assert_eq!(erc20.allowance(AccountId::from([0x1; 32]), AccountId::from([0x1; 32])), 150);
+        #[cfg(feature = "randomness")]
+        impl ink_engine::ChainExtension for MockRandomness {
+            fn func_id(&self) -> u32 {
+                9 << 16 | 1
+            }
+
+            fn call(&mut self, _input: &[u8], output: &mut Vec<u8>) -> u32 {
+                scale::Encode::encode_to(&Some([0x07_u8; 32]), output);
+                0
+            }
+        }
+
+        #[cfg(feature = "raffles")]
+        #[cfg(feature = "randomness")]
+        #[ink::test]
+        fn raffle_draws_with_chain_randomness() {
+            ink_env::test::register_chain_extension(MockRandomness);
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(AccountId::from([0xff; 32]));
+            let mut erc20 = Erc20::new(100);
+            let config = RandomnessConfig {
+                extension_id: 9,
+                function_id: 1,
+            };
+            assert_eq!(erc20.set_randomness_config(Some(config)), Ok(()));
+            assert_eq!(erc20.start_raffle(10, 6, 1, None), Ok(0));
+            assert_eq!(erc20.buy_tickets(0, 5), Ok(()));
+            advance_blocks(1);
+            let alice = AccountId::from([0x1; 32]);
+            assert_eq!(erc20.draw_raffle(0, None), Ok(vec![alice]));
+            assert_eq!(erc20.balance_of(alice), 100);
+        }
+
+        #[cfg(feature = "fees")]
+        #[ink::test]
+        fn swap_waits_for_threshold_and_is_untaxed() {
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(AccountId::from([0xff; 32]));
+            let mut erc20 = Erc20::new(100_000);
+            let bob = AccountId::from([0x2; 32]);
+            let router = AccountId::from([0x9; 32]);
+            assert_eq!(erc20.set_tax_rate(100), Ok(()));
+            let config = SwapConfig {
+                router,
+                threshold: 1_000,
+            };
+            assert_eq!(erc20.set_swap_config(Some(config.clone())), Ok(()));
+            assert_eq!(erc20.swap_config(), Some(config));
+            // Below the threshold no swap is attempted.
+            assert_eq!(erc20.transfer(bob, 10_000), Ok(()));
+            assert_eq!(erc20.accumulated_tax(), 100);
+
+            // The router's pull during a swap neither pays tax nor swaps again.
+            erc20.in_swap.set(&true);
+            assert_eq!(erc20.transfer(bob, 90_000), Ok(()));
+            assert_eq!(erc20.balance_of(bob), 99_900);
+            assert_eq!(erc20.accumulated_tax(), 100);
+        }
+
+        #[ink::test]
+        fn migrate_refuses_current_and_unversioned_storage() {
+            let mut erc20 = Erc20::new(100);
+            assert_eq!(erc20.storage_version(), STORAGE_VERSION);
+            assert_eq!(erc20.migrate(), Err(Error::AlreadyMigrated));
+
+            erc20.storage_version.set(&0);
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(AccountId::from([0x2; 32]));
+            assert_eq!(erc20.migrate(), Err(Error::NotOwner));
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(AccountId::from([0x1; 32]));
+            assert_eq!(erc20.migrate(), Err(Error::UnsupportedStorageVersion));
+            assert_eq!(erc20.storage_version(), 0);
+        }
+
+        #[ink::test]
+        fn allowance_expires_at_deadline() {
+            let mut erc20 = Erc20::new(100);
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            assert_eq!(erc20.approve_with_deadline(bob, 50, 12), Ok(()));
+            assert_eq!(erc20.allowance_expiry(alice, bob), Some(12));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(bob);
+            assert_eq!(erc20.transfer_from(alice, bob, 20), Ok(()));
+            assert_eq!(erc20.allowance(alice, bob), 30);
+            assert_eq!(erc20.allowance_expiry(alice, bob), Some(12));
+            advance_blocks(2);
+            assert_eq!(erc20.allowance(alice, bob), 0);
+            assert_eq!(erc20.transfer_from(alice, bob, 10), Err(Error::AllowanceExpired));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(alice);
+            assert_eq!(erc20.approve(bob, 10), Ok(()));
+            assert_eq!(erc20.allowance_expiry(alice, bob), None);
+        }
+
+        #[ink::test]
+        fn new_with_allocations_works() {
+            let erc20 = Erc20::new_with_allocations(
+                100,
+                vec![
+                    (AccountId::from([0x2; 32]), 60),
+                    (AccountId::from([0x3; 32]), 30),
+                    (AccountId::from([0x2; 32]), 10),
+                ],
+            );
+            assert_eq!(erc20.total_supply(), 100);
+            assert_eq!(erc20.balance_of(AccountId::from([0x1; 32])), 0);
+            assert_eq!(erc20.balance_of(AccountId::from([0x2; 32])), 70);
+            assert_eq!(erc20.balance_of(AccountId::from([0x3; 32])), 30);
+            // A Transfer and a Minted event per allocation.
+            assert_eq!(ink_env::test::recorded_events().count(), 6);
+        }
+
+        #[cfg(feature = "emissions")]
+        #[ink::test]
+        fn buckets_vest_independently() {
+            ink_env::test::set_callee::<ink_env::DefaultEnvironment>(AccountId::from([0xff; 32]));
+            let team = AccountId::from([0x2; 32]);
+            let investors = AccountId::from([0x3; 32]);
+            let bucket = |name: &str, beneficiary, curve| BucketConfig {
+                name: String::from(name),
+                beneficiary,
+                amount: 1_000,
+                cliff: 30,
+                duration: 60,
+                curve,
+            };
+            let mut erc20 = Erc20::new_with_buckets(
+                500,
+                vec![
+                    bucket("team", team, VestingCurve::Linear),
+                    bucket("investors", investors, VestingCurve::Steps(4)),
+                ],
+            );
+            assert_eq!(erc20.total_supply(), 2_500);
+            assert_eq!(erc20.bucket_info(String::from("team")).unwrap().beneficiary, team);
+            assert_eq!(
+                erc20.release_bucket(String::from("ecosystem")),
+                Err(Error::BucketNotFound)
+            );
+
+            // 24 ms: still within the cliff.
+            advance_blocks(4);
+            assert_eq!(
+                erc20.release_bucket(String::from("team")),
+                Err(Error::NothingToClaim)
+            );
+
+            // 42 ms: past the cliff, two of four 15 ms steps.
+            advance_blocks(3);
+            assert_eq!(erc20.bucket_balances(String::from("team")), (700, 300));
+            assert_eq!(erc20.release_bucket(String::from("team")), Ok(700));
+            assert_eq!(erc20.release_bucket(String::from("investors")), Ok(500));
+
+            advance_blocks(10);
+            assert_eq!(erc20.release_bucket(String::from("team")), Ok(300));
+            assert_eq!(erc20.release_bucket(String::from("investors")), Ok(500));
+            assert_eq!(erc20.balance_of(team), 1_000);
+            assert_eq!(erc20.balance_of(investors), 1_000);
+            assert_eq!(erc20.balance_of(AccountId::from([0xff; 32])), 0);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "allocations must add up to the total supply")]
+        fn new_with_allocations_rejects_mismatched_supply() {
+            Erc20::new_with_allocations(100, vec![(AccountId::from([0x2; 32]), 99)]);
+        }
+
+        #[cfg(feature = "migration")]
+        #[ink::test]
+        fn import_balances_skips_duplicates_and_zero_balances() {
+            let mut erc20 = Erc20::new(0);
+            erc20.import_balances(vec![
+                (AccountId::from([0x2; 32]), 30),
+                (AccountId::from([0x3; 32]), 0),
+                (AccountId::from([0x2; 32]), 30),
+                (AccountId::from([0x4; 32]), 70),
+            ]);
+            assert_eq!(erc20.balance_of(AccountId::from([0x2; 32])), 30);
+            assert_eq!(erc20.balance_of(AccountId::from([0x3; 32])), 0);
+            assert_eq!(erc20.balance_of(AccountId::from([0x4; 32])), 70);
+            assert_eq!(erc20.total_supply(), 100);
+            // A Transfer and a Minted event for the constructor and per
+            // imported holder.
+            assert_eq!(ink_env::test::recorded_events().count(), 6);
+        }
+    }
+
+    /// Property-based tests that run random operation sequences against the
+    /// off-chain environment and check the ledger invariants after every step.
+    #[cfg(all(test, feature = "mintable"))]
+    mod proptests {
+        use super::*;
+
+        use proptest::prelude::*;
+
+        /// Accounts taking part in the generated operations.
+        const ACCOUNTS: u8 = 4;
+
+        fn account(index: u8) -> AccountId {
+            AccountId::from([index % ACCOUNTS; 32])
+        }
+
+        #[derive(Debug, Clone)]
+        enum Op {
+            Transfer { caller: u8, to: u8, value: Balance },
+            Approve { caller: u8, spender: u8, value: Balance },
+            TransferFrom { caller: u8, from: u8, to: u8, value: Balance },
+            Mint { caller: u8, to: u8, value: Balance },
+        }
+
+        fn op() -> impl Strategy<Value = Op> {
+            prop_oneof![
+                (0..ACCOUNTS, 0..ACCOUNTS, 0..200u128)
+                    .prop_map(|(caller, to, value)| Op::Transfer { caller, to, value }),
+                (0..ACCOUNTS, 0..ACCOUNTS, 0..200u128)
+                    .prop_map(|(caller, spender, value)| Op::Approve { caller, spender, value }),
+                (0..ACCOUNTS, 0..ACCOUNTS, 0..ACCOUNTS, 0..200u128).prop_map(
+                    |(caller, from, to, value)| Op::TransferFrom { caller, from, to, value }
+                ),
+                (0..ACCOUNTS, 0..ACCOUNTS, 0..200u128)
+                    .prop_map(|(caller, to, value)| Op::Mint { caller, to, value }),
+            ]
+        }
+
+        /// Every balance and allowance between the participating accounts.
+        fn snapshot(erc20: &Erc20) -> (Vec<Balance>, Vec<Balance>) {
+            let balances = (0..ACCOUNTS)
+                .map(|owner| erc20.balance_of(account(owner)))
+                .collect();
+            let allowances = (0..ACCOUNTS)
+                .flat_map(|owner| (0..ACCOUNTS).map(move |spender| (owner, spender)))
+                .map(|(owner, spender)| erc20.allowance(account(owner), account(spender)))
+                .collect();
+            (balances, allowances)
+        }
+
+        fn apply(erc20: &mut Erc20, op: &Op) -> Result<()> {
+            let set_caller = |caller: u8| {
+                ink_env::test::set_caller::<ink_env::DefaultEnvironment>(account(caller))
+            };
+            match *op {
+                Op::Transfer { caller, to, value } => {
+                    set_caller(caller);
+                    erc20.transfer(account(to), value)
+                }
+                Op::Approve { caller, spender, value } => {
+                    set_caller(caller);
+                    erc20.approve(account(spender), value)
+                }
+                Op::TransferFrom { caller, from, to, value } => {
+                    set_caller(caller);
+                    erc20.transfer_from(account(from), account(to), value)
+                }
+                Op::Mint { caller, to, value } => {
+                    set_caller(caller);
+                    erc20.mint(account(to), value)
+                }
+            }
+        }
+
+        proptest! {
+            #[test]
+            fn ledger_invariants_hold(
+                initial_supply in 0..1_000u128,
+                ops in prop::collection::vec(op(), 1..50),
+            ) {
+                ink_env::test::run_test::<ink_env::DefaultEnvironment, _>(|_| {
+                    ink_env::test::set_caller::<ink_env::DefaultEnvironment>(account(1));
+                    let mut erc20 = Erc20::new(initial_supply);
+                    let mut expected_supply = initial_supply;
+                    for op in &ops {
+                        let before = snapshot(&erc20);
+                        let result = apply(&mut erc20, op);
+                        let after = snapshot(&erc20);
+
+                        // Balances are conserved across all participants.
+                        assert_eq!(after.0.iter().sum::<Balance>(), erc20.total_supply());
+                        if let (Op::Mint { value, .. }, Ok(())) = (op, &result) {
+                            expected_supply += value;
+                        }
+                        assert_eq!(erc20.total_supply(), expected_supply);
+                        // The holder list tracks exactly the non-zero balances.
+                        #[cfg(feature = "holders")]
+                        {
+                            let holders = after.0.iter().filter(|balance| **balance > 0).count();
+                            assert_eq!(erc20.holders_count() as usize, holders);
+                        }
+                        // A failed operation leaves the ledger untouched.
+                        if result.is_err() {
+                            assert_eq!(before, after, "{:?} mutated state", op);
+                        }
+                        // Spending through an allowance never increases it.
+                        if let (Op::TransferFrom { caller, from, .. }, Ok(())) = (op, &result) {
+                            let index = (*from * ACCOUNTS + *caller) as usize;
+                            assert!(after.1[index] <= before.1[index]);
+                        }
+                    }
+                    Ok(())
+                })
+                .unwrap();
+            }
         }
     }
 }
@@ -0,0 +1,87 @@
+//! Overflow-free fixed-point helpers for token accounting.
+
+const LOW_MASK: u128 = u64::MAX as u128;
+
+/// Computes `a * b / c`, rounding down, without overflowing on the product.
+///
+/// Returns `None` if `c` is zero or the result does not fit into a `u128`.
+pub fn mul_div(a: u128, b: u128, c: u128) -> Option<u128> {
+    if c == 0 {
+        return None
+    }
+    let (high, low) = full_mul(a, b);
+    if high == 0 {
+        return Some(low / c)
+    }
+    if high >= c {
+        return None
+    }
+    // Long division of the 256 bit product; `remainder < c` holds throughout.
+    let mut remainder = high;
+    let mut quotient = 0u128;
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((low >> bit) & 1);
+        quotient <<= 1;
+        if carry == 1 || remainder >= c {
+            remainder = remainder.wrapping_sub(c);
+            quotient |= 1;
+        }
+    }
+    Some(quotient)
+}
+
+/// Computes `a * b / c`, rounding up, without overflowing on the product.
+///
+/// Returns `None` if `c` is zero or the result does not fit into a `u128`.
+pub fn mul_div_up(a: u128, b: u128, c: u128) -> Option<u128> {
+    let quotient = mul_div(a, b, c)?;
+    if full_mul(quotient, c) == full_mul(a, b) {
+        return Some(quotient)
+    }
+    quotient.checked_add(1)
+}
+
+/// Returns the 256 bit product of `a` and `b` as `(high, low)` halves.
+fn full_mul(a: u128, b: u128) -> (u128, u128) {
+    let (a_high, a_low) = (a >> 64, a & LOW_MASK);
+    let (b_high, b_low) = (b >> 64, b & LOW_MASK);
+    let low_low = a_low * b_low;
+    let high_low = a_high * b_low;
+    let low_high = a_low * b_high;
+    let high_high = a_high * b_high;
+    let middle = (low_low >> 64) + (high_low & LOW_MASK) + (low_high & LOW_MASK);
+    let high = high_high + (high_low >> 64) + (low_high >> 64) + (middle >> 64);
+    let low = (middle << 64) | (low_low & LOW_MASK);
+    (high, low)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mul_div_matches_plain_arithmetic() {
+        assert_eq!(mul_div(6, 7, 2), Some(21));
+        assert_eq!(mul_div(10, 1, 3), Some(3));
+        assert_eq!(mul_div(1, 1, 0), None);
+    }
+
+    #[test]
+    fn mul_div_handles_wide_products() {
+        assert_eq!(mul_div(u128::MAX, u128::MAX, u128::MAX), Some(u128::MAX));
+        assert_eq!(mul_div(u128::MAX, 1 << 64, 1 << 65), Some(u128::MAX / 2));
+        assert_eq!(mul_div(1 << 100, 1 << 100, 1 << 90), Some(1 << 110));
+        assert_eq!(mul_div(u128::MAX, 2, 1), None);
+    }
+
+    #[test]
+    fn mul_div_up_rounds_remainders_up() {
+        assert_eq!(mul_div_up(6, 7, 2), Some(21));
+        assert_eq!(mul_div_up(10, 1, 3), Some(4));
+        assert_eq!(mul_div_up(1 << 100, 3, 1 << 101), Some(2));
+        assert_eq!(mul_div_up(u128::MAX, u128::MAX, u128::MAX), Some(u128::MAX));
+        assert_eq!(mul_div_up(u128::MAX, 2, 3), Some(u128::MAX / 3 * 2));
+        assert_eq!(mul_div_up(1, 1, 0), None);
+    }
+}
@@ -0,0 +1,385 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use ink_lang as ink;
+
+pub use self::multi_token::{MultiToken, MultiTokenRef};
+
+/// A family of fungible tokens in one contract, such as points, tickets and
+/// credits, each told apart by its `TokenId`.
+#[ink::contract]
+mod multi_token {
+    use ink_lang::codegen::EmitEvent;
+    use ink_prelude::{string::String, vec::Vec};
+    use ink_storage::{traits::SpreadAllocate, Mapping};
+
+    /// Identifies one token of the family.
+    pub type TokenId = u128;
+
+    #[ink(storage)]
+    #[derive(SpreadAllocate)]
+    pub struct MultiToken {
+        /// Balances per token and holder.
+        balances: Mapping<(TokenId, AccountId), Balance>,
+        /// Allowances per token, owner and spender.
+        allowances: Mapping<(TokenId, AccountId, AccountId), Balance>,
+        /// Total supply of every created token.
+        supplies: Mapping<TokenId, Balance>,
+        /// Metadata of every created token, such as a name or URI.
+        metadata: Mapping<TokenId, String>,
+        /// Account that created each token.
+        creators: Mapping<TokenId, AccountId>,
+    }
+
+    /// The multi-token error types.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Returned if a token with the id has already been created.
+        TokenExists,
+        /// Returned if no token with the id has been created.
+        TokenNotFound,
+        /// Returned if not enough balance to fulfill a request is available.
+        InsufficientBalance,
+        /// Returned if not enough allowance to fulfill a request is available.
+        InsufficientAllowance,
+    }
+
+    /// The multi-token result type.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    #[ink(event)]
+    pub struct TokenCreated {
+        #[ink(topic)]
+        id: TokenId,
+        #[ink(topic)]
+        creator: AccountId,
+        initial_supply: Balance,
+        metadata: String,
+    }
+
+    #[ink(event)]
+    pub struct Transfer {
+        #[ink(topic)]
+        id: TokenId,
+        #[ink(topic)]
+        from: Option<AccountId>,
+        #[ink(topic)]
+        to: Option<AccountId>,
+        value: Balance,
+    }
+
+    #[ink(event)]
+    pub struct Approval {
+        #[ink(topic)]
+        id: TokenId,
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        spender: AccountId,
+        value: Balance,
+    }
+
+    impl Default for MultiToken {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl MultiToken {
+        /// Creates a contract without any tokens.
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            ink_lang::utils::initialize_contract(|_: &mut Self| {})
+        }
+
+        /// Creates the token `id` with `metadata`, crediting `initial_supply`
+        /// to the caller.
+        #[ink(message)]
+        pub fn create_token(
+            &mut self,
+            id: TokenId,
+            initial_supply: Balance,
+            metadata: String,
+        ) -> Result<()> {
+            if self.creators.contains(id) {
+                return Err(Error::TokenExists)
+            }
+            let creator = self.env().caller();
+            self.creators.insert(id, &creator);
+            self.metadata.insert(id, &metadata);
+            self.supplies.insert(id, &initial_supply);
+            self.balances.insert((id, creator), &initial_supply);
+            EmitEvent::<MultiToken>::emit_event(
+                self.env(),
+                TokenCreated {
+                    id,
+                    creator,
+                    initial_supply,
+                    metadata,
+                },
+            );
+            EmitEvent::<MultiToken>::emit_event(
+                self.env(),
+                Transfer {
+                    id,
+                    from: None,
+                    to: Some(creator),
+                    value: initial_supply,
+                },
+            );
+            Ok(())
+        }
+
+        /// Returns the account that created the token `id`, if it exists.
+        #[ink(message)]
+        pub fn creator(&self, id: TokenId) -> Option<AccountId> {
+            self.creators.get(id)
+        }
+
+        /// Returns the metadata of the token `id`, if it exists.
+        #[ink(message)]
+        pub fn metadata(&self, id: TokenId) -> Option<String> {
+            self.metadata.get(id)
+        }
+
+        /// Returns the total supply of the token `id`.
+        #[ink(message)]
+        pub fn total_supply(&self, id: TokenId) -> Balance {
+            self.supplies.get(id).unwrap_or_default()
+        }
+
+        /// Returns the balance of `owner` in the token `id`.
+        #[ink(message)]
+        pub fn balance_of(&self, id: TokenId, owner: AccountId) -> Balance {
+            self.balances.get((id, owner)).unwrap_or_default()
+        }
+
+        /// Returns the balances of the given `(id, owner)` pairs, in the same
+        /// order.
+        #[ink(message)]
+        pub fn balance_of_batch(&self, queries: Vec<(TokenId, AccountId)>) -> Vec<Balance> {
+            queries
+                .into_iter()
+                .map(|(id, owner)| self.balance_of(id, owner))
+                .collect()
+        }
+
+        /// Returns the amount of the token `id` that `spender` may still move
+        /// on behalf of `owner`.
+        #[ink(message)]
+        pub fn allowance(&self, id: TokenId, owner: AccountId, spender: AccountId) -> Balance {
+            self.allowances.get((id, owner, spender)).unwrap_or_default()
+        }
+
+        /// Allows `spender` to move up to `value` of the caller's token `id`,
+        /// replacing any previous allowance.
+        #[ink(message)]
+        pub fn approve(&mut self, id: TokenId, spender: AccountId, value: Balance) -> Result<()> {
+            self.ensure_exists(id)?;
+            let owner = self.env().caller();
+            self.allowances.insert((id, owner, spender), &value);
+            EmitEvent::<MultiToken>::emit_event(
+                self.env(),
+                Approval {
+                    id,
+                    owner,
+                    spender,
+                    value,
+                },
+            );
+            Ok(())
+        }
+
+        /// Transfers `value` of the caller's token `id` to `to`.
+        #[ink(message)]
+        pub fn transfer(&mut self, id: TokenId, to: AccountId, value: Balance) -> Result<()> {
+            let from = self.env().caller();
+            self.transfer_batch_from_to(from, to, &[(id, value)])
+        }
+
+        /// Transfers several of the caller's tokens to `to` at once.
+        #[ink(message)]
+        pub fn transfer_batch(
+            &mut self,
+            to: AccountId,
+            transfers: Vec<(TokenId, Balance)>,
+        ) -> Result<()> {
+            let from = self.env().caller();
+            self.transfer_batch_from_to(from, to, &transfers)
+        }
+
+        /// Transfers `value` of `from`'s token `id` to `to`, spending the
+        /// caller's allowance.
+        #[ink(message)]
+        pub fn transfer_from(
+            &mut self,
+            id: TokenId,
+            from: AccountId,
+            to: AccountId,
+            value: Balance,
+        ) -> Result<()> {
+            self.spend_and_transfer(from, to, &[(id, value)])
+        }
+
+        /// Transfers several of `from`'s tokens to `to` at once, spending the
+        /// caller's allowance in each of them.
+        #[ink(message)]
+        pub fn transfer_from_batch(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            transfers: Vec<(TokenId, Balance)>,
+        ) -> Result<()> {
+            self.spend_and_transfer(from, to, &transfers)
+        }
+
+        /// Moves the `transfers` from `from` to `to`, spending the caller's
+        /// allowances after checking that they cover all of them.
+        fn spend_and_transfer(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            transfers: &[(TokenId, Balance)],
+        ) -> Result<()> {
+            let spender = self.env().caller();
+            let totals = totals_by_id(transfers).ok_or(Error::InsufficientAllowance)?;
+            for (id, total) in &totals {
+                if self.allowance(*id, from, spender) < *total {
+                    return Err(Error::InsufficientAllowance)
+                }
+            }
+            self.transfer_batch_from_to(from, to, transfers)?;
+            for (id, total) in totals {
+                let allowance = self.allowance(id, from, spender);
+                self.allowances.insert((id, from, spender), &(allowance - total));
+            }
+            Ok(())
+        }
+
+        /// Moves the `transfers` from `from` to `to` after checking that
+        /// `from` holds all of them.
+        fn transfer_batch_from_to(
+            &mut self,
+            from: AccountId,
+            to: AccountId,
+            transfers: &[(TokenId, Balance)],
+        ) -> Result<()> {
+            let totals = totals_by_id(transfers).ok_or(Error::InsufficientBalance)?;
+            for (id, total) in totals {
+                self.ensure_exists(id)?;
+                if self.balance_of(id, from) < total {
+                    return Err(Error::InsufficientBalance)
+                }
+            }
+            for &(id, value) in transfers {
+                let from_balance = self.balance_of(id, from);
+                self.balances.insert((id, from), &(from_balance - value));
+                // Read after debiting so a transfer to self leaves the balance unchanged.
+                let to_balance = self.balance_of(id, to);
+                self.balances.insert((id, to), &(to_balance + value));
+                EmitEvent::<MultiToken>::emit_event(
+                    self.env(),
+                    Transfer {
+                        id,
+                        from: Some(from),
+                        to: Some(to),
+                        value,
+                    },
+                );
+            }
+            Ok(())
+        }
+
+        fn ensure_exists(&self, id: TokenId) -> Result<()> {
+            if !self.creators.contains(id) {
+                return Err(Error::TokenNotFound)
+            }
+            Ok(())
+        }
+    }
+
+    /// Sums the values of `transfers` per token id, or returns `None` if a sum
+    /// overflows.
+    fn totals_by_id(transfers: &[(TokenId, Balance)]) -> Option<Vec<(TokenId, Balance)>> {
+        let mut totals: Vec<(TokenId, Balance)> = Vec::new();
+        for &(id, value) in transfers {
+            match totals.iter_mut().find(|(total_id, _)| *total_id == id) {
+                Some((_, total)) => *total = total.checked_add(value)?,
+                None => totals.push((id, value)),
+            }
+        }
+        Some(totals)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        use ink_lang as ink;
+
+        fn set_caller(caller: AccountId) {
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(caller);
+        }
+
+        #[ink::test]
+        fn tokens_are_created_once() {
+            let mut tokens = MultiToken::new();
+            let alice = AccountId::from([0x1; 32]);
+            assert_eq!(tokens.create_token(7, 100, String::from("points")), Ok(()));
+            assert_eq!(tokens.create_token(7, 1, String::new()), Err(Error::TokenExists));
+            assert_eq!(tokens.creator(7), Some(alice));
+            assert_eq!(tokens.metadata(7), Some(String::from("points")));
+            assert_eq!(tokens.total_supply(7), 100);
+            assert_eq!(tokens.balance_of(7, alice), 100);
+            assert_eq!(tokens.metadata(8), None);
+        }
+
+        #[ink::test]
+        fn balances_are_kept_per_token() {
+            let mut tokens = MultiToken::new();
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            assert_eq!(tokens.create_token(1, 100, String::new()), Ok(()));
+            assert_eq!(tokens.create_token(2, 50, String::new()), Ok(()));
+            assert_eq!(tokens.transfer(1, bob, 30), Ok(()));
+            assert_eq!(tokens.transfer(3, bob, 0), Err(Error::TokenNotFound));
+            assert_eq!(
+                tokens.transfer_batch(bob, vec![(2, 20), (1, 70), (1, 1)]),
+                Err(Error::InsufficientBalance)
+            );
+            assert_eq!(tokens.transfer_batch(bob, vec![(2, 20), (1, 60), (2, 5)]), Ok(()));
+            assert_eq!(
+                tokens.balance_of_batch(vec![(1, alice), (1, bob), (2, alice), (2, bob)]),
+                vec![10, 90, 25, 25]
+            );
+            assert_eq!(ink_env::test::recorded_events().count(), 8);
+        }
+
+        #[ink::test]
+        fn transfer_from_spends_allowances_per_token() {
+            let mut tokens = MultiToken::new();
+            let alice = AccountId::from([0x1; 32]);
+            let bob = AccountId::from([0x2; 32]);
+            let charlie = AccountId::from([0x3; 32]);
+            assert_eq!(tokens.create_token(1, 100, String::new()), Ok(()));
+            assert_eq!(tokens.create_token(2, 100, String::new()), Ok(()));
+            assert_eq!(tokens.approve(1, bob, 40), Ok(()));
+            assert_eq!(tokens.approve(2, bob, 10), Ok(()));
+
+            set_caller(bob);
+            assert_eq!(
+                tokens.transfer_from_batch(alice, charlie, vec![(1, 30), (2, 5), (2, 6)]),
+                Err(Error::InsufficientAllowance)
+            );
+            assert_eq!(
+                tokens.transfer_from_batch(alice, charlie, vec![(1, 30), (2, 10)]),
+                Ok(())
+            );
+            assert_eq!(tokens.transfer_from(1, alice, charlie, 10), Ok(()));
+            assert_eq!(tokens.allowance(1, alice, bob), 0);
+            assert_eq!(tokens.allowance(2, alice, bob), 0);
+            assert_eq!(tokens.balance_of(1, charlie), 40);
+            assert_eq!(tokens.balance_of(2, charlie), 10);
+        }
+    }
+}
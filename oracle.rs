@@ -0,0 +1,17 @@
+//! Binding for a runtime chain extension that serves price feeds.
+//!
+//! The runtime is expected to take a SCALE encoded `u32` feed identifier and
+//! answer with an `Option<u128>` price, `None` if the feed is unknown or stale.
+
+use ink_env::chain_extension::ChainExtensionMethod;
+
+/// Reads the price of `feed` from function `function_id` of chain extension
+/// `extension_id`.
+pub fn price(extension_id: u16, function_id: u16, feed: u32) -> Option<u128> {
+    let func_id = u32::from(extension_id) << 16 | u32::from(function_id);
+    ChainExtensionMethod::build(func_id)
+        .input::<u32>()
+        .output::<Option<u128>>()
+        .ignore_error_code()
+        .call(&feed)
+}
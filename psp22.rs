@@ -0,0 +1,66 @@
+//! OpenBrush flavour of the token, built with the `openbrush` feature.
+//!
+//! The contract keeps the constructors of the default build but takes its
+//! storage and messages from OpenBrush's PSP22, PSP22Metadata and Ownable
+//! implementations. None of the extensions of the default build are
+//! available here. OpenBrush 2.x needs a nightly toolchain.
+
+#[openbrush::contract]
+pub mod erc20 {
+    use ink_prelude::string::String;
+    use ink_storage::traits::SpreadAllocate;
+    use openbrush::{
+        contracts::{
+            ownable::*,
+            psp22::extensions::metadata::*,
+        },
+        traits::Storage,
+    };
+
+    #[ink(storage)]
+    #[derive(Default, SpreadAllocate, Storage)]
+    pub struct Erc20 {
+        #[storage_field]
+        psp22: psp22::Data,
+        #[storage_field]
+        metadata: metadata::Data,
+        #[storage_field]
+        ownable: ownable::Data,
+    }
+
+    impl PSP22 for Erc20 {}
+
+    impl PSP22Metadata for Erc20 {}
+
+    impl Ownable for Erc20 {}
+
+    impl Erc20 {
+        /// Creates a new token that credits `initial_supply` to the caller,
+        /// who also becomes the owner.
+        #[ink(constructor)]
+        pub fn new(initial_supply: Balance) -> Self {
+            Self::new_with_metadata(None, None, 0, initial_supply, Self::env().caller())
+        }
+
+        /// Creates a new token with metadata, crediting `initial_supply` to
+        /// the caller.
+        #[ink(constructor)]
+        pub fn new_with_metadata(
+            name: Option<String>,
+            symbol: Option<String>,
+            decimals: u8,
+            initial_supply: Balance,
+            owner: AccountId,
+        ) -> Self {
+            ink_lang::codegen::initialize_contract(|instance: &mut Self| {
+                instance.metadata.name = name;
+                instance.metadata.symbol = symbol;
+                instance.metadata.decimals = decimals;
+                instance._init_with_owner(owner);
+                instance
+                    ._mint(Self::env().caller(), initial_supply)
+                    .expect("initial supply fits into a balance");
+            })
+        }
+    }
+}
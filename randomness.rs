@@ -0,0 +1,19 @@
+//! Binding for a runtime chain extension that provides on-chain randomness.
+//!
+//! The runtime is expected to take a SCALE encoded 32 byte subject, e.g. the
+//! subject of `pallet-randomness-collective-flip` or a VRF based pallet, and
+//! answer with a random hash for it, or `None` if no randomness is available
+//! yet.
+
+use ink_env::chain_extension::ChainExtensionMethod;
+
+/// Reads the random hash for `subject` through function `function_id` of
+/// chain extension `extension_id`.
+pub fn random(extension_id: u16, function_id: u16, subject: [u8; 32]) -> Option<[u8; 32]> {
+    let func_id = u32::from(extension_id) << 16 | u32::from(function_id);
+    ChainExtensionMethod::build(func_id)
+        .input::<[u8; 32]>()
+        .output::<Option<[u8; 32]>>()
+        .ignore_error_code()
+        .call(&subject)
+}
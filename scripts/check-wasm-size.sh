@@ -0,0 +1,56 @@
+#!/usr/bin/env bash
+# Builds the contract for Wasm and fails if its code exceeds the size budget.
+# The default budget of 128 KiB is the `MaxCodeLen` of pallet-contracts on
+# most chains; pass a smaller one for stricter chains, and the token
+# extensions to build with (see `[features]` in Cargo.toml), if any:
+#
+#     scripts/check-wasm-size.sh [budget-in-bytes] [features]
+#
+# Custom sections such as the name section are left out of the measured size,
+# since `cargo contract build` strips them before upload.
+set -euo pipefail
+
+budget=${1:-131072}
+features=${2:-}
+cd "$(dirname "$0")/.."
+
+# Partial feature sets rely on `cfg_eval`, which is unstable.
+RUSTC_BOOTSTRAP=1 cargo build --quiet --release --lib \
+    --target wasm32-unknown-unknown --no-default-features --features "$features"
+wasm=target/wasm32-unknown-unknown/release/erc20.wasm
+
+size=$(python3 - "$wasm" <<'EOF'
+import sys
+
+data = open(sys.argv[1], "rb").read()
+assert data[:4] == b"\0asm", "not a Wasm module"
+
+
+def leb128(pos):
+    value = shift = 0
+    while True:
+        byte = data[pos]
+        pos += 1
+        value |= (byte & 0x7F) << shift
+        shift += 7
+        if byte < 0x80:
+            return value, pos
+
+
+size, pos = 8, 8
+while pos < len(data):
+    start = pos
+    section_id = data[pos]
+    length, pos = leb128(pos + 1)
+    pos += length
+    if section_id != 0:
+        size += pos - start
+print(size)
+EOF
+)
+
+echo "$wasm${features:+ ($features)}: $size bytes without custom sections (budget $budget)"
+if [ "$size" -gt "$budget" ]; then
+    echo "error: contract exceeds the size budget by $((size - budget)) bytes" >&2
+    exit 1
+fi
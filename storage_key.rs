@@ -0,0 +1,95 @@
+//! Storage fields pinned to a fixed storage key.
+
+use core::ops::{Deref, DerefMut};
+use ink_primitives::{Key, KeyPtr};
+use ink_storage::traits::{SpreadAllocate, SpreadLayout};
+
+/// Storage root key used by the ink! dispatcher.
+const ROOT_KEY: Key = Key::new([0x00; 32]);
+
+/// A storage field kept under the storage key `KEY` cells past the root key.
+///
+/// ink! derives the key of every field from its position in the storage
+/// struct, so adding a field in the middle or reordering fields moves the data
+/// of all fields behind it. A `ManualKey` field keeps its key wherever it is
+/// declared. The keys in use equal the positions the fields had when they were
+/// pinned.
+///
+/// A field only keeps its data while its storage type stays the same:
+/// `total_supply` became a `Lazy`, whose value sits at a key derived from the
+/// field key rather than at the field key itself.
+#[derive(Debug)]
+pub struct ManualKey<T, const KEY: u64> {
+    inner: T,
+}
+
+impl<T, const KEY: u64> ManualKey<T, KEY> {
+    /// Returns a key pointer to the storage key of the field.
+    fn key_ptr() -> KeyPtr {
+        let mut ptr = KeyPtr::from(ROOT_KEY);
+        ptr.advance_by(KEY);
+        ptr
+    }
+}
+
+impl<T, const KEY: u64> Deref for ManualKey<T, KEY> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T, const KEY: u64> DerefMut for ManualKey<T, KEY> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<T: SpreadLayout, const KEY: u64> SpreadLayout for ManualKey<T, KEY> {
+    const FOOTPRINT: u64 = T::FOOTPRINT;
+    const REQUIRES_DEEP_CLEAN_UP: bool = T::REQUIRES_DEEP_CLEAN_UP;
+
+    #[inline]
+    fn pull_spread(ptr: &mut KeyPtr) -> Self {
+        ptr.advance_by(Self::FOOTPRINT);
+        Self {
+            inner: T::pull_spread(&mut Self::key_ptr()),
+        }
+    }
+
+    #[inline]
+    fn push_spread(&self, ptr: &mut KeyPtr) {
+        ptr.advance_by(Self::FOOTPRINT);
+        self.inner.push_spread(&mut Self::key_ptr())
+    }
+
+    #[inline]
+    fn clear_spread(&self, ptr: &mut KeyPtr) {
+        ptr.advance_by(Self::FOOTPRINT);
+        self.inner.clear_spread(&mut Self::key_ptr())
+    }
+}
+
+impl<T: SpreadAllocate, const KEY: u64> SpreadAllocate for ManualKey<T, KEY> {
+    #[inline]
+    fn allocate_spread(ptr: &mut KeyPtr) -> Self {
+        ptr.advance_by(Self::FOOTPRINT);
+        Self {
+            inner: T::allocate_spread(&mut Self::key_ptr()),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T, const KEY: u64> ink_storage::traits::StorageLayout for ManualKey<T, KEY>
+where
+    T: SpreadLayout + ink_storage::traits::StorageLayout,
+{
+    fn layout(key_ptr: &mut KeyPtr) -> ink_metadata::layout::Layout {
+        key_ptr.advance_by(Self::FOOTPRINT);
+        T::layout(&mut Self::key_ptr())
+    }
+}
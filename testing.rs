@@ -0,0 +1,168 @@
+//! Fixtures for testing the token in the off-chain environment.
+//!
+//! Everything here drives the off-chain environment, so it must be used from
+//! within an `#[ink::test]`.
+
+use crate::erc20::Error;
+use crate::Erc20;
+use ink_env::{test::EmittedEvent, AccountId, DefaultEnvironment};
+use ink_prelude::{boxed::Box, vec::Vec};
+
+type Balance = <DefaultEnvironment as ink_env::Environment>::Balance;
+
+/// The events of the token, as decoded from the recorded ones.
+pub type Event = <Erc20 as ink_lang::reflect::ContractEventBase>::Type;
+
+/// A change applied to the token once its balances and allowances are set.
+type Configure = Box<dyn FnOnce(&mut Erc20) -> Result<(), Error>>;
+
+/// Sets up a token with balances, allowances and configuration in one go.
+///
+/// The deployer mints every balance and hands them out before the
+/// configuration is applied, so taxes and limits set up by `configure` do not
+/// affect them.
+pub struct Erc20TestBuilder {
+    deployer: AccountId,
+    caller: Option<AccountId>,
+    initial_supply: Balance,
+    balances: Vec<(AccountId, Balance)>,
+    allowances: Vec<(AccountId, AccountId, Balance)>,
+    configure: Vec<Configure>,
+}
+
+impl Default for Erc20TestBuilder {
+    fn default() -> Self {
+        Self {
+            deployer: accounts().alice,
+            caller: None,
+            initial_supply: 0,
+            balances: Vec::new(),
+            allowances: Vec::new(),
+            configure: Vec::new(),
+        }
+    }
+}
+
+impl Erc20TestBuilder {
+    /// Starts a token deployed by Alice, with no supply.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deploys the token from `deployer`, its owner and minter.
+    pub fn deployer(mut self, deployer: AccountId) -> Self {
+        self.deployer = deployer;
+        self
+    }
+
+    /// Leaves `caller` as the caller once the token is built, rather than
+    /// the deployer.
+    pub fn caller(mut self, caller: AccountId) -> Self {
+        self.caller = Some(caller);
+        self
+    }
+
+    /// Mints `value` to the deployer on top of the balances handed out.
+    pub fn initial_supply(mut self, value: Balance) -> Self {
+        self.initial_supply = value;
+        self
+    }
+
+    /// Gives `account` a balance of `value`.
+    pub fn balance(mut self, account: AccountId, value: Balance) -> Self {
+        self.balances.push((account, value));
+        self
+    }
+
+    /// Lets `spender` spend `value` of `owner`'s tokens.
+    pub fn allowance(mut self, owner: AccountId, spender: AccountId, value: Balance) -> Self {
+        self.allowances.push((owner, spender, value));
+        self
+    }
+
+    /// Applies `configure` as the deployer, after the balances and
+    /// allowances are set, e.g. `|erc20| erc20.set_tax_rate(100)`.
+    pub fn configure(
+        mut self,
+        configure: impl FnOnce(&mut Erc20) -> Result<(), Error> + 'static,
+    ) -> Self {
+        self.configure.push(Box::new(configure));
+        self
+    }
+
+    /// Deploys and sets up the token.
+    ///
+    /// # Panics
+    ///
+    /// If any of the transfers, approvals or changes fails.
+    pub fn build(self) -> Erc20 {
+        set_caller(self.deployer);
+        let handed_out: Balance = self.balances.iter().map(|(_, value)| value).sum();
+        let mut erc20 = Erc20::new(self.initial_supply + handed_out);
+        for (account, value) in self.balances {
+            erc20.transfer(account, value).expect("setting up a balance failed");
+        }
+        for (owner, spender, value) in self.allowances {
+            set_caller(owner);
+            erc20.approve(spender, value).expect("setting up an allowance failed");
+        }
+        set_caller(self.deployer);
+        for configure in self.configure {
+            configure(&mut erc20).expect("configuring the token failed");
+        }
+        set_caller(self.caller.unwrap_or(self.deployer));
+        erc20
+    }
+}
+
+/// Returns the default accounts of the off-chain environment.
+pub fn accounts() -> ink_env::test::DefaultAccounts<DefaultEnvironment> {
+    ink_env::test::default_accounts::<DefaultEnvironment>()
+}
+
+/// Makes `account` the caller of the following messages.
+pub fn set_caller(account: AccountId) {
+    ink_env::test::set_caller::<DefaultEnvironment>(account);
+}
+
+/// Moves on to the next block, 6 milliseconds later.
+pub fn advance_block() {
+    ink_env::test::advance_block::<DefaultEnvironment>();
+}
+
+/// Moves on `count` blocks.
+pub fn advance_blocks(count: u32) {
+    for _ in 0..count {
+        advance_block();
+    }
+}
+
+/// Returns the events emitted so far, oldest first.
+pub fn recorded() -> Vec<EmittedEvent> {
+    ink_env::test::recorded_events().collect()
+}
+
+/// Decodes the data of a recorded event.
+pub fn decode(event: &EmittedEvent) -> Event {
+    <Event as scale::Decode>::decode(&mut &event.data[..]).expect("invalid event data")
+}
+
+/// Returns the last event emitted, decoded.
+pub fn last_event() -> Event {
+    decode(recorded().last().expect("no event emitted"))
+}
+
+/// Returns the latest event emitted that `matches`, e.g.
+/// `|event| matches!(event, Event::Paused(_))`.
+///
+/// # Panics
+///
+/// If no such event was emitted.
+pub fn expect_event(matches: impl Fn(&Event) -> bool) -> Event {
+    recorded()
+        .iter()
+        .rev()
+        .map(decode)
+        .find(|event| matches(event))
+        .expect("no matching event emitted")
+}
@@ -0,0 +1,541 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use ink_lang as ink;
+
+pub use self::vault::{Vault, VaultRef};
+
+/// An ERC-4626 style vault holding a PSP22 asset, with the `erc20` token as
+/// its share.
+///
+/// The vault must be the minter of the share token, and the share token must
+/// not take transfer fees, since shares are redeemed by moving them to the
+/// vault and burning them there.
+///
+/// The owner may deploy idle assets to a strategy contract implementing
+/// `Strategy::invest(amount)`, `Strategy::divest(amount)` and
+/// `Strategy::total_assets()`. The strategy is sent the assets before `invest`
+/// is called and sends them back from `divest`. Its holdings count towards the
+/// share price as last reported by `harvest`.
+#[ink::contract]
+mod vault {
+    use erc20::{
+        math::{mul_div, mul_div_up},
+        Erc20Ref,
+    };
+    use ink_env::{
+        call::{build_call, Call, ExecutionInput, Selector},
+        DefaultEnvironment,
+    };
+    use ink_lang::codegen::EmitEvent;
+    use ink_prelude::vec::Vec;
+    use ink_storage::traits::SpreadAllocate;
+
+    #[ink(storage)]
+    #[derive(SpreadAllocate)]
+    pub struct Vault {
+        /// The PSP22 token deposited into the vault.
+        asset: AccountId,
+        /// The token issued as vault shares.
+        share: AccountId,
+        /// The account allowed to manage the strategy.
+        owner: AccountId,
+        /// The contract idle assets are invested in.
+        strategy: Option<AccountId>,
+        /// Assets held by the strategy as of the last harvest.
+        invested: Balance,
+    }
+
+    /// The vault error types.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        /// Returned if a deposit or withdrawal comes to zero assets or shares.
+        ZeroAmount,
+        /// Returned if a call to the asset contract fails.
+        AssetCallFailed,
+        /// Returned if a call to the share token fails.
+        ShareCallFailed,
+        /// Returned if an amount does not fit into a balance.
+        Overflow,
+        /// Returned if the caller is not the owner.
+        NotOwner,
+        /// Returned if no strategy is set.
+        NoStrategy,
+        /// Returned if the strategy is replaced while it still holds assets.
+        StrategyActive,
+        /// Returned if a call to the strategy fails.
+        StrategyCallFailed,
+    }
+
+    /// The vault result type.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    #[ink(event)]
+    pub struct Deposit {
+        #[ink(topic)]
+        owner: AccountId,
+        assets: Balance,
+        shares: Balance,
+    }
+
+    #[ink(event)]
+    pub struct Withdraw {
+        #[ink(topic)]
+        owner: AccountId,
+        assets: Balance,
+        shares: Balance,
+    }
+
+    #[ink(event)]
+    pub struct StrategyChanged {
+        strategy: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct Invested {
+        #[ink(topic)]
+        strategy: AccountId,
+        assets: Balance,
+    }
+
+    #[ink(event)]
+    pub struct Divested {
+        #[ink(topic)]
+        strategy: AccountId,
+        assets: Balance,
+    }
+
+    #[ink(event)]
+    pub struct Harvested {
+        #[ink(topic)]
+        strategy: AccountId,
+        gain: Balance,
+        loss: Balance,
+    }
+
+    impl Vault {
+        /// Creates a vault for the PSP22 `asset` issuing `share` tokens, owned
+        /// by the caller.
+        #[ink(constructor)]
+        pub fn new(asset: AccountId, share: AccountId) -> Self {
+            ink_lang::utils::initialize_contract(|vault: &mut Self| {
+                vault.asset = asset;
+                vault.share = share;
+                vault.owner = Self::env().caller();
+            })
+        }
+
+        /// Returns the PSP22 token deposited into the vault.
+        #[ink(message)]
+        pub fn asset(&self) -> AccountId {
+            self.asset
+        }
+
+        /// Returns the token issued as vault shares.
+        #[ink(message)]
+        pub fn share(&self) -> AccountId {
+            self.share
+        }
+
+        /// Returns the account allowed to manage the strategy.
+        #[ink(message)]
+        pub fn owner(&self) -> AccountId {
+            self.owner
+        }
+
+        /// Returns the contract idle assets are invested in.
+        #[ink(message)]
+        pub fn strategy(&self) -> Option<AccountId> {
+            self.strategy
+        }
+
+        /// Returns the assets held by the strategy as of the last harvest.
+        #[ink(message)]
+        pub fn invested(&self) -> Balance {
+            self.invested
+        }
+
+        /// Returns the assets backing all shares: those in the vault plus
+        /// those in the strategy.
+        #[ink(message)]
+        pub fn total_assets(&self) -> Balance {
+            self.asset_balance().saturating_add(self.invested)
+        }
+
+        /// Sets or removes the strategy. The current one must have been
+        /// divested of everything first.
+        ///
+        /// Only the owner may call this.
+        #[ink(message)]
+        pub fn set_strategy(&mut self, strategy: Option<AccountId>) -> Result<()> {
+            self.ensure_owner()?;
+            if self.invested > 0 {
+                return Err(Error::StrategyActive)
+            }
+            self.strategy = strategy;
+            EmitEvent::<Vault>::emit_event(self.env(), StrategyChanged { strategy });
+            Ok(())
+        }
+
+        /// Sends all idle assets to the strategy and returns the amount.
+        ///
+        /// Only the owner may call this.
+        #[ink(message)]
+        pub fn invest(&mut self) -> Result<Balance> {
+            self.ensure_owner()?;
+            let strategy = self.strategy.ok_or(Error::NoStrategy)?;
+            let assets = self.asset_balance();
+            if assets == 0 {
+                return Err(Error::ZeroAmount)
+            }
+            self.send_assets(strategy, assets)?;
+            let selector = ink_lang::selector_bytes!("Strategy::invest");
+            self.call_strategy(strategy, selector, assets)?;
+            self.invested = self.invested.saturating_add(assets);
+            EmitEvent::<Vault>::emit_event(self.env(), Invested { strategy, assets });
+            Ok(assets)
+        }
+
+        /// Takes `assets` back from the strategy and returns the amount
+        /// received.
+        ///
+        /// Only the owner may call this.
+        #[ink(message)]
+        pub fn divest(&mut self, assets: Balance) -> Result<Balance> {
+            self.ensure_owner()?;
+            self.divest_impl(assets)
+        }
+
+        /// Updates the assets held by the strategy to what it reports, so its
+        /// gains or losses since the last harvest move the share price.
+        /// Returns the new holdings.
+        ///
+        /// Only the owner may call this.
+        #[ink(message)]
+        pub fn harvest(&mut self) -> Result<Balance> {
+            self.ensure_owner()?;
+            let strategy = self.strategy.ok_or(Error::NoStrategy)?;
+            let reported = build_call::<DefaultEnvironment>()
+                .call_type(Call::new().callee(strategy))
+                .exec_input(ExecutionInput::new(Selector::new(
+                    ink_lang::selector_bytes!("Strategy::total_assets"),
+                )))
+                .returns::<Balance>()
+                .fire()
+                .map_err(|_| Error::StrategyCallFailed)?;
+            let (gain, loss) = (
+                reported.saturating_sub(self.invested),
+                self.invested.saturating_sub(reported),
+            );
+            self.invested = reported;
+            EmitEvent::<Vault>::emit_event(self.env(), Harvested { strategy, gain, loss });
+            Ok(reported)
+        }
+
+        /// Returns the shares `assets` are worth, rounding down.
+        #[ink(message)]
+        pub fn convert_to_shares(&self, assets: Balance) -> Balance {
+            to_shares(assets, self.total_assets(), self.total_shares(), false)
+                .unwrap_or(Balance::MAX)
+        }
+
+        /// Returns the assets `shares` are worth, rounding down.
+        #[ink(message)]
+        pub fn convert_to_assets(&self, shares: Balance) -> Balance {
+            to_assets(shares, self.total_assets(), self.total_shares(), false)
+                .unwrap_or(Balance::MAX)
+        }
+
+        /// Pulls `assets` from the caller, who must have approved the vault,
+        /// and mints them the shares they are worth. Returns the shares.
+        #[ink(message)]
+        pub fn deposit(&mut self, assets: Balance) -> Result<Balance> {
+            let shares = to_shares(assets, self.total_assets(), self.total_shares(), false)
+                .ok_or(Error::Overflow)?;
+            self.deposit_impl(assets, shares)?;
+            Ok(shares)
+        }
+
+        /// Mints `shares` to the caller for the assets they are worth, rounded
+        /// up, which the caller must have approved the vault to pull. Returns
+        /// the assets.
+        #[ink(message)]
+        pub fn mint(&mut self, shares: Balance) -> Result<Balance> {
+            let assets = to_assets(shares, self.total_assets(), self.total_shares(), true)
+                .ok_or(Error::Overflow)?;
+            self.deposit_impl(assets, shares)?;
+            Ok(assets)
+        }
+
+        /// Pays out `assets` to the caller for the shares they are worth,
+        /// rounded up, and returns the shares. The caller must have approved
+        /// the vault to take the shares.
+        #[ink(message)]
+        pub fn withdraw(&mut self, assets: Balance) -> Result<Balance> {
+            let shares = to_shares(assets, self.total_assets(), self.total_shares(), true)
+                .ok_or(Error::Overflow)?;
+            self.withdraw_impl(assets, shares)?;
+            Ok(shares)
+        }
+
+        /// Burns `shares` of the caller for the assets they are worth and
+        /// returns the assets. The caller must have approved the vault to take
+        /// the shares.
+        #[ink(message)]
+        pub fn redeem(&mut self, shares: Balance) -> Result<Balance> {
+            let assets = to_assets(shares, self.total_assets(), self.total_shares(), false)
+                .ok_or(Error::Overflow)?;
+            self.withdraw_impl(assets, shares)?;
+            Ok(assets)
+        }
+
+        fn deposit_impl(&mut self, assets: Balance, shares: Balance) -> Result<()> {
+            if assets == 0 || shares == 0 {
+                return Err(Error::ZeroAmount)
+            }
+            let owner = self.env().caller();
+            self.pull_assets(owner, assets)?;
+            self.share_ref()
+                .mint(owner, shares)
+                .map_err(|_| Error::ShareCallFailed)?;
+            EmitEvent::<Vault>::emit_event(
+                self.env(),
+                Deposit {
+                    owner,
+                    assets,
+                    shares,
+                },
+            );
+            Ok(())
+        }
+
+        fn withdraw_impl(&mut self, assets: Balance, shares: Balance) -> Result<()> {
+            if assets == 0 || shares == 0 {
+                return Err(Error::ZeroAmount)
+            }
+            let idle = self.asset_balance();
+            if idle < assets {
+                self.divest_impl(assets - idle)?;
+            }
+            let owner = self.env().caller();
+            let mut share = self.share_ref();
+            share
+                .transfer_from(owner, self.env().account_id(), shares)
+                .map_err(|_| Error::ShareCallFailed)?;
+            share.burn(shares).map_err(|_| Error::ShareCallFailed)?;
+            self.send_assets(owner, assets)?;
+            EmitEvent::<Vault>::emit_event(
+                self.env(),
+                Withdraw {
+                    owner,
+                    assets,
+                    shares,
+                },
+            );
+            Ok(())
+        }
+
+        fn divest_impl(&mut self, assets: Balance) -> Result<Balance> {
+            let strategy = self.strategy.ok_or(Error::NoStrategy)?;
+            let before = self.asset_balance();
+            let selector = ink_lang::selector_bytes!("Strategy::divest");
+            self.call_strategy(strategy, selector, assets)?;
+            let received = self.asset_balance().saturating_sub(before);
+            self.invested = self.invested.saturating_sub(received);
+            EmitEvent::<Vault>::emit_event(
+                self.env(),
+                Divested {
+                    strategy,
+                    assets: received,
+                },
+            );
+            Ok(received)
+        }
+
+        /// Calls the strategy message with `selector` and an amount of assets.
+        fn call_strategy(
+            &self,
+            strategy: AccountId,
+            selector: [u8; 4],
+            assets: Balance,
+        ) -> Result<()> {
+            build_call::<DefaultEnvironment>()
+                .call_type(Call::new().callee(strategy))
+                .exec_input(ExecutionInput::new(Selector::new(selector)).push_arg(assets))
+                .returns::<()>()
+                .fire()
+                .map_err(|_| Error::StrategyCallFailed)
+        }
+
+        fn ensure_owner(&self) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::NotOwner)
+            }
+            Ok(())
+        }
+
+        fn share_ref(&self) -> Erc20Ref {
+            ink_env::call::FromAccountId::from_account_id(self.share)
+        }
+
+        fn total_shares(&self) -> Balance {
+            self.share_ref().total_supply()
+        }
+
+        fn asset_balance(&self) -> Balance {
+            build_call::<DefaultEnvironment>()
+                .call_type(Call::new().callee(self.asset))
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink_lang::selector_bytes!(
+                        "PSP22::balance_of"
+                    )))
+                    .push_arg(self.env().account_id()),
+                )
+                .returns::<Balance>()
+                .fire()
+                .unwrap_or_default()
+        }
+
+        fn pull_assets(&self, from: AccountId, assets: Balance) -> Result<()> {
+            build_call::<DefaultEnvironment>()
+                .call_type(Call::new().callee(self.asset))
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink_lang::selector_bytes!(
+                        "PSP22::transfer_from"
+                    )))
+                    .push_arg(from)
+                    .push_arg(self.env().account_id())
+                    .push_arg(assets)
+                    .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<core::result::Result<(), u8>>()
+                .fire()
+                .ok()
+                .and_then(|result| result.ok())
+                .ok_or(Error::AssetCallFailed)
+        }
+
+        fn send_assets(&self, to: AccountId, assets: Balance) -> Result<()> {
+            build_call::<DefaultEnvironment>()
+                .call_type(Call::new().callee(self.asset))
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink_lang::selector_bytes!(
+                        "PSP22::transfer"
+                    )))
+                    .push_arg(to)
+                    .push_arg(assets)
+                    .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<core::result::Result<(), u8>>()
+                .fire()
+                .ok()
+                .and_then(|result| result.ok())
+                .ok_or(Error::AssetCallFailed)
+        }
+    }
+
+    /// Returns the shares `assets` are worth. The virtual share and asset
+    /// added to the totals keep the first depositor from inflating the share
+    /// price at the expense of later ones.
+    fn to_shares(
+        assets: Balance,
+        total_assets: Balance,
+        total_shares: Balance,
+        round_up: bool,
+    ) -> Option<Balance> {
+        let convert = if round_up { mul_div_up } else { mul_div };
+        convert(assets, total_shares.checked_add(1)?, total_assets.checked_add(1)?)
+    }
+
+    /// Returns the assets `shares` are worth, the inverse of `to_shares`.
+    fn to_assets(
+        shares: Balance,
+        total_assets: Balance,
+        total_shares: Balance,
+        round_up: bool,
+    ) -> Option<Balance> {
+        let convert = if round_up { mul_div_up } else { mul_div };
+        convert(shares, total_assets.checked_add(1)?, total_shares.checked_add(1)?)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        use ink_lang as ink;
+
+        #[test]
+        fn first_deposit_mints_shares_one_to_one() {
+            assert_eq!(to_shares(1_000, 0, 0, false), Some(1_000));
+            assert_eq!(to_assets(1_000, 0, 0, false), Some(1_000));
+        }
+
+        #[test]
+        fn conversions_follow_the_share_price() {
+            // Counting the virtual ones, 1_000 shares backed by 2_000 assets.
+            assert_eq!(to_shares(500, 1_999, 999, false), Some(250));
+            assert_eq!(to_assets(250, 1_999, 999, false), Some(500));
+            assert_eq!(to_shares(1, 1_999, 999, false), Some(0));
+            assert_eq!(to_shares(1, 1_999, 999, true), Some(1));
+            assert_eq!(to_assets(1, 1_999, 999, true), Some(2));
+        }
+
+        #[test]
+        fn round_trips_never_gain_assets() {
+            let (total_assets, total_shares) = (10_007, 3_001);
+            for assets in [1, 7, 1_000, 9_999] {
+                // Depositing and redeeming pays out at most the deposit.
+                let shares = to_shares(assets, total_assets, total_shares, false).unwrap();
+                let total_assets = total_assets + assets;
+                let total_shares = total_shares + shares;
+                let out = to_assets(shares, total_assets, total_shares, false).unwrap();
+                assert!(out <= assets);
+                // Withdrawing costs at least the shares the assets are worth.
+                let burnt = to_shares(out, total_assets, total_shares, true).unwrap();
+                assert!(burnt >= to_shares(out, total_assets, total_shares, false).unwrap());
+            }
+        }
+
+        #[test]
+        fn donations_do_not_steal_later_deposits() {
+            // The first depositor mints one share and donates a large amount.
+            let (total_assets, total_shares) = (1 + 1_000_000, 1);
+            let shares = to_shares(1_000_000, total_assets, total_shares, false).unwrap();
+            let out = to_assets(shares, total_assets + 1_000_000, total_shares + shares, false);
+            // The victim keeps half of the deposit rather than losing all of it.
+            assert_eq!(shares, 1);
+            assert!(out.unwrap() >= 500_000);
+        }
+
+        #[ink::test]
+        fn new_vault_records_its_tokens() {
+            let asset = AccountId::from([0x7; 32]);
+            let share = AccountId::from([0x8; 32]);
+            let vault = Vault::new(asset, share);
+            assert_eq!(vault.asset(), asset);
+            assert_eq!(vault.share(), share);
+            assert_eq!(vault.owner(), AccountId::from([0x1; 32]));
+            assert_eq!(vault.strategy(), None);
+        }
+
+        #[ink::test]
+        fn strategy_is_managed_by_the_owner() {
+            let mut vault = Vault::new(AccountId::from([0x7; 32]), AccountId::from([0x8; 32]));
+            let strategy = AccountId::from([0x9; 32]);
+            assert_eq!(vault.invest(), Err(Error::NoStrategy));
+            assert_eq!(vault.harvest(), Err(Error::NoStrategy));
+            assert_eq!(vault.set_strategy(Some(strategy)), Ok(()));
+            assert_eq!(vault.strategy(), Some(strategy));
+
+            // A strategy holding assets cannot be swapped out.
+            vault.invested = 100;
+            assert_eq!(vault.set_strategy(None), Err(Error::StrategyActive));
+
+            ink_env::test::set_caller::<ink_env::DefaultEnvironment>(AccountId::from([0x2; 32]));
+            assert_eq!(vault.set_strategy(None), Err(Error::NotOwner));
+            assert_eq!(vault.invest(), Err(Error::NotOwner));
+            assert_eq!(vault.divest(1), Err(Error::NotOwner));
+            assert_eq!(vault.harvest(), Err(Error::NotOwner));
+        }
+    }
+}
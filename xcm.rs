@@ -0,0 +1,25 @@
+//! Binding for a runtime chain extension that sends tokens over XCM.
+//!
+//! The runtime is expected to take a SCALE encoded `(para_id, beneficiary,
+//! amount)` tuple, dispatch a reserve transfer of the token's asset to the
+//! sibling parachain, and answer with the hash of the outbound XCM message, or
+//! `None` if it could not be sent.
+
+use ink_env::chain_extension::ChainExtensionMethod;
+
+/// Sends `amount` to `beneficiary` on parachain `para_id` through function
+/// `function_id` of chain extension `extension_id`.
+pub fn transfer(
+    extension_id: u16,
+    function_id: u16,
+    para_id: u32,
+    beneficiary: [u8; 32],
+    amount: u128,
+) -> Option<[u8; 32]> {
+    let func_id = u32::from(extension_id) << 16 | u32::from(function_id);
+    ChainExtensionMethod::build(func_id)
+        .input::<(u32, [u8; 32], u128)>()
+        .output::<Option<[u8; 32]>>()
+        .ignore_error_code()
+        .call(&(para_id, beneficiary, amount))
+}